@@ -0,0 +1,169 @@
+use crate::config::Config;
+use crate::error::{AppError, ErrorCode};
+
+use axum::{
+    body::Body,
+    http::{header, HeaderValue, Request, StatusCode},
+    response::{IntoResponse, Response},
+};
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use tracing::debug;
+
+/// Paths served without an API key whatever `AUTH_EXCLUDE_PATHS` says
+const PUBLIC_PATHS: &[&str] = &["/version"];
+
+/// Paths under this prefix always need an API key, even if `AUTH_EXCLUDE_PATHS` lists them
+const ADMIN_PATH_PREFIX: &str = "/admin/";
+
+/// The configured API key a request was authenticated with, added to the request's
+/// extensions by [`ApiKeyService`]
+///
+/// Only present when the bearer token matched a key, including on paths that do not
+/// require one, so later layers can tell clients apart by a key they cannot forge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthenticatedKey(pub String);
+
+/// Requires an `Authorization: Bearer <key>` header matching one of the configured keys
+///
+/// With no keys configured every request is let through, so local development keeps
+/// working without credentials.
+#[derive(Debug, Clone)]
+pub struct ApiKeyLayer {
+    keys: Arc<Vec<String>>,
+    exclude_paths: Arc<HashSet<String>>,
+}
+
+impl ApiKeyLayer {
+    pub fn new(keys: Vec<String>, exclude_paths: Vec<String>) -> Self {
+        Self {
+            keys: Arc::new(keys),
+            exclude_paths: Arc::new(
+                exclude_paths
+                    .into_iter()
+                    .filter(|path| !path.starts_with(ADMIN_PATH_PREFIX))
+                    .chain(PUBLIC_PATHS.iter().map(|path| path.to_string()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Builds the layer from `API_KEYS` and `AUTH_EXCLUDE_PATHS`
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.api_keys(), config.auth_exclude_paths())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.keys.is_empty()
+    }
+}
+
+impl<S> Layer<S> for ApiKeyLayer {
+    type Service = ApiKeyService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ApiKeyService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiKeyService<S> {
+    inner: S,
+    layer: ApiKeyLayer,
+}
+
+impl<S> ApiKeyService<S> {
+    fn is_authorized(&self, request: &Request<Body>, key: Option<&AuthenticatedKey>) -> bool {
+        !self.layer.is_enabled()
+            || key.is_some()
+            || self.layer.exclude_paths.contains(request.uri().path())
+    }
+
+    /// The configured key matching the request's bearer token, if any
+    fn authenticated_key(&self, request: &Request<Body>) -> Option<AuthenticatedKey> {
+        let token = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim)?;
+
+        // Check every key so the response time does not reveal which one matched
+        self.layer
+            .keys
+            .iter()
+            .fold(None, |found, key| {
+                let matches = constant_time_eq(key.as_bytes(), token.as_bytes());
+                found.or(matches.then_some(key))
+            })
+            .map(|key| AuthenticatedKey(key.clone()))
+    }
+}
+
+impl<S> Service<Request<Body>> for ApiKeyService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<Body>) -> Self::Future {
+        let key = self.authenticated_key(&request);
+        if self.is_authorized(&request, key.as_ref()) {
+            if let Some(key) = key {
+                request.extensions_mut().insert(key);
+            }
+            return Box::pin(self.inner.call(request));
+        }
+
+        debug!(
+            "Rejected unauthenticated request to {}",
+            request.uri().path()
+        );
+        Box::pin(async { Ok(unauthorized()) })
+    }
+}
+
+fn unauthorized() -> Response {
+    let mut response = AppError::new(
+        StatusCode::UNAUTHORIZED,
+        ErrorCode::Unauthorized,
+        "missing or invalid API key",
+    )
+    .into_response();
+    response
+        .headers_mut()
+        .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    response
+}
+
+/// Compares two byte strings in time that depends only on their lengths
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+}
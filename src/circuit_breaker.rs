@@ -0,0 +1,190 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+/// Circuit breaker guarding calls to the LLM
+///
+/// After `failure_threshold` consecutive failures the circuit opens and calls fail
+/// fast for `cooldown`. Once the cooldown has passed a single probe call is let
+/// through: success closes the circuit again, failure reopens it for another cooldown.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+/// Externally visible state of a [`CircuitBreaker`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Calls go through; failures are being counted
+    #[default]
+    Closed,
+    /// Calls fail fast until the cooldown has passed
+    Open,
+    /// The cooldown has passed and the next call probes the LLM
+    HalfOpen,
+}
+
+#[derive(Debug)]
+enum BreakerState {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen { probe_started: Option<Instant> },
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            state: Mutex::new(BreakerState::Closed { failures: 0 }),
+        }
+    }
+
+    /// Returns whether a call may be made now
+    ///
+    /// While half-open only one probe is let through; should it never report back,
+    /// another is allowed after a further cooldown.
+    pub fn try_acquire(&self) -> bool {
+        let mut state = self.state.lock().expect("circuit breaker lock poisoned");
+        let now = Instant::now();
+        match *state {
+            BreakerState::Closed { .. } => true,
+            BreakerState::Open { until } if now < until => false,
+            BreakerState::HalfOpen {
+                probe_started: Some(started),
+            } if now.duration_since(started) < self.cooldown => false,
+            BreakerState::Open { .. } | BreakerState::HalfOpen { .. } => {
+                info!("LLM circuit half-open; probing the backend");
+                *state = BreakerState::HalfOpen {
+                    probe_started: Some(now),
+                };
+                true
+            }
+        }
+    }
+
+    /// Records a successful call, closing the circuit
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().expect("circuit breaker lock poisoned");
+        if !matches!(*state, BreakerState::Closed { .. }) {
+            info!("LLM circuit closed");
+        }
+        *state = BreakerState::Closed { failures: 0 };
+    }
+
+    /// Records a failed call, opening the circuit once the threshold is reached
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock().expect("circuit breaker lock poisoned");
+        let failures = match *state {
+            BreakerState::Closed { failures } => failures + 1,
+            BreakerState::HalfOpen { .. } => self.failure_threshold,
+            // A call started before the circuit opened; the cooldown already runs
+            BreakerState::Open { .. } => return,
+        };
+
+        *state = if failures >= self.failure_threshold {
+            warn!(
+                failures,
+                "LLM circuit open; failing fast for {:?}", self.cooldown
+            );
+            BreakerState::Open {
+                until: Instant::now() + self.cooldown,
+            }
+        } else {
+            BreakerState::Closed { failures }
+        };
+    }
+
+    pub fn state(&self) -> CircuitState {
+        let state = self.state.lock().expect("circuit breaker lock poisoned");
+        match *state {
+            BreakerState::Closed { .. } => CircuitState::Closed,
+            BreakerState::Open { until } if Instant::now() < until => CircuitState::Open,
+            BreakerState::Open { .. } | BreakerState::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+}
+
+impl Default for CircuitBreaker {
+    /// Opens after 5 consecutive failures for 30 seconds
+    fn default() -> Self {
+        Self::new(5, Duration::from_secs(30))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::advance;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_consecutive_failures_open_the_circuit() {
+        let breaker = CircuitBreaker::new(3, Duration::from_secs(30));
+
+        for _ in 0..2 {
+            assert!(breaker.try_acquire());
+            breaker.record_failure();
+        }
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        // A success resets the count
+        breaker.record_success();
+        for _ in 0..2 {
+            breaker.record_failure();
+        }
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.try_acquire());
+
+        advance(Duration::from_secs(29)).await;
+        assert!(!breaker.try_acquire());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_half_open_probe_closes_or_reopens() {
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(30));
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        advance(Duration::from_secs(30)).await;
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        // Only one probe at a time
+        assert!(breaker.try_acquire());
+        assert!(!breaker.try_acquire());
+
+        // A failed probe reopens the circuit for a full cooldown
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        advance(Duration::from_secs(15)).await;
+        assert!(!breaker.try_acquire());
+
+        advance(Duration::from_secs(15)).await;
+        assert!(breaker.try_acquire());
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.try_acquire());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_abandoned_probe_is_replaced_after_cooldown() {
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(10));
+        breaker.record_failure();
+        advance(Duration::from_secs(10)).await;
+
+        // The probe never reports back, e.g. because its job was cancelled
+        assert!(breaker.try_acquire());
+        advance(Duration::from_secs(9)).await;
+        assert!(!breaker.try_acquire());
+        advance(Duration::from_secs(1)).await;
+        assert!(breaker.try_acquire());
+    }
+}
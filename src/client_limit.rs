@@ -0,0 +1,230 @@
+use crate::auth::AuthenticatedKey;
+use crate::config::Config;
+use crate::error::{AppError, ErrorCode};
+use crate::throttle::TokenBucket;
+
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    http::{header, HeaderValue, Method, Request, StatusCode},
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tower::{Layer, Service};
+use tracing::debug;
+
+/// Clients tracked before idle ones, whose bucket has refilled, are forgotten
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Limits how many requests each client may submit per minute, independently of the
+/// global LLM throttle, so one noisy client cannot fill the queue for everyone
+///
+/// Clients are told apart by the API key [`crate::auth::ApiKeyLayer`] authenticated
+/// them with, and by the address they connect from otherwise, e.g. without `API_KEYS`
+/// or on a path in `AUTH_EXCLUDE_PATHS` sent without a valid key. The layer must run
+/// inside the API key layer. Only the endpoints that queue work are
+/// limited; status polling and results are not. Each client gets a token bucket, so
+/// a client that was idle may burst up to a minute's worth of submissions.
+#[derive(Clone)]
+pub struct ClientRateLimitLayer {
+    requests_per_minute: Option<u32>,
+    clients: Arc<Mutex<HashMap<String, Arc<TokenBucket>>>>,
+}
+
+impl ClientRateLimitLayer {
+    /// `requests_per_minute` of `None` lets every request through
+    pub fn new(requests_per_minute: Option<u32>) -> Self {
+        Self {
+            requests_per_minute,
+            clients: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Builds the layer from `PER_CLIENT_REQUESTS_PER_MINUTE`
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.per_client_requests_per_minute)
+    }
+
+    /// The identity a request is counted against
+    ///
+    /// Unverified bearer tokens are ignored, so a client cannot get a fresh bucket by
+    /// sending a made-up one.
+    fn client_key(&self, request: &Request<Body>) -> String {
+        if let Some(AuthenticatedKey(api_key)) = request.extensions().get() {
+            return format!("key:{api_key}");
+        }
+
+        match request.extensions().get::<ConnectInfo<SocketAddr>>() {
+            Some(ConnectInfo(addr)) => format!("ip:{}", addr.ip()),
+            None => "ip:unknown".to_string(),
+        }
+    }
+
+    /// Takes a token from the client's bucket, or returns how long until the next one
+    fn try_acquire(&self, client: String, requests_per_minute: u32) -> Result<(), Duration> {
+        let bucket = {
+            let mut clients = self.clients.lock().expect("client limiter lock poisoned");
+            if clients.len() >= PRUNE_THRESHOLD {
+                clients.retain(|_, bucket| !bucket.is_full());
+            }
+            clients
+                .entry(client)
+                .or_insert_with(|| Arc::new(TokenBucket::per_minute(requests_per_minute)))
+                .clone()
+        };
+        bucket.try_acquire()
+    }
+}
+
+/// Whether a request queues work and so counts against the client's limit
+fn is_submission(request: &Request<Body>) -> bool {
+    if request.method() != Method::POST {
+        return false;
+    }
+    let path = request.uri().path();
+    matches!(path, "/validate" | "/validate/batch" | "/validate-sync")
+        || (path.starts_with("/validate/") && path.ends_with("/retry"))
+}
+
+impl<S> Layer<S> for ClientRateLimitLayer {
+    type Service = ClientRateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ClientRateLimitService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ClientRateLimitService<S> {
+    inner: S,
+    layer: ClientRateLimitLayer,
+}
+
+impl<S> Service<Request<Body>> for ClientRateLimitService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let Some(requests_per_minute) = self.layer.requests_per_minute else {
+            return Box::pin(self.inner.call(request));
+        };
+        if !is_submission(&request) {
+            return Box::pin(self.inner.call(request));
+        }
+
+        let client = self.layer.client_key(&request);
+        match self.layer.try_acquire(client, requests_per_minute) {
+            Ok(()) => Box::pin(self.inner.call(request)),
+            Err(wait) => {
+                debug!(
+                    "Client exceeded {} requests per minute on {}",
+                    requests_per_minute,
+                    request.uri().path()
+                );
+                Box::pin(async move { Ok(rate_limited(requests_per_minute, wait)) })
+            }
+        }
+    }
+}
+
+fn rate_limited(requests_per_minute: u32, wait: Duration) -> Response {
+    // Rounded up, so retrying after the advertised delay finds a token
+    let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+    let mut response = AppError::new(
+        StatusCode::TOO_MANY_REQUESTS,
+        ErrorCode::RateLimited,
+        format!("rate limit of {requests_per_minute} requests per minute exceeded"),
+    )
+    .with_details(format!("retry after {retry_after} seconds"))
+    .into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::{service_fn, ServiceExt};
+
+    fn submission_from(ip: &str) -> Request<Body> {
+        let mut request = Request::post("/validate").body(Body::empty()).unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::new(ip.parse().unwrap(), 40000)));
+        request
+    }
+
+    async fn respond(layer: &ClientRateLimitLayer, request: Request<Body>) -> Response {
+        let service = layer.layer(service_fn(|_: Request<Body>| async {
+            Ok::<_, std::convert::Infallible>(StatusCode::ACCEPTED.into_response())
+        }));
+        service.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_limits_each_ip_separately() {
+        let layer = ClientRateLimitLayer::new(Some(2));
+
+        for _ in 0..2 {
+            let response = respond(&layer, submission_from("10.0.0.1")).await;
+            assert_eq!(response.status(), StatusCode::ACCEPTED);
+        }
+        let response = respond(&layer, submission_from("10.0.0.1")).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "30");
+
+        // Another address, and requests that do not queue work, are unaffected
+        let response = respond(&layer, submission_from("10.0.0.2")).await;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let request = Request::get("/status/abc").body(Body::empty()).unwrap();
+        assert_eq!(
+            respond(&layer, request).await.status(),
+            StatusCode::ACCEPTED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_disabled_without_limit() {
+        let layer = ClientRateLimitLayer::new(None);
+        for _ in 0..10 {
+            let response = respond(&layer, submission_from("10.0.0.1")).await;
+            assert_eq!(response.status(), StatusCode::ACCEPTED);
+        }
+    }
+
+    #[test]
+    fn test_is_submission() {
+        let post = |path: &str| Request::post(path).body(Body::empty()).unwrap();
+        assert!(is_submission(&post("/validate")));
+        assert!(is_submission(&post("/validate/batch")));
+        assert!(is_submission(&post("/validate-sync")));
+        assert!(is_submission(&post("/validate/abc/retry")));
+        assert!(!is_submission(&post("/validate/check")));
+        assert!(!is_submission(&post("/inspect")));
+        assert!(!is_submission(
+            &Request::delete("/validate/abc")
+                .body(Body::empty())
+                .unwrap()
+        ));
+    }
+}
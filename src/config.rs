@@ -1,8 +1,25 @@
+use crate::models::IdAlphabet;
 use crate::storage::{StorageError, StorageUri};
+use crate::validation::llm::{LlmError, PromptTemplate, VerdictKeywords};
+use crate::validation::vision::{GenerationOptions, PoolOptions};
+use chrono::FixedOffset;
+use reqwest::Url;
 use serde::Deserialize;
 use std::time::Duration;
 use thiserror::Error;
 
+/// Room left beside a base64 inline image for the rest of the request JSON
+const INLINE_JSON_OVERHEAD: usize = 64 * 1024;
+
+/// Shortest generated processing id; 8 base58 characters allow over 10^14 ids
+const MIN_PROCESSING_ID_LENGTH: usize = 8;
+
+/// Longest generated processing id
+const MAX_PROCESSING_ID_LENGTH: usize = 64;
+
+/// Most requests the worker may validate at once
+const MAX_WORKER_CONCURRENCY: usize = 64;
+
 #[derive(Debug, Error)]
 pub enum ConfigError {
     #[error("Environment variable error: {0}")]
@@ -15,6 +32,62 @@ pub enum ConfigError {
     Io(#[from] std::io::Error),
 }
 
+/// Wire format of the vision LLM API
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LlmBackend {
+    /// OpenAI-compatible `/v1/chat/completions` with `image_url` content parts
+    #[default]
+    #[serde(rename = "openai")]
+    OpenAi,
+    /// Ollama's native `/api/chat` with an `images` array
+    Ollama,
+}
+
+/// How the content check reads the model's reply
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LlmResponseParser {
+    /// The reply must start with `ACCEPTED` or `REJECTED`; anything else rejects
+    #[default]
+    Prefix,
+    /// Also reads prose replies such as "Yes, this image shows..." or "does not match"
+    Keyword,
+    /// Asks the model a second time to classify replies without a verdict prefix
+    Judge,
+}
+
+/// Verdict given to replies containing none of the verdict keywords
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DefaultVerdict {
+    #[default]
+    Rejected,
+    Accepted,
+}
+
+/// How a request listing several alternative `content` descriptions is checked
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentAlternativesStrategy {
+    /// One LLM call per description, each drawing on the throttle
+    #[default]
+    Separate,
+    /// A single LLM call with every description folded into one prompt
+    Combined,
+}
+
+/// Output format of the service logs
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines for terminals
+    #[default]
+    Text,
+    /// One JSON object per line, with event and span fields as attributes
+    Json,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
     #[serde(default = "default_host")]
@@ -30,17 +103,343 @@ pub struct Config {
     #[serde(default = "default_llm_model_name")]
     pub llm_model_name: String,
 
+    /// Comma-separated models requests may choose with `model`, besides `llm_model_name`
+    #[serde(default)]
+    pub llm_allowed_models: Option<String>,
+
+    /// Request format spoken by the LLM API at `llm_api_url`
+    #[serde(default)]
+    pub llm_backend: LlmBackend,
+
+    /// How replies that ignore the ACCEPTED/REJECTED instruction are read
+    #[serde(default)]
+    pub llm_response_parser: LlmResponseParser,
+
+    /// Comma-separated words a reply accepts the image with; unset means `ACCEPTED`
+    #[serde(default)]
+    pub accept_keywords: Option<String>,
+
+    /// Comma-separated words a reply rejects the image with; unset means `REJECTED`
+    #[serde(default)]
+    pub reject_keywords: Option<String>,
+
+    /// Look for verdict keywords anywhere in the reply, not only at its start
+    #[serde(default)]
+    pub verdict_keywords_anywhere: bool,
+
+    /// Verdict for replies with no verdict keyword that the response parser cannot read
+    #[serde(default)]
+    pub default_verdict: DefaultVerdict,
+
+    /// Whether alternative content descriptions get one LLM call each or share one
+    #[serde(default)]
+    pub content_alternatives_strategy: ContentAlternativesStrategy,
+
+    /// Sampling temperature, 0 to 2; low values keep verdicts deterministic
+    #[serde(default = "default_llm_temperature")]
+    pub llm_temperature: f32,
+
+    /// Most tokens the model may generate per answer
+    #[serde(default = "default_llm_num_predict")]
+    pub llm_num_predict: u32,
+
+    /// Attempts per LLM call before the image fails
+    #[serde(default = "default_llm_max_retries")]
+    pub llm_max_retries: u32,
+
+    /// Consecutive failed LLM calls after which the circuit breaker opens
+    #[serde(default = "default_llm_circuit_failure_threshold")]
+    pub llm_circuit_failure_threshold: u32,
+
+    /// How long the open circuit breaker fails LLM calls fast before probing again
+    #[serde(default = "default_llm_circuit_cooldown_seconds")]
+    pub llm_circuit_cooldown_seconds: u64,
+
+    /// Idle connections to the LLM API kept open for reuse
+    #[serde(default = "default_llm_pool_max_idle_per_host")]
+    pub llm_pool_max_idle_per_host: usize,
+
+    /// How long an idle connection to the LLM API is kept open
+    #[serde(default = "default_llm_pool_idle_timeout_seconds")]
+    pub llm_pool_idle_timeout_seconds: u64,
+
+    /// Interval of TCP keep-alive probes on LLM API connections; 0 disables them
+    #[serde(default = "default_llm_tcp_keepalive_seconds")]
+    pub llm_tcp_keepalive_seconds: u64,
+
     #[serde(default = "default_request_timeout_seconds")]
     pub request_timeout_seconds: u64,
 
     #[serde(default = "default_processing_timeout_minutes")]
     pub processing_timeout_minutes: u64,
 
+    /// Largest `timeout-seconds` a request may set to override the processing timeout
+    #[serde(default = "default_max_request_timeout_seconds")]
+    pub max_request_timeout_seconds: u64,
+
+    /// How long completed, failed and cancelled records stay queryable after finishing
+    #[serde(default = "default_result_retention_minutes")]
+    pub result_retention_minutes: u64,
+
+    /// How long an `Idempotency-Key` keeps returning the request it first submitted
+    #[serde(default = "default_idempotency_key_ttl_minutes")]
+    pub idempotency_key_ttl_minutes: u64,
+
+    /// How long image hashes are remembered for `reject_duplicate_within`, which may
+    /// not ask for a longer window
+    #[serde(default = "default_duplicate_retention_minutes")]
+    pub duplicate_retention_minutes: u64,
+
     #[serde(default = "default_queue_size")]
     pub queue_size: usize,
 
     #[serde(default = "default_throttle_requests_per_minute")]
     pub throttle_requests_per_minute: u32,
+
+    /// Number of queued requests the worker validates at the same time
+    #[serde(default = "default_worker_concurrency")]
+    pub worker_concurrency: usize,
+
+    /// Also emit the misspelled legacy `resons` key in results (deprecated)
+    #[serde(default = "default_emit_legacy_reasons_key")]
+    pub emit_legacy_reasons_key: bool,
+
+    /// Maximum decoded size of a base64 image sent inline in the `image` field
+    #[serde(default = "default_max_inline_image_bytes")]
+    pub max_inline_image_bytes: usize,
+
+    /// Comma-separated hosts `image-path` URLs may be downloaded from; unset refuses URLs
+    #[serde(default)]
+    pub allowed_image_hosts: Option<String>,
+
+    /// Maximum size of an image downloaded from an `image-path` URL or a bucket
+    #[serde(default = "default_max_image_download_bytes")]
+    pub max_image_download_bytes: usize,
+
+    /// Time allowed for downloading an image from an `image-path` URL
+    #[serde(default = "default_image_download_timeout_seconds")]
+    pub image_download_timeout_seconds: u64,
+
+    /// Offset (minutes east of UTC) assumed for EXIF timestamps without an offset tag
+    #[serde(default)]
+    pub exif_default_timezone_offset: i32,
+
+    /// Time allowed for reading EXIF metadata from an image before giving up
+    #[serde(default = "default_exif_timeout_seconds")]
+    pub exif_timeout_seconds: u64,
+
+    /// Images whose parsed EXIF is kept in memory for repeated validations; 0 disables
+    /// the cache
+    #[serde(default = "default_exif_cache_entries")]
+    pub exif_cache_entries: usize,
+
+    /// Directory where processing records are persisted across restarts; unset keeps
+    /// records in memory only
+    #[serde(default)]
+    pub persistence_path: Option<String>,
+
+    /// Comma-separated bearer tokens accepted by the API; unset disables authentication
+    #[serde(default)]
+    pub api_keys: Option<String>,
+
+    /// Comma-separated paths (e.g. `/health,/metrics`) served without an API key
+    #[serde(default)]
+    pub auth_exclude_paths: Option<String>,
+
+    /// Submissions each client may make per minute, keyed by API key when `api_keys`
+    /// is set and by IP address otherwise; unset leaves clients unlimited
+    #[serde(default)]
+    pub per_client_requests_per_minute: Option<u32>,
+
+    /// Comma-separated origins allowed to call the API from a browser, or `*` for any;
+    /// unset allows any origin with any method and header
+    #[serde(default)]
+    pub cors_allowed_origins: Option<String>,
+
+    /// Comma-separated methods allowed for cross-origin requests
+    #[serde(default)]
+    pub cors_allowed_methods: Option<String>,
+
+    /// Let cross-origin requests carry credentials such as the `Authorization` header
+    #[serde(default)]
+    pub cors_allow_credentials: bool,
+
+    /// Timeout for each attempt to deliver a completion callback
+    #[serde(default = "default_callback_timeout_seconds")]
+    pub callback_timeout_seconds: u64,
+
+    /// File holding the content-check prompt, with a `{content}` placeholder; unset uses
+    /// the built-in English prompt
+    #[serde(default)]
+    pub prompt_template_path: Option<String>,
+
+    /// Convert HEIC/HEIF images to JPEG before sending them to the LLM
+    #[serde(default)]
+    pub transcode_heic: bool,
+
+    /// External converter invoked as `<command> <input> <output.jpg>`
+    #[serde(default = "default_heic_transcode_command")]
+    pub heic_transcode_command: String,
+
+    /// Convert AVIF images to PNG before sending them to the LLM
+    #[serde(default)]
+    pub transcode_avif: bool,
+
+    /// External converter invoked as `<command> <input> <output.png>`
+    #[serde(default = "default_avif_transcode_command")]
+    pub avif_transcode_command: String,
+
+    /// Downscale images so their longer side is at most this many pixels before sending
+    /// them to the LLM; unset sends them at full size
+    #[serde(default)]
+    pub max_image_dimension: Option<u32>,
+
+    /// Rotate images upright by their EXIF orientation before sending them to the LLM
+    #[serde(default = "default_auto_orient")]
+    pub auto_orient: bool,
+
+    /// Send the image's embedded EXIF thumbnail to the LLM instead of the image when
+    /// the thumbnail is at least `exif_thumbnail_min_dimension` pixels on its longer side
+    #[serde(default)]
+    pub use_exif_thumbnail_if_present: bool,
+
+    /// Smallest longer side, in pixels, of an EXIF thumbnail sent instead of the image
+    #[serde(default = "default_exif_thumbnail_min_dimension")]
+    pub exif_thumbnail_min_dimension: u32,
+
+    /// Keep the image sent to the LLM for each request, for `GET /debug/{id}/image`,
+    /// as long as the request's results
+    #[serde(default)]
+    pub debug_retain_processed_images: bool,
+
+    /// Return the model's explanation of its content verdict in the results
+    #[serde(default)]
+    pub include_llm_explanation: bool,
+
+    /// List the storage bucket at startup and refuse to start if it is unreachable
+    #[serde(default)]
+    pub storage_verify_on_start: bool,
+
+    /// Skip checking at startup that the LLM backend serves `llm_model_name`
+    #[serde(default)]
+    pub skip_model_check: bool,
+
+    /// How long shutdown waits for in-progress requests to finish
+    #[serde(default = "default_shutdown_grace_seconds")]
+    pub shutdown_grace_seconds: u64,
+
+    /// Largest request body accepted by any endpoint; must leave room for a base64
+    /// inline image of `max_inline_image_bytes`
+    #[serde(default = "default_max_request_body_bytes")]
+    pub max_request_body_bytes: usize,
+
+    /// Times a job is re-run after a transient storage or LLM failure
+    #[serde(default = "default_job_max_retries")]
+    pub job_max_retries: u32,
+
+    /// Delay before the first job retry; doubles with each further attempt
+    #[serde(default = "default_job_retry_backoff_seconds")]
+    pub job_retry_backoff_seconds: u64,
+
+    /// `text` for human-readable logs, `json` for log aggregators
+    #[serde(default)]
+    pub log_format: LogFormat,
+
+    /// Nominatim-compatible search endpoint used to resolve `place_name` locations;
+    /// unset rejects images whose location is given by name
+    #[serde(default)]
+    pub geocoder_url: Option<String>,
+
+    /// Length of generated processing ids; unset keeps UUIDs
+    #[serde(default)]
+    pub processing_id_length: Option<usize>,
+
+    /// Characters of generated processing ids when `processing_id_length` is set
+    #[serde(default)]
+    pub processing_id_alphabet: IdAlphabet,
+
+    /// Most processing ids `POST /status/batch` looks up in one request
+    #[serde(default = "default_max_status_batch_ids")]
+    pub max_status_batch_ids: usize,
+}
+
+/// Defaults matching the environment defaults, for programmatic construction.
+///
+/// The required `image_base_dir` and `llm_api_url` are left empty and must be set
+/// before the configuration passes validation.
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            host: default_host(),
+            port: default_port(),
+            image_base_dir: String::new(),
+            llm_api_url: String::new(),
+            llm_model_name: default_llm_model_name(),
+            llm_allowed_models: None,
+            llm_backend: LlmBackend::default(),
+            llm_response_parser: LlmResponseParser::default(),
+            accept_keywords: None,
+            reject_keywords: None,
+            verdict_keywords_anywhere: false,
+            default_verdict: DefaultVerdict::default(),
+            content_alternatives_strategy: ContentAlternativesStrategy::default(),
+            llm_temperature: default_llm_temperature(),
+            llm_num_predict: default_llm_num_predict(),
+            llm_max_retries: default_llm_max_retries(),
+            llm_circuit_failure_threshold: default_llm_circuit_failure_threshold(),
+            llm_circuit_cooldown_seconds: default_llm_circuit_cooldown_seconds(),
+            llm_pool_max_idle_per_host: default_llm_pool_max_idle_per_host(),
+            llm_pool_idle_timeout_seconds: default_llm_pool_idle_timeout_seconds(),
+            llm_tcp_keepalive_seconds: default_llm_tcp_keepalive_seconds(),
+            request_timeout_seconds: default_request_timeout_seconds(),
+            processing_timeout_minutes: default_processing_timeout_minutes(),
+            max_request_timeout_seconds: default_max_request_timeout_seconds(),
+            result_retention_minutes: default_result_retention_minutes(),
+            idempotency_key_ttl_minutes: default_idempotency_key_ttl_minutes(),
+            duplicate_retention_minutes: default_duplicate_retention_minutes(),
+            queue_size: default_queue_size(),
+            throttle_requests_per_minute: default_throttle_requests_per_minute(),
+            worker_concurrency: default_worker_concurrency(),
+            emit_legacy_reasons_key: default_emit_legacy_reasons_key(),
+            max_inline_image_bytes: default_max_inline_image_bytes(),
+            allowed_image_hosts: None,
+            max_image_download_bytes: default_max_image_download_bytes(),
+            image_download_timeout_seconds: default_image_download_timeout_seconds(),
+            exif_default_timezone_offset: 0,
+            exif_timeout_seconds: default_exif_timeout_seconds(),
+            exif_cache_entries: default_exif_cache_entries(),
+            persistence_path: None,
+            api_keys: None,
+            auth_exclude_paths: None,
+            per_client_requests_per_minute: None,
+            cors_allowed_origins: None,
+            cors_allowed_methods: None,
+            cors_allow_credentials: false,
+            callback_timeout_seconds: default_callback_timeout_seconds(),
+            prompt_template_path: None,
+            transcode_heic: false,
+            heic_transcode_command: default_heic_transcode_command(),
+            transcode_avif: false,
+            avif_transcode_command: default_avif_transcode_command(),
+            max_image_dimension: None,
+            auto_orient: default_auto_orient(),
+            use_exif_thumbnail_if_present: false,
+            debug_retain_processed_images: false,
+            include_llm_explanation: false,
+            exif_thumbnail_min_dimension: default_exif_thumbnail_min_dimension(),
+            storage_verify_on_start: false,
+            skip_model_check: false,
+            shutdown_grace_seconds: default_shutdown_grace_seconds(),
+            max_request_body_bytes: default_max_request_body_bytes(),
+            job_max_retries: default_job_max_retries(),
+            job_retry_backoff_seconds: default_job_retry_backoff_seconds(),
+            log_format: LogFormat::default(),
+            geocoder_url: None,
+            processing_id_length: None,
+            processing_id_alphabet: IdAlphabet::default(),
+            max_status_batch_ids: default_max_status_batch_ids(),
+        }
+    }
 }
 
 impl Config {
@@ -52,10 +451,71 @@ impl Config {
         Ok(config)
     }
 
+    /// Reads the configuration again for a reload on SIGHUP
+    ///
+    /// Unlike at startup, values in `.env` override those already in the environment,
+    /// since that is where a running service's settings are changed.
+    pub fn reload_from_env() -> Result<Self, ConfigError> {
+        dotenvy::dotenv_override().ok();
+
+        let config: Config = envy::from_env()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// This config with the settings a running service reloads taken from `reloaded`
+    ///
+    /// Only `THROTTLE_REQUESTS_PER_MINUTE`, `LLM_MODEL_NAME` and `PROMPT_TEMPLATE_PATH`
+    /// are reloaded. Also returns the restart-only settings, such as `PORT`, that differ
+    /// in `reloaded` and are ignored.
+    pub fn with_reloaded(&self, reloaded: &Config) -> (Config, Vec<&'static str>) {
+        let ignored = [
+            ("HOST", self.host != reloaded.host),
+            ("PORT", self.port != reloaded.port),
+            (
+                "IMAGE_BASE_DIR",
+                self.image_base_dir != reloaded.image_base_dir,
+            ),
+            ("LLM_API_URL", self.llm_api_url != reloaded.llm_api_url),
+            ("LLM_BACKEND", self.llm_backend != reloaded.llm_backend),
+            ("QUEUE_SIZE", self.queue_size != reloaded.queue_size),
+            (
+                "WORKER_CONCURRENCY",
+                self.worker_concurrency != reloaded.worker_concurrency,
+            ),
+            (
+                "PERSISTENCE_PATH",
+                self.persistence_path != reloaded.persistence_path,
+            ),
+            ("API_KEYS", self.api_keys != reloaded.api_keys),
+            (
+                "MAX_REQUEST_BODY_BYTES",
+                self.max_request_body_bytes != reloaded.max_request_body_bytes,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(name, changed)| changed.then_some(name))
+        .collect();
+
+        let config = Config {
+            throttle_requests_per_minute: reloaded.throttle_requests_per_minute,
+            llm_model_name: reloaded.llm_model_name.clone(),
+            prompt_template_path: reloaded.prompt_template_path.clone(),
+            ..self.clone()
+        };
+        (config, ignored)
+    }
+
     pub fn get_storage_uri(&self) -> Result<StorageUri, StorageError> {
         StorageUri::parse(&self.image_base_dir)
     }
 
+    /// Checks that the storage location is reachable, including credentials for buckets
+    pub async fn verify_storage(&self) -> Result<(), ConfigError> {
+        self.get_storage_uri()?.verify().await?;
+        Ok(())
+    }
+
     #[cfg(test)]
     fn from_env_no_dotenv() -> Result<Self, ConfigError> {
         let config: Config = envy::from_env()?;
@@ -83,6 +543,102 @@ impl Config {
             )));
         }
 
+        if !(0.0..=2.0).contains(&self.llm_temperature) {
+            return Err(ConfigError::Validation(format!(
+                "LLM temperature must be between 0 and 2, got: {}",
+                self.llm_temperature
+            )));
+        }
+
+        if self.llm_num_predict == 0 {
+            return Err(ConfigError::Validation(
+                "LLM num_predict must be greater than 0".into(),
+            ));
+        }
+
+        if self.llm_max_retries == 0 {
+            return Err(ConfigError::Validation(
+                "LLM max retries must be at least 1".into(),
+            ));
+        }
+
+        if self.llm_circuit_failure_threshold == 0 {
+            return Err(ConfigError::Validation(
+                "LLM circuit failure threshold must be at least 1".into(),
+            ));
+        }
+
+        if self.llm_circuit_cooldown_seconds == 0 {
+            return Err(ConfigError::Validation(
+                "LLM circuit cooldown must be greater than 0".into(),
+            ));
+        }
+
+        if self.llm_pool_idle_timeout_seconds == 0 {
+            return Err(ConfigError::Validation(
+                "LLM pool idle timeout must be greater than 0".into(),
+            ));
+        }
+
+        if let Some(geocoder_url) = &self.geocoder_url {
+            match Url::parse(geocoder_url) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+                _ => {
+                    return Err(ConfigError::Validation(format!(
+                        "Geocoder URL must be an http or https URL, got: {geocoder_url}"
+                    )))
+                }
+            }
+        }
+
+        let cors_origins = self.cors_allowed_origins();
+        for origin in &cors_origins {
+            let valid = origin == "*"
+                || Url::parse(origin).is_ok_and(|url| {
+                    matches!(url.scheme(), "http" | "https")
+                        && url.origin().ascii_serialization() == *origin
+                });
+            if !valid {
+                return Err(ConfigError::Validation(format!(
+                    "CORS allowed origins must be `*` or http(s) origins such as https://app.example.com, got: {origin}"
+                )));
+            }
+        }
+        if cors_origins.iter().any(|origin| origin == "*") {
+            if cors_origins.len() > 1 {
+                return Err(ConfigError::Validation(
+                    "CORS allowed origin `*` cannot be combined with other origins".into(),
+                ));
+            }
+            if self.cors_allow_credentials {
+                return Err(ConfigError::Validation(
+                    "CORS credentials cannot be allowed for any origin (`*`)".into(),
+                ));
+            }
+        }
+        for method in self.cors_allowed_methods() {
+            if method.parse::<axum::http::Method>().is_err() {
+                return Err(ConfigError::Validation(format!(
+                    "invalid CORS allowed method: {method}"
+                )));
+            }
+        }
+
+        if let Some(length) = self.processing_id_length {
+            if !(MIN_PROCESSING_ID_LENGTH..=MAX_PROCESSING_ID_LENGTH).contains(&length) {
+                return Err(ConfigError::Validation(format!(
+                    "Processing id length must be between {} and {}, got: {}",
+                    MIN_PROCESSING_ID_LENGTH, MAX_PROCESSING_ID_LENGTH, length
+                )));
+            }
+        }
+
+        if self.per_client_requests_per_minute == Some(0) {
+            return Err(ConfigError::Validation(
+                "Per-client requests per minute must be greater than 0".into(),
+            ));
+        }
+
         // Validate reasonable queue size
         if self.queue_size == 0 || self.queue_size > 10000 {
             return Err(ConfigError::Validation(format!(
@@ -98,52 +654,418 @@ impl Config {
             ));
         }
 
+        if self.worker_concurrency == 0 || self.worker_concurrency > MAX_WORKER_CONCURRENCY {
+            return Err(ConfigError::Validation(format!(
+                "Worker concurrency must be between 1 and {}, got: {}",
+                MAX_WORKER_CONCURRENCY, self.worker_concurrency
+            )));
+        }
+
+        if self.result_retention_minutes == 0 {
+            return Err(ConfigError::Validation(
+                "Result retention minutes must be greater than 0".into(),
+            ));
+        }
+
+        if self.idempotency_key_ttl_minutes == 0 {
+            return Err(ConfigError::Validation(
+                "Idempotency key TTL minutes must be greater than 0".into(),
+            ));
+        }
+
+        if self.duplicate_retention_minutes == 0 {
+            return Err(ConfigError::Validation(
+                "Duplicate retention minutes must be greater than 0".into(),
+            ));
+        }
+
+        if self.max_image_dimension == Some(0) {
+            return Err(ConfigError::Validation(
+                "Max image dimension must be greater than 0".into(),
+            ));
+        }
+
+        if self.image_download_timeout_seconds == 0 {
+            return Err(ConfigError::Validation(
+                "Image download timeout must be greater than 0".into(),
+            ));
+        }
+
+        if self.exif_thumbnail_min_dimension == 0 {
+            return Err(ConfigError::Validation(
+                "EXIF thumbnail min dimension must be greater than 0".into(),
+            ));
+        }
+
+        // Inline images grow by a third when base64 encoded and still have to fit
+        // through the body limit, with headroom for the rest of the JSON
+        let inline_body_bytes = self.max_inline_image_bytes.div_ceil(3) * 4 + INLINE_JSON_OVERHEAD;
+        if self.max_request_body_bytes < inline_body_bytes {
+            return Err(ConfigError::Validation(format!(
+                "Max request body bytes must be at least {} to fit a base64 inline image of {} bytes, got: {}",
+                inline_body_bytes, self.max_inline_image_bytes, self.max_request_body_bytes
+            )));
+        }
+
+        // Validate EXIF timezone offset falls within real-world offsets (UTC-12 to UTC+14)
+        if !(-720..=840).contains(&self.exif_default_timezone_offset) {
+            return Err(ConfigError::Validation(format!(
+                "EXIF default timezone offset must be between -720 and 840 minutes, got: {}",
+                self.exif_default_timezone_offset
+            )));
+        }
+
+        if self.max_request_timeout_seconds == 0 {
+            return Err(ConfigError::Validation(
+                "Max request timeout must be greater than 0".into(),
+            ));
+        }
+
+        if self.max_status_batch_ids == 0 {
+            return Err(ConfigError::Validation(
+                "Max status batch ids must be greater than 0".into(),
+            ));
+        }
+
+        if self.exif_timeout_seconds == 0 {
+            return Err(ConfigError::Validation(
+                "EXIF timeout must be greater than 0".into(),
+            ));
+        }
+
+        // Validate the prompt template loads and has its placeholder
+        if let Err(e) = self.prompt_template() {
+            return Err(ConfigError::Validation(format!(
+                "Prompt template {} is unusable: {}",
+                self.prompt_template_path.as_deref().unwrap_or_default(),
+                e
+            )));
+        }
+
         Ok(())
     }
 
+    /// Offset applied to EXIF timestamps that do not record their own
+    pub fn exif_default_offset(&self) -> FixedOffset {
+        FixedOffset::east_opt(self.exif_default_timezone_offset * 60)
+            .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap())
+    }
+
+    /// The models from `LLM_ALLOWED_MODELS`, trimmed, with empty entries dropped
+    pub fn llm_allowed_models(&self) -> Vec<String> {
+        split_list(self.llm_allowed_models.as_deref())
+    }
+
+    /// Whether a request may ask for `model`; the default model is always allowed
+    pub fn is_model_allowed(&self, model: &str) -> bool {
+        model == self.llm_model_name || self.llm_allowed_models().iter().any(|m| m == model)
+    }
+
+    /// The API keys from `API_KEYS`, trimmed, with empty entries dropped
+    pub fn api_keys(&self) -> Vec<String> {
+        split_list(self.api_keys.as_deref())
+    }
+
+    /// The paths from `AUTH_EXCLUDE_PATHS`, trimmed, with empty entries dropped
+    pub fn auth_exclude_paths(&self) -> Vec<String> {
+        split_list(self.auth_exclude_paths.as_deref())
+    }
+
+    /// The origins from `CORS_ALLOWED_ORIGINS`, trimmed, with empty entries dropped
+    pub fn cors_allowed_origins(&self) -> Vec<String> {
+        split_list(self.cors_allowed_origins.as_deref())
+    }
+
+    /// The upper-cased methods from `CORS_ALLOWED_METHODS`, or those the API serves
+    pub fn cors_allowed_methods(&self) -> Vec<String> {
+        let methods = split_list(self.cors_allowed_methods.as_deref());
+        if methods.is_empty() {
+            return ["GET", "POST", "DELETE"].map(String::from).to_vec();
+        }
+        methods.iter().map(|method| method.to_uppercase()).collect()
+    }
+
+    /// The template from `PROMPT_TEMPLATE_PATH`, or the default prompt when unset
+    pub fn prompt_template(&self) -> Result<PromptTemplate, LlmError> {
+        match &self.prompt_template_path {
+            Some(path) => PromptTemplate::from_file(path),
+            None => Ok(PromptTemplate::default()),
+        }
+    }
+
+    pub fn generation_options(&self) -> GenerationOptions {
+        GenerationOptions {
+            temperature: self.llm_temperature,
+            max_tokens: self.llm_num_predict,
+        }
+    }
+
+    /// Connection pool settings of the HTTP client used for LLM calls
+    pub fn pool_options(&self) -> PoolOptions {
+        PoolOptions {
+            max_idle_per_host: self.llm_pool_max_idle_per_host,
+            idle_timeout: Duration::from_secs(self.llm_pool_idle_timeout_seconds),
+            tcp_keepalive: (self.llm_tcp_keepalive_seconds > 0)
+                .then(|| Duration::from_secs(self.llm_tcp_keepalive_seconds)),
+        }
+    }
+
+    /// The verdict keywords, with `ACCEPTED` and `REJECTED` when a list is unset or empty
+    pub fn verdict_keywords(&self) -> VerdictKeywords {
+        let keywords = |value: Option<&str>, default: &str| {
+            let keywords = split_list(value);
+            if keywords.is_empty() {
+                vec![default.to_string()]
+            } else {
+                keywords
+            }
+        };
+        let verdict_keywords = VerdictKeywords::new(
+            keywords(self.accept_keywords.as_deref(), "ACCEPTED"),
+            keywords(self.reject_keywords.as_deref(), "REJECTED"),
+        )
+        .with_default_verdict(self.default_verdict == DefaultVerdict::Accepted);
+        if self.verdict_keywords_anywhere {
+            verdict_keywords.anywhere()
+        } else {
+            verdict_keywords
+        }
+    }
+
+    pub fn llm_circuit_cooldown(&self) -> Duration {
+        Duration::from_secs(self.llm_circuit_cooldown_seconds)
+    }
+
     pub fn request_timeout(&self) -> Duration {
         Duration::from_secs(self.request_timeout_seconds)
     }
 
+    /// The hosts from `ALLOWED_IMAGE_HOSTS`, trimmed, with empty entries dropped
+    pub fn allowed_image_hosts(&self) -> Vec<String> {
+        split_list(self.allowed_image_hosts.as_deref())
+    }
+
+    pub fn image_download_timeout(&self) -> Duration {
+        Duration::from_secs(self.image_download_timeout_seconds)
+    }
+
+    pub fn exif_timeout(&self) -> Duration {
+        Duration::from_secs(self.exif_timeout_seconds)
+    }
+
+    pub fn callback_timeout(&self) -> Duration {
+        Duration::from_secs(self.callback_timeout_seconds)
+    }
+
+    pub fn shutdown_grace(&self) -> Duration {
+        Duration::from_secs(self.shutdown_grace_seconds)
+    }
+
+    /// Backoff before re-running a job that has failed `attempts` times
+    pub fn job_retry_backoff(&self, attempts: u32) -> Duration {
+        Duration::from_secs(self.job_retry_backoff_seconds)
+            .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+    }
+
     pub fn processing_timeout(&self) -> Duration {
         Duration::from_secs(self.processing_timeout_minutes * 60)
     }
 
+    pub fn max_request_timeout(&self) -> Duration {
+        Duration::from_secs(self.max_request_timeout_seconds)
+    }
+
+    /// Processing timeout for a request setting `timeout_seconds`, clamped to
+    /// `MAX_REQUEST_TIMEOUT_SECONDS`; requests without one get the global timeout
+    pub fn processing_timeout_for(&self, timeout_seconds: Option<u64>) -> Duration {
+        match timeout_seconds {
+            Some(seconds) => Duration::from_secs(seconds).min(self.max_request_timeout()),
+            None => self.processing_timeout(),
+        }
+    }
+
+    /// The longest any request may be processed, with or without its own timeout
+    pub fn longest_processing_timeout(&self) -> Duration {
+        self.processing_timeout().max(self.max_request_timeout())
+    }
+
+    pub fn result_retention(&self) -> Duration {
+        Duration::from_secs(self.result_retention_minutes * 60)
+    }
+
+    pub fn idempotency_key_ttl(&self) -> Duration {
+        Duration::from_secs(self.idempotency_key_ttl_minutes * 60)
+    }
+
+    pub fn duplicate_retention(&self) -> Duration {
+        Duration::from_secs(self.duplicate_retention_minutes * 60)
+    }
+
+    /// Minimum spacing of LLM-bound requests at the sustained throttle rate
+    ///
+    /// Bursts after idle periods may start closer together. A rate of 0, which
+    /// validation rejects, is treated as 1 rather than dividing by zero.
     pub fn throttle_interval(&self) -> Duration {
-        Duration::from_secs(60 / self.throttle_requests_per_minute as u64)
+        Duration::from_secs_f64(60.0 / f64::from(self.throttle_requests_per_minute.max(1)))
+    }
+
+    pub fn server_address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
     }
+}
+
+fn split_list(value: Option<&str>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn default_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_port() -> u16 {
+    3000
+}
+
+fn default_llm_model_name() -> String {
+    "llava:7b".to_string()
+}
+
+fn default_llm_temperature() -> f32 {
+    0.1
+}
+
+fn default_llm_num_predict() -> u32 {
+    500
+}
+
+fn default_llm_max_retries() -> u32 {
+    3
+}
+
+fn default_llm_circuit_failure_threshold() -> u32 {
+    5
+}
+
+fn default_llm_circuit_cooldown_seconds() -> u64 {
+    30
+}
+
+fn default_llm_pool_max_idle_per_host() -> usize {
+    32
+}
+
+fn default_llm_pool_idle_timeout_seconds() -> u64 {
+    90
+}
+
+fn default_llm_tcp_keepalive_seconds() -> u64 {
+    60
+}
+
+fn default_request_timeout_seconds() -> u64 {
+    30
+}
+
+fn default_processing_timeout_minutes() -> u64 {
+    5
+}
+
+fn default_max_request_timeout_seconds() -> u64 {
+    30 * 60
+}
+
+fn default_result_retention_minutes() -> u64 {
+    60
+}
+
+fn default_idempotency_key_ttl_minutes() -> u64 {
+    24 * 60
+}
+
+fn default_duplicate_retention_minutes() -> u64 {
+    24 * 60
+}
+
+fn default_queue_size() -> usize {
+    100
+}
+
+fn default_worker_concurrency() -> usize {
+    1
+}
+
+fn default_throttle_requests_per_minute() -> u32 {
+    60
+}
+
+fn default_emit_legacy_reasons_key() -> bool {
+    true
+}
+
+fn default_max_inline_image_bytes() -> usize {
+    10 * 1024 * 1024
+}
+
+fn default_max_image_download_bytes() -> usize {
+    20 * 1024 * 1024
+}
+
+fn default_image_download_timeout_seconds() -> u64 {
+    30
+}
+
+fn default_exif_timeout_seconds() -> u64 {
+    10
+}
 
-    pub fn server_address(&self) -> String {
-        format!("{}:{}", self.host, self.port)
-    }
+fn default_exif_cache_entries() -> usize {
+    1024
 }
 
-fn default_host() -> String {
-    "127.0.0.1".to_string()
+fn default_callback_timeout_seconds() -> u64 {
+    10
 }
 
-fn default_port() -> u16 {
-    3000
+fn default_auto_orient() -> bool {
+    true
 }
 
-fn default_llm_model_name() -> String {
-    "llava:7b".to_string()
+fn default_exif_thumbnail_min_dimension() -> u32 {
+    160
 }
 
-fn default_request_timeout_seconds() -> u64 {
-    30
+fn default_job_max_retries() -> u32 {
+    2
 }
 
-fn default_processing_timeout_minutes() -> u64 {
+fn default_job_retry_backoff_seconds() -> u64 {
     5
 }
 
-fn default_queue_size() -> usize {
+fn default_max_request_body_bytes() -> usize {
+    16 * 1024 * 1024
+}
+
+fn default_max_status_batch_ids() -> usize {
     100
 }
 
-fn default_throttle_requests_per_minute() -> u32 {
-    60
+fn default_shutdown_grace_seconds() -> u64 {
+    10
+}
+
+fn default_heic_transcode_command() -> String {
+    "heif-convert".to_string()
+}
+
+fn default_avif_transcode_command() -> String {
+    "avifdec".to_string()
 }
 
 #[cfg(test)]
@@ -158,11 +1080,66 @@ mod tests {
         env::remove_var("HOST");
         env::remove_var("PORT");
         env::remove_var("LLM_MODEL_NAME");
+        env::remove_var("LLM_ALLOWED_MODELS");
         env::remove_var("REQUEST_TIMEOUT_SECONDS");
         env::remove_var("PROCESSING_TIMEOUT_MINUTES");
+        env::remove_var("MAX_REQUEST_TIMEOUT_SECONDS");
+        env::remove_var("RESULT_RETENTION_MINUTES");
+        env::remove_var("IDEMPOTENCY_KEY_TTL_MINUTES");
         env::remove_var("THROTTLE_REQUESTS_PER_MINUTE");
+        env::remove_var("WORKER_CONCURRENCY");
         env::remove_var("IMAGE_BASE_DIR");
         env::remove_var("LLM_API_URL");
+        env::remove_var("EMIT_LEGACY_REASONS_KEY");
+        env::remove_var("MAX_INLINE_IMAGE_BYTES");
+        env::remove_var("ALLOWED_IMAGE_HOSTS");
+        env::remove_var("MAX_IMAGE_DOWNLOAD_BYTES");
+        env::remove_var("IMAGE_DOWNLOAD_TIMEOUT_SECONDS");
+        env::remove_var("MAX_STATUS_BATCH_IDS");
+        env::remove_var("EXIF_DEFAULT_TIMEZONE_OFFSET");
+        env::remove_var("LLM_BACKEND");
+        env::remove_var("LLM_RESPONSE_PARSER");
+        env::remove_var("LLM_TEMPERATURE");
+        env::remove_var("LLM_NUM_PREDICT");
+        env::remove_var("LLM_MAX_RETRIES");
+        env::remove_var("LLM_CIRCUIT_FAILURE_THRESHOLD");
+        env::remove_var("LLM_CIRCUIT_COOLDOWN_SECONDS");
+        env::remove_var("LLM_POOL_MAX_IDLE_PER_HOST");
+        env::remove_var("LLM_POOL_IDLE_TIMEOUT_SECONDS");
+        env::remove_var("LLM_TCP_KEEPALIVE_SECONDS");
+        env::remove_var("PERSISTENCE_PATH");
+        env::remove_var("API_KEYS");
+        env::remove_var("AUTH_EXCLUDE_PATHS");
+        env::remove_var("PER_CLIENT_REQUESTS_PER_MINUTE");
+        env::remove_var("CORS_ALLOWED_ORIGINS");
+        env::remove_var("CORS_ALLOWED_METHODS");
+        env::remove_var("CORS_ALLOW_CREDENTIALS");
+        env::remove_var("EXIF_TIMEOUT_SECONDS");
+        env::remove_var("EXIF_CACHE_ENTRIES");
+        env::remove_var("CALLBACK_TIMEOUT_SECONDS");
+        env::remove_var("PROMPT_TEMPLATE_PATH");
+        env::remove_var("TRANSCODE_HEIC");
+        env::remove_var("HEIC_TRANSCODE_COMMAND");
+        env::remove_var("TRANSCODE_AVIF");
+        env::remove_var("AVIF_TRANSCODE_COMMAND");
+        env::remove_var("MAX_IMAGE_DIMENSION");
+        env::remove_var("AUTO_ORIENT");
+        env::remove_var("USE_EXIF_THUMBNAIL_IF_PRESENT");
+        env::remove_var("DEBUG_RETAIN_PROCESSED_IMAGES");
+        env::remove_var("INCLUDE_LLM_EXPLANATION");
+        env::remove_var("EXIF_THUMBNAIL_MIN_DIMENSION");
+        env::remove_var("STORAGE_VERIFY_ON_START");
+        env::remove_var("SKIP_MODEL_CHECK");
+        env::remove_var("SHUTDOWN_GRACE_SECONDS");
+        env::remove_var("MAX_REQUEST_BODY_BYTES");
+        env::remove_var("JOB_MAX_RETRIES");
+        env::remove_var("JOB_RETRY_BACKOFF_SECONDS");
+        env::remove_var("LOG_FORMAT");
+        env::remove_var("GEOCODER_URL");
+        env::remove_var("PROCESSING_ID_LENGTH");
+        env::remove_var("PROCESSING_ID_ALPHABET");
+        env::remove_var("DUPLICATE_RETENTION_MINUTES");
+        env::remove_var("CONTENT_ALTERNATIVES_STRATEGY");
 
         // Set minimal required env vars
         env::set_var("IMAGE_BASE_DIR", "/tmp");
@@ -174,10 +1151,198 @@ mod tests {
         assert_eq!(config.host, "127.0.0.1");
         assert_eq!(config.port, 3000);
         assert_eq!(config.llm_model_name, "llava:7b");
+        assert!(config.llm_allowed_models().is_empty());
         assert_eq!(config.request_timeout_seconds, 30);
         assert_eq!(config.processing_timeout_minutes, 5);
+        assert_eq!(config.max_request_timeout(), Duration::from_secs(1800));
+        assert_eq!(config.result_retention(), Duration::from_secs(3600));
+        assert_eq!(config.idempotency_key_ttl(), Duration::from_secs(24 * 3600));
+        assert_eq!(config.duplicate_retention(), Duration::from_secs(24 * 3600));
         assert_eq!(config.queue_size, 100);
         assert_eq!(config.throttle_requests_per_minute, 60);
+        assert_eq!(config.worker_concurrency, 1);
+        assert!(config.emit_legacy_reasons_key);
+        assert_eq!(config.max_inline_image_bytes, 10 * 1024 * 1024);
+        assert!(config.allowed_image_hosts().is_empty());
+        assert_eq!(config.max_image_download_bytes, 20 * 1024 * 1024);
+        assert_eq!(config.image_download_timeout(), Duration::from_secs(30));
+        assert_eq!(config.exif_default_timezone_offset, 0);
+        assert_eq!(config.exif_timeout(), Duration::from_secs(10));
+        assert_eq!(config.exif_cache_entries, 1024);
+        assert_eq!(config.llm_backend, LlmBackend::OpenAi);
+        assert_eq!(config.llm_response_parser, LlmResponseParser::Prefix);
+        assert_eq!(config.generation_options(), GenerationOptions::default());
+        assert_eq!(config.pool_options(), PoolOptions::default());
+        assert_eq!(config.llm_max_retries, 3);
+        assert_eq!(config.llm_circuit_failure_threshold, 5);
+        assert_eq!(config.llm_circuit_cooldown(), Duration::from_secs(30));
+        assert!(config.persistence_path.is_none());
+        assert!(config.api_keys().is_empty());
+        assert!(config.auth_exclude_paths().is_empty());
+        assert!(config.per_client_requests_per_minute.is_none());
+        assert!(config.cors_allowed_origins().is_empty());
+        assert_eq!(config.cors_allowed_methods(), vec!["GET", "POST", "DELETE"]);
+        assert!(!config.cors_allow_credentials);
+        assert_eq!(config.callback_timeout(), Duration::from_secs(10));
+        assert!(config.prompt_template_path.is_none());
+        assert!(!config.transcode_heic);
+        assert_eq!(config.heic_transcode_command, "heif-convert");
+        assert!(!config.transcode_avif);
+        assert_eq!(config.avif_transcode_command, "avifdec");
+        assert!(config.max_image_dimension.is_none());
+        assert!(config.auto_orient);
+        assert!(!config.use_exif_thumbnail_if_present);
+        assert!(!config.debug_retain_processed_images);
+        assert!(!config.include_llm_explanation);
+        assert_eq!(config.exif_thumbnail_min_dimension, 160);
+        assert!(!config.storage_verify_on_start);
+        assert!(!config.skip_model_check);
+        assert_eq!(config.shutdown_grace(), Duration::from_secs(10));
+        assert_eq!(config.max_request_body_bytes, 16 * 1024 * 1024);
+        assert_eq!(config.job_max_retries, 2);
+        assert_eq!(config.job_retry_backoff(1), Duration::from_secs(5));
+        assert_eq!(config.log_format, LogFormat::Text);
+        assert!(config.geocoder_url.is_none());
+        assert!(config.processing_id_length.is_none());
+        assert_eq!(config.processing_id_alphabet, IdAlphabet::Base58);
+        assert_eq!(config.max_status_batch_ids, 100);
+        assert_eq!(config.job_retry_backoff(3), Duration::from_secs(20));
+        assert_eq!(
+            config.content_alternatives_strategy,
+            ContentAlternativesStrategy::Separate
+        );
+    }
+
+    #[test]
+    fn test_throttle_interval() {
+        let interval = |throttle_requests_per_minute| {
+            Config {
+                throttle_requests_per_minute,
+                ..Config::default()
+            }
+            .throttle_interval()
+        };
+
+        assert_eq!(interval(1), Duration::from_secs(60));
+        assert_eq!(interval(60), Duration::from_secs(1));
+        assert_eq!(interval(120), Duration::from_millis(500));
+        assert_eq!(interval(90), Duration::from_secs_f64(60.0 / 90.0));
+        assert_eq!(interval(0), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_config_with_reloaded() {
+        let running = Config {
+            throttle_requests_per_minute: 30,
+            queue_size: 100,
+            ..Config::default()
+        };
+        let reloaded = Config {
+            throttle_requests_per_minute: 120,
+            llm_model_name: "llava:34b".to_string(),
+            prompt_template_path: Some("/etc/image-checker/prompt.txt".to_string()),
+            port: running.port + 1,
+            queue_size: 500,
+            ..running.clone()
+        };
+
+        let (config, ignored) = running.with_reloaded(&reloaded);
+        assert_eq!(config.throttle_requests_per_minute, 120);
+        assert_eq!(config.llm_model_name, "llava:34b");
+        assert_eq!(
+            config.prompt_template_path.as_deref(),
+            Some("/etc/image-checker/prompt.txt")
+        );
+        assert_eq!(config.port, running.port);
+        assert_eq!(config.queue_size, 100);
+        assert_eq!(ignored, vec!["PORT", "QUEUE_SIZE"]);
+
+        let (_, ignored) = running.with_reloaded(&running);
+        assert!(ignored.is_empty());
+    }
+
+    #[test]
+    fn test_config_api_keys_list() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            ("API_KEYS".to_string(), " key-one, key-two,,".to_string()),
+            (
+                "AUTH_EXCLUDE_PATHS".to_string(),
+                "/health,/metrics".to_string(),
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(config.api_keys(), vec!["key-one", "key-two"]);
+        assert_eq!(config.auth_exclude_paths(), vec!["/health", "/metrics"]);
+    }
+
+    #[test]
+    fn test_config_llm_allowed_models() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            ("LLM_MODEL_NAME".to_string(), "llava:7b".to_string()),
+            (
+                "LLM_ALLOWED_MODELS".to_string(),
+                " llava:34b, moondream,".to_string(),
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(config.llm_allowed_models(), vec!["llava:34b", "moondream"]);
+        assert!(config.is_model_allowed("llava:7b"));
+        assert!(config.is_model_allowed("moondream"));
+        assert!(!config.is_model_allowed("llava:13b"));
+        assert!(!config.is_model_allowed("Moondream"));
+    }
+
+    #[test]
+    fn test_config_cors() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            (
+                "CORS_ALLOWED_ORIGINS".to_string(),
+                "https://app.example.com, http://localhost:5173".to_string(),
+            ),
+            ("CORS_ALLOWED_METHODS".to_string(), "get,post".to_string()),
+            ("CORS_ALLOW_CREDENTIALS".to_string(), "true".to_string()),
+        ])
+        .unwrap();
+        assert_eq!(
+            config.cors_allowed_origins(),
+            vec!["https://app.example.com", "http://localhost:5173"]
+        );
+        assert_eq!(config.cors_allowed_methods(), vec!["GET", "POST"]);
+        assert!(config.validate().is_ok());
+
+        let invalid = [
+            ("https://app.example.com/path", None, false),
+            ("app.example.com", None, false),
+            ("*,https://app.example.com", None, false),
+            ("*", None, true),
+            ("https://app.example.com", Some("GET,NOT A METHOD"), false),
+        ];
+        for (origins, methods, credentials) in invalid {
+            let config = Config {
+                cors_allowed_origins: Some(origins.to_string()),
+                cors_allowed_methods: methods.map(str::to_string),
+                cors_allow_credentials: credentials,
+                ..config.clone()
+            };
+            assert!(config.validate().is_err(), "{origins} {methods:?}");
+        }
     }
 
     #[test]
@@ -193,6 +1358,7 @@ mod tests {
             processing_timeout_minutes: 5,
             queue_size: 100,
             throttle_requests_per_minute: 60,
+            ..Config::default()
         };
 
         let result = config.validate();
@@ -201,6 +1367,352 @@ mod tests {
         assert!(error_msg.contains("must start with http:// or https://"));
     }
 
+    #[test]
+    fn test_config_max_image_dimension() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            ("MAX_IMAGE_DIMENSION".to_string(), "1536".to_string()),
+        ])
+        .unwrap();
+        assert_eq!(config.max_image_dimension, Some(1536));
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            max_image_dimension: Some(0),
+            ..config
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_exif_thumbnail() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            (
+                "USE_EXIF_THUMBNAIL_IF_PRESENT".to_string(),
+                "true".to_string(),
+            ),
+            (
+                "EXIF_THUMBNAIL_MIN_DIMENSION".to_string(),
+                "320".to_string(),
+            ),
+        ])
+        .unwrap();
+        assert!(config.use_exif_thumbnail_if_present);
+        assert_eq!(config.exif_thumbnail_min_dimension, 320);
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            exif_thumbnail_min_dimension: 0,
+            ..config
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_llm_generation_settings() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            ("LLM_TEMPERATURE".to_string(), "0.7".to_string()),
+            ("LLM_NUM_PREDICT".to_string(), "2048".to_string()),
+            ("LLM_MAX_RETRIES".to_string(), "5".to_string()),
+        ])
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.generation_options(),
+            GenerationOptions {
+                temperature: 0.7,
+                max_tokens: 2048,
+            }
+        );
+        assert_eq!(config.llm_max_retries, 5);
+
+        for invalid in [
+            Config {
+                llm_temperature: 2.5,
+                ..config.clone()
+            },
+            Config {
+                llm_temperature: -0.1,
+                ..config.clone()
+            },
+            Config {
+                llm_temperature: f32::NAN,
+                ..config.clone()
+            },
+            Config {
+                llm_num_predict: 0,
+                ..config.clone()
+            },
+            Config {
+                llm_max_retries: 0,
+                ..config.clone()
+            },
+        ] {
+            assert!(invalid.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn test_config_llm_pool_options() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            ("LLM_POOL_MAX_IDLE_PER_HOST".to_string(), "128".to_string()),
+            (
+                "LLM_POOL_IDLE_TIMEOUT_SECONDS".to_string(),
+                "30".to_string(),
+            ),
+            ("LLM_TCP_KEEPALIVE_SECONDS".to_string(), "0".to_string()),
+        ])
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.pool_options(),
+            PoolOptions {
+                max_idle_per_host: 128,
+                idle_timeout: Duration::from_secs(30),
+                tcp_keepalive: None,
+            }
+        );
+
+        let invalid = Config {
+            llm_pool_idle_timeout_seconds: 0,
+            ..config
+        };
+        assert!(invalid
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("LLM pool idle timeout must be greater than 0"));
+    }
+
+    #[test]
+    fn test_config_llm_circuit_breaker() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            ("LLM_CIRCUIT_FAILURE_THRESHOLD".to_string(), "2".to_string()),
+            (
+                "LLM_CIRCUIT_COOLDOWN_SECONDS".to_string(),
+                "120".to_string(),
+            ),
+        ])
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.llm_circuit_failure_threshold, 2);
+        assert_eq!(config.llm_circuit_cooldown(), Duration::from_secs(120));
+
+        for invalid in [
+            Config {
+                llm_circuit_failure_threshold: 0,
+                ..config.clone()
+            },
+            Config {
+                llm_circuit_cooldown_seconds: 0,
+                ..config.clone()
+            },
+        ] {
+            assert!(invalid.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn test_config_geocoder_url() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            (
+                "GEOCODER_URL".to_string(),
+                "https://nominatim.openstreetmap.org/search".to_string(),
+            ),
+        ])
+        .unwrap();
+        assert!(config.validate().is_ok());
+
+        for invalid in [
+            "nominatim.openstreetmap.org/search",
+            "ftp://geocoder/search",
+        ] {
+            let config = Config {
+                geocoder_url: Some(invalid.to_string()),
+                ..config.clone()
+            };
+            assert!(config.validate().is_err(), "{invalid} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_config_processing_ids() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            ("PROCESSING_ID_LENGTH".to_string(), "12".to_string()),
+            ("PROCESSING_ID_ALPHABET".to_string(), "base62".to_string()),
+        ])
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.processing_id_length, Some(12));
+        assert_eq!(config.processing_id_alphabet, IdAlphabet::Base62);
+
+        for length in [4, 65] {
+            let config = Config {
+                processing_id_length: Some(length),
+                ..config.clone()
+            };
+            assert!(
+                config.validate().is_err(),
+                "length {length} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_config_result_retention() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            ("RESULT_RETENTION_MINUTES".to_string(), "1440".to_string()),
+        ])
+        .unwrap();
+        assert_eq!(config.result_retention(), Duration::from_secs(24 * 3600));
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            result_retention_minutes: 0,
+            ..config
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_idempotency_key_ttl() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            ("IDEMPOTENCY_KEY_TTL_MINUTES".to_string(), "90".to_string()),
+        ])
+        .unwrap();
+        assert_eq!(config.idempotency_key_ttl(), Duration::from_secs(90 * 60));
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            idempotency_key_ttl_minutes: 0,
+            ..config
+        };
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("Idempotency key TTL"));
+    }
+
+    #[test]
+    fn test_config_duplicate_retention() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            ("DUPLICATE_RETENTION_MINUTES".to_string(), "120".to_string()),
+        ])
+        .unwrap();
+        assert_eq!(config.duplicate_retention(), Duration::from_secs(120 * 60));
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            duplicate_retention_minutes: 0,
+            ..config
+        };
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("Duplicate retention"));
+    }
+
+    #[test]
+    fn test_config_worker_concurrency() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            ("WORKER_CONCURRENCY".to_string(), "4".to_string()),
+        ])
+        .unwrap();
+        assert_eq!(config.worker_concurrency, 4);
+        assert!(config.validate().is_ok());
+
+        for worker_concurrency in [0, MAX_WORKER_CONCURRENCY + 1] {
+            let config = Config {
+                worker_concurrency,
+                ..config.clone()
+            };
+            assert!(config
+                .validate()
+                .unwrap_err()
+                .to_string()
+                .contains("Worker concurrency must be between"));
+        }
+    }
+
+    #[test]
+    fn test_config_body_limit_fits_inline_images() {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            max_inline_image_bytes: 12 * 1024 * 1024,
+            ..Config::default()
+        };
+
+        // 12MB of image is 16MB of base64, leaving no room for the JSON around it
+        let result = config.validate();
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Max request body bytes must be at least"));
+
+        let config = Config {
+            max_request_body_bytes: 32 * 1024 * 1024,
+            ..config
+        };
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_config_validation_invalid_queue_size() {
         // Use a different environment variable scope to avoid conflicts
@@ -226,6 +1738,7 @@ mod tests {
             processing_timeout_minutes: 5,
             queue_size: 0, // This should cause validation to fail
             throttle_requests_per_minute: 60,
+            ..Config::default()
         };
 
         let result = config.validate();
@@ -277,6 +1790,7 @@ mod tests {
             processing_timeout_minutes: 5,
             queue_size: 100,
             throttle_requests_per_minute: 60,
+            ..Config::default()
         };
 
         // Should validate successfully
@@ -285,7 +1799,7 @@ mod tests {
 
         // Should parse storage URI correctly
         let storage_uri = config.get_storage_uri().unwrap();
-        assert_eq!(storage_uri.to_local_path(), "/tmp");
+        assert_eq!(storage_uri.to_local_path(), Some("/tmp"));
     }
 
     #[test]
@@ -293,13 +1807,14 @@ mod tests {
         let config = Config {
             host: "127.0.0.1".to_string(),
             port: 3000,
-            image_base_dir: "s3://bucket/path".to_string(),
+            image_base_dir: "ftp://bucket/path".to_string(),
             llm_api_url: "http://localhost:8080".to_string(),
             llm_model_name: "llava:7b".to_string(),
             request_timeout_seconds: 30,
             processing_timeout_minutes: 5,
             queue_size: 100,
             throttle_requests_per_minute: 60,
+            ..Config::default()
         };
 
         // Should fail validation due to unsupported scheme
@@ -311,6 +1826,52 @@ mod tests {
             .contains("Unsupported URI scheme"));
     }
 
+    #[test]
+    fn test_config_with_s3_uri() {
+        let config = Config {
+            host: "127.0.0.1".to_string(),
+            port: 3000,
+            image_base_dir: "s3://bucket/path".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            llm_model_name: "llava:7b".to_string(),
+            request_timeout_seconds: 30,
+            processing_timeout_minutes: 5,
+            queue_size: 100,
+            throttle_requests_per_minute: 60,
+            ..Config::default()
+        };
+
+        // S3 buckets cannot be checked synchronously, so validation should pass
+        let result = config.validate();
+        assert!(result.is_ok(), "Config validation failed: {:?}", result);
+
+        let storage_uri = config.get_storage_uri().unwrap();
+        assert!(storage_uri.is_remote());
+        assert_eq!(storage_uri.to_string(), "s3://bucket/path");
+    }
+
+    #[test]
+    fn test_config_with_gcs_uri() {
+        let config = Config {
+            image_base_dir: "gs://bucket/path".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            ..Config::default()
+        };
+
+        let result = config.validate();
+        assert!(result.is_ok(), "Config validation failed: {:?}", result);
+        assert!(config.get_storage_uri().unwrap().is_remote());
+
+        let config = Config {
+            image_base_dir: "gs:///path".to_string(),
+            ..config
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::Storage(StorageError::InvalidUri(_)))
+        ));
+    }
+
     #[test]
     fn test_config_with_invalid_file_uri() {
         let config = Config {
@@ -323,6 +1884,7 @@ mod tests {
             processing_timeout_minutes: 5,
             queue_size: 100,
             throttle_requests_per_minute: 60,
+            ..Config::default()
         };
 
         // Should fail validation due to invalid URI format
@@ -333,4 +1895,317 @@ mod tests {
             .to_string()
             .contains("Invalid URI format"));
     }
+
+    #[test]
+    fn test_config_exif_default_timezone_offset() {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            exif_default_timezone_offset: 330,
+            ..Config::default()
+        };
+        assert!(config.validate().is_ok());
+        assert_eq!(config.exif_default_offset().local_minus_utc(), 330 * 60);
+
+        let config = Config {
+            exif_default_timezone_offset: 900,
+            ..config
+        };
+        let result = config.validate();
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("between -720 and 840 minutes"));
+    }
+
+    #[test]
+    fn test_config_max_request_timeout() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            ("MAX_REQUEST_TIMEOUT_SECONDS".to_string(), "600".to_string()),
+        ])
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.processing_timeout_for(None),
+            Duration::from_secs(300)
+        );
+        assert_eq!(
+            config.processing_timeout_for(Some(5)),
+            Duration::from_secs(5)
+        );
+        assert_eq!(
+            config.processing_timeout_for(Some(3600)),
+            Duration::from_secs(600)
+        );
+        assert_eq!(
+            config.longest_processing_timeout(),
+            Duration::from_secs(600)
+        );
+
+        let config = Config {
+            max_request_timeout_seconds: 0,
+            ..config
+        };
+        let result = config.validate();
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Max request timeout must be greater than 0"));
+    }
+
+    #[test]
+    fn test_config_exif_timeout() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            ("EXIF_TIMEOUT_SECONDS".to_string(), "3".to_string()),
+        ])
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.exif_timeout(), Duration::from_secs(3));
+
+        let config = Config {
+            exif_timeout_seconds: 0,
+            ..config
+        };
+        let result = config.validate();
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("EXIF timeout must be greater than 0"));
+    }
+
+    #[test]
+    fn test_config_exif_cache_entries() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            ("EXIF_CACHE_ENTRIES".to_string(), "0".to_string()),
+        ])
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.exif_cache_entries, 0);
+    }
+
+    #[test]
+    fn test_config_prompt_template_path() {
+        use std::io::Write;
+
+        let mut template_file = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            template_file,
+            "Does the photo show {{content}}? ACCEPTED or REJECTED"
+        )
+        .unwrap();
+
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            prompt_template_path: Some(template_file.path().to_string_lossy().into_owned()),
+            ..Config::default()
+        };
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.prompt_template().unwrap().render("a red door"),
+            "Does the photo show a red door? ACCEPTED or REJECTED"
+        );
+
+        // A template without the placeholder fails at startup
+        let mut template_file = tempfile::NamedTempFile::new().unwrap();
+        write!(template_file, "Does the photo match? ACCEPTED or REJECTED").unwrap();
+        let config = Config {
+            prompt_template_path: Some(template_file.path().to_string_lossy().into_owned()),
+            ..config
+        };
+        let result = config.validate();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("{content}"));
+    }
+
+    #[test]
+    fn test_config_llm_backend_from_env() {
+        let vars = |backend: &str| {
+            vec![
+                ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+                (
+                    "LLM_API_URL".to_string(),
+                    "http://localhost:11434".to_string(),
+                ),
+                ("LLM_BACKEND".to_string(), backend.to_string()),
+            ]
+        };
+
+        let config: Config = envy::from_iter(vars("ollama")).unwrap();
+        assert_eq!(config.llm_backend, LlmBackend::Ollama);
+
+        let config: Config = envy::from_iter(vars("openai")).unwrap();
+        assert_eq!(config.llm_backend, LlmBackend::OpenAi);
+
+        assert!(envy::from_iter::<_, Config>(vars("gemini")).is_err());
+    }
+
+    #[test]
+    fn test_config_llm_response_parser_from_env() {
+        let vars = |parser: &str| {
+            vec![
+                ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+                (
+                    "LLM_API_URL".to_string(),
+                    "http://localhost:11434".to_string(),
+                ),
+                ("LLM_RESPONSE_PARSER".to_string(), parser.to_string()),
+            ]
+        };
+
+        let config: Config = envy::from_iter(vars("keyword")).unwrap();
+        assert_eq!(config.llm_response_parser, LlmResponseParser::Keyword);
+
+        let config: Config = envy::from_iter(vars("judge")).unwrap();
+        assert_eq!(config.llm_response_parser, LlmResponseParser::Judge);
+
+        assert!(envy::from_iter::<_, Config>(vars("regex")).is_err());
+    }
+
+    #[test]
+    fn test_config_verdict_keywords_from_env() {
+        let base = vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:11434".to_string(),
+            ),
+        ];
+
+        let config: Config = envy::from_iter(base.clone()).unwrap();
+        assert_eq!(config.default_verdict, DefaultVerdict::Rejected);
+        assert_eq!(config.verdict_keywords(), VerdictKeywords::default());
+
+        let mut vars = base;
+        vars.extend([
+            ("ACCEPT_KEYWORDS".to_string(), "PASS, OUI ,".to_string()),
+            ("REJECT_KEYWORDS".to_string(), "FAIL,NON".to_string()),
+            ("VERDICT_KEYWORDS_ANYWHERE".to_string(), "true".to_string()),
+            ("DEFAULT_VERDICT".to_string(), "accepted".to_string()),
+        ]);
+        let config: Config = envy::from_iter(vars).unwrap();
+        assert_eq!(
+            config.verdict_keywords(),
+            VerdictKeywords::new(
+                vec!["PASS".to_string(), "OUI".to_string()],
+                vec!["FAIL".to_string(), "NON".to_string()],
+            )
+            .anywhere()
+            .with_default_verdict(true)
+        );
+    }
+
+    #[test]
+    fn test_config_content_alternatives_strategy() {
+        let vars = vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:11434".to_string(),
+            ),
+            (
+                "CONTENT_ALTERNATIVES_STRATEGY".to_string(),
+                "combined".to_string(),
+            ),
+        ];
+
+        let config: Config = envy::from_iter(vars).unwrap();
+        assert_eq!(
+            config.content_alternatives_strategy,
+            ContentAlternativesStrategy::Combined
+        );
+    }
+
+    #[test]
+    fn test_config_image_downloads() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            (
+                "ALLOWED_IMAGE_HOSTS".to_string(),
+                "images.example.com, cdn.example.com,".to_string(),
+            ),
+            (
+                "MAX_IMAGE_DOWNLOAD_BYTES".to_string(),
+                "1048576".to_string(),
+            ),
+            (
+                "IMAGE_DOWNLOAD_TIMEOUT_SECONDS".to_string(),
+                "5".to_string(),
+            ),
+        ])
+        .unwrap();
+        assert_eq!(
+            config.allowed_image_hosts(),
+            ["images.example.com", "cdn.example.com"]
+        );
+        assert_eq!(config.max_image_download_bytes, 1024 * 1024);
+        assert_eq!(config.image_download_timeout(), Duration::from_secs(5));
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            image_download_timeout_seconds: 0,
+            ..config
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_skip_model_check() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            ("SKIP_MODEL_CHECK".to_string(), "true".to_string()),
+        ])
+        .unwrap();
+        assert!(config.skip_model_check);
+    }
+
+    #[test]
+    fn test_config_per_client_requests_per_minute() {
+        let config: Config = envy::from_iter(vec![
+            ("IMAGE_BASE_DIR".to_string(), "/tmp".to_string()),
+            (
+                "LLM_API_URL".to_string(),
+                "http://localhost:8080".to_string(),
+            ),
+            (
+                "PER_CLIENT_REQUESTS_PER_MINUTE".to_string(),
+                "30".to_string(),
+            ),
+        ])
+        .unwrap();
+        assert_eq!(config.per_client_requests_per_minute, Some(30));
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            per_client_requests_per_minute: Some(0),
+            ..config
+        };
+        assert!(config.validate().is_err());
+    }
 }
@@ -0,0 +1,194 @@
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Json, Response};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Stable, machine-readable identifier of an API error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// The request is malformed or has invalid values
+    BadRequest,
+    /// The `Authorization` header is missing or holds an unknown API key
+    Unauthorized,
+    /// The endpoint, processing id or image does not exist
+    NotFound,
+    MethodNotAllowed,
+    /// The request is in a state that does not allow the operation
+    Conflict,
+    /// The request was cancelled before it produced results
+    Cancelled,
+    PayloadTooLarge,
+    UnsupportedMediaType,
+    /// A `422` from outside the handlers; JSON bodies that do not match the expected
+    /// fields are a `BadRequest` naming the `field`
+    UnprocessableEntity,
+    /// The queue has no room left; retry later
+    QueueFull,
+    /// The client sent more requests than `PER_CLIENT_REQUESTS_PER_MINUTE` allows;
+    /// retry after `Retry-After` seconds
+    RateLimited,
+    /// The request is still being processed
+    NotReady,
+    /// Processing the request failed
+    ProcessingFailed,
+    /// Processing did not finish within its timeout
+    Timeout,
+    /// The service is shutting down
+    ServiceUnavailable,
+    InternalError,
+}
+
+impl ErrorCode {
+    /// The code for an error response produced outside the handlers, such as an
+    /// extractor rejection, from its status alone
+    pub fn for_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::BAD_REQUEST => ErrorCode::BadRequest,
+            StatusCode::UNAUTHORIZED => ErrorCode::Unauthorized,
+            StatusCode::NOT_FOUND => ErrorCode::NotFound,
+            StatusCode::METHOD_NOT_ALLOWED => ErrorCode::MethodNotAllowed,
+            StatusCode::CONFLICT => ErrorCode::Conflict,
+            StatusCode::PAYLOAD_TOO_LARGE => ErrorCode::PayloadTooLarge,
+            StatusCode::UNSUPPORTED_MEDIA_TYPE => ErrorCode::UnsupportedMediaType,
+            StatusCode::UNPROCESSABLE_ENTITY => ErrorCode::UnprocessableEntity,
+            StatusCode::TOO_MANY_REQUESTS => ErrorCode::QueueFull,
+            StatusCode::SERVICE_UNAVAILABLE => ErrorCode::ServiceUnavailable,
+            StatusCode::GATEWAY_TIMEOUT => ErrorCode::Timeout,
+            status if status.is_client_error() => ErrorCode::BadRequest,
+            _ => ErrorCode::InternalError,
+        }
+    }
+}
+
+/// Error returned by every handler, rendered as an [`ApiErrorBody`]
+#[derive(Debug, Clone)]
+pub struct AppError {
+    pub status: StatusCode,
+    pub code: ErrorCode,
+    pub message: String,
+    pub details: Option<String>,
+    /// Path of the request field at fault, e.g. `analysis-request.content`
+    pub field: Option<String>,
+}
+
+impl AppError {
+    pub fn new(status: StatusCode, code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+            details: None,
+            field: None,
+        }
+    }
+
+    /// Adds a hint on how to fix the request
+    pub fn with_details(mut self, details: impl Into<String>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+
+    /// Names the request field at fault
+    pub fn with_field(mut self, field: impl Into<String>) -> Self {
+        self.field = Some(field.into());
+        self
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, ErrorCode::BadRequest, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, ErrorCode::NotFound, message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::CONFLICT, ErrorCode::Conflict, message)
+    }
+
+    /// A failure the client cannot do anything about; the cause is only logged
+    pub fn internal() -> Self {
+        Self::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::InternalError,
+            "internal server error",
+        )
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let body = ApiErrorBody {
+            error: ApiError {
+                code: self.code,
+                message: self.message,
+                details: self.details,
+                field: self.field,
+            },
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+/// Body of every error response: `{"error": {"code", "message", "details"}}`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiErrorBody {
+    pub error: ApiError,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    /// Human-readable description of what went wrong
+    pub message: String,
+    /// How to fix the request, when there is more to say than `message`
+    pub details: Option<String>,
+    /// Path of the request field at fault, for bodies that do not match the expected shape
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_error_envelope_shape() {
+        let response = AppError::bad_request("content description is required")
+            .with_details("see the API documentation")
+            .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "error": {
+                    "code": "BAD_REQUEST",
+                    "message": "content description is required",
+                    "details": "see the API documentation"
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_codes_for_status() {
+        assert_eq!(
+            ErrorCode::for_status(StatusCode::UNPROCESSABLE_ENTITY),
+            ErrorCode::UnprocessableEntity
+        );
+        assert_eq!(
+            ErrorCode::for_status(StatusCode::IM_A_TEAPOT),
+            ErrorCode::BadRequest
+        );
+        assert_eq!(
+            ErrorCode::for_status(StatusCode::BAD_GATEWAY),
+            ErrorCode::InternalError
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use crate::error::{AppError, ErrorCode};
+use crate::models::ValidationRequest;
+use axum::async_trait;
+use axum::body::Bytes;
+use axum::extract::{FromRequest, Multipart, Request};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use base64::{engine::general_purpose, Engine as _};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+/// JSON body extractor whose rejections name the offending field
+///
+/// Like `axum::Json`, but a body that does not match the expected shape is a `400`
+/// with the path of the field at fault, e.g. `analysis-request.location.max_distance`,
+/// in the error envelope's `field`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApiJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ApiJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !has_json_content_type(req.headers()) {
+            return Err(AppError::new(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                ErrorCode::UnsupportedMediaType,
+                "expected request with `Content-Type: application/json`",
+            )
+            .into_response());
+        }
+
+        // Oversized bodies are rejected here and wrapped by the error envelope layer
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        parse_json(&bytes)
+            .map(ApiJson)
+            .map_err(IntoResponse::into_response)
+    }
+}
+
+/// Body of `POST /validate`: JSON, or `multipart/form-data` carrying the image itself
+///
+/// A multipart body has an `image` file part and an `analysis-request` JSON part, and
+/// optionally `callback-url` and `timeout-seconds` text parts. The uploaded image
+/// becomes an inline image, so it is staged and validated like base64 JSON ones.
+#[derive(Debug, Clone)]
+pub struct ValidationSubmission(pub ValidationRequest);
+
+#[async_trait]
+impl<S> FromRequest<S> for ValidationSubmission
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if content_type_essence(req.headers()).as_deref() != Some("multipart/form-data") {
+            let ApiJson(request) = ApiJson::from_request(req, state).await?;
+            return Ok(ValidationSubmission(request));
+        }
+
+        let multipart = Multipart::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        parse_multipart(multipart).await.map(ValidationSubmission)
+    }
+}
+
+/// Builds a validation request from the parts of a multipart upload
+async fn parse_multipart(mut multipart: Multipart) -> Result<ValidationRequest, Response> {
+    let mut body = Map::new();
+    // Oversized bodies fail while reading a part and are wrapped by the error envelope layer
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(IntoResponse::into_response)?
+    {
+        let name = field.name().unwrap_or_default().to_string();
+        let value = match name.as_str() {
+            "image" => {
+                let media_type = field
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let bytes = field.bytes().await.map_err(IntoResponse::into_response)?;
+                if bytes.is_empty() {
+                    return Err(AppError::bad_request("image part is empty")
+                        .with_field("image")
+                        .into_response());
+                }
+                Value::String(format!(
+                    "data:{media_type};base64,{}",
+                    general_purpose::STANDARD.encode(&bytes)
+                ))
+            }
+            "analysis-request" => {
+                let bytes = field.bytes().await.map_err(IntoResponse::into_response)?;
+                parse_json::<Value>(&bytes).map_err(|e| {
+                    AppError::bad_request(format!("invalid analysis-request part: {}", e.message))
+                        .with_field("analysis-request")
+                        .into_response()
+                })?
+            }
+            "callback-url" => {
+                Value::String(field.text().await.map_err(IntoResponse::into_response)?)
+            }
+            "timeout-seconds" => {
+                let text = field.text().await.map_err(IntoResponse::into_response)?;
+                let seconds = text.trim().parse::<u64>().map_err(|_| {
+                    AppError::bad_request(format!("invalid timeout-seconds: {text:?}"))
+                        .with_field("timeout-seconds")
+                        .into_response()
+                })?;
+                Value::from(seconds)
+            }
+            _ => return Err(
+                AppError::bad_request(format!("unexpected multipart part `{name}`"))
+                    .with_details(
+                        "expected `image`, `analysis-request`, `callback-url` or `timeout-seconds`",
+                    )
+                    .into_response(),
+            ),
+        };
+        body.insert(name, value);
+    }
+
+    if !body.contains_key("image") {
+        return Err(AppError::bad_request("image part is required")
+            .with_field("image")
+            .into_response());
+    }
+
+    // Deserialize as a JSON body would, so field errors name the same paths
+    parse_json(Value::Object(body).to_string().as_bytes()).map_err(IntoResponse::into_response)
+}
+
+fn has_json_content_type(headers: &HeaderMap) -> bool {
+    content_type_essence(headers).is_some_and(|essence| {
+        essence == "application/json"
+            || (essence.starts_with("application/") && essence.ends_with("+json"))
+    })
+}
+
+/// The media type of the `Content-Type` header, lowercased and without parameters
+fn content_type_essence(headers: &HeaderMap) -> Option<String> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())?;
+    Some(
+        content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase(),
+    )
+}
+
+/// Deserializes a JSON body, describing where it went wrong on failure
+pub fn parse_json<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, AppError> {
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(describe_error)?;
+    deserializer.end().map_err(|e| malformed(&e))?;
+    Ok(value)
+}
+
+fn malformed(error: &serde_json::Error) -> AppError {
+    AppError::bad_request(format!("malformed JSON: {}", without_position(error))).with_details(
+        format!("at line {} column {}", error.line(), error.column()),
+    )
+}
+
+fn describe_error(error: serde_path_to_error::Error<serde_json::Error>) -> AppError {
+    let inner = error.inner();
+    if !inner.is_data() {
+        return malformed(inner);
+    }
+    let message = without_position(inner);
+
+    // A missing field is reported at the object that lacks it; name the field itself
+    let path = error.path().to_string();
+    let field = match message
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.strip_suffix('`'))
+    {
+        Some(missing) if path == "." => missing.to_string(),
+        Some(missing) => format!("{path}.{missing}"),
+        None if path == "." => {
+            return AppError::bad_request(format!("invalid request body: {message}"))
+        }
+        None => path,
+    };
+    AppError::bad_request(format!("invalid {field}: {message}")).with_field(field)
+}
+
+/// serde_json's message without the trailing "at line L column C"
+fn without_position(error: &serde_json::Error) -> String {
+    let message = error.to_string();
+    let position = format!(" at line {} column {}", error.line(), error.column());
+    message
+        .strip_suffix(&position)
+        .map(str::to_string)
+        .unwrap_or(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse_error(body: serde_json::Value) -> AppError {
+        parse_json::<ValidationRequest>(body.to_string().as_bytes()).unwrap_err()
+    }
+
+    #[test]
+    fn test_errors_name_the_field() {
+        let error = parse_error(json!({ "image-path": "a.jpg", "analysis-request": {} }));
+        assert_eq!(error.status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.field.as_deref(), Some("analysis-request.content"));
+        assert_eq!(
+            error.message,
+            "invalid analysis-request.content: missing field `content`"
+        );
+
+        let error = parse_error(json!({ "image-path": "a.jpg" }));
+        assert_eq!(error.field.as_deref(), Some("analysis-request"));
+
+        let error = parse_error(json!({
+            "image-path": "a.jpg",
+            "analysis-request": {
+                "content": "Birds",
+                "location": { "lat": 51.5, "long": -0.1, "max_distance": "far" }
+            }
+        }));
+        assert_eq!(
+            error.field.as_deref(),
+            Some("analysis-request.location.max_distance")
+        );
+        assert!(
+            error.message.contains("invalid type: string \"far\""),
+            "{}",
+            error.message
+        );
+        assert!(!error.message.contains("at line"));
+    }
+
+    #[test]
+    fn test_malformed_json() {
+        let error = parse_json::<ValidationRequest>(b"{\"image-path\": ").unwrap_err();
+        assert_eq!(error.status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.field, None);
+        assert!(
+            error.message.starts_with("malformed JSON"),
+            "{}",
+            error.message
+        );
+        assert_eq!(error.details.as_deref(), Some("at line 1 column 15"));
+
+        let error = parse_json::<serde_json::Value>(b"{} trailing").unwrap_err();
+        assert!(
+            error.message.contains("trailing characters"),
+            "{}",
+            error.message
+        );
+    }
+
+    #[test]
+    fn test_json_content_types() {
+        let headers = |content_type: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::CONTENT_TYPE, content_type.parse().unwrap());
+            headers
+        };
+        assert!(has_json_content_type(&headers("application/json")));
+        assert!(has_json_content_type(&headers(
+            "Application/JSON; charset=utf-8"
+        )));
+        assert!(has_json_content_type(&headers("application/problem+json")));
+        assert!(!has_json_content_type(&headers("text/plain")));
+        assert!(!has_json_content_type(&HeaderMap::new()));
+    }
+}
@@ -0,0 +1,226 @@
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use thiserror::Error;
+use tracing::debug;
+
+#[derive(Debug, Error)]
+pub enum GeocoderError {
+    #[error("HTTP request error: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("geocoder returned status {0}")]
+    Status(reqwest::StatusCode),
+    #[error("no matching place found")]
+    NotFound,
+    #[error("invalid geocoder response: {0}")]
+    InvalidResponse(String),
+}
+
+/// Resolved places kept in memory; the cache is emptied when it fills up
+const MAX_CACHED_PLACES: usize = 1024;
+
+/// One match from a Nominatim-compatible `/search` endpoint
+#[derive(Debug, Deserialize)]
+struct SearchResult {
+    /// `[south, north, west, east]` in degrees, encoded as strings
+    boundingbox: [String; 4],
+}
+
+/// Resolves place names to their bounding box through a Nominatim-compatible API
+pub struct Geocoder {
+    client: Client,
+    url: Url,
+    /// Bounding polygons by lowercased place name
+    cache: Mutex<HashMap<String, Vec<(f64, f64)>>>,
+}
+
+impl Geocoder {
+    /// Creates a geocoder querying the search endpoint at `url`
+    pub fn new(url: Url, timeout: Duration) -> Self {
+        let client = Client::builder()
+            .timeout(timeout)
+            .user_agent(concat!("image-checker/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            url,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the `(lat, long)` corners of the place's bounding box
+    ///
+    /// Successful lookups are cached; failures are not, so a flaky geocoder is
+    /// retried on the next request.
+    pub async fn resolve(&self, place_name: &str) -> Result<Vec<(f64, f64)>, GeocoderError> {
+        let key = place_name.to_lowercase();
+        if let Some(polygon) = self.cache.lock().expect("geocoder lock poisoned").get(&key) {
+            debug!("Geocoder cache hit for {:?}", place_name);
+            return Ok(polygon.clone());
+        }
+
+        let polygon = self.search(place_name).await?;
+        debug!("Geocoded {:?} to {:?}", place_name, polygon);
+
+        let mut cache = self.cache.lock().expect("geocoder lock poisoned");
+        if cache.len() >= MAX_CACHED_PLACES {
+            cache.clear();
+        }
+        cache.insert(key, polygon.clone());
+        Ok(polygon)
+    }
+
+    async fn search(&self, place_name: &str) -> Result<Vec<(f64, f64)>, GeocoderError> {
+        let response = self
+            .client
+            .get(self.url.clone())
+            .query(&[("q", place_name), ("format", "jsonv2"), ("limit", "1")])
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(GeocoderError::Status(status));
+        }
+
+        let results: Vec<SearchResult> = response
+            .json()
+            .await
+            .map_err(|e| GeocoderError::InvalidResponse(e.to_string()))?;
+        let result = results.into_iter().next().ok_or(GeocoderError::NotFound)?;
+        bounding_box_polygon(&result.boundingbox)
+    }
+}
+
+/// Turns a Nominatim `[south, north, west, east]` box into its four corners
+fn bounding_box_polygon(bbox: &[String; 4]) -> Result<Vec<(f64, f64)>, GeocoderError> {
+    let mut degrees = [0.0; 4];
+    for (value, text) in degrees.iter_mut().zip(bbox) {
+        *value = text
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| {
+                GeocoderError::InvalidResponse(format!("bad bounding box value: {text:?}"))
+            })?;
+    }
+
+    let [south, north, west, east] = degrees;
+    if !(-90.0..=90.0).contains(&south) || !(-90.0..=90.0).contains(&north) || south > north {
+        return Err(GeocoderError::InvalidResponse(format!(
+            "bad bounding box latitudes: {south} to {north}"
+        )));
+    }
+    if !(-180.0..=180.0).contains(&west) || !(-180.0..=180.0).contains(&east) || west > east {
+        return Err(GeocoderError::InvalidResponse(format!(
+            "unsupported bounding box longitudes: {west} to {east}"
+        )));
+    }
+
+    Ok(vec![
+        (south, west),
+        (south, east),
+        (north, east),
+        (north, west),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    /// Serves a Nominatim-style search that knows only Richmond Park, counting lookups
+    async fn spawn_geocoder() -> (Url, Arc<AtomicU32>) {
+        use axum::extract::Query;
+        use axum::{routing::get, Json};
+        use serde_json::{json, Value};
+
+        let lookups = Arc::new(AtomicU32::new(0));
+        let counter = lookups.clone();
+        let app = axum::Router::new().route(
+            "/search",
+            get(
+                move |Query(params): Query<HashMap<String, String>>| async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    assert_eq!(params["format"], "jsonv2");
+                    let results: Value = if params["q"].eq_ignore_ascii_case("richmond park") {
+                        json!([{
+                            "display_name": "Richmond Park, London",
+                            "boundingbox": ["51.4258", "51.4694", "-0.3050", "-0.2413"]
+                        }])
+                    } else {
+                        json!([])
+                    };
+                    Json(results)
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (
+            Url::parse(&format!("http://{addr}/search")).unwrap(),
+            lookups,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_resolves_bounding_box_and_caches_it() {
+        let (url, lookups) = spawn_geocoder().await;
+        let geocoder = Geocoder::new(url, Duration::from_secs(5));
+
+        let polygon = geocoder.resolve("Richmond Park").await.unwrap();
+        assert_eq!(
+            polygon,
+            vec![
+                (51.4258, -0.3050),
+                (51.4258, -0.2413),
+                (51.4694, -0.2413),
+                (51.4694, -0.3050),
+            ]
+        );
+
+        // Served from the cache, whatever the capitalisation
+        assert_eq!(geocoder.resolve("richmond park").await.unwrap(), polygon);
+        assert_eq!(lookups.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_place_and_failing_geocoder() {
+        let (url, lookups) = spawn_geocoder().await;
+        let geocoder = Geocoder::new(url.clone(), Duration::from_secs(5));
+
+        // Failures are not cached
+        for _ in 0..2 {
+            let result = geocoder.resolve("Atlantis").await;
+            assert!(matches!(result, Err(GeocoderError::NotFound)));
+        }
+        assert_eq!(lookups.load(Ordering::SeqCst), 2);
+
+        let missing = Geocoder::new(url.join("/missing").unwrap(), Duration::from_secs(5));
+        let result = missing.resolve("Richmond Park").await;
+        assert!(
+            matches!(result, Err(GeocoderError::Status(status)) if status == reqwest::StatusCode::NOT_FOUND)
+        );
+    }
+
+    #[test]
+    fn test_bounding_box_polygon_rejects_bad_boxes() {
+        let bbox = |values: [&str; 4]| values.map(str::to_string);
+
+        assert!(bounding_box_polygon(&bbox(["10", "11", "20", "21"])).is_ok());
+        assert!(bounding_box_polygon(&bbox(["north", "11", "20", "21"])).is_err());
+        assert!(bounding_box_polygon(&bbox(["11", "10", "20", "21"])).is_err());
+        assert!(bounding_box_polygon(&bbox(["10", "91", "20", "21"])).is_err());
+        // Boxes crossing the antimeridian cannot be checked as a simple polygon
+        assert!(bounding_box_polygon(&bbox(["-18", "-16", "177", "-179"])).is_err());
+    }
+}
@@ -1,93 +1,401 @@
+use crate::config::Config;
+use crate::error::ApiErrorBody;
+use crate::error::{AppError, ErrorCode};
+use crate::extract::{ApiJson, ValidationSubmission};
 use crate::models::{
-    ProcessingRequest, ProcessingStatus, StatusResponse, ValidationRequest, ValidationResponse,
+    AnalysisRequest, ImageInspection, InspectRequest, JobListResponse, PartialResultsResponse,
+    ProcessingRequest, ProcessingStatus, Resolution, StatusResponse, ValidationContext,
+    ValidationRequest, ValidationResponse,
 };
-use crate::queue::{ProcessingQueue, QueueError, QueueStats};
+use crate::openapi::ApiDoc;
+use crate::queue::{ProcessingQueue, QueueError, QueueSnapshot, QueueStats};
+use crate::utils::parse_duration;
+use crate::validation::inline::{decode_inline_image, InlineImageError};
+use crate::validation::{LlmError, ProcessorError, RemoteImageError};
+use crate::webhook::validate_callback_url;
 
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
-    Json as JsonExtractor,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::timeout;
 use tracing::{debug, error, warn};
+use utoipa::{IntoParams, OpenApi, ToSchema};
 
-#[derive(Debug, Serialize)]
-pub struct ApiResponse<T> {
-    pub success: bool,
-    pub data: Option<T>,
-    pub error: Option<String>,
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SubmitResponse {
+    #[serde(rename = "processing-id")]
+    pub processing_id: String,
+    /// `accepted`, or the current status when an `Idempotency-Key` was reused
+    pub status: String,
 }
 
-impl<T> ApiResponse<T> {
-    pub fn success(data: T) -> Self {
-        Self {
-            success: true,
-            data: Some(data),
-            error: None,
-        }
-    }
+/// Header naming a submission so that retries return the original request
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
 
-    pub fn error(message: String) -> Self {
-        Self {
-            success: false,
-            data: None,
-            error: Some(message),
-        }
-    }
-}
+/// Longest accepted `Idempotency-Key`
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
 
-#[derive(Debug, Serialize)]
-pub struct SubmitResponse {
+/// Header carrying the time by which the client needs the result, in Unix milliseconds
+const REQUEST_DEADLINE_HEADER: &str = "x-request-deadline";
+
+/// Outcome of one item of a `POST /validate/batch` submission
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchItemResponse {
+    /// Position of the item in the submitted array
+    pub index: usize,
     #[serde(rename = "processing-id")]
     pub processing_id: String,
+    /// `accepted` when queued, `rejected` otherwise
     pub status: String,
+    /// Why the item was rejected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct HealthResponse {
     pub status: String,
     pub version: String,
     pub queue_stats: QueueStats,
 }
 
+/// Build information embedded at compile time
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VersionResponse {
+    pub version: String,
+    /// Commit the binary was built from, or `unknown` outside a git checkout
+    pub git_sha: String,
+    /// RFC 3339 build time in UTC
+    pub build_timestamp: String,
+    pub rustc_version: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    /// `ready`, or `unavailable` when the LLM API cannot be reached
+    pub status: String,
+    /// Why the LLM API is unreachable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Checks the content description, constraints, model, callback URL and timeout,
+/// returning the parsed constraints
+fn validate_analysis_request(
+    processing_request: &ProcessingRequest,
+    config: &Config,
+) -> Result<ValidationContext, AppError> {
+    if processing_request.analysis_request.content.is_empty() {
+        warn!("Validation request missing content description");
+        return Err(AppError::bad_request("content description is required")
+            .with_field("analysis-request.content"));
+    }
+
+    // Reject constraints that can never be evaluated, such as a location with both
+    // a radius and a polygon, before the request is queued
+    let context = ValidationContext::try_from(processing_request.analysis_request.clone())
+        .map_err(|e| {
+            warn!("Validation request has invalid constraints: {}", e);
+            AppError::bad_request(format!("invalid analysis request: {}", e))
+        })?;
+
+    if let Some(model) = context.model.as_deref() {
+        if !config.is_model_allowed(model) {
+            warn!("Validation request asks for disallowed model {:?}", model);
+            return Err(AppError::bad_request(format!(
+                "model {model} is not allowed"
+            )));
+        }
+    }
+
+    if let Some(minutes) = context.reject_duplicate_within {
+        let max_minutes = config.duplicate_retention_minutes;
+        if minutes > max_minutes {
+            warn!(
+                "Validation request asks for a {} minute duplicate window",
+                minutes
+            );
+            return Err(AppError::bad_request(format!(
+                "reject_duplicate_within must be at most {max_minutes} minutes, got: {minutes}"
+            )));
+        }
+    }
+
+    if let Some(callback_url) = &processing_request.callback_url {
+        if let Err(e) = validate_callback_url(callback_url) {
+            warn!("Validation request has invalid callback URL: {}", e);
+            return Err(AppError::bad_request(format!(
+                "invalid callback-url: {}",
+                e
+            )));
+        }
+    }
+
+    if let Some(timeout_seconds) = processing_request.timeout_seconds {
+        let max_seconds = config.max_request_timeout_seconds;
+        if !(1..=max_seconds).contains(&timeout_seconds) {
+            warn!(
+                "Validation request has out-of-bounds timeout of {}s",
+                timeout_seconds
+            );
+            return Err(AppError::bad_request(format!(
+                "timeout-seconds must be between 1 and {max_seconds}, got: {timeout_seconds}"
+            )));
+        }
+    }
+
+    Ok(context)
+}
+
+/// Reports what the service sees about an image, without running the content check
+///
+/// Missing GPS or timestamp metadata is reported in the body; only an image that
+/// cannot be found or read is an error.
+#[utoipa::path(
+    post,
+    path = "/inspect",
+    request_body = InspectRequest,
+    responses(
+        (status = 200, description = "Image metadata", body = ImageInspection),
+        (status = 400, description = "Missing image or unsupported image format", body = ApiErrorBody),
+        (status = 404, description = "Image not found", body = ApiErrorBody),
+        (status = 413, description = "Inline image or request body too large", body = ApiErrorBody),
+    )
+)]
+pub async fn inspect_image(
+    State(queue): State<ProcessingQueue>,
+    ApiJson(request): ApiJson<InspectRequest>,
+) -> Result<Json<ImageInspection>, AppError> {
+    debug!("Inspecting image {:?}", request.image_path);
+
+    if request.get_inline_image().is_none() && request.get_image_path().is_none() {
+        return Err(AppError::bad_request("image path is required"));
+    }
+
+    queue.inspect(&request).await.map(Json).map_err(|e| {
+        let rejection = match &e {
+            ProcessorError::ImageNotFound(_) => AppError::not_found(e.to_string()),
+            ProcessorError::InlineImage(InlineImageError::TooLarge { .. }) => AppError::new(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                ErrorCode::PayloadTooLarge,
+                e.to_string(),
+            ),
+            ProcessorError::InlineImage(_)
+            | ProcessorError::RemoteImage(
+                RemoteImageError::HostNotAllowed(_) | RemoteImageError::InvalidUrl(_),
+            )
+            | ProcessorError::Llm(LlmError::InvalidImage(_)) => {
+                AppError::bad_request(e.to_string())
+            }
+            _ => {
+                error!("Failed to inspect image: {}", e);
+                return AppError::internal();
+            }
+        };
+        debug!("Image inspection failed: {}", e);
+        rejection
+    })
+}
+
+/// Checks the fields every submission needs before it is queued
+fn validate_submission(
+    processing_request: &ProcessingRequest,
+    queue: &ProcessingQueue,
+) -> Result<(), AppError> {
+    validate_analysis_request(processing_request, &queue.config())?;
+
+    // Require an image, rejecting inline data that is malformed or too large
+    if let Some(inline_image) = processing_request.get_inline_image() {
+        let max_bytes = queue.config().max_inline_image_bytes;
+        match decode_inline_image(inline_image, max_bytes) {
+            Ok(_) => {}
+            Err(e @ InlineImageError::TooLarge { .. }) => {
+                warn!("Inline image rejected: {}", e);
+                return Err(AppError::new(
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    ErrorCode::PayloadTooLarge,
+                    format!(
+                        "inline image exceeds the maximum size of {} bytes",
+                        max_bytes
+                    ),
+                ));
+            }
+            Err(e) => {
+                warn!("Inline image rejected: {}", e);
+                return Err(AppError::bad_request(format!(
+                    "invalid inline image: {}",
+                    e
+                )));
+            }
+        }
+    } else if processing_request.get_image_path().is_none() {
+        warn!("Validation request missing image path");
+        return Err(AppError::bad_request("image path is required"));
+    }
+
+    Ok(())
+}
+
+/// Wraps a submitted request under a newly generated processing id
+async fn new_processing_request(
+    queue: &ProcessingQueue,
+    request: ValidationRequest,
+) -> Result<ProcessingRequest, AppError> {
+    match queue.new_processing_id().await {
+        Ok(processing_id) => Ok(ProcessingRequest::with_id(request, processing_id)),
+        Err(e) => {
+            error!("Failed to generate a processing id: {}", e);
+            Err(AppError::internal())
+        }
+    }
+}
+
+/// Reads the optional `Idempotency-Key` header
+fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>, AppError> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+
+    match value.to_str().map(str::trim) {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LEN => {
+            Ok(Some(key.to_string()))
+        }
+        _ => Err(AppError::bad_request(format!(
+            "Idempotency-Key must be 1 to {MAX_IDEMPOTENCY_KEY_LEN} visible ASCII characters"
+        ))),
+    }
+}
+
+/// Reads the optional `X-Request-Deadline` header
+fn request_deadline(headers: &HeaderMap) -> Result<Option<SystemTime>, AppError> {
+    let Some(value) = headers.get(REQUEST_DEADLINE_HEADER) else {
+        return Ok(None);
+    };
+
+    value
+        .to_str()
+        .ok()
+        .and_then(|millis| millis.trim().parse::<u64>().ok())
+        .and_then(|millis| UNIX_EPOCH.checked_add(Duration::from_millis(millis)))
+        .map(Some)
+        .ok_or_else(|| {
+            AppError::bad_request("X-Request-Deadline must be a Unix time in milliseconds")
+        })
+}
+
+/// Attaches the processing id to the enclosing request span, which declares the field
+fn record_processing_id(processing_id: &str) {
+    tracing::Span::current().record("processing_id", processing_id);
+}
+
+/// Maps a queue submission failure onto the matching HTTP error
+fn queue_error_response(processing_id: &str, error: QueueError) -> AppError {
+    match error {
+        QueueError::QueueFull => {
+            warn!(processing_id = %processing_id, "Queue is full, rejecting request");
+            AppError::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                ErrorCode::QueueFull,
+                "queue is full, please retry later",
+            )
+        }
+        QueueError::QueueClosed => {
+            error!(processing_id = %processing_id, "Queue is closed, rejecting request");
+            AppError::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorCode::ServiceUnavailable,
+                "service is shutting down",
+            )
+        }
+        e => {
+            error!(processing_id = %processing_id, "Queue error: {}", e);
+            AppError::internal()
+        }
+    }
+}
+
+/// `multipart/form-data` body of `POST /validate`, for uploading the image itself
+#[derive(ToSchema)]
+pub struct ValidationUpload {
+    /// The image file
+    #[schema(value_type = String, format = Binary)]
+    pub image: Vec<u8>,
+    #[schema(rename = "analysis-request")]
+    pub analysis_request: AnalysisRequest,
+    #[schema(rename = "callback-url")]
+    pub callback_url: Option<String>,
+    #[schema(rename = "timeout-seconds")]
+    pub timeout_seconds: Option<u64>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/validate",
+    request_body(content(
+        (ValidationRequest = "application/json"),
+        (ValidationUpload = "multipart/form-data"),
+    )),
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Returns the request first submitted with this key instead of queueing a new one"),
+        ("X-Request-Deadline" = Option<u64>, Header, description = "Unix time in milliseconds by which the result is needed; the request fails if it is still queued then, and processing stops at it"),
+    ),
+    responses(
+        (status = 202, description = "Request queued, or the request first submitted with the same Idempotency-Key", body = SubmitResponse),
+        (status = 400, description = "Invalid request format, Idempotency-Key or X-Request-Deadline", body = ApiErrorBody),
+        (status = 413, description = "Inline image or request body too large", body = ApiErrorBody),
+        (status = 429, description = "Queue is full, or the client exceeded PER_CLIENT_REQUESTS_PER_MINUTE", body = ApiErrorBody),
+        (status = 503, description = "Service shutting down", body = ApiErrorBody),
+    )
+)]
 pub async fn submit_validation(
     State(queue): State<ProcessingQueue>,
-    JsonExtractor(request): JsonExtractor<ValidationRequest>,
-) -> Result<(StatusCode, Json<SubmitResponse>), (StatusCode, Json<ApiResponse<()>>)> {
+    headers: HeaderMap,
+    ValidationSubmission(request): ValidationSubmission,
+) -> Result<(StatusCode, Json<SubmitResponse>), AppError> {
+    let idempotency_key = idempotency_key(&headers)?;
+    let deadline = request_deadline(&headers)?;
+
     // Generate processing request with auto-generated ID
-    let processing_request = ProcessingRequest::from_request(request);
+    let processing_request = new_processing_request(&queue, request)
+        .await?
+        .with_deadline(deadline);
 
+    record_processing_id(&processing_request.processing_id);
     debug!(
-        "Received validation request, assigned ID: {}",
-        processing_request.processing_id
+        processing_id = %processing_request.processing_id,
+        "Received validation request"
     );
 
-    if processing_request.analysis_request.content.is_empty() {
-        warn!("Validation request missing content description");
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ApiResponse::error(
-                "content description is required".to_string(),
-            )),
-        ));
-    }
+    validate_submission(&processing_request, &queue)?;
 
-    // Check if image path is provided
-    if processing_request.get_image_path().is_none() {
-        warn!("Validation request missing image path");
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ApiResponse::error("image path is required".to_string())),
-        ));
+    // A retried submission gets the request its key was first used for
+    if let Some(key) = &idempotency_key {
+        if let Some((processing_id, status)) = queue
+            .claim_idempotency_key(key, &processing_request.processing_id)
+            .await
+        {
+            record_processing_id(&processing_id);
+            debug!(processing_id = %processing_id, "Idempotency key reused");
+            return Ok((
+                StatusCode::ACCEPTED,
+                Json(SubmitResponse {
+                    processing_id,
+                    status: status.as_str().to_string(),
+                }),
+            ));
+        }
     }
 
     // Submit to processing queue
     match queue.submit_validation(processing_request.clone()).await {
         Ok(()) => {
             debug!(
-                "Successfully queued validation request: {}",
-                processing_request.processing_id
+                processing_id = %processing_request.processing_id,
+                "Successfully queued validation request"
             );
             Ok((
                 StatusCode::ACCEPTED,
@@ -97,114 +405,625 @@ pub async fn submit_validation(
                 }),
             ))
         }
-        Err(QueueError::QueueFull) => {
-            warn!(
-                "Queue is full, rejecting request: {}",
-                processing_request.processing_id
-            );
-            Err((
-                StatusCode::TOO_MANY_REQUESTS,
-                Json(ApiResponse::error(
-                    "queue is full, please retry later".to_string(),
-                )),
+        Err(e) => {
+            // Let the client retry with the same key
+            if let Some(key) = &idempotency_key {
+                queue
+                    .release_idempotency_key(key, &processing_request.processing_id)
+                    .await;
+            }
+            Err(queue_error_response(&processing_request.processing_id, e))
+        }
+    }
+}
+
+/// Queues several validation requests at once
+///
+/// Items are validated and queued independently, in order. Returns 202 when every
+/// item was queued, or 207 when some were rejected, e.g. because the queue filled up
+/// partway through the batch.
+#[utoipa::path(
+    post,
+    path = "/validate/batch",
+    request_body = Vec<ValidationRequest>,
+    responses(
+        (status = 202, description = "Every request queued", body = Vec<BatchItemResponse>),
+        (status = 207, description = "Some requests rejected", body = Vec<BatchItemResponse>),
+        (status = 400, description = "Empty batch", body = ApiErrorBody),
+        (status = 429, description = "The client exceeded PER_CLIENT_REQUESTS_PER_MINUTE", body = ApiErrorBody),
+    )
+)]
+pub async fn submit_validation_batch(
+    State(queue): State<ProcessingQueue>,
+    ApiJson(requests): ApiJson<Vec<ValidationRequest>>,
+) -> Result<(StatusCode, Json<Vec<BatchItemResponse>>), AppError> {
+    if requests.is_empty() {
+        return Err(AppError::bad_request(
+            "batch must contain at least one request",
+        ));
+    }
+    debug!("Received batch of {} validation requests", requests.len());
+
+    let mut items = Vec::with_capacity(requests.len());
+    for (index, request) in requests.into_iter().enumerate() {
+        let processing_request = match new_processing_request(&queue, request).await {
+            Ok(processing_request) => processing_request,
+            Err(rejection) => {
+                items.push(BatchItemResponse {
+                    index,
+                    processing_id: String::new(),
+                    status: "rejected".to_string(),
+                    error: Some(rejection.message),
+                });
+                continue;
+            }
+        };
+        let processing_id = processing_request.processing_id.clone();
+
+        let outcome = match validate_submission(&processing_request, &queue) {
+            Ok(()) => queue
+                .try_submit_validation(processing_request)
+                .await
+                .map_err(|e| queue_error_response(&processing_id, e)),
+            Err(rejection) => Err(rejection),
+        };
+
+        items.push(match outcome {
+            Ok(()) => BatchItemResponse {
+                index,
+                processing_id,
+                status: "accepted".to_string(),
+                error: None,
+            },
+            Err(rejection) => BatchItemResponse {
+                index,
+                processing_id,
+                status: "rejected".to_string(),
+                error: Some(rejection.message),
+            },
+        });
+    }
+
+    let status = if items.iter().all(|item| item.error.is_none()) {
+        StatusCode::ACCEPTED
+    } else {
+        StatusCode::MULTI_STATUS
+    };
+    Ok((status, Json(items)))
+}
+
+/// Parses a validation request and returns its normalized constraints without queueing it
+///
+/// No image is read and nothing is submitted, so clients can check their request
+/// format and constraint values while integrating.
+#[utoipa::path(
+    post,
+    path = "/validate/check",
+    request_body = ValidationRequest,
+    responses(
+        (status = 200, description = "Request is valid; the body holds its normalized constraints"),
+        (status = 400, description = "Invalid request format", body = ApiErrorBody),
+    )
+)]
+pub async fn check_validation(
+    State(queue): State<ProcessingQueue>,
+    ApiJson(request): ApiJson<ValidationRequest>,
+) -> Result<Json<ValidationContext>, AppError> {
+    let processing_request = ProcessingRequest::from_request(request);
+    debug!("Checking validation request without processing");
+
+    validate_analysis_request(&processing_request, &queue.config()).map(Json)
+}
+
+/// Queues a validation request and blocks until its results are available
+///
+/// Requests go through the same queue and throttle as `/validate`; the response is
+/// returned directly, or 504 if it is not ready within the processing timeout.
+#[utoipa::path(
+    post,
+    path = "/validate-sync",
+    request_body = ValidationRequest,
+    responses(
+        (status = 200, description = "Validation complete", body = ValidationResponse),
+        (status = 400, description = "Invalid request format", body = ApiErrorBody),
+        (status = 410, description = "Processing was cancelled", body = ApiErrorBody),
+        (status = 413, description = "Inline image or request body too large", body = ApiErrorBody),
+        (status = 429, description = "Queue is full, or the client exceeded PER_CLIENT_REQUESTS_PER_MINUTE", body = ApiErrorBody),
+        (status = 500, description = "Processing failed", body = ApiErrorBody),
+        (status = 504, description = "Processing did not complete in time", body = ApiErrorBody),
+    )
+)]
+pub async fn validate_sync(
+    State(queue): State<ProcessingQueue>,
+    ApiJson(request): ApiJson<ValidationRequest>,
+) -> Result<Json<ValidationResponse>, AppError> {
+    let processing_request = new_processing_request(&queue, request).await?;
+    let processing_id = processing_request.processing_id.clone();
+
+    record_processing_id(&processing_id);
+    debug!(
+        processing_id = %processing_id,
+        "Received synchronous validation request"
+    );
+
+    validate_submission(&processing_request, &queue)?;
+
+    let processing_timeout = queue
+        .config()
+        .processing_timeout_for(processing_request.timeout_seconds);
+    let completion = queue
+        .submit_and_wait(processing_request)
+        .await
+        .map_err(|e| queue_error_response(&processing_id, e))?;
+
+    match timeout(processing_timeout, completion).await {
+        Ok(Ok(Some(result))) => {
+            debug!(processing_id = %processing_id, "Returning synchronous results");
+            Ok(Json(result))
+        }
+        Ok(Ok(None)) if queue.get_status(&processing_id).await == ProcessingStatus::Cancelled => {
+            debug!(processing_id = %processing_id, "Processing was cancelled");
+            Err(AppError::new(
+                StatusCode::GONE,
+                ErrorCode::Cancelled,
+                "processing was cancelled",
             ))
         }
-        Err(QueueError::QueueClosed) => {
-            error!(
-                "Queue is closed, rejecting request: {}",
-                processing_request.processing_id
-            );
-            Err((
+        Ok(Ok(None)) => {
+            debug!(processing_id = %processing_id, "Processing failed");
+            Err(AppError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorCode::ProcessingFailed,
+                "processing failed",
+            ))
+        }
+        Ok(Err(_)) => {
+            error!(processing_id = %processing_id, "Queue dropped synchronous request");
+            Err(AppError::new(
                 StatusCode::SERVICE_UNAVAILABLE,
-                Json(ApiResponse::error("service is shutting down".to_string())),
+                ErrorCode::ServiceUnavailable,
+                "service is shutting down",
             ))
         }
-        Err(e) => {
-            error!(
-                "Queue error for request {}: {}",
-                processing_request.processing_id, e
-            );
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::error("internal server error".to_string())),
+        Err(_) => {
+            warn!(processing_id = %processing_id, "Synchronous request timed out");
+            Err(AppError::new(
+                StatusCode::GATEWAY_TIMEOUT,
+                ErrorCode::Timeout,
+                "processing did not complete in time",
             ))
         }
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/status/{id}",
+    params(("id" = String, Path, description = "Processing ID returned on submission")),
+    responses(
+        (status = 200, description = "Current status", body = StatusResponse),
+        (status = 404, description = "Processing ID not found", body = ApiErrorBody),
+    )
+)]
 pub async fn check_status(
     State(queue): State<ProcessingQueue>,
     Path(processing_id): Path<String>,
-) -> Result<Json<StatusResponse>, (StatusCode, Json<ApiResponse<()>>)> {
-    debug!("Checking status for: {}", processing_id);
+) -> Result<Json<StatusResponse>, AppError> {
+    record_processing_id(&processing_id);
+    debug!(processing_id = %processing_id, "Checking status");
 
     let status = queue.get_status(&processing_id).await;
 
     match status {
         ProcessingStatus::NotFound => {
-            debug!("Processing ID not found: {}", processing_id);
-            Err((
-                StatusCode::NOT_FOUND,
-                Json(ApiResponse::error("processing ID not found".to_string())),
-            ))
+            debug!(processing_id = %processing_id, "Processing ID not found");
+            Err(AppError::not_found("processing ID not found"))
         }
         _ => {
-            debug!("Status for {}: {:?}", processing_id, status);
+            debug!(processing_id = %processing_id, status = ?status, "Status found");
+            let attempts = queue.get_attempts(&processing_id).await;
+            let reason = queue.get_failure_reason(&processing_id).await;
             Ok(Json(StatusResponse {
                 processing_id,
                 status,
+                attempts,
+                reason,
             }))
         }
     }
 }
 
+/// Looks up the statuses of several requests at once
+///
+/// Returns one entry per id, in order; unknown ids are `not_found` rather than an error.
+#[utoipa::path(
+    post,
+    path = "/status/batch",
+    request_body = Vec<String>,
+    responses(
+        (status = 200, description = "Status of each processing id", body = Vec<StatusResponse>),
+        (status = 400, description = "Empty batch or more ids than `MAX_STATUS_BATCH_IDS`", body = ApiErrorBody),
+    )
+)]
+pub async fn check_status_batch(
+    State(queue): State<ProcessingQueue>,
+    ApiJson(processing_ids): ApiJson<Vec<String>>,
+) -> Result<Json<Vec<StatusResponse>>, AppError> {
+    let max_ids = queue.config().max_status_batch_ids;
+    if processing_ids.is_empty() {
+        return Err(AppError::bad_request(
+            "batch must contain at least one processing id",
+        ));
+    }
+    if processing_ids.len() > max_ids {
+        return Err(AppError::bad_request(format!(
+            "batch of {} processing ids exceeds the limit of {max_ids}",
+            processing_ids.len()
+        )));
+    }
+    debug!("Checking status of {} requests", processing_ids.len());
+
+    Ok(Json(queue.get_statuses(processing_ids).await))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ResultsQuery {
+    /// Long-poll for up to this long (e.g. `30s`) before reporting the request as pending
+    pub wait: Option<String>,
+    /// Return the sub-checks finished so far while the request is still in progress
+    pub partial: Option<bool>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/results/{id}",
+    params(
+        ("id" = String, Path, description = "Processing ID returned on submission; a `.csv` suffix asks for CSV"),
+        ResultsQuery,
+    ),
+    responses(
+        (status = 200, description = "Validation results", body = ValidationResponse, content_type = "application/json"),
+        (status = 200, description = "Validation results as a CSV header and row, with `Accept: text/csv` or a `.csv` suffix", body = String, content_type = "text/csv"),
+        (status = 202, description = "Processing not complete; sub-check outcomes so far with `partial=true`", body = PartialResultsResponse),
+        (status = 400, description = "Invalid wait parameter", body = ApiErrorBody),
+        (status = 404, description = "Processing ID not found", body = ApiErrorBody),
+        (status = 410, description = "Processing was cancelled", body = ApiErrorBody),
+        (status = 500, description = "Processing failed", body = ApiErrorBody),
+    )
+)]
 pub async fn get_results(
     State(queue): State<ProcessingQueue>,
     Path(processing_id): Path<String>,
-) -> Result<Json<ValidationResponse>, (StatusCode, Json<ApiResponse<()>>)> {
-    debug!("Getting results for: {}", processing_id);
+    Query(query): Query<ResultsQuery>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    // Axum cannot route a suffix after a path parameter, so `/results/:id.csv` lands here
+    let (processing_id, csv) = match processing_id.strip_suffix(".csv") {
+        Some(processing_id) => (processing_id.to_string(), true),
+        None => (processing_id, accepts_csv(&headers)),
+    };
+    record_processing_id(&processing_id);
+    debug!(processing_id = %processing_id, "Getting results");
 
-    // First check if the processing ID exists
-    let status = queue.get_status(&processing_id).await;
+    // First check if the processing ID exists, waiting for it to finish if asked to
+    let status = match query.wait.as_deref() {
+        None => queue.get_status(&processing_id).await,
+        Some(wait) => {
+            let wait = parse_duration(wait)
+                .map_err(|e| AppError::bad_request(format!("invalid wait parameter: {}", e)))?;
+            // No request stays pending longer than the processing timeout
+            let wait = wait.min(queue.config().longest_processing_timeout());
+            queue.wait_for_completion(&processing_id, wait).await
+        }
+    };
 
     match status {
         ProcessingStatus::NotFound => {
-            debug!("Processing ID not found: {}", processing_id);
-            Err((
-                StatusCode::NOT_FOUND,
-                Json(ApiResponse::error("processing ID not found".to_string())),
-            ))
+            debug!(processing_id = %processing_id, "Processing ID not found");
+            Err(AppError::not_found("processing ID not found"))
+        }
+        ProcessingStatus::InProgress if query.partial == Some(true) => {
+            // The request may have finished since its status was read
+            let results = queue
+                .get_partial_results(&processing_id)
+                .await
+                .unwrap_or_default();
+            debug!(processing_id = %processing_id, "Returning partial results");
+            Ok((
+                StatusCode::ACCEPTED,
+                Json(PartialResultsResponse {
+                    processing_id,
+                    partial: true,
+                    results,
+                }),
+            )
+                .into_response())
         }
         ProcessingStatus::Accepted | ProcessingStatus::InProgress => {
-            debug!("Results not ready for: {}", processing_id);
-            Err((
+            debug!(processing_id = %processing_id, "Results not ready");
+            Err(AppError::new(
                 StatusCode::ACCEPTED,
-                Json(ApiResponse::error("processing not complete".to_string())),
+                ErrorCode::NotReady,
+                "processing not complete",
             ))
         }
         ProcessingStatus::Failed => {
-            debug!("Processing failed for: {}", processing_id);
-            Err((
+            debug!(processing_id = %processing_id, "Processing failed");
+            Err(AppError::new(
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResponse::error("processing failed".to_string())),
+                ErrorCode::ProcessingFailed,
+                "processing failed",
+            ))
+        }
+        ProcessingStatus::Cancelled => {
+            debug!(processing_id = %processing_id, "Processing was cancelled");
+            Err(AppError::new(
+                StatusCode::GONE,
+                ErrorCode::Cancelled,
+                "processing was cancelled",
             ))
         }
         ProcessingStatus::Completed => match queue.get_result(&processing_id).await {
+            Some(result) if csv => {
+                debug!(processing_id = %processing_id, "Returning results as CSV");
+                Ok((
+                    [(header::CONTENT_TYPE, "text/csv; charset=utf-8")],
+                    results_csv(&result),
+                )
+                    .into_response())
+            }
             Some(result) => {
-                debug!("Returning results for: {}", processing_id);
-                Ok(Json(result))
+                debug!(processing_id = %processing_id, "Returning results");
+                Ok(Json(result).into_response())
             }
             None => {
-                error!("Results missing for completed request: {}", processing_id);
-                Err((
+                error!(processing_id = %processing_id, "Results missing for completed request");
+                Err(AppError::new(
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiResponse::error("results not available".to_string())),
+                    ErrorCode::InternalError,
+                    "results not available",
                 ))
             }
         },
     }
 }
 
+/// Whether the `Accept` header asks for `text/csv`; JSON is served otherwise
+fn accepts_csv(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|media_range| {
+            let mut parts = media_range.split(';').map(str::trim);
+            let essence = parts.next().unwrap_or_default();
+            let refused = parts.any(|param| {
+                param.strip_prefix("q=").and_then(|q| q.parse::<f32>().ok()) == Some(0.0)
+            });
+            essence.eq_ignore_ascii_case("text/csv") && !refused
+        })
+}
+
+/// A CSV header and one row with the outcome of a request
+///
+/// Reason codes and messages are joined with `;` within their column, in the same order.
+fn results_csv(response: &ValidationResponse) -> String {
+    let reasons = response.results.reasons.as_deref().unwrap_or_default();
+    let codes: Vec<String> = reasons
+        .iter()
+        .map(|reason| {
+            serde_json::to_value(reason.code)
+                .ok()
+                .and_then(|code| code.as_str().map(str::to_string))
+                .unwrap_or_default()
+        })
+        .collect();
+    let messages: Vec<&str> = reasons
+        .iter()
+        .map(|reason| reason.message.as_str())
+        .collect();
+    let resolution = match response.results.resolution {
+        Resolution::Accepted => "accepted",
+        Resolution::Rejected => "rejected",
+    };
+
+    let row = [
+        response.processing_id.as_str(),
+        resolution,
+        &codes.join(";"),
+        &messages.join("; "),
+    ]
+    .map(csv_field)
+    .join(",");
+    format!("processing-id,resolution,reason_codes,reasons\r\n{row}\r\n")
+}
+
+/// Quotes a CSV field when it holds a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/validate/{id}",
+    params(("id" = String, Path, description = "Processing ID returned on submission")),
+    responses(
+        (status = 200, description = "Request cancelled", body = StatusResponse),
+        (status = 404, description = "Processing ID not found", body = ApiErrorBody),
+        (status = 409, description = "Processing already finished", body = ApiErrorBody),
+    )
+)]
+pub async fn cancel_validation(
+    State(queue): State<ProcessingQueue>,
+    Path(processing_id): Path<String>,
+) -> Result<Json<StatusResponse>, AppError> {
+    record_processing_id(&processing_id);
+    debug!(processing_id = %processing_id, "Cancellation requested");
+
+    match queue.cancel(&processing_id).await {
+        Ok(()) => Ok(Json(StatusResponse {
+            attempts: queue.get_attempts(&processing_id).await,
+            processing_id,
+            status: ProcessingStatus::Cancelled,
+            reason: None,
+        })),
+        Err(QueueError::NotFound) => {
+            debug!(processing_id = %processing_id, "Processing ID not found");
+            Err(AppError::not_found("processing ID not found"))
+        }
+        Err(QueueError::AlreadyFinished) => {
+            debug!(processing_id = %processing_id, "Cannot cancel finished request");
+            Err(AppError::conflict("processing already finished"))
+        }
+        Err(e) => {
+            error!(processing_id = %processing_id, "Failed to cancel request: {}", e);
+            Err(AppError::internal())
+        }
+    }
+}
+
+/// Re-runs a completed, failed or cancelled request with its original parameters
+#[utoipa::path(
+    post,
+    path = "/validate/{id}/retry",
+    params(
+        ("id" = String, Path, description = "Processing ID returned on submission"),
+        ("X-Request-Deadline" = Option<u64>, Header, description = "Unix time in milliseconds by which the re-run result is needed; the original deadline is not reused"),
+    ),
+    responses(
+        (status = 202, description = "Request queued again", body = SubmitResponse),
+        (status = 400, description = "Invalid X-Request-Deadline", body = ApiErrorBody),
+        (status = 404, description = "Processing ID or original request not found", body = ApiErrorBody),
+        (status = 409, description = "Processing not finished yet", body = ApiErrorBody),
+        (status = 429, description = "The client exceeded PER_CLIENT_REQUESTS_PER_MINUTE", body = ApiErrorBody),
+        (status = 503, description = "Service shutting down", body = ApiErrorBody),
+    )
+)]
+pub async fn retry_validation(
+    State(queue): State<ProcessingQueue>,
+    Path(processing_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<SubmitResponse>), AppError> {
+    record_processing_id(&processing_id);
+    debug!(processing_id = %processing_id, "Re-run requested");
+    let deadline = request_deadline(&headers)?;
+
+    match queue.rerun(&processing_id, deadline).await {
+        Ok(()) => Ok((
+            StatusCode::ACCEPTED,
+            Json(SubmitResponse {
+                processing_id,
+                status: ProcessingStatus::Accepted.as_str().to_string(),
+            }),
+        )),
+        Err(QueueError::NotFound) => {
+            debug!(processing_id = %processing_id, "Processing ID not found");
+            Err(AppError::not_found("processing ID not found"))
+        }
+        Err(QueueError::RequestUnavailable) => {
+            debug!(processing_id = %processing_id, "Original request not stored");
+            Err(AppError::not_found("original request no longer available"))
+        }
+        Err(QueueError::NotFinished) => {
+            debug!(processing_id = %processing_id, "Cannot re-run unfinished request");
+            Err(AppError::conflict("processing not finished"))
+        }
+        Err(e) => Err(queue_error_response(&processing_id, e)),
+    }
+}
+
+/// Returns the image exactly as it was sent to the model, after transcoding,
+/// orientation and downscaling, to debug content mismatches
+#[utoipa::path(
+    get,
+    path = "/debug/{id}/image",
+    params(("id" = String, Path, description = "Processing ID returned on submission")),
+    responses(
+        (status = 200, description = "Image bytes sent to the model", content_type = "image/*"),
+        (status = 404, description = "No image retained for the processing ID; needs `DEBUG_RETAIN_PROCESSED_IMAGES`", body = ApiErrorBody),
+    )
+)]
+pub async fn debug_image(
+    State(queue): State<ProcessingQueue>,
+    Path(processing_id): Path<String>,
+) -> Result<Response, AppError> {
+    record_processing_id(&processing_id);
+
+    let image = queue.get_processed_image(&processing_id).ok_or_else(|| {
+        debug!(processing_id = %processing_id, "No processed image retained");
+        AppError::not_found("no processed image retained for this processing ID")
+    })?;
+    debug!(processing_id = %processing_id, bytes = image.bytes.len(), "Returning processed image");
+    Ok(([(header::CONTENT_TYPE, image.content_type)], image.bytes).into_response())
+}
+
+/// Page size used by `GET /jobs` when `limit` is not given
+const DEFAULT_JOBS_LIMIT: usize = 50;
+/// Largest page `GET /jobs` returns; bigger limits are clamped
+const MAX_JOBS_LIMIT: usize = 500;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct JobsQuery {
+    /// Only list jobs with this status, e.g. `in_progress`
+    pub status: Option<String>,
+    /// Page size, 50 by default and at most 500
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/jobs",
+    params(JobsQuery),
+    responses(
+        (status = 200, description = "Jobs, newest first", body = JobListResponse),
+        (status = 400, description = "Invalid status parameter", body = ApiErrorBody),
+    )
+)]
+pub async fn list_jobs(
+    State(queue): State<ProcessingQueue>,
+    Query(query): Query<JobsQuery>,
+) -> Result<Json<JobListResponse>, AppError> {
+    let status = query
+        .status
+        .map(|status| {
+            serde_json::from_value::<ProcessingStatus>(serde_json::Value::String(status.clone()))
+                .map_err(|_| AppError::bad_request(format!("invalid status parameter: {}", status)))
+        })
+        .transpose()?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_JOBS_LIMIT)
+        .min(MAX_JOBS_LIMIT);
+    let offset = query.offset.unwrap_or(0);
+    debug!(
+        "Listing jobs (status: {:?}, limit: {}, offset: {})",
+        status, limit, offset
+    );
+
+    let (jobs, total) = queue.list_jobs(status.as_ref(), limit, offset).await;
+
+    Ok(Json(JobListResponse {
+        jobs,
+        total,
+        limit,
+        offset,
+    }))
+}
+
+/// Liveness check; answers as long as the service is running
+#[utoipa::path(
+    get,
+    path = "/health",
+    responses((status = 200, description = "Service is healthy", body = HealthResponse))
+)]
 pub async fn health_check(State(queue): State<ProcessingQueue>) -> Json<HealthResponse> {
     debug!("Health check requested");
 
@@ -217,32 +1036,174 @@ pub async fn health_check(State(queue): State<ProcessingQueue>) -> Json<HealthRe
     })
 }
 
+/// Build information; always served without an API key
+#[utoipa::path(
+    get,
+    path = "/version",
+    responses((status = 200, description = "Build information", body = VersionResponse))
+)]
+pub async fn version() -> Json<VersionResponse> {
+    Json(VersionResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_sha: env!("GIT_SHA").to_string(),
+        build_timestamp: env!("BUILD_TIMESTAMP").to_string(),
+        rustc_version: env!("RUSTC_VERSION").to_string(),
+    })
+}
+
+/// Readiness check; fails while the LLM API cannot be reached
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    responses(
+        (status = 200, description = "The LLM API is reachable", body = ReadinessResponse),
+        (status = 503, description = "The LLM API is unreachable", body = ReadinessResponse)
+    )
+)]
+pub async fn readiness_check(
+    State(queue): State<ProcessingQueue>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    debug!("Readiness check requested");
+
+    match queue.check_llm_ready().await {
+        Ok(()) => (
+            StatusCode::OK,
+            Json(ReadinessResponse {
+                status: "ready".to_string(),
+                error: None,
+            }),
+        ),
+        Err(e) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ReadinessResponse {
+                status: "unavailable".to_string(),
+                error: Some(e),
+            }),
+        ),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/stats",
+    responses((status = 200, description = "Queue statistics", body = QueueStats))
+)]
 pub async fn queue_stats(State(queue): State<ProcessingQueue>) -> Json<QueueStats> {
     debug!("Queue stats requested");
 
     Json(queue.get_queue_stats().await)
 }
 
-#[derive(Debug, Serialize)]
-pub struct ErrorResponse {
-    pub error: String,
-    pub details: Option<String>,
+/// Queue depth, job ages, worker status and throttle tokens, for debugging
+#[utoipa::path(
+    get,
+    path = "/admin/queue",
+    responses((status = 200, description = "Queue internals", body = QueueSnapshot))
+)]
+pub async fn admin_queue(State(queue): State<ProcessingQueue>) -> Json<QueueSnapshot> {
+    debug!("Queue snapshot requested");
+
+    Json(queue.get_queue_snapshot().await)
 }
 
-pub async fn handle_404() -> (StatusCode, Json<ErrorResponse>) {
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FlushResponse {
+    /// Accepted and in-progress requests dropped from the queue
+    pub flushed: usize,
+}
+
+/// Drops every request that has not finished yet
+#[utoipa::path(
+    post,
+    path = "/admin/queue/flush",
+    responses((status = 200, description = "Unfinished requests dropped", body = FlushResponse))
+)]
+pub async fn flush_queue(State(queue): State<ProcessingQueue>) -> Json<FlushResponse> {
+    Json(FlushResponse {
+        flushed: queue.flush().await,
+    })
+}
+
+/// Prometheus scrape endpoint
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses((status = 200, description = "Prometheus metrics", content_type = "text/plain"))
+)]
+pub async fn metrics() -> impl IntoResponse {
     (
-        StatusCode::NOT_FOUND,
-        Json(ErrorResponse {
-            error: "endpoint not found".to_string(),
-            details: Some("check the API documentation for available endpoints".to_string()),
-        }),
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        crate::metrics::render(),
     )
 }
 
+/// OpenAPI description of the endpoints and their request and response bodies
+pub async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+pub async fn handle_404() -> AppError {
+    AppError::not_found("endpoint not found")
+        .with_details("check the API documentation for available endpoints")
+}
+
+/// Longest plain-text error body carried over into the JSON error envelope
+const MAX_PLAIN_ERROR_BYTES: usize = 4096;
+
+/// Wraps error responses not produced by the handlers in the JSON error envelope
+///
+/// Extractor rejections, such as malformed JSON or a bad path parameter, and axum's
+/// own 405s come back as plain text; their text becomes the error message. Bodies
+/// over the limit are refused by `RequestBodyLimitLayer` when their `Content-Length`
+/// is too large, or by the JSON extractor when a streamed body overruns it; both get
+/// a message naming the limit. JSON errors from handlers pass through untouched.
+pub async fn error_envelope(State(queue): State<ProcessingQueue>, response: Response) -> Response {
+    let status = response.status();
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+    if !(status.is_client_error() || status.is_server_error()) || is_json {
+        return response;
+    }
+
+    if status == StatusCode::PAYLOAD_TOO_LARGE {
+        return AppError::new(status, ErrorCode::PayloadTooLarge, "request body too large")
+            .with_details(format!(
+                "request bodies are limited to {} bytes",
+                queue.config().max_request_body_bytes
+            ))
+            .into_response();
+    }
+
+    let headers = response.headers().clone();
+    let text = axum::body::to_bytes(response.into_body(), MAX_PLAIN_ERROR_BYTES)
+        .await
+        .map(|body| String::from_utf8_lossy(&body).trim().to_string())
+        .unwrap_or_default();
+    let message = if text.is_empty() {
+        status
+            .canonical_reason()
+            .unwrap_or("request failed")
+            .to_lowercase()
+    } else {
+        text
+    };
+
+    let mut enveloped =
+        AppError::new(status, ErrorCode::for_status(status), message).into_response();
+    // Keep headers such as `Allow` on a 405, but describe the new body
+    for (name, value) in &headers {
+        if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+            enveloped.headers_mut().insert(name, value.clone());
+        }
+    }
+    enveloped
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::Config;
     use axum::{
         body::Body,
         http::{Request, StatusCode},
@@ -261,6 +1222,7 @@ mod tests {
             processing_timeout_minutes: 5,
             queue_size: 100,
             throttle_requests_per_minute: 60,
+            ..Config::default()
         }
     }
 
@@ -326,6 +1288,23 @@ mod tests {
         assert_eq!(response.status(), StatusCode::ACCEPTED);
     }
 
+    #[tokio::test]
+    async fn test_queue_full_error_envelope() {
+        let response = queue_error_response("abc", QueueError::QueueFull).into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["code"], "QUEUE_FULL");
+        assert_eq!(
+            body["error"]["message"],
+            "queue is full, please retry later"
+        );
+        assert!(body["error"]["details"].is_null());
+    }
+
     #[tokio::test]
     async fn test_check_status_not_found() {
         let config = create_test_config();
@@ -371,4 +1350,46 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::OK);
     }
+
+    #[test]
+    fn test_results_csv_quotes_fields() {
+        use crate::models::{ReasonCode, RejectionReason, ValidationResults};
+
+        let response = ValidationResponse {
+            processing_id: "abc123".to_string(),
+            results: ValidationResults::rejected(vec![
+                RejectionReason::new(
+                    ReasonCode::ContentMismatch,
+                    "image content does not match description: 'Birds, \"three\"'",
+                ),
+                RejectionReason::new(ReasonCode::GpsMissing, "no GPS"),
+            ]),
+        };
+        assert_eq!(
+            results_csv(&response),
+            "processing-id,resolution,reason_codes,reasons\r\n\
+             abc123,rejected,CONTENT_MISMATCH;GPS_MISSING,\
+             \"image content does not match description: 'Birds, \"\"three\"\"'; no GPS\"\r\n"
+        );
+
+        let accepted = ValidationResponse {
+            processing_id: "abc123".to_string(),
+            results: ValidationResults::accepted(),
+        };
+        assert!(results_csv(&accepted).ends_with("\r\nabc123,accepted,,\r\n"));
+    }
+
+    #[test]
+    fn test_accepts_csv() {
+        let accept = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::ACCEPT, value.parse().unwrap());
+            accepts_csv(&headers)
+        };
+        assert!(accept("text/csv"));
+        assert!(accept("application/json;q=0.9, Text/CSV"));
+        assert!(!accept("text/csv;q=0"));
+        assert!(!accept("application/json"));
+        assert!(!accepts_csv(&HeaderMap::new()));
+    }
 }
@@ -1,10 +1,23 @@
+pub mod auth;
+pub mod circuit_breaker;
+pub mod client_limit;
 pub mod config;
+pub mod error;
+pub mod extract;
+pub mod geocoder;
 pub mod handlers;
+pub mod metrics;
 pub mod models;
+pub mod openapi;
+pub mod persistence;
 pub mod queue;
+pub mod rates;
+pub mod readiness;
 pub mod storage;
+pub mod throttle;
 pub mod utils;
 pub mod validation;
+pub mod webhook;
 
 pub use config::{Config, ConfigError};
 pub use models::*;
@@ -1,41 +1,72 @@
+use image_checker::auth::ApiKeyLayer;
+use image_checker::client_limit::ClientRateLimitLayer;
+use image_checker::config::LogFormat;
 use image_checker::handlers::{
-    check_status, get_results, handle_404, health_check, queue_stats, submit_validation,
+    admin_queue, cancel_validation, check_status, check_status_batch, check_validation,
+    debug_image, error_envelope, flush_queue, get_results, handle_404, health_check, inspect_image,
+    list_jobs, metrics, openapi, queue_stats, readiness_check, retry_validation, submit_validation,
+    submit_validation_batch, validate_sync, version,
 };
+use image_checker::readiness::{check_model_available, MODEL_CHECK_TIMEOUT};
 use image_checker::{Config, ProcessingQueue};
 
 use axum::{
-    routing::{get, post},
+    body::Body,
+    extract::DefaultBodyLimit,
+    http::{header, HeaderName, HeaderValue, Method, Request},
+    middleware::map_response_with_state,
+    routing::{delete, get, post},
     Router,
 };
 use std::net::SocketAddr;
 use std::str::FromStr;
+use std::time::Duration;
 use tokio::signal;
 use tower::ServiceBuilder;
 use tower_http::{
-    cors::CorsLayer,
-    trace::{DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse, TraceLayer},
+    compression::{
+        predicate::{DefaultPredicate, Predicate, SizeAbove},
+        CompressionLayer,
+    },
+    cors::{AllowOrigin, CorsLayer},
+    limit::RequestBodyLimitLayer,
+    trace::{DefaultOnRequest, DefaultOnResponse, TraceLayer},
 };
-use tracing::{error, info, warn, Level};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing::{error, field, info, info_span, warn, Level, Span, Subscriber};
+use tracing_subscriber::fmt::{self, MakeWriter};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
 
 #[tokio::main]
 async fn main() {
+    // Load configuration first, since it selects the log format
+    let config = Config::from_env();
+
     // Initialize logging
-    init_logging();
+    init_logging(
+        config
+            .as_ref()
+            .map(|config| config.log_format)
+            .unwrap_or_default(),
+    );
 
     info!(
         "Starting Image Checker service v{}",
         env!("CARGO_PKG_VERSION")
     );
 
-    // Load configuration
-    let config = match Config::from_env() {
+    let config = match config {
         Ok(config) => {
             info!("Configuration loaded successfully");
             info!("  +- Image base directory: {}", config.image_base_dir);
             info!("  +---------- LLM API URL: {}", config.llm_api_url);
             info!("  +------------ LLM MODEL: {}", config.llm_model_name);
             info!("  +------------Queue size: {}", config.queue_size);
+            info!(
+                "  +----- Throttle interval: {:?} ({} requests/minute)",
+                config.throttle_interval(),
+                config.throttle_requests_per_minute
+            );
             config
         }
         Err(e) => {
@@ -44,6 +75,32 @@ async fn main() {
         }
     };
 
+    if config.storage_verify_on_start {
+        if let Err(e) = config.verify_storage().await {
+            error!("Storage {} is not reachable: {}", config.image_base_dir, e);
+            std::process::exit(1);
+        }
+        info!("Storage {} is reachable", config.image_base_dir);
+    }
+
+    if !config.skip_model_check {
+        if let Err(e) = check_model_available(
+            &config.llm_api_url,
+            config.llm_backend,
+            &config.llm_model_name,
+            MODEL_CHECK_TIMEOUT,
+        )
+        .await
+        {
+            error!("{}; set SKIP_MODEL_CHECK=true to start anyway", e);
+            std::process::exit(1);
+        }
+        info!("LLM model {} is available", config.llm_model_name);
+    }
+
+    // Install the Prometheus recorder before anything records metrics
+    image_checker::metrics::init();
+
     // Create processing queue
     let queue = ProcessingQueue::new(&config);
     info!(
@@ -51,6 +108,17 @@ async fn main() {
         config.queue_size
     );
 
+    // Apply changes to the reloadable settings without a restart
+    #[cfg(unix)]
+    tokio::spawn(reload_on_hangup(queue.clone()));
+
+    if config.api_keys().is_empty() {
+        warn!("API_KEYS is not set; the API is open to unauthenticated requests");
+    }
+    if config.cors_allowed_origins().is_empty() {
+        warn!("CORS_ALLOWED_ORIGINS is not set; browsers may call the API from any origin");
+    }
+
     // Build the application router
     let app = build_router(queue.clone());
 
@@ -78,9 +146,14 @@ async fn main() {
     };
 
     // Start server with graceful shutdown
-    if let Err(e) = axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal(queue))
-        .await
+    let shutdown_grace = config.shutdown_grace();
+    // The peer address identifies clients for PER_CLIENT_REQUESTS_PER_MINUTE
+    if let Err(e) = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal(queue, shutdown_grace))
+    .await
     {
         error!("Server error: {}", e);
         std::process::exit(1);
@@ -89,43 +162,188 @@ async fn main() {
     info!("Image Checker service stopped");
 }
 
-fn init_logging() {
+fn init_logging(format: LogFormat) {
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "image_checker=info,tower_http=debug".into()),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(log_layer(format, std::io::stdout))
         .init();
 }
 
+/// Formatting layer writing `format` lines to `writer`
+fn log_layer<S, W>(format: LogFormat, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    match format {
+        LogFormat::Text => fmt::layer().with_writer(writer).boxed(),
+        LogFormat::Json => fmt::layer().json().with_writer(writer).boxed(),
+    }
+}
+
+/// Span for each HTTP request; handlers fill in `processing_id` once it is known
+fn request_span(request: &Request<Body>) -> Span {
+    info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        version = ?request.version(),
+        processing_id = field::Empty,
+    )
+}
+
+/// Responses smaller than this are sent uncompressed, since compressing them saves little
+const COMPRESSION_MIN_BYTES: u16 = 1024;
+
+/// Compresses responses with gzip or brotli when the client accepts it
+///
+/// Images and streams are skipped, as by tower-http's default predicate, as are
+/// responses under `COMPRESSION_MIN_BYTES`.
+fn compression_layer() -> CompressionLayer<impl Predicate> {
+    CompressionLayer::new()
+        .compress_when(DefaultPredicate::new().and(SizeAbove::new(COMPRESSION_MIN_BYTES)))
+}
+
+/// CORS policy from `CORS_ALLOWED_ORIGINS`, `CORS_ALLOWED_METHODS` and
+/// `CORS_ALLOW_CREDENTIALS`; permissive when no origin is configured
+fn cors_layer(config: &Config) -> CorsLayer {
+    let origins = config.cors_allowed_origins();
+    if origins.is_empty() {
+        return CorsLayer::permissive();
+    }
+
+    // Config validation guarantees the origins and methods parse
+    let allow_origin = if origins == ["*"] {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(
+            origins
+                .iter()
+                .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+        )
+    };
+    let methods: Vec<Method> = config
+        .cors_allowed_methods()
+        .iter()
+        .filter_map(|method| method.parse().ok())
+        .collect();
+
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods(methods)
+        .allow_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            HeaderName::from_static("idempotency-key"),
+        ])
+        .allow_credentials(config.cors_allow_credentials)
+}
+
 fn build_router(queue: ProcessingQueue) -> Router {
+    // Config validation guarantees this leaves room for base64 inline images
+    let config = queue.config();
+    let body_limit = config.max_request_body_bytes;
+    let api_key_layer = ApiKeyLayer::from_config(&config);
+    let client_rate_limit_layer = ClientRateLimitLayer::from_config(&config);
+    let cors_layer = cors_layer(&config);
+
     Router::new()
         // API routes
         .route("/validate", post(submit_validation))
+        .route("/validate/batch", post(submit_validation_batch))
+        .route("/validate/check", post(check_validation))
+        .route("/validate/:id", delete(cancel_validation))
+        .route("/validate/:id/retry", post(retry_validation))
+        .route("/validate-sync", post(validate_sync))
+        .route("/inspect", post(inspect_image))
+        .route("/status/batch", post(check_status_batch))
         .route("/status/:id", get(check_status))
         .route("/results/:id", get(get_results))
+        .route("/debug/:id/image", get(debug_image))
+        .route("/jobs", get(list_jobs))
         // Health and monitoring routes
         .route("/health", get(health_check))
+        .route("/health/ready", get(readiness_check))
+        .route("/version", get(version))
         .route("/stats", get(queue_stats))
+        .route("/admin/queue", get(admin_queue))
+        .route("/admin/queue/flush", post(flush_queue))
+        .route("/metrics", get(metrics))
+        .route("/openapi.json", get(openapi))
         // 404 handler
         .fallback(handle_404)
         // Add shared state
-        .with_state(queue)
+        .with_state(queue.clone())
+        // The layer enforces the limit for every route, so axum's own default is lifted
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(body_limit))
+        .layer(map_response_with_state(queue, error_envelope))
         // Add middleware
         .layer(
             ServiceBuilder::new()
                 .layer(
                     TraceLayer::new_for_http()
-                        .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
+                        .make_span_with(request_span)
                         .on_request(DefaultOnRequest::new().level(Level::INFO))
                         .on_response(DefaultOnResponse::new().level(Level::INFO)),
                 )
-                .layer(CorsLayer::permissive()), // Allow CORS for development
+                .layer(compression_layer())
+                .layer(cors_layer)
+                // Inside CORS so preflight requests are answered without credentials
+                .layer(api_key_layer)
+                // Inside authentication, which names the verified API key of each request
+                .layer(client_rate_limit_layer),
         )
 }
 
-async fn shutdown_signal(queue: ProcessingQueue) {
+/// Reloads the configuration on every SIGHUP
+///
+/// Only the settings [`Config::with_reloaded`] takes are applied; a configuration that
+/// fails to load or validate is logged and the running one kept.
+#[cfg(unix)]
+async fn reload_on_hangup(queue: ProcessingQueue) {
+    let mut hangup = match signal::unix::signal(signal::unix::SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            error!(
+                "Failed to install SIGHUP handler; config reload is disabled: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    while hangup.recv().await.is_some() {
+        info!("Received SIGHUP, reloading configuration");
+        let applied = Config::reload_from_env().and_then(|reloaded| queue.reload(&reloaded));
+        match applied {
+            Ok(ignored) => {
+                let config = queue.config();
+                info!(
+                    "Configuration reloaded: throttle {}/min, model {}, prompt template {}",
+                    config.throttle_requests_per_minute,
+                    config.llm_model_name,
+                    config.prompt_template_path.as_deref().unwrap_or("built-in")
+                );
+                for setting in ignored {
+                    warn!(
+                        "{} changed but only takes effect on restart; ignored",
+                        setting
+                    );
+                }
+            }
+            Err(e) => error!(
+                "Failed to reload configuration; keeping the running one: {}",
+                e
+            ),
+        }
+    }
+}
+
+async fn shutdown_signal(queue: ProcessingQueue, grace: Duration) {
     let ctrl_c = async {
         signal::ctrl_c()
             .await
@@ -156,9 +374,22 @@ async fn shutdown_signal(queue: ProcessingQueue) {
     info!("Shutting down processing queue...");
     queue.shutdown().await;
 
-    // Give some time for in-flight requests to complete
-    warn!("Waiting 10 seconds for in-flight requests to complete...");
-    tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+    // Give in-flight requests up to the grace period to complete
+    info!(
+        "Waiting up to {:?} for in-flight requests to complete...",
+        grace
+    );
+    let abandoned = queue.await_idle(grace).await;
+    if abandoned > 0 {
+        warn!(
+            "Shutdown grace period expired; abandoning {} in-progress requests",
+            abandoned
+        );
+    }
+    let failed = queue.fail_unfinished().await;
+    if failed > 0 {
+        warn!("Marked {} unfinished requests as failed", failed);
+    }
 
     info!("Graceful shutdown complete");
 }
@@ -166,10 +397,9 @@ async fn shutdown_signal(queue: ProcessingQueue) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use axum::{
-        body::Body,
-        http::{Request, StatusCode},
-    };
+    use axum::http::StatusCode;
+    use std::io;
+    use std::sync::{Arc, Mutex};
     use tower::ServiceExt;
 
     #[tokio::test]
@@ -184,6 +414,7 @@ mod tests {
             processing_timeout_minutes: 5,
             queue_size: 100,
             throttle_requests_per_minute: 60,
+            ..Config::default()
         };
 
         let queue = ProcessingQueue::new(&config);
@@ -214,6 +445,7 @@ mod tests {
             processing_timeout_minutes: 5,
             queue_size: 100,
             throttle_requests_per_minute: 60,
+            ..Config::default()
         };
 
         let queue = ProcessingQueue::new(&config);
@@ -231,4 +463,222 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
+
+    fn create_authenticated_app() -> Router {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            api_keys: Some("first-key,second-key".to_string()),
+            auth_exclude_paths: Some("/health,/metrics,/admin/queue".to_string()),
+            ..Config::default()
+        };
+
+        build_router(ProcessingQueue::new(&config))
+    }
+
+    async fn get_status_code(app: &Router, uri: &str, token: Option<&str>) -> StatusCode {
+        let mut request = Request::builder().uri(uri);
+        if let Some(token) = token {
+            request = request.header("authorization", format!("Bearer {token}"));
+        }
+
+        app.clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_api_key_required() {
+        let app = create_authenticated_app();
+
+        assert_eq!(
+            get_status_code(&app, "/stats", None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            get_status_code(&app, "/stats", Some("wrong-key")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            get_status_code(&app, "/stats", Some("second-key")).await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_api_key_excluded_paths() {
+        let app = create_authenticated_app();
+
+        assert_eq!(get_status_code(&app, "/health", None).await, StatusCode::OK);
+        assert_eq!(
+            get_status_code(&app, "/metrics", None).await,
+            StatusCode::OK
+        );
+        // Public whatever AUTH_EXCLUDE_PATHS says
+        assert_eq!(
+            get_status_code(&app, "/version", None).await,
+            StatusCode::OK
+        );
+        // Admin routes need a key whatever AUTH_EXCLUDE_PATHS says
+        assert_eq!(
+            get_status_code(&app, "/admin/queue", None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            get_status_code(&app, "/admin/queue", Some("first-key")).await,
+            StatusCode::OK
+        );
+    }
+
+    async fn allowed_origin(app: &Router, origin: &str) -> Option<String> {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/health")
+                    .header("origin", origin)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .map(|value| value.to_str().unwrap().to_string())
+    }
+
+    #[tokio::test]
+    async fn test_cors_allowed_origins() {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            cors_allowed_origins: Some("https://app.example.com".to_string()),
+            cors_allow_credentials: true,
+            ..Config::default()
+        };
+        let app = build_router(ProcessingQueue::new(&config));
+
+        assert_eq!(
+            allowed_origin(&app, "https://app.example.com").await,
+            Some("https://app.example.com".to_string())
+        );
+        assert_eq!(allowed_origin(&app, "https://evil.example.com").await, None);
+
+        // Without configured origins every origin is allowed
+        let config = Config {
+            cors_allowed_origins: None,
+            cors_allow_credentials: false,
+            ..config
+        };
+        let app = build_router(ProcessingQueue::new(&config));
+        assert_eq!(
+            allowed_origin(&app, "https://evil.example.com").await,
+            Some("*".to_string())
+        );
+    }
+
+    /// Fetches `uri` accepting gzip, returning the content encoding and decoded body
+    async fn get_gzip(app: &Router, uri: &str) -> (Option<String>, String) {
+        use std::io::Read;
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(uri)
+                    .header(header::ACCEPT_ENCODING, "gzip")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let encoding = response
+            .headers()
+            .get(header::CONTENT_ENCODING)
+            .map(|value| value.to_str().unwrap().to_string());
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        let mut body = String::new();
+        match encoding.as_deref() {
+            Some("gzip") => {
+                flate2::read::GzDecoder::new(&bytes[..])
+                    .read_to_string(&mut body)
+                    .unwrap();
+            }
+            _ => body = String::from_utf8(bytes.to_vec()).unwrap(),
+        }
+        (encoding, body)
+    }
+
+    #[tokio::test]
+    async fn test_responses_are_compressed() {
+        image_checker::metrics::init();
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            ..Config::default()
+        };
+        let app = build_router(ProcessingQueue::new(&config));
+
+        let (encoding, body) = get_gzip(&app, "/openapi.json").await;
+        assert_eq!(encoding.as_deref(), Some("gzip"));
+        assert!(body.len() > COMPRESSION_MIN_BYTES as usize);
+        let doc: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert!(doc["paths"]["/validate"].is_object());
+
+        // Small responses are not worth compressing
+        let (encoding, body) = get_gzip(&app, "/version").await;
+        assert_eq!(encoding, None);
+        assert!(body.contains("git_sha"));
+
+        // Compressed or not, the metrics still read as the text exposition format
+        let (_, body) = get_gzip(&app, "/metrics").await;
+        for line in body.lines().filter(|line| !line.starts_with('#')) {
+            let value = line.rsplit(' ').next().unwrap();
+            assert!(value.parse::<f64>().is_ok(), "{line}");
+        }
+    }
+
+    /// Collects everything the log layer writes
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_logs_carry_processing_id() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber =
+            tracing_subscriber::registry().with(log_layer(LogFormat::Json, move || writer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = info_span!("request", processing_id = field::Empty);
+            let _entered = span.enter();
+            Span::current().record("processing_id", "abc-123");
+            info!(attempt = 2, "Processing failed");
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let line: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["fields"]["message"], "Processing failed");
+        assert_eq!(line["fields"]["attempt"], 2);
+        assert_eq!(line["span"]["processing_id"], "abc-123");
+    }
 }
@@ -0,0 +1,153 @@
+use ::metrics::{
+    counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram, Unit,
+};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::warn;
+
+pub const SUBMISSIONS_TOTAL: &str = "image_checker_submissions_total";
+pub const REJECTIONS_TOTAL: &str = "image_checker_rejections_total";
+pub const LLM_REQUEST_DURATION_SECONDS: &str = "image_checker_llm_request_duration_seconds";
+pub const QUEUE_DEPTH: &str = "image_checker_queue_depth";
+pub const THROTTLE_WAIT_SECONDS: &str = "image_checker_throttle_wait_seconds";
+
+/// Histogram buckets in seconds, from fast local models up to slow retried calls
+const LATENCY_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0];
+
+static HANDLE: OnceLock<Option<PrometheusHandle>> = OnceLock::new();
+
+/// The constraint a rejected request failed, used as the `reason` label
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectionReason {
+    Image,
+    Content,
+    Location,
+    Datetime,
+    Camera,
+    Edited,
+    Duplicate,
+    Similarity,
+    Score,
+    Dimensions,
+}
+
+impl RejectionReason {
+    fn as_label(self) -> &'static str {
+        match self {
+            RejectionReason::Image => "image",
+            RejectionReason::Content => "content",
+            RejectionReason::Location => "location",
+            RejectionReason::Datetime => "datetime",
+            RejectionReason::Camera => "camera",
+            RejectionReason::Edited => "edited",
+            RejectionReason::Duplicate => "duplicate",
+            RejectionReason::Similarity => "similarity",
+            RejectionReason::Score => "score",
+            RejectionReason::Dimensions => "dimensions",
+        }
+    }
+}
+
+/// Installs the global Prometheus recorder; later calls are no-ops
+///
+/// Metrics recorded before this runs are discarded, so call it at startup.
+pub fn init() {
+    HANDLE.get_or_init(|| {
+        let recorder = PrometheusBuilder::new()
+            .set_buckets_for_metric(
+                Matcher::Full(LLM_REQUEST_DURATION_SECONDS.to_string()),
+                LATENCY_BUCKETS,
+            )
+            .and_then(|builder| {
+                builder.set_buckets_for_metric(
+                    Matcher::Full(THROTTLE_WAIT_SECONDS.to_string()),
+                    LATENCY_BUCKETS,
+                )
+            })
+            .and_then(|builder| builder.install_recorder());
+
+        match recorder {
+            Ok(handle) => {
+                describe_metrics();
+                Some(handle)
+            }
+            Err(e) => {
+                warn!("Failed to install Prometheus recorder: {}", e);
+                None
+            }
+        }
+    });
+}
+
+/// Renders all metrics in the Prometheus text exposition format
+pub fn render() -> String {
+    init();
+    HANDLE
+        .get()
+        .and_then(Option::as_ref)
+        .map(PrometheusHandle::render)
+        .unwrap_or_default()
+}
+
+fn describe_metrics() {
+    describe_counter!(
+        SUBMISSIONS_TOTAL,
+        "Validation requests accepted into the queue"
+    );
+    describe_counter!(
+        REJECTIONS_TOTAL,
+        "Failed constraints in rejected validations, by reason"
+    );
+    describe_histogram!(
+        LLM_REQUEST_DURATION_SECONDS,
+        Unit::Seconds,
+        "Latency of individual LLM API calls"
+    );
+    describe_gauge!(QUEUE_DEPTH, "Requests waiting for the worker");
+    describe_histogram!(
+        THROTTLE_WAIT_SECONDS,
+        Unit::Seconds,
+        "Time the worker spent throttled per request"
+    );
+}
+
+pub fn record_submission() {
+    counter!(SUBMISSIONS_TOTAL).increment(1);
+}
+
+pub fn record_enqueued() {
+    gauge!(QUEUE_DEPTH).increment(1.0);
+}
+
+pub fn record_dequeued() {
+    gauge!(QUEUE_DEPTH).decrement(1.0);
+}
+
+pub fn record_rejection(reason: RejectionReason) {
+    counter!(REJECTIONS_TOTAL, "reason" => reason.as_label()).increment(1);
+}
+
+pub fn record_llm_latency(elapsed: Duration) {
+    histogram!(LLM_REQUEST_DURATION_SECONDS).record(elapsed.as_secs_f64());
+}
+
+pub fn record_throttle_wait(elapsed: Duration) {
+    histogram!(THROTTLE_WAIT_SECONDS).record(elapsed.as_secs_f64());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_includes_recorded_metrics() {
+        init();
+        record_rejection(RejectionReason::Location);
+        record_llm_latency(Duration::from_millis(300));
+
+        let output = render();
+        assert!(output.contains("image_checker_rejections_total{reason=\"location\"}"));
+        assert!(output.contains("image_checker_llm_request_duration_seconds_bucket"));
+    }
+}
@@ -1,75 +1,767 @@
-use chrono::{DateTime, FixedOffset};
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Deserialize, Clone)]
+use crate::utils::parse_iso8601_duration;
+use crate::validation::inline::is_inline_image;
+use crate::validation::phash::DEFAULT_SIMILARITY_THRESHOLD;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone};
+use chrono_tz::Tz;
+use rand::Rng;
+use regex::Regex;
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::sync::LazyLock;
+use std::time::SystemTime;
+use utoipa::ToSchema;
+
+#[derive(Debug, Deserialize, Clone, ToSchema)]
 pub struct ValidationRequest {
+    /// Path of the image under the image base directory, or several candidate paths
     #[serde(rename = "image-path")]
-    pub image_path: Option<String>,
+    pub image_path: Option<ImagePaths>,
 
+    /// Image sent inline as a data URL or raw base64, used when `image-path` is absent
     pub image: Option<String>,
 
     #[serde(rename = "analysis-request")]
     pub analysis_request: AnalysisRequest,
+
+    /// http(s) URL that receives the results when processing finishes
+    #[serde(rename = "callback-url", alias = "callback_url")]
+    pub callback_url: Option<String>,
+
+    /// Processing timeout for this request instead of `PROCESSING_TIMEOUT_MINUTES`; at
+    /// most `MAX_REQUEST_TIMEOUT_SECONDS`
+    #[serde(rename = "timeout-seconds", alias = "timeout_seconds")]
+    pub timeout_seconds: Option<u64>,
 }
 
 impl ValidationRequest {
     pub fn get_image_path(&self) -> Option<String> {
-        self.image_path.clone().or_else(|| self.image.clone())
+        self.image_path
+            .as_ref()
+            .and_then(ImagePaths::first)
+            .map(str::to_string)
+            .or_else(|| self.image.clone())
+    }
+}
+
+/// Body of `POST /inspect`: an image given the same way as for validation
+#[derive(Debug, Deserialize, Clone, ToSchema)]
+pub struct InspectRequest {
+    /// Path of the image under the image base directory
+    #[serde(rename = "image-path")]
+    pub image_path: Option<String>,
+
+    /// Image sent inline as a data URL or raw base64, used when `image-path` is absent
+    pub image: Option<String>,
+}
+
+impl InspectRequest {
+    pub fn get_image_path(&self) -> Option<String> {
+        self.image_path
+            .clone()
+            .or_else(|| self.image.clone().filter(|image| !is_inline_image(image)))
+    }
+
+    /// Returns the base64 image data when `image` carries the image inline
+    pub fn get_inline_image(&self) -> Option<&str> {
+        self.image
+            .as_deref()
+            .filter(|image| self.image_path.is_none() && is_inline_image(image))
+    }
+}
+
+/// What the service sees about an image, without running the content check
+#[derive(Debug, Serialize, Clone, PartialEq, ToSchema)]
+pub struct ImageInspection {
+    /// Path the image was read from; absent for inline images
+    pub resolved_path: Option<String>,
+
+    /// Format detected from the magic bytes, e.g. `jpeg` or `heif`
+    pub format: Option<String>,
+
+    /// Pixel dimensions, when the format can be decoded
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+
+    /// Whether EXIF carries GPS coordinates
+    pub has_gps: bool,
+
+    /// Whether EXIF carries a capture or modification timestamp
+    pub has_timestamp: bool,
+}
+
+/// `image-path` as either a single path or an array of candidate paths
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+#[serde(untagged)]
+pub enum ImagePaths {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl ImagePaths {
+    pub fn first(&self) -> Option<&str> {
+        match self {
+            ImagePaths::Single(path) => Some(path),
+            ImagePaths::Multiple(paths) => paths.first().map(String::as_str),
+        }
+    }
+
+    pub fn to_vec(&self) -> Vec<String> {
+        match self {
+            ImagePaths::Single(path) => vec![path.clone()],
+            ImagePaths::Multiple(paths) => paths.clone(),
+        }
+    }
+}
+
+impl From<String> for ImagePaths {
+    fn from(path: String) -> Self {
+        ImagePaths::Single(path)
+    }
+}
+
+impl From<Vec<String>> for ImagePaths {
+    fn from(paths: Vec<String>) -> Self {
+        ImagePaths::Multiple(paths)
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct ProcessingRequest {
     pub processing_id: String,
-    pub image_path: Option<String>,
+    pub image_path: Option<ImagePaths>,
     pub image: Option<String>,
     pub analysis_request: AnalysisRequest,
+    pub callback_url: Option<String>,
+    pub timeout_seconds: Option<u64>,
+    /// The client's `X-Request-Deadline`, past which processing is pointless
+    pub deadline: Option<SystemTime>,
 }
 
 impl ProcessingRequest {
+    /// Wraps a request under a fresh UUID
     pub fn from_request(request: ValidationRequest) -> Self {
-        use uuid::Uuid;
+        Self::with_id(request, uuid::Uuid::new_v4().to_string())
+    }
 
+    pub fn with_id(request: ValidationRequest, processing_id: String) -> Self {
         Self {
-            processing_id: Uuid::new_v4().to_string(),
+            processing_id,
             image_path: request.image_path,
             image: request.image,
             analysis_request: request.analysis_request,
+            callback_url: request.callback_url,
+            timeout_seconds: request.timeout_seconds,
+            deadline: None,
         }
     }
 
+    /// Sets the time by which the client needs the result
+    #[must_use]
+    pub fn with_deadline(mut self, deadline: Option<SystemTime>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Returns the (first) image path, falling back to `image` when it is not inline data
     pub fn get_image_path(&self) -> Option<String> {
-        self.image_path.clone().or_else(|| self.image.clone())
+        self.get_image_paths().into_iter().next()
+    }
+
+    /// Returns every image path in the request, in the order given
+    pub fn get_image_paths(&self) -> Vec<String> {
+        match &self.image_path {
+            Some(paths) => paths.to_vec(),
+            None => self
+                .image
+                .clone()
+                .filter(|image| !is_inline_image(image))
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Returns the base64 image data when `image` carries the image inline
+    pub fn get_inline_image(&self) -> Option<&str> {
+        self.image.as_deref().filter(|image| is_inline_image(image))
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+/// Characters used for generated processing ids
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IdAlphabet {
+    /// Digits and letters without the look-alikes `0`, `O`, `I` and `l`
+    #[default]
+    Base58,
+    /// Digits and all ASCII letters
+    Base62,
+}
+
+impl IdAlphabet {
+    pub fn chars(self) -> &'static [u8] {
+        match self {
+            IdAlphabet::Base58 => b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz",
+            IdAlphabet::Base62 => b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz",
+        }
+    }
+}
+
+/// A random, URL-safe processing id of `length` characters from `alphabet`
+pub fn generate_processing_id(length: usize, alphabet: IdAlphabet) -> String {
+    let chars = alphabet.chars();
+    let mut rng = rand::thread_rng();
+    (0..length)
+        .map(|_| chars[rng.gen_range(0..chars.len())] as char)
+        .collect()
+}
+
+/// A radius around `lat`/`long`, a `polygon` or a `place_name`, optionally with an
+/// altitude range
+#[derive(Debug, Deserialize, Clone, ToSchema)]
 pub struct LocationRequest {
-    pub long: f64,
-    pub lat: f64,
-    pub max_distance: f64,
+    pub long: Option<f64>,
+    pub lat: Option<f64>,
+    pub max_distance: Option<f64>,
+    /// Unit of `max_distance`; meters when omitted
+    pub distance_unit: Option<DistanceUnit>,
+    pub polygon: Option<Vec<[f64; 2]>>, // [lat, long] vertices
+    /// Named place resolved to its bounding box through `GEOCODER_URL`
+    pub place_name: Option<String>,
+    pub altitude: Option<AltitudeRequest>,
+    /// Widens `max_distance` by the estimated precision of the image's GPS coordinates,
+    /// so readings stored with few decimals are not rejected just over the limit
+    pub tolerate_gps_precision: Option<bool>,
+}
+
+/// Unit of a location's `max_distance`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, ToSchema)]
+pub enum DistanceUnit {
+    #[default]
+    #[serde(rename = "m")]
+    Meters,
+    #[serde(rename = "km")]
+    Kilometers,
+    #[serde(rename = "mi")]
+    Miles,
+}
+
+impl DistanceUnit {
+    pub fn meters_per_unit(self) -> f64 {
+        match self {
+            DistanceUnit::Meters => 1.0,
+            DistanceUnit::Kilometers => 1000.0,
+            DistanceUnit::Miles => 1609.344,
+        }
+    }
+
+    pub fn to_meters(self, distance: f64) -> f64 {
+        distance * self.meters_per_unit()
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, ToSchema)]
+pub struct AltitudeRequest {
+    pub min: Option<f64>, // meters above sea level
+    pub max: Option<f64>, // meters above sea level
+}
+
+/// Allowed image size in pixels; any bound may be left open
+#[derive(Debug, Deserialize, Clone, ToSchema)]
+pub struct DimensionsRequest {
+    pub min_width: Option<u32>,
+    pub min_height: Option<u32>,
+    pub max_width: Option<u32>,
+    pub max_height: Option<u32>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+/// Two of `start`, `end` and `duration`, a `local_date` in a `timezone`, or a phrase
+/// such as `"image was taken not more than 10 minutes after 2025-08-01T15:23:00+01:00"`
+#[derive(Debug, Deserialize, Clone, ToSchema)]
+#[serde(remote = "Self")]
 pub struct DateTimeRequest {
     pub start: Option<String>,
     pub end: Option<String>,
-    pub duration: Option<u64>, // duration in minutes
+    pub duration: Option<DateTimeDuration>,
+    /// Calendar day, e.g. `"2025-08-01"`, covered from midnight to midnight in `timezone`
+    pub local_date: Option<String>,
+    /// IANA timezone name for `local_date`, e.g. `"Europe/London"`
+    pub timezone: Option<String>,
+}
+
+/// "not more than N minutes after|before <time>", anywhere at the end of a phrase
+static DATETIME_PHRASE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?:.*\s)?not\s+more\s+than\s+(\d+)\s+minutes?\s+(after|before)\s+(\S+)$")
+        .expect("valid datetime phrase regex")
+});
+
+impl DateTimeRequest {
+    /// Requires exactly two of `start`, `end` and `duration`, or `local_date` and
+    /// `timezone` on their own
+    fn check_field_count(&self) -> Result<(), String> {
+        if self.local_date.is_some() || self.timezone.is_some() {
+            if self.local_date.is_none()
+                || self.timezone.is_none()
+                || self.start.is_some()
+                || self.end.is_some()
+                || self.duration.is_some()
+            {
+                return Err(
+                    "local_date and timezone must be provided together, without start, end or duration"
+                        .to_string(),
+                );
+            }
+            return Ok(());
+        }
+
+        let field_count = [
+            self.start.is_some(),
+            self.end.is_some(),
+            self.duration.is_some(),
+        ]
+        .iter()
+        .filter(|&&x| x)
+        .count();
+
+        if field_count != 2 {
+            return Err(
+                "Exactly two out of three fields (start, end, duration) must be provided"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Maps "not more than N minutes after <time>" to `start` + `duration`, and
+    /// "... before <time>" to `end` + `duration`
+    ///
+    /// The time is parsed later like any `start` or `end`.
+    pub fn from_phrase(phrase: &str) -> Result<Self, String> {
+        let captures = DATETIME_PHRASE
+            .captures(phrase.trim().trim_end_matches('.'))
+            .ok_or_else(|| {
+                format!(
+                    "Unrecognized datetime phrase: \"{phrase}\"; expected \"not more than <N> minutes after|before <time>\""
+                )
+            })?;
+        let minutes = captures[1]
+            .parse()
+            .map_err(|_| format!("Datetime phrase duration is too large: {}", &captures[1]))?;
+        let time = Some(captures[3].to_string());
+        let duration = Some(DateTimeDuration::Minutes(minutes));
+
+        Ok(if captures[2].eq_ignore_ascii_case("after") {
+            DateTimeRequest {
+                start: time,
+                end: None,
+                duration,
+                local_date: None,
+                timezone: None,
+            }
+        } else {
+            DateTimeRequest {
+                start: None,
+                end: time,
+                duration,
+                local_date: None,
+                timezone: None,
+            }
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for DateTimeRequest {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DateTimeVisitor;
+
+        impl<'de> Visitor<'de> for DateTimeVisitor {
+            type Value = DateTimeRequest;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str(
+                    "datetime fields or a \"not more than N minutes after <time>\" phrase",
+                )
+            }
+
+            fn visit_str<E: de::Error>(self, phrase: &str) -> Result<Self::Value, E> {
+                DateTimeRequest::from_phrase(phrase).map_err(E::custom)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                // The derived, field-by-field implementation generated by `remote = "Self"`
+                let request =
+                    DateTimeRequest::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                request.check_field_count().map_err(de::Error::custom)?;
+                Ok(request)
+            }
+        }
+
+        deserializer.deserialize_any(DateTimeVisitor)
+    }
+}
+
+/// `duration` as either whole minutes or an ISO-8601 duration such as `"PT1H30M"`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+#[serde(untagged)]
+pub enum DateTimeDuration {
+    Minutes(i64),
+    Iso8601(String),
 }
 
-#[derive(Debug, Deserialize, Clone)]
+impl DateTimeDuration {
+    /// The length of the window, rejecting zero and negative durations
+    pub fn to_duration(&self) -> Result<chrono::Duration, String> {
+        let duration = match self {
+            DateTimeDuration::Minutes(minutes) => chrono::Duration::try_minutes(*minutes)
+                .ok_or_else(|| format!("Duration is out of range: {minutes} minutes"))?,
+            DateTimeDuration::Iso8601(value) => {
+                let duration = parse_iso8601_duration(value)?;
+                chrono::Duration::from_std(duration)
+                    .map_err(|_| format!("Duration is out of range: {value:?}"))?
+            }
+        };
+
+        if duration <= chrono::Duration::zero() {
+            return Err(format!("Duration must be greater than zero, got: {self}"));
+        }
+        Ok(duration)
+    }
+}
+
+impl std::fmt::Display for DateTimeDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DateTimeDuration::Minutes(minutes) => write!(f, "{minutes} minutes"),
+            DateTimeDuration::Iso8601(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// What the image must show: one description, or alternatives combined by
+/// `content_match_mode`, e.g. `["a cat", "a dog"]`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+#[serde(untagged)]
+pub enum ContentRequest {
+    Single(String),
+    Alternatives(Vec<String>),
+}
+
+impl ContentRequest {
+    /// Whether no description was given at all
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Single(description) => description.is_empty(),
+            Self::Alternatives(descriptions) => descriptions.is_empty(),
+        }
+    }
+
+    /// The trimmed descriptions, in the order given
+    pub fn descriptions(&self) -> Vec<String> {
+        match self {
+            Self::Single(description) => vec![description.trim().to_string()],
+            Self::Alternatives(descriptions) => descriptions
+                .iter()
+                .map(|description| description.trim().to_string())
+                .collect(),
+        }
+    }
+}
+
+impl From<&str> for ContentRequest {
+    fn from(description: &str) -> Self {
+        Self::Single(description.to_string())
+    }
+}
+
+impl From<String> for ContentRequest {
+    fn from(description: String) -> Self {
+        Self::Single(description)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, ToSchema)]
 pub struct AnalysisRequest {
     #[serde(rename = "image-path")]
     pub image_path: Option<String>,
 
-    pub content: String,
+    pub content: ContentRequest,
+
+    /// How the verdicts combine when `content` lists several descriptions
+    #[serde(default)]
+    pub content_match_mode: MatchMode,
 
     pub location: Option<LocationRequest>,
 
     pub datetime: Option<DateTimeRequest>,
+
+    /// How results combine when `image-path` lists several images
+    #[serde(default)]
+    pub match_mode: MatchMode,
+
+    /// Cameras the image must come from, as "Make Model" strings
+    pub allowed_cameras: Option<Vec<String>>,
+
+    /// Which failed checks reject the image; omitted checks are hard
+    pub severity: Option<ConstraintSeverity>,
+
+    /// Vision model for the content check instead of `LLM_MODEL_NAME`; must be listed
+    /// in `LLM_ALLOWED_MODELS`
+    pub model: Option<String>,
+
+    /// Reject images whose EXIF suggests they were edited after capture
+    #[serde(default)]
+    pub reject_edited: bool,
+
+    /// Words the model's explanation must mention for the content check to pass
+    pub required_keywords: Option<Vec<String>>,
+
+    /// Words that reject the image when the model's explanation mentions them, even
+    /// if the model accepted it
+    pub forbidden_keywords: Option<Vec<String>>,
+
+    /// Reject the image if a near-identical one was validated this many minutes ago
+    /// or less; at most `DUPLICATE_RETENTION_MINUTES`
+    pub reject_duplicate_within: Option<u64>,
+
+    /// Image the submitted one must show the same scene or object as, resolved like
+    /// `image-path`
+    pub reference_image_path: Option<String>,
+
+    /// Lowest similarity to `reference_image_path` that passes, from 0.0 to 1.0
+    pub similarity_threshold: Option<f32>,
+
+    /// Weight of each check in an overall score; requires `accept_threshold`
+    pub weights: Option<CheckWeights>,
+
+    /// Lowest weighted score that accepts the image, from 0.0 to 1.0
+    pub accept_threshold: Option<f32>,
+
+    /// Reject images whose EXIF records the device moving faster than this, in km/h
+    pub max_speed_kmh: Option<f64>,
+
+    /// Smallest and largest width and height the image may have, in pixels
+    pub dimensions: Option<DimensionsRequest>,
+
+    /// List the outcome of every check in the results, not only the failed ones
+    #[serde(default)]
+    pub verbose: bool,
+
+    /// Reject images that still carry GPS coordinates, e.g. where they must be stripped
+    #[serde(default)]
+    pub forbid_gps: bool,
+
+    /// Reject images that carry a capture timestamp
+    #[serde(default)]
+    pub forbid_timestamp: bool,
+
+    /// What a constrained check does when the image lacks the metadata it needs
+    #[serde(default)]
+    pub on_missing_metadata: MissingMetadataPolicy,
 }
 
-#[derive(Debug, Serialize, Clone)]
+impl AnalysisRequest {
+    /// A request that only checks `content`, with every other field at its default
+    pub fn new(content: impl Into<ContentRequest>) -> Self {
+        Self {
+            image_path: None,
+            content: content.into(),
+            content_match_mode: MatchMode::All,
+            location: None,
+            datetime: None,
+            match_mode: MatchMode::All,
+            allowed_cameras: None,
+            severity: None,
+            model: None,
+            reject_edited: false,
+            required_keywords: None,
+            forbidden_keywords: None,
+            reject_duplicate_within: None,
+            reference_image_path: None,
+            similarity_threshold: None,
+            weights: None,
+            accept_threshold: None,
+            max_speed_kmh: None,
+            dimensions: None,
+            verbose: false,
+            forbid_gps: false,
+            forbid_timestamp: false,
+            on_missing_metadata: MissingMetadataPolicy::Reject,
+        }
+    }
+}
+
+/// Whether a failed check rejects the image or is only reported as a warning
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Hard,
+    Soft,
+}
+
+/// Severity of each check; `location` also covers the altitude and speed constraints
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, ToSchema)]
+#[serde(default)]
+pub struct ConstraintSeverity {
+    pub content: Severity,
+    pub location: Severity,
+    pub datetime: Severity,
+    pub camera: Severity,
+    pub edited: Severity,
+}
+
+impl ConstraintSeverity {
+    /// Severity of the check a reason code belongs to
+    ///
+    /// Reasons not tied to a single check, such as unreadable images, are always hard.
+    pub fn for_code(&self, code: ReasonCode) -> Severity {
+        match code {
+            ReasonCode::ContentMismatch => self.content,
+            ReasonCode::GpsMissing
+            | ReasonCode::GpsPresent
+            | ReasonCode::LocationOutOfRange
+            | ReasonCode::AltitudeMissing
+            | ReasonCode::AltitudeOutOfRange
+            | ReasonCode::SpeedMissing
+            | ReasonCode::SpeedOutOfRange
+            | ReasonCode::PlaceNotResolved => self.location,
+            ReasonCode::TimestampMissing
+            | ReasonCode::TimestampOutOfRange
+            | ReasonCode::TimestampPresent => self.datetime,
+            ReasonCode::CameraMissing | ReasonCode::CameraNotAllowed => self.camera,
+            ReasonCode::ImageEdited => self.edited,
+            ReasonCode::DuplicateImage
+            | ReasonCode::ReferenceMismatch
+            | ReasonCode::ScoreBelowThreshold
+            | ReasonCode::DimensionsOutOfRange
+            | ReasonCode::ImageNotFound
+            | ReasonCode::ImageHostNotAllowed
+            | ReasonCode::ValidationError => Severity::Hard,
+        }
+    }
+}
+
+/// Weight of each check in the overall score compared with `accept_threshold`
+///
+/// A weighted check no longer rejects the image on its own. A passing check adds its
+/// weight, the content check its weight times the model's confidence, and the image is
+/// accepted when the sum divided by the total weight reaches the threshold. Checks
+/// without a weight keep their `severity`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct CheckWeights {
+    pub content: Option<f32>,
+    pub location: Option<f32>,
+    pub datetime: Option<f32>,
+    pub camera: Option<f32>,
+    pub edited: Option<f32>,
+}
+
+impl CheckWeights {
+    fn entries(&self) -> [(&'static str, Option<f32>); 5] {
+        [
+            ("content", self.content),
+            ("location", self.location),
+            ("datetime", self.datetime),
+            ("camera", self.camera),
+            ("edited", self.edited),
+        ]
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (check, weight) in self.entries() {
+            if let Some(weight) = weight {
+                if !weight.is_finite() || weight < 0.0 {
+                    return Err(format!(
+                        "weights.{check} must be a non-negative number, got: {weight}"
+                    ));
+                }
+            }
+        }
+        if self.total() <= 0.0 {
+            return Err("weights requires at least one positive weight".to_string());
+        }
+        Ok(())
+    }
+
+    fn total(&self) -> f32 {
+        self.entries()
+            .iter()
+            .filter_map(|(_, weight)| *weight)
+            .sum()
+    }
+
+    /// Weighted share of the checks that passed, from 0.0 to 1.0
+    ///
+    /// `content` is the credit of the content check: the model's confidence when it
+    /// accepted the image, 0.0 otherwise.
+    pub fn score(
+        &self,
+        content: f32,
+        location: bool,
+        datetime: bool,
+        camera: bool,
+        unedited: bool,
+    ) -> f32 {
+        let credit = |passed: bool| if passed { 1.0 } else { 0.0 };
+        let earned: f32 = [
+            content,
+            credit(location),
+            credit(datetime),
+            credit(camera),
+            credit(unedited),
+        ]
+        .iter()
+        .zip(self.entries())
+        .filter_map(|(credit, (_, weight))| weight.map(|weight| credit * weight))
+        .sum();
+        earned / self.total()
+    }
+
+    /// `severity` with every weighted check set to `weighted`
+    pub fn apply_to(&self, severity: ConstraintSeverity, weighted: Severity) -> ConstraintSeverity {
+        let pick = |weight: Option<f32>, severity: Severity| {
+            if weight.is_some() {
+                weighted
+            } else {
+                severity
+            }
+        };
+        ConstraintSeverity {
+            content: pick(self.content, severity.content),
+            location: pick(self.location, severity.location),
+            datetime: pick(self.datetime, severity.datetime),
+            camera: pick(self.camera, severity.camera),
+            edited: pick(self.edited, severity.edited),
+        }
+    }
+}
+
+/// How a check treats an image without the metadata it needs, such as a location
+/// constraint on an image without GPS coordinates
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MissingMetadataPolicy {
+    /// The check fails, naming the missing metadata
+    #[default]
+    Reject,
+    /// The check passes without a word
+    Pass,
+    /// The check passes, reporting the missing metadata as a warning
+    Warn,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    /// Every image must pass
+    #[default]
+    All,
+    /// At least one image must pass; images after the first passing one are skipped
+    Any,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct ValidationResponse {
     #[serde(rename = "processing-id")]
     pub processing_id: String,
@@ -77,30 +769,419 @@ pub struct ValidationResponse {
     pub results: ValidationResults,
 }
 
-#[derive(Debug, Serialize, Clone)]
+/// Sub-check outcomes known so far for a request that is still in progress
+///
+/// Each field stays unset until its check has finished; the location, datetime and
+/// camera checks finish together once the EXIF metadata has been read. For
+/// multi-image requests this covers the image currently being validated.
+#[derive(Debug, Serialize, Clone, Default, PartialEq, ToSchema)]
+pub struct PartialResults {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<bool>,
+    /// Model confidence in the content verdict, from 0.0 to 1.0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_confidence: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub datetime: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera: Option<bool>,
+}
+
+/// Body of `GET /results/:id?partial=true` while the request is in progress
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct PartialResultsResponse {
+    #[serde(rename = "processing-id")]
+    pub processing_id: String,
+    /// Always true; the final results replace these once processing completes
+    pub partial: bool,
+    pub results: PartialResults,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct ValidationResults {
     pub resolution: Resolution,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasons: Option<Vec<RejectionReason>>,
+
+    /// Deprecated duplicate of the reason messages under the historical misspelled key
+    ///
+    /// Kept as plain strings, the shape consumers of the old key expect.
     #[serde(rename = "resons", skip_serializing_if = "Option::is_none")]
-    pub reasons: Option<Vec<String>>,
+    pub legacy_reasons: Option<Vec<String>>,
+
+    /// Per-constraint confidence, present when the content check reached the model
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<ConfidenceScores>,
+
+    /// Per-image outcomes, present when the request listed several images
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub images: Option<Vec<ImageResult>>,
+
+    /// Outcome of each check, present when the request set `verbose`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checks: Option<Vec<CheckOutcome>>,
+
+    /// The model's reasoning behind its content verdict, present when
+    /// `INCLUDE_LLM_EXPLANATION` is set and the content check reached the model
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
 }
 
-#[derive(Debug, Serialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct ImageResult {
+    #[serde(rename = "image-path")]
+    pub image_path: String,
+
+    pub resolution: Resolution,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasons: Option<Vec<RejectionReason>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<ConfidenceScores>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checks: Option<Vec<CheckOutcome>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+}
+
+impl ImageResult {
+    pub fn new(image_path: String, results: ValidationResults) -> Self {
+        Self {
+            image_path,
+            resolution: results.resolution,
+            reasons: results.reasons,
+            confidence: results.confidence,
+            checks: results.checks,
+            explanation: results.explanation,
+        }
+    }
+}
+
+/// A check listed in verbose results
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Check {
+    Content,
+    /// Covers the location, altitude and speed constraints
+    Location,
+    Datetime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckState {
+    Passed,
+    Failed,
+    /// The request set no constraint for the check, or validation stopped before it ran
+    Skipped,
+}
+
+impl CheckState {
+    /// State of a check that only runs when the request constrains it
+    pub fn of(constrained: bool, valid: bool) -> Self {
+        match (constrained, valid) {
+            (false, _) => Self::Skipped,
+            (true, true) => Self::Passed,
+            (true, false) => Self::Failed,
+        }
+    }
+
+    /// Whether the check ran and failed; skipped checks do not reject the image
+    pub fn is_failed(self) -> bool {
+        self == Self::Failed
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct CheckOutcome {
+    pub check: Check,
+    pub state: CheckState,
+}
+
+impl CheckOutcome {
+    /// Outcomes of the content, location and datetime checks, in that order
+    pub fn list(content: CheckState, location: CheckState, datetime: CheckState) -> Vec<Self> {
+        [
+            (Check::Content, content),
+            (Check::Location, location),
+            (Check::Datetime, datetime),
+        ]
+        .into_iter()
+        .map(|(check, state)| Self { check, state })
+        .collect()
+    }
+}
+
+/// Why a validation was rejected, as a stable code plus human-readable text
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct RejectionReason {
+    pub code: ReasonCode,
+    pub message: String,
+
+    /// Set when the reason did not reject the image, e.g. a failed check with soft
+    /// severity or a location accepted only within GPS precision
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub warning: bool,
+}
+
+impl RejectionReason {
+    pub fn new(code: ReasonCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            warning: false,
+        }
+    }
+
+    /// Marks the reason as advisory
+    #[must_use]
+    pub fn as_warning(mut self) -> Self {
+        self.warning = true;
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReasonCode {
+    ContentMismatch,
+    GpsMissing,
+    /// With `forbid_gps`, the image carries GPS coordinates
+    GpsPresent,
+    LocationOutOfRange,
+    AltitudeMissing,
+    AltitudeOutOfRange,
+    /// `max_speed_kmh` was given but the image has no GPS speed
+    SpeedMissing,
+    /// The image was taken while moving faster than `max_speed_kmh`
+    SpeedOutOfRange,
+    /// The image is smaller or larger than `dimensions` allows
+    DimensionsOutOfRange,
+    TimestampMissing,
+    TimestampOutOfRange,
+    /// With `forbid_timestamp`, the image carries a capture timestamp
+    TimestampPresent,
+    CameraMissing,
+    CameraNotAllowed,
+    /// EXIF suggests the image was edited, with `reject_edited` set
+    ImageEdited,
+    /// A near-identical image was validated within `reject_duplicate_within`
+    DuplicateImage,
+    /// The image is less similar to `reference_image_path` than `similarity_threshold`
+    ReferenceMismatch,
+    /// The weighted score of the checks is below `accept_threshold`
+    ScoreBelowThreshold,
+    ImageNotFound,
+    /// The `image-path` URL's host is not in `ALLOWED_IMAGE_HOSTS`
+    ImageHostNotAllowed,
+    /// A `place_name` location could not be geocoded
+    PlaceNotResolved,
+    /// The image could not be checked, e.g. unreadable EXIF or a failed LLM call
+    ValidationError,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct ConfidenceScores {
+    /// Model confidence in the content verdict, from 0.0 to 1.0
+    pub content: f32,
+
+    /// Perceptual similarity to the request's reference image, from 0.0 to 1.0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<f32>,
+
+    /// Weighted score compared with the request's `accept_threshold`, from 0.0 to 1.0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+}
+
+impl ValidationResults {
+    pub fn accepted() -> Self {
+        Self {
+            resolution: Resolution::Accepted,
+            reasons: None,
+            legacy_reasons: None,
+            confidence: None,
+            images: None,
+            checks: None,
+            explanation: None,
+        }
+    }
+
+    /// An accepted result that still reports soft-failed checks, if any
+    pub fn accepted_with_warnings(warnings: Vec<RejectionReason>) -> Self {
+        Self {
+            reasons: (!warnings.is_empty()).then_some(warnings),
+            ..Self::accepted()
+        }
+    }
+
+    pub fn rejected(reasons: Vec<RejectionReason>) -> Self {
+        Self {
+            resolution: Resolution::Rejected,
+            reasons: Some(reasons),
+            legacy_reasons: None,
+            confidence: None,
+            images: None,
+            checks: None,
+            explanation: None,
+        }
+    }
+
+    /// Combines per-image outcomes according to the match mode
+    ///
+    /// Rejection messages are prefixed with the image they belong to; codes and
+    /// warning flags are kept. Accepted results carry the warnings of every image.
+    pub fn combine(match_mode: MatchMode, images: Vec<ImageResult>) -> Self {
+        let is_accepted = |image: &ImageResult| image.resolution == Resolution::Accepted;
+        let accepted = match match_mode {
+            MatchMode::All => images.iter().all(is_accepted),
+            MatchMode::Any => images.iter().any(is_accepted),
+        };
+
+        let prefixed_reasons = |image: &ImageResult| -> Vec<RejectionReason> {
+            image
+                .reasons
+                .iter()
+                .flatten()
+                .map(|reason| RejectionReason {
+                    message: format!("{}: {}", image.image_path, reason.message),
+                    ..reason.clone()
+                })
+                .collect()
+        };
+
+        let mut results = if accepted {
+            Self::accepted_with_warnings(
+                images
+                    .iter()
+                    .flat_map(prefixed_reasons)
+                    .filter(|reason| reason.warning)
+                    .collect(),
+            )
+        } else {
+            Self::rejected(
+                images
+                    .iter()
+                    .filter(|image| !is_accepted(image))
+                    .flat_map(prefixed_reasons)
+                    .collect(),
+            )
+        };
+        results.images = Some(images);
+        results
+    }
+
+    /// Attaches the model's explanation of its content verdict, if it gave one
+    #[must_use]
+    pub fn with_explanation(mut self, explanation: Option<String>) -> Self {
+        self.explanation = explanation.filter(|explanation| !explanation.is_empty());
+        self
+    }
+
+    /// Attaches the per-check outcomes listed for verbose requests
+    #[must_use]
+    pub fn with_checks(mut self, checks: Option<Vec<CheckOutcome>>) -> Self {
+        self.checks = checks;
+        self
+    }
+
+    /// Attaches the model's confidence in the content verdict
+    #[must_use]
+    pub fn with_content_confidence(mut self, confidence: f32) -> Self {
+        let (similarity, score) = self
+            .confidence
+            .map_or((None, None), |scores| (scores.similarity, scores.score));
+        self.confidence = Some(ConfidenceScores {
+            content: confidence,
+            similarity,
+            score,
+        });
+        self
+    }
+
+    /// Attaches the similarity to the reference image next to the content confidence
+    #[must_use]
+    pub fn with_similarity(mut self, similarity: Option<f32>) -> Self {
+        if let Some(scores) = &mut self.confidence {
+            scores.similarity = similarity;
+        }
+        self
+    }
+
+    /// Attaches the weighted score next to the content confidence
+    #[must_use]
+    pub fn with_score(mut self, score: Option<f32>) -> Self {
+        if let Some(scores) = &mut self.confidence {
+            scores.score = score;
+        }
+        self
+    }
+
+    /// Copies the reason messages into the legacy `resons` key for consumers still reading the typo
+    #[must_use]
+    pub fn with_legacy_reasons_key(mut self) -> Self {
+        self.legacy_reasons = self.reasons.as_ref().map(|reasons| {
+            reasons
+                .iter()
+                .map(|reason| reason.message.clone())
+                .collect()
+        });
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Resolution {
     Accepted,
     Rejected,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct StatusResponse {
     #[serde(rename = "processing-id")]
     pub processing_id: String,
 
     pub status: ProcessingStatus,
+
+    /// Processing attempts so far; above 1 when transient failures were retried
+    pub attempts: u32,
+
+    /// Why the request failed, when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
 }
 
-#[derive(Debug, Serialize, Clone, PartialEq)]
+/// One entry of the `GET /jobs` listing
+#[derive(Debug, Serialize, Clone, PartialEq, ToSchema)]
+pub struct JobSummary {
+    #[serde(rename = "processing-id")]
+    pub processing_id: String,
+
+    pub status: ProcessingStatus,
+
+    /// Submission time in milliseconds since the Unix epoch
+    pub submitted_at_ms: i64,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct JobListResponse {
+    pub jobs: Vec<JobSummary>,
+
+    /// Number of jobs matching the filter, before pagination
+    pub total: usize,
+
+    pub limit: usize,
+    pub offset: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ProcessingStatus {
     Accepted,
@@ -110,73 +1191,266 @@ pub enum ProcessingStatus {
     Failed,
     #[serde(rename = "not_found")]
     NotFound,
+    Cancelled,
 }
 
-#[derive(Debug, Clone)]
-pub struct LocationConstraint {
-    pub max_distance_meters: f64,
-    pub latitude: f64,
-    pub longitude: f64,
+impl ProcessingStatus {
+    /// The status as it is serialized
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Accepted => "accepted",
+            Self::InProgress => "in_progress",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::NotFound => "not_found",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LocationConstraint {
+    /// Within `max_distance_meters` of a center point
+    Radius {
+        latitude: f64,
+        longitude: f64,
+        max_distance_meters: f64,
+        /// Unit the distance was requested in, used when reporting distances
+        distance_unit: DistanceUnit,
+        /// Allow the estimated GPS precision on top of `max_distance_meters`
+        tolerate_gps_precision: bool,
+    },
+    /// Inside the polygon whose `(lat, long)` vertices are listed in order
+    Polygon(Vec<(f64, f64)>),
+    /// Inside a named place, which is geocoded when the image is validated
+    Place(String),
 }
 
-impl From<LocationRequest> for LocationConstraint {
-    fn from(request: LocationRequest) -> Self {
-        LocationConstraint {
-            max_distance_meters: request.max_distance,
-            latitude: request.lat,
-            longitude: request.long,
+impl TryFrom<LocationRequest> for LocationConstraint {
+    type Error = String;
+
+    fn try_from(request: LocationRequest) -> Result<Self, Self::Error> {
+        let has_radius_fields =
+            request.lat.is_some() || request.long.is_some() || request.max_distance.is_some();
+
+        if request.distance_unit.is_some() && request.max_distance.is_none() {
+            return Err("Location distance_unit requires max_distance".to_string());
+        }
+        if request.tolerate_gps_precision.is_some() && request.max_distance.is_none() {
+            return Err("Location tolerate_gps_precision requires max_distance".to_string());
+        }
+
+        if let Some(place_name) = request.place_name {
+            if has_radius_fields || request.polygon.is_some() {
+                return Err(
+                    "Location place_name cannot be combined with lat, long, max_distance or polygon"
+                        .to_string(),
+                );
+            }
+            let place_name = place_name.trim();
+            if place_name.is_empty() {
+                return Err("Location place_name must not be empty".to_string());
+            }
+            return Ok(LocationConstraint::Place(place_name.to_string()));
+        }
+
+        match (
+            request.polygon,
+            request.lat,
+            request.long,
+            request.max_distance,
+        ) {
+            (Some(_), ..) if has_radius_fields => Err(
+                "Location accepts either lat, long and max_distance or polygon, not both"
+                    .to_string(),
+            ),
+            (Some(polygon), ..) => {
+                if polygon.len() < 3 {
+                    return Err("Location polygon requires at least 3 vertices".to_string());
+                }
+                if let Some([lat, long]) = polygon.iter().find(|[lat, long]| {
+                    !(-90.0..=90.0).contains(lat) || !(-180.0..=180.0).contains(long)
+                }) {
+                    return Err(format!("Invalid polygon vertex: [{lat}, {long}]"));
+                }
+                Ok(LocationConstraint::Polygon(
+                    polygon.into_iter().map(|[lat, long]| (lat, long)).collect(),
+                ))
+            }
+            (None, Some(lat), Some(long), _)
+                if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&long) =>
+            {
+                Err(format!("Invalid location coordinates: [{lat}, {long}]"))
+            }
+            (None, Some(_), Some(_), Some(max_distance)) if !max_distance.is_finite() => Err(
+                format!("Location max_distance must be a finite number, got: {max_distance}"),
+            ),
+            (None, Some(_), Some(_), Some(max_distance)) if max_distance <= 0.0 => Err(format!(
+                "Location max_distance must be positive, got: {max_distance}"
+            )),
+            (None, Some(lat), Some(long), Some(max_distance)) => {
+                let distance_unit = request.distance_unit.unwrap_or_default();
+                Ok(LocationConstraint::Radius {
+                    latitude: lat,
+                    longitude: long,
+                    max_distance_meters: distance_unit.to_meters(max_distance),
+                    distance_unit,
+                    tolerate_gps_precision: request.tolerate_gps_precision.unwrap_or(false),
+                })
+            }
+            (None, ..) => Err(
+                "Location requires either lat, long and max_distance, polygon or place_name"
+                    .to_string(),
+            ),
         }
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct DateTimeConstraint {
-    pub start_time: DateTime<FixedOffset>,
-    pub end_time: DateTime<FixedOffset>,
+/// Allowed altitude range in meters above sea level; either bound may be open
+#[derive(Debug, Serialize, Clone)]
+pub struct AltitudeConstraint {
+    pub min_meters: Option<f64>,
+    pub max_meters: Option<f64>,
 }
 
-impl TryFrom<DateTimeRequest> for DateTimeConstraint {
-    type Error = String;
+impl From<AltitudeRequest> for AltitudeConstraint {
+    fn from(request: AltitudeRequest) -> Self {
+        AltitudeConstraint {
+            min_meters: request.min,
+            max_meters: request.max,
+        }
+    }
+}
 
-    fn try_from(request: DateTimeRequest) -> Result<Self, Self::Error> {
-        use chrono::Duration;
+/// Allowed image size in pixels; any bound may be open, but not all of them
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct DimensionsConstraint {
+    pub min_width: Option<u32>,
+    pub min_height: Option<u32>,
+    pub max_width: Option<u32>,
+    pub max_height: Option<u32>,
+}
 
-        // Validate that we have exactly 2 out of 3 fields
-        let field_count = [
-            request.start.is_some(),
-            request.end.is_some(),
-            request.duration.is_some(),
-        ]
-        .iter()
-        .filter(|&&x| x)
-        .count();
+impl TryFrom<DimensionsRequest> for DimensionsConstraint {
+    type Error = String;
 
-        if field_count != 2 {
+    fn try_from(request: DimensionsRequest) -> Result<Self, Self::Error> {
+        let bounds = [
+            request.min_width,
+            request.min_height,
+            request.max_width,
+            request.max_height,
+        ];
+        if bounds.iter().all(Option::is_none) {
             return Err(
-                "Exactly two out of three fields (start, end, duration) must be provided"
+                "dimensions requires at least one of min_width, min_height, max_width, max_height"
                     .to_string(),
             );
         }
+        for (axis, min, max) in [
+            ("width", request.min_width, request.max_width),
+            ("height", request.min_height, request.max_height),
+        ] {
+            if let (Some(min), Some(max)) = (min, max) {
+                if min > max {
+                    return Err(format!(
+                        "dimensions min_{axis} ({min}) must not exceed max_{axis} ({max})"
+                    ));
+                }
+            }
+        }
 
-        let parse_datetime = |dt_str: &str| -> Result<DateTime<FixedOffset>, String> {
-            DateTime::parse_from_rfc3339(dt_str)
-                .or_else(|_| {
-                    // Try parsing custom format like "2025-08-01T15:23:00Z+1"
-                    if let Some(base_dt) = dt_str.strip_suffix("Z+1") {
-                        let dt_with_tz = format!("{}+01:00", base_dt.trim_end_matches('Z'));
-                        DateTime::parse_from_rfc3339(&dt_with_tz)
-                    } else {
-                        DateTime::parse_from_rfc3339(dt_str)
-                    }
-                })
-                .map_err(|_| format!("Invalid datetime format: {dt_str}"))
-        };
+        Ok(DimensionsConstraint {
+            min_width: request.min_width,
+            min_height: request.min_height,
+            max_width: request.max_width,
+            max_height: request.max_height,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DateTimeConstraint {
+    pub start_time: DateTime<FixedOffset>,
+    pub end_time: DateTime<FixedOffset>,
+}
+
+/// Parses an RFC 3339 time, or one with the nonstandard `Z+N` or `Z-N` suffix for a UTC
+/// offset of N hours, e.g. `"2025-08-01T15:23:00Z+1"` for 15:23 at UTC+1
+fn parse_request_datetime(value: &str) -> Result<DateTime<FixedOffset>, String> {
+    let invalid = || format!("Invalid datetime format: {value}");
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime);
+    }
+
+    let (base, offset) = value.rsplit_once('Z').ok_or_else(invalid)?;
+    let (sign, hours) = match offset.split_at_checked(1) {
+        Some((sign @ ("+" | "-"), hours)) => (sign, hours),
+        _ => return Err(invalid()),
+    };
+    let hours: u8 = Some(hours)
+        .filter(|hours| !hours.is_empty() && hours.bytes().all(|b| b.is_ascii_digit()))
+        .and_then(|hours| hours.parse().ok())
+        .filter(|hours| *hours <= 14)
+        .ok_or_else(invalid)?;
+    DateTime::parse_from_rfc3339(&format!("{base}{sign}{hours:02}:00")).map_err(|_| invalid())
+}
+
+/// The first instant of `date` in `timezone`
+///
+/// Where a DST change skips midnight, the day starts when the clocks resume.
+fn start_of_local_day(date: NaiveDate, timezone: Tz) -> Result<DateTime<FixedOffset>, String> {
+    let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+    (0..=3)
+        .find_map(|hour| {
+            timezone
+                .from_local_datetime(&(midnight + chrono::Duration::hours(hour)))
+                .earliest()
+        })
+        .map(|start| start.fixed_offset())
+        .ok_or_else(|| format!("Cannot find the start of {date} in {timezone}"))
+}
+
+/// Expands `local_date` to the day from its midnight to the next in `timezone`
+fn local_day(
+    local_date: &str,
+    timezone: &str,
+) -> Result<(DateTime<FixedOffset>, DateTime<FixedOffset>), String> {
+    let timezone: Tz = timezone.parse().map_err(|_| {
+        format!("Unknown timezone: {timezone}; expected an IANA name such as Europe/London")
+    })?;
+    let date = NaiveDate::parse_from_str(local_date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid local_date: {local_date}; expected YYYY-MM-DD"))?;
+    let next_date = date
+        .succ_opt()
+        .ok_or_else(|| format!("local_date is out of range: {local_date}"))?;
+
+    Ok((
+        start_of_local_day(date, timezone)?,
+        start_of_local_day(next_date, timezone)?,
+    ))
+}
+
+impl TryFrom<DateTimeRequest> for DateTimeConstraint {
+    type Error = String;
+
+    fn try_from(request: DateTimeRequest) -> Result<Self, Self::Error> {
+        request.check_field_count()?;
+
+        if let (Some(local_date), Some(timezone)) = (&request.local_date, &request.timezone) {
+            let (start_time, end_time) = local_day(local_date, timezone)?;
+            return Ok(DateTimeConstraint {
+                start_time,
+                end_time,
+            });
+        }
 
         let (start_time, end_time) = match (request.start, request.end, request.duration) {
             // Case 1: start + end provided
             (Some(start_str), Some(end_str), None) => {
-                let start = parse_datetime(&start_str)?;
-                let end = parse_datetime(&end_str)?;
+                let start = parse_request_datetime(&start_str)?;
+                let end = parse_request_datetime(&end_str)?;
 
                 if end <= start {
                     return Err("End time must be after start time".to_string());
@@ -185,74 +1459,531 @@ impl TryFrom<DateTimeRequest> for DateTimeConstraint {
                 (start, end)
             }
 
-            // Case 2: start + duration provided
-            (Some(start_str), None, Some(duration_minutes)) => {
-                let start = parse_datetime(&start_str)?;
-                let end = start + Duration::minutes(duration_minutes as i64);
-                (start, end)
+            // Case 2: start + duration provided
+            (Some(start_str), None, Some(duration)) => {
+                let start = parse_request_datetime(&start_str)?;
+                let end = start + duration.to_duration()?;
+                (start, end)
+            }
+
+            // Case 3: end + duration provided
+            (None, Some(end_str), Some(duration)) => {
+                let end = parse_request_datetime(&end_str)?;
+                let start = end - duration.to_duration()?;
+                (start, end)
+            }
+
+            _ => return Err("Invalid combination of fields provided".to_string()),
+        };
+
+        Ok(DateTimeConstraint {
+            start_time,
+            end_time,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ValidationContext {
+    /// Descriptions the image is checked against, combined by `content_match_mode`
+    pub content_checks: Vec<String>,
+    pub content_match_mode: MatchMode,
+    pub location_constraint: Option<LocationConstraint>,
+    pub altitude_constraint: Option<AltitudeConstraint>,
+    pub datetime_constraint: Option<DateTimeConstraint>,
+    pub allowed_cameras: Option<Vec<String>>,
+    pub severity: ConstraintSeverity,
+    /// Vision model requested for the content check, if not the default
+    pub model: Option<String>,
+    pub reject_edited: bool,
+    /// Trimmed `required_keywords`, without blank entries
+    pub required_keywords: Vec<String>,
+    /// Trimmed `forbidden_keywords`, without blank entries
+    pub forbidden_keywords: Vec<String>,
+    /// Window in minutes for rejecting near-identical images
+    pub reject_duplicate_within: Option<u64>,
+    /// Image the submitted one is compared with, as given in the request
+    pub reference_image_path: Option<String>,
+    /// Lowest similarity to the reference image that passes; set with a reference
+    pub similarity_threshold: Option<f32>,
+    /// Weights of the checks scored against `accept_threshold`
+    pub weights: Option<CheckWeights>,
+    /// Lowest weighted score that accepts the image; set with `weights`
+    pub accept_threshold: Option<f32>,
+    /// Fastest the device may have moved when the image was taken, in km/h
+    pub max_speed_kmh: Option<f64>,
+    pub dimensions_constraint: Option<DimensionsConstraint>,
+    pub verbose: bool,
+    /// Images with GPS coordinates fail the location check
+    pub forbid_gps: bool,
+    /// Images with a capture timestamp fail the datetime check
+    pub forbid_timestamp: bool,
+    pub on_missing_metadata: MissingMetadataPolicy,
+    /// Perceptual hash of the reference image, computed once per request
+    #[serde(skip)]
+    pub reference_hash: Option<u64>,
+    /// Request being validated, whose own earlier images are not duplicates
+    #[serde(skip)]
+    pub processing_id: Option<String>,
+}
+
+impl ValidationContext {
+    /// Ties the context to the request it validates
+    pub fn with_processing_id(mut self, processing_id: impl Into<String>) -> Self {
+        self.processing_id = Some(processing_id.into());
+        self
+    }
+
+    /// How far back a near-identical image rejects this one, if at all
+    pub fn duplicate_window(&self) -> Option<std::time::Duration> {
+        self.reject_duplicate_within
+            .map(|minutes| std::time::Duration::from_secs(minutes * 60))
+    }
+
+    /// Whether any check needs the image's EXIF metadata
+    pub fn needs_metadata(&self) -> bool {
+        self.location_constraint.is_some()
+            || self.altitude_constraint.is_some()
+            || self.max_speed_kmh.is_some()
+            || self.datetime_constraint.is_some()
+            || self.allowed_cameras.is_some()
+            || self.reject_edited
+            || self.forbid_gps
+            || self.forbid_timestamp
+    }
+}
+
+impl TryFrom<AnalysisRequest> for ValidationContext {
+    type Error = String;
+
+    fn try_from(request: AnalysisRequest) -> Result<Self, Self::Error> {
+        let altitude_constraint = request
+            .location
+            .as_ref()
+            .and_then(|location| location.altitude.clone())
+            .map(AltitudeConstraint::from);
+        let location_constraint = request
+            .location
+            .map(LocationConstraint::try_from)
+            .transpose()?;
+
+        if let Some(altitude) = &altitude_constraint {
+            match (altitude.min_meters, altitude.max_meters) {
+                (None, None) => {
+                    return Err("Altitude constraint requires at least one of min, max".to_string())
+                }
+                (Some(min), Some(max)) if min > max => {
+                    return Err(format!(
+                        "Altitude min ({min}m) must not exceed max ({max}m)"
+                    ))
+                }
+                _ => {}
+            }
+        }
+
+        let datetime_constraint = if let Some(datetime) = request.datetime {
+            Some(DateTimeConstraint::try_from(datetime)?)
+        } else {
+            None
+        };
+
+        let allowed_cameras = request
+            .allowed_cameras
+            .map(|cameras| {
+                let cameras: Vec<String> = cameras
+                    .iter()
+                    .map(|camera| camera.trim().to_string())
+                    .filter(|camera| !camera.is_empty())
+                    .collect();
+                if cameras.is_empty() {
+                    Err("allowed_cameras requires at least one camera".to_string())
+                } else {
+                    Ok(cameras)
+                }
+            })
+            .transpose()?;
+
+        let model = request.model.map(|model| model.trim().to_string());
+        if model.as_deref() == Some("") {
+            return Err("model must not be empty".to_string());
+        }
+
+        if request.reject_duplicate_within == Some(0) {
+            return Err("reject_duplicate_within must be at least 1 minute".to_string());
+        }
+
+        let reference_image_path = request
+            .reference_image_path
+            .map(|path| path.trim().to_string());
+        if reference_image_path.as_deref() == Some("") {
+            return Err("reference_image_path must not be empty".to_string());
+        }
+        let similarity_threshold = match (&reference_image_path, request.similarity_threshold) {
+            (None, Some(_)) => {
+                return Err("similarity_threshold requires reference_image_path".to_string())
+            }
+            (_, Some(threshold)) if !(0.0..=1.0).contains(&threshold) => {
+                return Err(format!(
+                    "similarity_threshold must be between 0 and 1, got: {threshold}"
+                ))
+            }
+            (Some(_), threshold) => Some(threshold.unwrap_or(DEFAULT_SIMILARITY_THRESHOLD)),
+            (None, None) => None,
+        };
+
+        match (&request.weights, request.accept_threshold) {
+            (Some(_), None) => return Err("weights requires accept_threshold".to_string()),
+            (None, Some(_)) => return Err("accept_threshold requires weights".to_string()),
+            (_, Some(threshold)) if !(0.0..=1.0).contains(&threshold) => {
+                return Err(format!(
+                    "accept_threshold must be between 0 and 1, got: {threshold}"
+                ))
+            }
+            (Some(weights), _) => weights.validate()?,
+            (None, None) => {}
+        }
+
+        if let Some(max_speed) = request.max_speed_kmh {
+            if !max_speed.is_finite() || max_speed < 0.0 {
+                return Err(format!(
+                    "max_speed_kmh must be a non-negative number, got: {max_speed}"
+                ));
+            }
+        }
+
+        if request.forbid_gps && (location_constraint.is_some() || request.max_speed_kmh.is_some())
+        {
+            return Err(
+                "forbid_gps cannot be combined with a location constraint or max_speed_kmh"
+                    .to_string(),
+            );
+        }
+        if request.forbid_timestamp && datetime_constraint.is_some() {
+            return Err(
+                "forbid_timestamp cannot be combined with a datetime constraint".to_string(),
+            );
+        }
+
+        let dimensions_constraint = request
+            .dimensions
+            .map(DimensionsConstraint::try_from)
+            .transpose()?;
+
+        let content_checks = request.content.descriptions();
+        if content_checks.is_empty() || content_checks.iter().any(String::is_empty) {
+            return Err("content descriptions must not be blank".to_string());
+        }
+
+        Ok(ValidationContext {
+            content_checks,
+            content_match_mode: request.content_match_mode,
+            location_constraint,
+            altitude_constraint,
+            datetime_constraint,
+            allowed_cameras,
+            severity: request.severity.unwrap_or_default(),
+            model,
+            reject_edited: request.reject_edited,
+            required_keywords: keyword_list(request.required_keywords),
+            forbidden_keywords: keyword_list(request.forbidden_keywords),
+            reject_duplicate_within: request.reject_duplicate_within,
+            reference_image_path,
+            similarity_threshold,
+            weights: request.weights,
+            accept_threshold: request.accept_threshold,
+            max_speed_kmh: request.max_speed_kmh,
+            dimensions_constraint,
+            verbose: request.verbose,
+            forbid_gps: request.forbid_gps,
+            forbid_timestamp: request.forbid_timestamp,
+            on_missing_metadata: request.on_missing_metadata,
+            reference_hash: None,
+            processing_id: None,
+        })
+    }
+}
+
+/// Trims the keywords and drops blank ones
+fn keyword_list(keywords: Option<Vec<String>>) -> Vec<String> {
+    keywords
+        .unwrap_or_default()
+        .iter()
+        .map(|keyword| keyword.trim().to_string())
+        .filter(|keyword| !keyword.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location_constraint_from_request() {
+        let location_request = LocationRequest {
+            long: Some(-0.266108),
+            lat: Some(51.492191),
+            max_distance: Some(100.0),
+            polygon: None,
+            place_name: None,
+            distance_unit: None,
+            altitude: None,
+            tolerate_gps_precision: None,
+        };
+
+        let constraint = LocationConstraint::try_from(location_request).unwrap();
+
+        assert_eq!(
+            constraint,
+            LocationConstraint::Radius {
+                latitude: 51.492191,
+                longitude: -0.266108,
+                max_distance_meters: 100.0,
+                distance_unit: DistanceUnit::Meters,
+                tolerate_gps_precision: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_location_constraint_rejects_out_of_range_values() {
+        let request = |lat, long, max_distance| LocationRequest {
+            long: Some(long),
+            lat: Some(lat),
+            max_distance: Some(max_distance),
+            polygon: None,
+            place_name: None,
+            distance_unit: None,
+            altitude: None,
+            tolerate_gps_precision: None,
+        };
+
+        assert!(LocationConstraint::try_from(request(91.0, 0.5, 100.0)).is_err());
+        assert!(LocationConstraint::try_from(request(51.5, -180.5, 100.0)).is_err());
+        assert!(LocationConstraint::try_from(request(51.5, -0.1, 0.0)).is_err());
+        assert!(LocationConstraint::try_from(request(-90.0, 180.0, 1.0)).is_ok());
+    }
+
+    #[test]
+    fn test_location_constraint_rejects_non_finite_values() {
+        let request = |lat, long, max_distance| LocationRequest {
+            long: Some(long),
+            lat: Some(lat),
+            max_distance: Some(max_distance),
+            polygon: None,
+            place_name: None,
+            distance_unit: None,
+            altitude: None,
+            tolerate_gps_precision: None,
+        };
+
+        assert!(LocationConstraint::try_from(request(f64::NAN, -0.1, 100.0)).is_err());
+        assert!(LocationConstraint::try_from(request(51.5, f64::INFINITY, 100.0)).is_err());
+        for max_distance in [f64::NAN, f64::INFINITY] {
+            let error =
+                LocationConstraint::try_from(request(51.5, -0.1, max_distance)).unwrap_err();
+            assert!(error.contains("finite"), "{error}");
+        }
+    }
+
+    #[test]
+    fn test_location_polygon_constraint_from_request() {
+        let json = r#"{
+            "content": "Picnic in the park",
+            "location": {
+                "polygon": [[51.50, -0.17], [51.50, -0.15], [51.51, -0.15], [51.51, -0.17]]
             }
+        }"#;
 
-            // Case 3: end + duration provided
-            (None, Some(end_str), Some(duration_minutes)) => {
-                let end = parse_datetime(&end_str)?;
-                let start = end - Duration::minutes(duration_minutes as i64);
-                (start, end)
-            }
+        let request: AnalysisRequest = serde_json::from_str(json).unwrap();
+        let context = ValidationContext::try_from(request).unwrap();
+        assert_eq!(
+            context.location_constraint,
+            Some(LocationConstraint::Polygon(vec![
+                (51.50, -0.17),
+                (51.50, -0.15),
+                (51.51, -0.15),
+                (51.51, -0.17),
+            ]))
+        );
+    }
 
-            _ => return Err("Invalid combination of fields provided".to_string()),
+    #[test]
+    fn test_location_polygon_constraint_validation() {
+        let polygon = vec![[51.50, -0.17], [51.50, -0.15], [51.51, -0.15]];
+        let request = |lat, max_distance, polygon| LocationRequest {
+            long: None,
+            lat,
+            max_distance,
+            polygon,
+            place_name: None,
+            distance_unit: None,
+            altitude: None,
+            tolerate_gps_precision: None,
         };
 
-        Ok(DateTimeConstraint {
-            start_time,
-            end_time,
-        })
+        // Polygon and radius fields are mutually exclusive
+        let error =
+            LocationConstraint::try_from(request(Some(51.5), Some(100.0), Some(polygon.clone())))
+                .unwrap_err();
+        assert!(error.contains("not both"));
+        assert!(LocationConstraint::try_from(request(None, Some(100.0), Some(polygon))).is_err());
+
+        // A polygon needs at least three valid vertices
+        let line = vec![[51.50, -0.17], [51.50, -0.15]];
+        assert!(LocationConstraint::try_from(request(None, None, Some(line))).is_err());
+        let out_of_range = vec![[51.50, -0.17], [95.0, -0.15], [51.51, -0.15]];
+        assert!(LocationConstraint::try_from(request(None, None, Some(out_of_range))).is_err());
+
+        // Neither form supplied
+        assert!(LocationConstraint::try_from(request(Some(51.5), None, None)).is_err());
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct ValidationContext {
-    pub content_check: String,
-    pub location_constraint: Option<LocationConstraint>,
-    pub datetime_constraint: Option<DateTimeConstraint>,
-}
+    #[test]
+    fn test_location_distance_units() {
+        let radius = |json: &str| {
+            let request: LocationRequest = serde_json::from_str(json).unwrap();
+            LocationConstraint::try_from(request)
+        };
 
-impl TryFrom<AnalysisRequest> for ValidationContext {
-    type Error = String;
+        let cases = [
+            (r#""max_distance": 250"#, 250.0, DistanceUnit::Meters),
+            (
+                r#""max_distance": 250, "distance_unit": "m""#,
+                250.0,
+                DistanceUnit::Meters,
+            ),
+            (
+                r#""max_distance": 1.5, "distance_unit": "km""#,
+                1500.0,
+                DistanceUnit::Kilometers,
+            ),
+            (
+                r#""max_distance": 2, "distance_unit": "mi""#,
+                3218.688,
+                DistanceUnit::Miles,
+            ),
+        ];
+        for (fields, expected_meters, expected_unit) in cases {
+            let json = format!(r#"{{ "lat": 51.5, "long": -0.12, {fields} }}"#);
+            let Ok(LocationConstraint::Radius {
+                max_distance_meters,
+                distance_unit,
+                ..
+            }) = radius(&json)
+            else {
+                panic!("expected a radius constraint for {json}");
+            };
+            assert!(
+                (max_distance_meters - expected_meters).abs() < 1e-9,
+                "{json}"
+            );
+            assert_eq!(distance_unit, expected_unit);
+        }
 
-    fn try_from(request: AnalysisRequest) -> Result<Self, Self::Error> {
-        let location_constraint = request.location.map(LocationConstraint::from);
+        // Unknown units are rejected when parsing, and a unit needs a distance
+        assert!(serde_json::from_str::<LocationRequest>(
+            r#"{ "lat": 51.5, "long": -0.12, "max_distance": 1, "distance_unit": "ft" }"#
+        )
+        .is_err());
+        let error =
+            radius(r#"{ "place_name": "Richmond Park", "distance_unit": "km" }"#).unwrap_err();
+        assert!(error.contains("requires max_distance"));
+    }
 
-        let datetime_constraint = if let Some(datetime) = request.datetime {
-            Some(DateTimeConstraint::try_from(datetime)?)
-        } else {
-            None
+    #[test]
+    fn test_location_tolerate_gps_precision() {
+        let radius = |json: &str| {
+            let request: LocationRequest = serde_json::from_str(json).unwrap();
+            LocationConstraint::try_from(request)
         };
 
-        Ok(ValidationContext {
-            content_check: request.content,
-            location_constraint,
-            datetime_constraint,
-        })
-    }
-}
+        for (json, expected) in [
+            (
+                r#"{ "lat": 51.5, "long": -0.12, "max_distance": 100 }"#,
+                false,
+            ),
+            (
+                r#"{ "lat": 51.5, "long": -0.12, "max_distance": 100, "tolerate_gps_precision": true }"#,
+                true,
+            ),
+        ] {
+            let Ok(LocationConstraint::Radius {
+                tolerate_gps_precision,
+                ..
+            }) = radius(json)
+            else {
+                panic!("expected a radius constraint for {json}");
+            };
+            assert_eq!(tolerate_gps_precision, expected, "{json}");
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let error = radius(
+            r#"{ "polygon": [[51.5, -0.1], [51.6, -0.1], [51.6, 0.0]], "tolerate_gps_precision": true }"#,
+        )
+        .unwrap_err();
+        assert!(error.contains("tolerate_gps_precision requires max_distance"));
+    }
 
     #[test]
-    fn test_location_constraint_from_request() {
-        let location_request = LocationRequest {
-            long: -0.266108,
-            lat: 51.492191,
-            max_distance: 100.0,
+    fn test_location_place_name_constraint() {
+        let json = r#"{
+            "content": "Deer in a park",
+            "location": { "place_name": " Richmond Park " }
+        }"#;
+        let request: AnalysisRequest = serde_json::from_str(json).unwrap();
+        let context = ValidationContext::try_from(request).unwrap();
+        assert_eq!(
+            context.location_constraint,
+            Some(LocationConstraint::Place("Richmond Park".to_string()))
+        );
+
+        let request = |place_name: &str, lat: Option<f64>| LocationRequest {
+            long: lat.map(|_| -0.27),
+            lat,
+            max_distance: lat.map(|_| 100.0),
+            distance_unit: None,
+            polygon: None,
+            place_name: Some(place_name.to_string()),
+            altitude: None,
+            tolerate_gps_precision: None,
         };
+        let error =
+            LocationConstraint::try_from(request("Richmond Park", Some(51.44))).unwrap_err();
+        assert!(error.contains("cannot be combined"));
+        assert!(LocationConstraint::try_from(request("  ", None)).is_err());
+    }
 
-        let constraint = LocationConstraint::from(location_request);
+    #[test]
+    fn test_location_altitude_constraint() {
+        let json = r#"{
+            "content": "Rooftop view",
+            "location": {
+                "long": -0.266108,
+                "lat": 51.492191,
+                "max_distance": 100.0,
+                "altitude": { "max": 300.0 }
+            }
+        }"#;
 
-        assert_eq!(constraint.max_distance_meters, 100.0);
-        assert!((constraint.latitude - 51.492191).abs() < 0.000001);
-        assert!((constraint.longitude + 0.266108).abs() < 0.000001);
+        let request: AnalysisRequest = serde_json::from_str(json).unwrap();
+        let context = ValidationContext::try_from(request).unwrap();
+        let altitude = context.altitude_constraint.unwrap();
+        assert_eq!(altitude.min_meters, None);
+        assert_eq!(altitude.max_meters, Some(300.0));
+
+        // Inverted and empty ranges are rejected
+        let mut request: AnalysisRequest = serde_json::from_str(json).unwrap();
+        request.location.as_mut().unwrap().altitude = Some(AltitudeRequest {
+            min: Some(500.0),
+            max: Some(300.0),
+        });
+        assert!(ValidationContext::try_from(request.clone()).is_err());
+
+        request.location.as_mut().unwrap().altitude = Some(AltitudeRequest {
+            min: None,
+            max: None,
+        });
+        assert!(ValidationContext::try_from(request).is_err());
     }
 
     #[test]
@@ -260,7 +1991,9 @@ mod tests {
         let datetime_request = DateTimeRequest {
             start: Some("2025-08-01T15:23:00+01:00".to_string()),
             end: None,
-            duration: Some(10), // 10 minutes
+            duration: Some(DateTimeDuration::Minutes(10)), // 10 minutes
+            local_date: None,
+            timezone: None,
         };
 
         let constraint = DateTimeConstraint::try_from(datetime_request).unwrap();
@@ -281,6 +2014,8 @@ mod tests {
             start: Some("2025-08-01T15:23:00+01:00".to_string()),
             end: Some("2025-08-01T15:33:00+01:00".to_string()),
             duration: None,
+            local_date: None,
+            timezone: None,
         };
 
         let constraint = DateTimeConstraint::try_from(datetime_request).unwrap();
@@ -300,7 +2035,9 @@ mod tests {
         let datetime_request = DateTimeRequest {
             start: None,
             end: Some("2025-08-01T15:33:00+01:00".to_string()),
-            duration: Some(10), // 10 minutes
+            duration: Some(DateTimeDuration::Minutes(10)), // 10 minutes
+            local_date: None,
+            timezone: None,
         };
 
         let constraint = DateTimeConstraint::try_from(datetime_request).unwrap();
@@ -315,6 +2052,185 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_datetime_constraint_local_day() {
+        let local_day = |local_date: &str, timezone: &str| {
+            DateTimeConstraint::try_from(DateTimeRequest {
+                start: None,
+                end: None,
+                duration: None,
+                local_date: Some(local_date.to_string()),
+                timezone: Some(timezone.to_string()),
+            })
+            .map(|constraint| (constraint.start_time, constraint.end_time))
+        };
+        let instant = |rfc3339| DateTime::parse_from_rfc3339(rfc3339).unwrap();
+
+        // A summer day in London is in BST
+        let (start, end) = local_day("2025-08-01", "Europe/London").unwrap();
+        assert_eq!(start, instant("2025-08-01T00:00:00+01:00"));
+        assert_eq!(end, instant("2025-08-02T00:00:00+01:00"));
+
+        // Clocks go forward: a 23-hour day starting in GMT and ending in BST
+        let (start, end) = local_day("2025-03-30", "Europe/London").unwrap();
+        assert_eq!(start, instant("2025-03-30T00:00:00+00:00"));
+        assert_eq!(end, instant("2025-03-31T00:00:00+01:00"));
+        assert_eq!(end - start, chrono::Duration::hours(23));
+        assert_eq!(start.offset().local_minus_utc(), 0);
+        assert_eq!(end.offset().local_minus_utc(), 3600);
+
+        // Clocks go back: a 25-hour day in New York
+        let (start, end) = local_day("2025-11-02", "America/New_York").unwrap();
+        assert_eq!(start, instant("2025-11-02T00:00:00-04:00"));
+        assert_eq!(end, instant("2025-11-03T00:00:00-05:00"));
+        assert_eq!(end - start, chrono::Duration::hours(25));
+
+        // Clocks went forward at midnight, so the day began at 01:00
+        let (start, _) = local_day("2018-11-04", "America/Sao_Paulo").unwrap();
+        assert_eq!(start, instant("2018-11-04T01:00:00-02:00"));
+
+        assert!(local_day("2025-08-01", "Mars/Olympus_Mons")
+            .unwrap_err()
+            .contains("Unknown timezone"));
+        assert!(local_day("01/08/2025", "Europe/London")
+            .unwrap_err()
+            .contains("Invalid local_date"));
+    }
+
+    #[test]
+    fn test_datetime_local_day_fields() {
+        let parse = |json: &str| serde_json::from_str::<DateTimeRequest>(json);
+
+        let request =
+            parse(r#"{ "local_date": "2025-08-01", "timezone": "Europe/London" }"#).unwrap();
+        assert_eq!(request.local_date.as_deref(), Some("2025-08-01"));
+
+        for json in [
+            r#"{ "local_date": "2025-08-01" }"#,
+            r#"{ "timezone": "Europe/London" }"#,
+            r#"{ "local_date": "2025-08-01", "timezone": "Europe/London", "duration": 10 }"#,
+        ] {
+            let error = parse(json).unwrap_err().to_string();
+            assert!(
+                error.contains("local_date and timezone must be provided together"),
+                "{json}: {error}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_datetime_constraint_iso8601_duration() {
+        let window = |duration: DateTimeDuration| {
+            DateTimeConstraint::try_from(DateTimeRequest {
+                start: Some("2025-08-01T15:00:00+01:00".to_string()),
+                end: None,
+                duration: Some(duration),
+                local_date: None,
+                timezone: None,
+            })
+            .map(|constraint| constraint.end_time.format("%H:%M").to_string())
+        };
+
+        assert_eq!(
+            window(DateTimeDuration::Iso8601("PT90M".to_string())).unwrap(),
+            "16:30"
+        );
+        assert_eq!(
+            window(DateTimeDuration::Iso8601("PT2H".to_string())).unwrap(),
+            "17:00"
+        );
+        assert_eq!(window(DateTimeDuration::Minutes(45)).unwrap(), "15:45");
+
+        let error = window(DateTimeDuration::Iso8601("ninety minutes".to_string())).unwrap_err();
+        assert!(error.contains("Invalid ISO-8601 duration"));
+        let error = window(DateTimeDuration::Iso8601("PT0M".to_string())).unwrap_err();
+        assert!(error.contains("greater than zero"));
+        let error = window(DateTimeDuration::Minutes(-10)).unwrap_err();
+        assert!(error.contains("greater than zero"));
+
+        // Integers and strings are both accepted in JSON
+        let request: DateTimeRequest =
+            serde_json::from_str(r#"{"end": "2025-08-01T15:00:00Z", "duration": "PT1H"}"#).unwrap();
+        assert_eq!(
+            request.duration,
+            Some(DateTimeDuration::Iso8601("PT1H".to_string()))
+        );
+        let request: DateTimeRequest =
+            serde_json::from_str(r#"{"end": "2025-08-01T15:00:00Z", "duration": 30}"#).unwrap();
+        assert_eq!(request.duration, Some(DateTimeDuration::Minutes(30)));
+    }
+
+    #[test]
+    fn test_datetime_phrase() {
+        let window = |json: &str| {
+            let request: DateTimeRequest = serde_json::from_str(json)?;
+            DateTimeConstraint::try_from(request)
+                .map(|constraint| {
+                    (
+                        constraint.start_time.to_rfc3339(),
+                        constraint.end_time.to_rfc3339(),
+                    )
+                })
+                .map_err(serde::de::Error::custom)
+        };
+
+        assert_eq!(
+            window(r#""image was taken not more than 10 minutes after 2025-08-01T15:23:00Z+1""#)
+                .unwrap(),
+            (
+                "2025-08-01T15:23:00+01:00".to_string(),
+                "2025-08-01T15:33:00+01:00".to_string()
+            )
+        );
+        assert_eq!(
+            window(r#""Not more than 1 minute before 2025-08-01T15:23:00Z.""#).unwrap(),
+            (
+                "2025-08-01T15:22:00+00:00".to_string(),
+                "2025-08-01T15:23:00+00:00".to_string()
+            )
+        );
+
+        let error: serde_json::Error = window(r#""sometime last tuesday afternoon""#).unwrap_err();
+        assert!(
+            error.to_string().contains("Unrecognized datetime phrase"),
+            "{error}"
+        );
+        let error = window(r#""not more than 10 minutes after teatime""#).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Invalid datetime format: teatime"));
+
+        // Fields still deserialize, with errors naming the offending field
+        assert!(window(r#"{"start": "2025-08-01T15:23:00Z", "duration": 10}"#).is_ok());
+        let error = window(r#"{"start": 1, "duration": 10}"#).unwrap_err();
+        assert!(error.to_string().contains("invalid type"), "{error}");
+    }
+
+    #[test]
+    fn test_datetime_utc_hour_offset_suffix() {
+        for (value, expected) in [
+            ("2025-08-01T15:23:00Z+1", "2025-08-01T15:23:00+01:00"),
+            ("2025-08-01T15:23:00Z+10", "2025-08-01T15:23:00+10:00"),
+            ("2025-08-01T15:23:00Z-5", "2025-08-01T15:23:00-05:00"),
+            ("2025-08-01T15:23:00+02:00", "2025-08-01T15:23:00+02:00"),
+        ] {
+            assert_eq!(
+                parse_request_datetime(value).unwrap().to_rfc3339(),
+                expected
+            );
+        }
+
+        for value in [
+            "2025-08-01T15:23:00Z+",
+            "2025-08-01T15:23:00Z+15",
+            "2025-08-01T15:23:00Z++1",
+            "2025-08-01T15:23:00Z1",
+            "2025-08-01 15:23",
+        ] {
+            assert!(parse_request_datetime(value).is_err(), "{value}");
+        }
+    }
+
     #[test]
     fn test_datetime_constraint_invalid_combinations() {
         // Test with no fields
@@ -322,6 +2238,8 @@ mod tests {
             start: None,
             end: None,
             duration: None,
+            local_date: None,
+            timezone: None,
         });
         assert!(result.is_err());
 
@@ -329,7 +2247,9 @@ mod tests {
         let result = DateTimeConstraint::try_from(DateTimeRequest {
             start: Some("2025-08-01T15:23:00+01:00".to_string()),
             end: Some("2025-08-01T15:33:00+01:00".to_string()),
-            duration: Some(10),
+            duration: Some(DateTimeDuration::Minutes(10)),
+            local_date: None,
+            timezone: None,
         });
         assert!(result.is_err());
 
@@ -338,10 +2258,69 @@ mod tests {
             start: Some("2025-08-01T15:23:00+01:00".to_string()),
             end: None,
             duration: None,
+            local_date: None,
+            timezone: None,
         });
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_allowed_cameras_constraint() {
+        let json = r#"{
+            "content": "Delivery at the front door",
+            "allowed_cameras": [" Apple iPhone 15 ", "Google Pixel 8"]
+        }"#;
+        let request: AnalysisRequest = serde_json::from_str(json).unwrap();
+        let context = ValidationContext::try_from(request).unwrap();
+        assert_eq!(
+            context.allowed_cameras,
+            Some(vec![
+                "Apple iPhone 15".to_string(),
+                "Google Pixel 8".to_string()
+            ])
+        );
+
+        let json = r#"{"content": "Delivery", "allowed_cameras": [""]}"#;
+        let request: AnalysisRequest = serde_json::from_str(json).unwrap();
+        assert!(ValidationContext::try_from(request).is_err());
+    }
+
+    #[test]
+    fn test_constraint_severity() {
+        // Defaults to hard for every check
+        let request: AnalysisRequest = serde_json::from_str(r#"{"content": "x"}"#).unwrap();
+        let context = ValidationContext::try_from(request).unwrap();
+        assert_eq!(context.severity, ConstraintSeverity::default());
+        assert_eq!(context.severity.content, Severity::Hard);
+
+        let json = r#"{"content": "x", "severity": {"content": "soft"}}"#;
+        let request: AnalysisRequest = serde_json::from_str(json).unwrap();
+        let severity = ValidationContext::try_from(request).unwrap().severity;
+        assert_eq!(
+            severity.for_code(ReasonCode::ContentMismatch),
+            Severity::Soft
+        );
+        assert_eq!(
+            severity.for_code(ReasonCode::LocationOutOfRange),
+            Severity::Hard
+        );
+
+        let json = r#"{"content": "x", "severity": {"location": "soft"}}"#;
+        let request: AnalysisRequest = serde_json::from_str(json).unwrap();
+        let severity = ValidationContext::try_from(request).unwrap().severity;
+        assert_eq!(
+            severity.for_code(ReasonCode::AltitudeOutOfRange),
+            Severity::Soft
+        );
+        assert_eq!(
+            severity.for_code(ReasonCode::ValidationError),
+            Severity::Hard
+        );
+
+        let json = r#"{"content": "x", "severity": {"content": "advisory"}}"#;
+        assert!(serde_json::from_str::<AnalysisRequest>(json).is_err());
+    }
+
     #[test]
     fn test_validation_request_image_path() {
         // Test request with image-path
@@ -359,6 +2338,101 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validation_request_multiple_image_paths() {
+        let json = r#"{
+            "image-path": ["/path/to/first.jpg", "/path/to/second.jpg"],
+            "analysis-request": {
+                "content": "test content",
+                "match_mode": "any"
+            }
+        }"#;
+
+        let request: ValidationRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(request.analysis_request.match_mode, MatchMode::Any);
+
+        let request = ProcessingRequest::from_request(request);
+        assert_eq!(
+            request.get_image_paths(),
+            vec!["/path/to/first.jpg", "/path/to/second.jpg"]
+        );
+        assert_eq!(
+            request.get_image_path(),
+            Some("/path/to/first.jpg".to_string())
+        );
+
+        // A single path and the default mode still work
+        let json = r#"{
+            "image-path": "/path/to/image.jpg",
+            "analysis-request": { "content": "test content" }
+        }"#;
+        let request: ValidationRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(request.analysis_request.match_mode, MatchMode::All);
+        let request = ProcessingRequest::from_request(request);
+        assert_eq!(request.get_image_paths(), vec!["/path/to/image.jpg"]);
+    }
+
+    #[test]
+    fn test_validation_results_combine() {
+        let images = vec![
+            ImageResult::new(
+                "a.jpg".to_string(),
+                ValidationResults::rejected(vec![RejectionReason::new(
+                    ReasonCode::ImageNotFound,
+                    "cannot locate image",
+                )]),
+            ),
+            ImageResult::new("b.jpg".to_string(), ValidationResults::accepted()),
+        ];
+
+        let any = ValidationResults::combine(MatchMode::Any, images.clone());
+        assert_eq!(any.resolution, Resolution::Accepted);
+        assert_eq!(any.reasons, None);
+        assert_eq!(any.images.as_ref().unwrap().len(), 2);
+
+        let all = ValidationResults::combine(MatchMode::All, images);
+        assert_eq!(all.resolution, Resolution::Rejected);
+        assert_eq!(
+            all.reasons,
+            Some(vec![RejectionReason::new(
+                ReasonCode::ImageNotFound,
+                "a.jpg: cannot locate image"
+            )])
+        );
+
+        let json = serde_json::to_value(&all).unwrap();
+        assert_eq!(json["images"][0]["image-path"], "a.jpg");
+        assert_eq!(json["images"][1]["resolution"], "accepted");
+    }
+
+    #[test]
+    fn test_validation_results_combine_keeps_warnings() {
+        let warning =
+            RejectionReason::new(ReasonCode::ContentMismatch, "no birds in image").as_warning();
+        let images = vec![
+            ImageResult::new(
+                "a.jpg".to_string(),
+                ValidationResults::accepted_with_warnings(vec![warning.clone()]),
+            ),
+            ImageResult::new("b.jpg".to_string(), ValidationResults::accepted()),
+        ];
+
+        let all = ValidationResults::combine(MatchMode::All, images);
+        assert_eq!(all.resolution, Resolution::Accepted);
+        assert_eq!(
+            all.reasons,
+            Some(vec![RejectionReason {
+                message: "a.jpg: no birds in image".to_string(),
+                ..warning
+            }])
+        );
+
+        assert_eq!(
+            ValidationResults::accepted_with_warnings(vec![]).reasons,
+            None
+        );
+    }
+
     #[test]
     fn test_validation_request_image_null() {
         // Test request with image: null
@@ -373,18 +2447,34 @@ mod tests {
         assert_eq!(request.get_image_path(), None);
     }
 
+    #[test]
+    fn test_processing_request_inline_image() {
+        let json = r#"{
+            "image": "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==",
+            "analysis-request": {
+                "content": "test content"
+            }
+        }"#;
+
+        let request: ValidationRequest = serde_json::from_str(json).unwrap();
+        let request = ProcessingRequest::from_request(request);
+        assert_eq!(request.get_image_path(), None);
+        assert!(request
+            .get_inline_image()
+            .unwrap()
+            .starts_with("data:image/png"));
+    }
+
     #[test]
     fn test_validation_response_accepted() {
         let response = ValidationResponse {
             processing_id: "001".to_string(),
-            results: ValidationResults {
-                resolution: Resolution::Accepted,
-                reasons: None,
-            },
+            results: ValidationResults::accepted(),
         };
 
         let json = serde_json::to_string(&response).unwrap();
         assert!(json.contains("\"resolution\":\"accepted\""));
+        assert!(!json.contains("reasons"));
         assert!(!json.contains("resons"));
     }
 
@@ -392,15 +2482,334 @@ mod tests {
     fn test_validation_response_rejected() {
         let response = ValidationResponse {
             processing_id: "001".to_string(),
-            results: ValidationResults {
-                resolution: Resolution::Rejected,
-                reasons: Some(vec!["cannot locate image".to_string()]),
-            },
+            results: ValidationResults::rejected(vec![RejectionReason::new(
+                ReasonCode::ImageNotFound,
+                "cannot locate image",
+            )]),
         };
 
-        let json = serde_json::to_string(&response).unwrap();
-        assert!(json.contains("\"resolution\":\"rejected\""));
-        assert!(json.contains("\"resons\"")); // Note the typo
-        assert!(json.contains("cannot locate image"));
+        let json: serde_json::Value = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["results"]["resolution"], "rejected");
+        assert_eq!(
+            json["results"]["reasons"][0],
+            serde_json::json!({ "code": "IMAGE_NOT_FOUND", "message": "cannot locate image" })
+        );
+        assert!(json["results"].get("resons").is_none());
+    }
+
+    #[test]
+    fn test_validation_response_with_legacy_reasons_key() {
+        let response = ValidationResponse {
+            processing_id: "001".to_string(),
+            results: ValidationResults::rejected(vec![RejectionReason::new(
+                ReasonCode::ImageNotFound,
+                "cannot locate image",
+            )])
+            .with_legacy_reasons_key(),
+        };
+
+        let json: serde_json::Value = serde_json::to_value(&response).unwrap();
+        assert_eq!(
+            json["results"]["reasons"][0]["message"],
+            "cannot locate image"
+        );
+        // The legacy key keeps its plain-string shape
+        assert_eq!(json["results"]["resons"][0], "cannot locate image");
+    }
+
+    #[test]
+    fn test_validation_response_accepted_with_legacy_reasons_key() {
+        // No reasons means neither key is emitted, even with the legacy key enabled
+        let results = ValidationResults::accepted().with_legacy_reasons_key();
+
+        let json = serde_json::to_string(&results).unwrap();
+        assert!(!json.contains("reasons"));
+        assert!(!json.contains("resons"));
+    }
+
+    #[test]
+    fn test_validation_results_content_confidence() {
+        let json = serde_json::to_value(ValidationResults::accepted()).unwrap();
+        assert!(json.get("confidence").is_none());
+
+        let results = ValidationResults::accepted().with_content_confidence(0.87);
+        let json = serde_json::to_value(&results).unwrap();
+        assert_eq!(json["confidence"]["content"].as_f64().unwrap() as f32, 0.87);
+        assert!(json["confidence"].get("similarity").is_none());
+
+        let results = results.with_similarity(Some(0.75));
+        let json = serde_json::to_value(&results).unwrap();
+        assert_eq!(json["confidence"]["similarity"], 0.75);
+    }
+
+    #[test]
+    fn test_validation_results_checks() {
+        let json = serde_json::to_value(ValidationResults::accepted()).unwrap();
+        assert!(json.get("checks").is_none());
+
+        let results = ValidationResults::accepted().with_checks(Some(CheckOutcome::list(
+            CheckState::Passed,
+            CheckState::Failed,
+            CheckState::Skipped,
+        )));
+        assert_eq!(
+            serde_json::to_value(&results).unwrap()["checks"],
+            serde_json::json!([
+                { "check": "content", "state": "passed" },
+                { "check": "location", "state": "failed" },
+                { "check": "datetime", "state": "skipped" }
+            ])
+        );
+
+        let request: AnalysisRequest =
+            serde_json::from_str(r#"{ "content": "Front door", "verbose": true }"#).unwrap();
+        assert!(ValidationContext::try_from(request).unwrap().verbose);
+    }
+
+    #[test]
+    fn test_forbid_gps_and_timestamp_conflicts() {
+        let context = |json: &str| {
+            let request: AnalysisRequest = serde_json::from_str(json).unwrap();
+            ValidationContext::try_from(request)
+        };
+
+        let forbidding =
+            context(r#"{ "content": "Birds", "forbid_gps": true, "forbid_timestamp": true }"#)
+                .unwrap();
+        assert!(forbidding.forbid_gps);
+        assert!(forbidding.forbid_timestamp);
+        let defaults = context(r#"{ "content": "Birds" }"#).unwrap();
+        assert!(!defaults.forbid_gps);
+        assert!(!defaults.forbid_timestamp);
+
+        let error = context(
+            r#"{ "content": "Birds", "forbid_gps": true,
+                 "location": { "lat": 51.5, "long": -0.1, "max_distance": 100 } }"#,
+        )
+        .unwrap_err();
+        assert!(error.contains("forbid_gps cannot be combined"));
+        let error = context(r#"{ "content": "Birds", "forbid_gps": true, "max_speed_kmh": 30 }"#)
+            .unwrap_err();
+        assert!(error.contains("forbid_gps cannot be combined"));
+        let error = context(
+            r#"{ "content": "Birds", "forbid_timestamp": true,
+                 "datetime": { "start": "2025-08-01T15:23:00Z", "duration": 10 } }"#,
+        )
+        .unwrap_err();
+        assert!(error.contains("forbid_timestamp cannot be combined"));
+    }
+
+    #[test]
+    fn test_on_missing_metadata() {
+        let context = |json: &str| {
+            let request: AnalysisRequest = serde_json::from_str(json).unwrap();
+            ValidationContext::try_from(request).unwrap()
+        };
+
+        assert_eq!(
+            context(r#"{ "content": "Birds" }"#).on_missing_metadata,
+            MissingMetadataPolicy::Reject
+        );
+        assert_eq!(
+            context(r#"{ "content": "Birds", "on_missing_metadata": "warn" }"#).on_missing_metadata,
+            MissingMetadataPolicy::Warn
+        );
+        assert!(serde_json::from_str::<AnalysisRequest>(
+            r#"{ "content": "Birds", "on_missing_metadata": "ignore" }"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_content_alternatives() {
+        let context = |json: &str| {
+            let request: AnalysisRequest = serde_json::from_str(json).unwrap();
+            ValidationContext::try_from(request)
+        };
+
+        let single = context(r#"{ "content": "Birds" }"#).unwrap();
+        assert_eq!(single.content_checks, vec!["Birds"]);
+        assert_eq!(single.content_match_mode, MatchMode::All);
+
+        let alternatives =
+            context(r#"{ "content": ["A cat ", "A dog"], "content_match_mode": "any" }"#).unwrap();
+        assert_eq!(alternatives.content_checks, vec!["A cat", "A dog"]);
+        assert_eq!(alternatives.content_match_mode, MatchMode::Any);
+
+        let error = context(r#"{ "content": ["A cat", " "] }"#).unwrap_err();
+        assert_eq!(error, "content descriptions must not be blank");
+        let request: AnalysisRequest = serde_json::from_str(r#"{ "content": [] }"#).unwrap();
+        assert!(request.content.is_empty());
+    }
+
+    #[test]
+    fn test_reference_image_constraint() {
+        let context = |json: &str| {
+            let request: AnalysisRequest = serde_json::from_str(json).unwrap();
+            ValidationContext::try_from(request)
+        };
+
+        let text_only = context(r#"{ "content": "Front door" }"#).unwrap();
+        assert_eq!(text_only.reference_image_path, None);
+        assert_eq!(text_only.similarity_threshold, None);
+
+        let with_default =
+            context(r#"{ "content": "Front door", "reference_image_path": " door.jpg " }"#)
+                .unwrap();
+        assert_eq!(
+            with_default.reference_image_path.as_deref(),
+            Some("door.jpg")
+        );
+        assert_eq!(
+            with_default.similarity_threshold,
+            Some(DEFAULT_SIMILARITY_THRESHOLD)
+        );
+        let with_threshold = context(
+            r#"{ "content": "Front door", "reference_image_path": "door.jpg", "similarity_threshold": 0.9 }"#,
+        )
+        .unwrap();
+        assert_eq!(with_threshold.similarity_threshold, Some(0.9));
+
+        for (json, expected) in [
+            (
+                r#"{ "content": "Front door", "similarity_threshold": 0.9 }"#,
+                "requires reference_image_path",
+            ),
+            (
+                r#"{ "content": "Front door", "reference_image_path": "door.jpg", "similarity_threshold": 1.5 }"#,
+                "between 0 and 1",
+            ),
+            (
+                r#"{ "content": "Front door", "reference_image_path": "  " }"#,
+                "must not be empty",
+            ),
+        ] {
+            let error = context(json).unwrap_err();
+            assert!(error.contains(expected), "{json}: {error}");
+        }
+    }
+
+    #[test]
+    fn test_check_weights() {
+        let context = |json: &str| {
+            let request: AnalysisRequest = serde_json::from_str(json).unwrap();
+            ValidationContext::try_from(request)
+        };
+
+        let weighted = context(
+            r#"{ "content": "Birds", "weights": { "content": 0.6, "location": 0.3, "datetime": 0.1 }, "accept_threshold": 0.7 }"#,
+        )
+        .unwrap();
+        let weights = weighted.weights.unwrap();
+        assert_eq!(weighted.accept_threshold, Some(0.7));
+        assert!((weights.score(1.0, false, true, false, false) - 0.7).abs() < 1e-6);
+        assert!((weights.score(0.5, true, true, true, true) - 0.7).abs() < 1e-6);
+        // Weights need not add up to 1
+        let doubled = CheckWeights {
+            content: Some(2.0),
+            location: Some(2.0),
+            ..CheckWeights::default()
+        };
+        assert_eq!(doubled.score(1.0, false, false, false, false), 0.5);
+
+        let severity = weights.apply_to(ConstraintSeverity::default(), Severity::Soft);
+        assert_eq!(severity.location, Severity::Soft);
+        assert_eq!(severity.camera, Severity::Hard);
+
+        for (json, expected) in [
+            (
+                r#"{ "content": "Birds", "weights": { "content": 1 } }"#,
+                "weights requires accept_threshold",
+            ),
+            (
+                r#"{ "content": "Birds", "accept_threshold": 0.5 }"#,
+                "accept_threshold requires weights",
+            ),
+            (
+                r#"{ "content": "Birds", "weights": { "content": 1 }, "accept_threshold": 2 }"#,
+                "between 0 and 1",
+            ),
+            (
+                r#"{ "content": "Birds", "weights": { "content": -1 }, "accept_threshold": 0.5 }"#,
+                "weights.content must be a non-negative number",
+            ),
+            (
+                r#"{ "content": "Birds", "weights": { "content": 0 }, "accept_threshold": 0.5 }"#,
+                "at least one positive weight",
+            ),
+        ] {
+            let error = context(json).unwrap_err();
+            assert!(error.contains(expected), "{json}: {error}");
+        }
+    }
+
+    #[test]
+    fn test_max_speed_constraint() {
+        let context = |json: &str| {
+            let request: AnalysisRequest = serde_json::from_str(json).unwrap();
+            ValidationContext::try_from(request)
+        };
+
+        let limited = context(r#"{ "content": "Birds", "max_speed_kmh": 30 }"#).unwrap();
+        assert_eq!(limited.max_speed_kmh, Some(30.0));
+        assert_eq!(
+            limited.severity.for_code(ReasonCode::SpeedOutOfRange),
+            limited.severity.location
+        );
+
+        let error = context(r#"{ "content": "Birds", "max_speed_kmh": -5 }"#).unwrap_err();
+        assert!(error.contains("non-negative"), "{error}");
+    }
+
+    #[test]
+    fn test_dimensions_constraint() {
+        let context = |json: &str| {
+            let request: AnalysisRequest = serde_json::from_str(json).unwrap();
+            ValidationContext::try_from(request)
+        };
+
+        let bounded = context(
+            r#"{ "content": "Birds", "dimensions": { "min_width": 1024, "max_width": 8000 } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            bounded.dimensions_constraint,
+            Some(DimensionsConstraint {
+                min_width: Some(1024),
+                min_height: None,
+                max_width: Some(8000),
+                max_height: None,
+            })
+        );
+
+        for (json, expected) in [
+            (
+                r#"{ "content": "Birds", "dimensions": {} }"#,
+                "requires at least one",
+            ),
+            (
+                r#"{ "content": "Birds", "dimensions": { "min_height": 800, "max_height": 600 } }"#,
+                "min_height (800) must not exceed max_height (600)",
+            ),
+        ] {
+            let error = context(json).unwrap_err();
+            assert!(error.contains(expected), "{json}: {error}");
+        }
+    }
+
+    #[test]
+    fn test_generate_processing_id() {
+        for alphabet in [IdAlphabet::Base58, IdAlphabet::Base62] {
+            let id = generate_processing_id(12, alphabet);
+            assert_eq!(id.len(), 12);
+            assert!(id.bytes().all(|c| alphabet.chars().contains(&c)), "{id}");
+            assert_ne!(id, generate_processing_id(12, alphabet));
+        }
+
+        assert_eq!(IdAlphabet::Base58.chars().len(), 58);
+        assert_eq!(IdAlphabet::Base62.chars().len(), 62);
+        // Base58 leaves out characters that are easily misread
+        let id = generate_processing_id(1000, IdAlphabet::Base58);
+        assert!(!id.contains(['0', 'O', 'I', 'l']));
     }
 }
@@ -0,0 +1,117 @@
+use crate::error::ApiErrorBody;
+use crate::handlers;
+use utoipa::OpenApi;
+
+/// OpenAPI document served at `GET /openapi.json`
+///
+/// Schemas are derived from the model types, so field names follow their serde renames.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Image Checker",
+        description = "Validates images against content, location, time and camera constraints"
+    ),
+    paths(
+        handlers::submit_validation,
+        handlers::submit_validation_batch,
+        handlers::check_validation,
+        handlers::inspect_image,
+        handlers::validate_sync,
+        handlers::check_status,
+        handlers::check_status_batch,
+        handlers::get_results,
+        handlers::debug_image,
+        handlers::cancel_validation,
+        handlers::retry_validation,
+        handlers::list_jobs,
+        handlers::health_check,
+        handlers::readiness_check,
+        handlers::version,
+        handlers::queue_stats,
+        handlers::admin_queue,
+        handlers::flush_queue,
+        handlers::metrics,
+    ),
+    components(schemas(ApiErrorBody))
+)]
+pub struct ApiDoc;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{ApiError, ErrorCode};
+
+    fn schema_properties(doc: &serde_json::Value, name: &str) -> Vec<String> {
+        let mut properties: Vec<String> = doc["components"]["schemas"][name]["properties"]
+            .as_object()
+            .unwrap_or_else(|| panic!("schema {name} has no properties"))
+            .keys()
+            .cloned()
+            .collect();
+        properties.sort();
+        properties
+    }
+
+    #[test]
+    fn test_schemas_use_serde_names() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+
+        assert_eq!(
+            schema_properties(&doc, "ValidationRequest"),
+            [
+                "analysis-request",
+                "callback-url",
+                "image",
+                "image-path",
+                "timeout-seconds"
+            ]
+        );
+        assert_eq!(
+            schema_properties(&doc, "ValidationUpload"),
+            [
+                "analysis-request",
+                "callback-url",
+                "image",
+                "timeout-seconds"
+            ]
+        );
+        assert!(schema_properties(&doc, "ValidationResults").contains(&"resons".to_string()));
+        assert_eq!(
+            doc["components"]["schemas"]["ProcessingStatus"]["enum"],
+            serde_json::json!([
+                "accepted",
+                "in_progress",
+                "completed",
+                "failed",
+                "not_found",
+                "cancelled"
+            ])
+        );
+        assert_eq!(
+            doc["components"]["schemas"]["ValidationRequest"]["required"],
+            serde_json::json!(["analysis-request"])
+        );
+    }
+
+    #[test]
+    fn test_error_schema_matches_error_envelope() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let body = serde_json::to_value(ApiErrorBody {
+            error: ApiError {
+                code: ErrorCode::NotFound,
+                message: "boom".to_string(),
+                details: None,
+                field: Some("analysis-request.content".to_string()),
+            },
+        })
+        .unwrap();
+
+        let keys = |value: &serde_json::Value| {
+            let mut keys: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+            keys.sort();
+            keys
+        };
+        assert_eq!(schema_properties(&doc, "ApiErrorBody"), keys(&body));
+        assert_eq!(schema_properties(&doc, "ApiError"), keys(&body["error"]));
+    }
+}
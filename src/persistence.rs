@@ -0,0 +1,214 @@
+use crate::models::{ProcessingStatus, ValidationResponse};
+use crate::queue::ProcessingRecord;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use thiserror::Error;
+use tracing::{debug, info, warn};
+
+#[derive(Debug, Error)]
+pub enum PersistenceError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// The durable part of a [`ProcessingRecord`]
+///
+/// `Instant`s cannot outlive the process, so the submission time is stored as
+/// wall-clock time and converted back on load.
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedRecord {
+    status: ProcessingStatus,
+    submitted_at: DateTime<Utc>,
+    result: Option<ValidationResponse>,
+    #[serde(default)]
+    attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    failure_reason: Option<String>,
+}
+
+/// Stores processing records as one JSON file per request under `PERSISTENCE_PATH`
+#[derive(Debug, Clone)]
+pub struct RecordStore {
+    dir: PathBuf,
+}
+
+impl RecordStore {
+    /// Opens the store, creating the directory if it does not exist
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, PersistenceError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Writes the record, replacing any previous state for the request
+    pub async fn save(
+        &self,
+        processing_id: &str,
+        record: &ProcessingRecord,
+    ) -> Result<(), PersistenceError> {
+        let persisted = PersistedRecord {
+            status: record.status.clone(),
+            submitted_at: Utc::now()
+                - chrono::Duration::from_std(record.submitted_at.elapsed()).unwrap_or_default(),
+            result: record.result.clone(),
+            attempts: record.attempts,
+            failure_reason: record.failure_reason.clone(),
+        };
+        let json = serde_json::to_vec(&persisted)?;
+
+        // Write then rename so a crash never leaves a half-written record behind
+        let path = self.record_path(processing_id);
+        let tmp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, json).await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+
+        debug!(processing_id = %processing_id, status = ?record.status, "Persisted record");
+        Ok(())
+    }
+
+    /// Deletes the record; removing one that was never stored is not an error
+    pub async fn remove(&self, processing_id: &str) -> Result<(), PersistenceError> {
+        match tokio::fs::remove_file(self.record_path(processing_id)).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Reads every stored record
+    ///
+    /// Requests that were accepted or in progress when the process stopped can no
+    /// longer complete, so they are reloaded (and re-saved) as failed. Unreadable
+    /// files are skipped with a warning.
+    pub fn load_all(&self) -> Result<HashMap<String, ProcessingRecord>, PersistenceError> {
+        let mut records = HashMap::new();
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(processing_id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            let persisted: PersistedRecord = match fs::read(&path)
+                .map_err(PersistenceError::from)
+                .and_then(|bytes| Ok(serde_json::from_slice(&bytes)?))
+            {
+                Ok(persisted) => persisted,
+                Err(e) => {
+                    warn!("Skipping unreadable persisted record {:?}: {}", path, e);
+                    continue;
+                }
+            };
+
+            let age = (Utc::now() - persisted.submitted_at)
+                .to_std()
+                .unwrap_or_default();
+            let mut record = ProcessingRecord::new();
+            record.submitted_at = Instant::now().checked_sub(age).unwrap_or_else(Instant::now);
+            record.status = persisted.status;
+            record.result = persisted.result;
+            record.attempts = persisted.attempts;
+            record.failure_reason = persisted.failure_reason;
+
+            match record.status {
+                ProcessingStatus::Accepted | ProcessingStatus::InProgress => {
+                    warn!(processing_id = %processing_id, "Request was interrupted by a restart");
+                    record.fail();
+                    let json = serde_json::to_vec(&PersistedRecord {
+                        status: record.status.clone(),
+                        submitted_at: persisted.submitted_at,
+                        result: None,
+                        attempts: persisted.attempts,
+                        failure_reason: None,
+                    })?;
+                    fs::write(&path, json)?;
+                }
+                _ => record.completed_at = Some(record.submitted_at),
+            }
+
+            records.insert(processing_id.to_string(), record);
+        }
+
+        info!(
+            "Loaded {} persisted records from {:?}",
+            records.len(),
+            self.dir
+        );
+        Ok(records)
+    }
+
+    fn record_path(&self, processing_id: &str) -> PathBuf {
+        self.dir.join(format!("{processing_id}.json"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ReasonCode, RejectionReason, ValidationResults};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_save_and_load_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let store = RecordStore::open(dir.path()).unwrap();
+
+        let mut completed = ProcessingRecord::new();
+        completed.complete_with_result(ValidationResponse {
+            processing_id: "done".to_string(),
+            results: ValidationResults::rejected(vec![RejectionReason::new(
+                ReasonCode::ContentMismatch,
+                "no birds",
+            )]),
+        });
+        store.save("done", &completed).await.unwrap();
+
+        let mut in_flight = ProcessingRecord::new();
+        in_flight.start_processing();
+        store.save("running", &in_flight).await.unwrap();
+
+        let records = store.load_all().unwrap();
+        assert_eq!(records.len(), 2);
+
+        let done = &records["done"];
+        assert_eq!(done.status, ProcessingStatus::Completed);
+        assert_eq!(
+            done.result.as_ref().unwrap().results.reasons,
+            Some(vec![RejectionReason::new(
+                ReasonCode::ContentMismatch,
+                "no birds"
+            )])
+        );
+
+        // Work in flight at shutdown cannot resume, so it comes back failed
+        assert_eq!(records["running"].status, ProcessingStatus::Failed);
+        let reloaded = store.load_all().unwrap();
+        assert_eq!(reloaded["running"].status, ProcessingStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_remove_and_skip_unreadable_files() {
+        let dir = TempDir::new().unwrap();
+        let store = RecordStore::open(dir.path()).unwrap();
+
+        store.save("gone", &ProcessingRecord::new()).await.unwrap();
+        store.remove("gone").await.unwrap();
+        store.remove("never-stored").await.unwrap();
+
+        fs::write(dir.path().join("corrupt.json"), b"{not json").unwrap();
+        fs::write(dir.path().join("notes.txt"), b"ignored").unwrap();
+
+        assert!(store.load_all().unwrap().is_empty());
+    }
+}
@@ -1,17 +1,33 @@
-use crate::config::Config;
-use crate::models::{ProcessingRequest, ProcessingStatus, ValidationResponse};
-use crate::validation::ValidationProcessor;
+use crate::circuit_breaker::{CircuitBreaker, CircuitState};
+use crate::config::{Config, ConfigError};
+use crate::metrics;
+use crate::models::{
+    generate_processing_id, ImageInspection, InspectRequest, JobSummary, PartialResults,
+    ProcessingRequest, ProcessingStatus, StatusResponse, ValidationResponse,
+};
+use crate::persistence::RecordStore;
+use crate::rates::{QueueRates, WindowRates};
+use crate::readiness::{LlmProbe, PROBE_CACHE_TTL, PROBE_TIMEOUT};
+use crate::throttle::TokenBucket;
+use crate::validation::{
+    ProcessedImage, ProcessedImages, ProcessorError, Progress, RequestValidator, SeenImages,
+    ValidationProcessor,
+};
+use crate::webhook::{CallbackPayload, WebhookClient};
 
-use std::collections::HashMap;
+use arc_swap::ArcSwap;
+use std::collections::{hash_map::Entry, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
-use tokio::sync::{mpsc, RwLock, Semaphore};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::{mpsc, oneshot, Mutex, Notify, RwLock, Semaphore};
 use tokio::time::{sleep, timeout};
-use tracing::{debug, error, info, warn};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, info_span, warn, Instrument};
 
 #[derive(Debug)]
 pub enum QueueItem {
-    ValidationRequest(ProcessingRequest),
+    ValidationRequest(Box<ProcessingRequest>),
     StatusQuery(String, tokio::sync::oneshot::Sender<ProcessingStatus>),
     ResultQuery(
         String,
@@ -27,6 +43,20 @@ pub struct ProcessingRecord {
     pub started_at: Option<Instant>,
     pub completed_at: Option<Instant>,
     pub result: Option<ValidationResponse>,
+    /// Number of times processing has started, including retries
+    pub attempts: u32,
+    /// Cancelled when a client abandons the request via `DELETE /validate/:id`
+    pub cancel_token: CancellationToken,
+    /// Woken when the request completes, fails or is cancelled
+    pub finished: Arc<Notify>,
+    /// Sub-check outcomes reported while the request is in progress
+    pub progress: Progress,
+    /// The submitted request, kept so it can be re-run; not persisted
+    pub request: Option<ProcessingRequest>,
+    /// Time by which the client needs the result, from `X-Request-Deadline`
+    pub deadline: Option<SystemTime>,
+    /// Why the request failed, when known
+    pub failure_reason: Option<String>,
 }
 
 impl Default for ProcessingRecord {
@@ -43,91 +73,587 @@ impl ProcessingRecord {
             started_at: None,
             completed_at: None,
             result: None,
+            attempts: 0,
+            cancel_token: CancellationToken::new(),
+            finished: Arc::new(Notify::new()),
+            progress: Progress::default(),
+            request: None,
+            deadline: None,
+            failure_reason: None,
+        }
+    }
+
+    /// A record for a newly queued request, keeping it for re-runs
+    pub fn for_request(request: &ProcessingRequest) -> Self {
+        Self {
+            request: Some(request.clone()),
+            deadline: request.deadline,
+            ..Self::new()
         }
     }
 
     pub fn start_processing(&mut self) {
         self.status = ProcessingStatus::InProgress;
         self.started_at = Some(Instant::now());
+        self.attempts += 1;
+        self.progress.reset();
+    }
+
+    /// Puts a failed attempt back in the queued state until it is re-run
+    pub fn retry(&mut self) {
+        self.status = ProcessingStatus::Accepted;
     }
 
     pub fn complete_with_result(&mut self, result: ValidationResponse) {
         self.status = ProcessingStatus::Completed;
         self.completed_at = Some(Instant::now());
         self.result = Some(result);
+        self.finished.notify_waiters();
     }
 
     pub fn fail(&mut self) {
         self.status = ProcessingStatus::Failed;
         self.completed_at = Some(Instant::now());
+        self.finished.notify_waiters();
+    }
+
+    /// Fails the request, reporting `reason` with its status
+    pub fn fail_with_reason(&mut self, reason: impl Into<String>) {
+        self.failure_reason = Some(reason.into());
+        self.fail();
+    }
+
+    /// Time left before the deadline, zero once it has passed; `None` without one
+    pub fn time_to_deadline(&self) -> Option<Duration> {
+        self.deadline.map(|deadline| {
+            deadline
+                .duration_since(SystemTime::now())
+                .unwrap_or_default()
+        })
+    }
+
+    pub fn cancel(&mut self) {
+        self.status = ProcessingStatus::Cancelled;
+        self.completed_at = Some(Instant::now());
+        self.cancel_token.cancel();
+        self.finished.notify_waiters();
+    }
+
+    /// Whether the request has reached a final status
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.status,
+            ProcessingStatus::Completed | ProcessingStatus::Failed | ProcessingStatus::Cancelled
+        )
     }
 
     pub fn is_expired(&self, timeout: Duration) -> bool {
         self.submitted_at.elapsed() > timeout
     }
+
+    /// Whether cleanup may drop the record
+    ///
+    /// Finished records are kept for `retention` after they finish so their results
+    /// stay queryable; the rest are dropped `processing_timeout` after submission.
+    pub fn is_evictable(&self, processing_timeout: Duration, retention: Duration) -> bool {
+        match self.completed_at.filter(|_| self.is_finished()) {
+            Some(completed_at) => completed_at.elapsed() > retention,
+            None => self.is_expired(processing_timeout),
+        }
+    }
+}
+
+/// How often `await_idle` re-checks for in-progress requests
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Short processing ids generated before giving up on finding an unused one
+const MAX_ID_ATTEMPTS: usize = 5;
+
+/// Failure reason of requests whose deadline passed while they were queued
+pub const DEADLINE_EXCEEDED_REASON: &str = "deadline exceeded before processing";
+
+/// Failure reason of requests still unfinished when shutdown gave up on them
+pub const SHUTDOWN_ABANDONED_REASON: &str = "service shut down before processing finished";
+
+/// Counts a job from when the worker takes it off the queue until its task ends
+struct ActiveJob(Arc<AtomicUsize>);
+
+impl ActiveJob {
+    fn start(active_jobs: &Arc<AtomicUsize>) -> Self {
+        active_jobs.fetch_add(1, Ordering::SeqCst);
+        Self(active_jobs.clone())
+    }
+}
+
+impl Drop for ActiveJob {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
+/// Processing ids by `Idempotency-Key`, with when each key was first used
+type IdempotencyKeys = Arc<Mutex<HashMap<String, (String, Instant)>>>;
+
+/// Senders notified once a request finishes; `None` signals failure or timeout
+type CompletionWaiters = Arc<Mutex<HashMap<String, oneshot::Sender<Option<ValidationResponse>>>>>;
+
 #[derive(Clone)]
 pub struct ProcessingQueue {
     sender: mpsc::Sender<QueueItem>,
     status_map: Arc<RwLock<HashMap<String, ProcessingRecord>>>,
-    throttle_semaphore: Arc<Semaphore>,
+    completion_waiters: CompletionWaiters,
+    /// Kept in memory only, so keys are forgotten on restart
+    idempotency_keys: IdempotencyKeys,
+    throttle: Arc<TokenBucket>,
+    /// Shared with the processor's LLM client; reported in the queue stats
+    circuit_breaker: Arc<CircuitBreaker>,
+    /// Replaced with the reloadable settings on [`ProcessingQueue::reload`]
+    config: Arc<ArcSwap<Config>>,
+    /// Durable copy of `status_map`, written through on every status change
+    store: Option<RecordStore>,
+    /// Answers `POST /inspect` directly, bypassing the queue and throttle
+    inspector: Arc<ValidationProcessor>,
+    /// Reachability check behind `GET /health/ready`
+    llm_probe: Arc<LlmProbe>,
+    /// Times the worker recovered from a panic; reported in the queue stats
+    worker_restarts: Arc<AtomicU64>,
+    /// Perceptual hashes of recently validated images, shared with the processor
+    seen_images: Arc<SeenImages>,
+    /// Set once shutdown starts, so submissions are refused while the worker drains
+    draining: Arc<AtomicBool>,
+    /// Jobs the worker has taken off the queue that have not finished, including those
+    /// still waiting for a throttle token
+    active_jobs: Arc<AtomicUsize>,
+    /// Images as sent to the model; `None` unless `DEBUG_RETAIN_PROCESSED_IMAGES` is set
+    processed_images: Option<Arc<ProcessedImages>>,
+    /// Rolling submission and completion rates; reported in the queue stats
+    rates: Arc<QueueRates>,
 }
 
 impl ProcessingQueue {
     pub fn new(config: &Config) -> Self {
+        let throttle = Arc::new(TokenBucket::per_minute(config.throttle_requests_per_minute));
+        let circuit_breaker = Arc::new(Self::circuit_breaker_for(config));
+        let seen_images = Arc::new(SeenImages::new(config.duplicate_retention()));
+        let processed_images = config
+            .debug_retain_processed_images
+            .then(|| Arc::new(ProcessedImages::new(config.result_retention())));
+        let mut processor = ValidationProcessor::new(config)
+            .with_throttle(throttle.clone())
+            .with_circuit_breaker(circuit_breaker.clone())
+            .with_seen_images(seen_images.clone());
+        if let Some(processed_images) = &processed_images {
+            processor = processor.with_processed_images(processed_images.clone());
+        }
+        let processor = Arc::new(processor);
+        let mut queue = Self::start(
+            config,
+            processor.clone(),
+            processor,
+            throttle,
+            circuit_breaker,
+            seen_images,
+        );
+        queue.processed_images = processed_images;
+        queue
+    }
+
+    /// Creates a queue whose worker validates requests with `validator`
+    pub fn with_validator(config: &Config, validator: Arc<dyn RequestValidator>) -> Self {
+        let throttle = Arc::new(TokenBucket::per_minute(config.throttle_requests_per_minute));
+        let inspector = Arc::new(ValidationProcessor::new(config));
+        let circuit_breaker = Arc::new(Self::circuit_breaker_for(config));
+        let seen_images = Arc::new(SeenImages::new(config.duplicate_retention()));
+        Self::start(
+            config,
+            validator,
+            inspector,
+            throttle,
+            circuit_breaker,
+            seen_images,
+        )
+    }
+
+    fn circuit_breaker_for(config: &Config) -> CircuitBreaker {
+        CircuitBreaker::new(
+            config.llm_circuit_failure_threshold,
+            config.llm_circuit_cooldown(),
+        )
+    }
+
+    fn start(
+        config: &Config,
+        validator: Arc<dyn RequestValidator>,
+        inspector: Arc<ValidationProcessor>,
+        throttle: Arc<TokenBucket>,
+        circuit_breaker: Arc<CircuitBreaker>,
+        seen_images: Arc<SeenImages>,
+    ) -> Self {
         let (sender, receiver) = mpsc::channel(config.queue_size);
-        let status_map = Arc::new(RwLock::new(HashMap::new()));
-        let throttle_semaphore =
-            Arc::new(Semaphore::new(config.throttle_requests_per_minute as usize));
+
+        let store = config
+            .persistence_path
+            .as_ref()
+            .map(|path| RecordStore::open(path).expect("Failed to open persistence directory"));
+        let records = match &store {
+            Some(store) => store
+                .load_all()
+                .expect("Failed to load persisted processing records"),
+            None => HashMap::new(),
+        };
+        let status_map = Arc::new(RwLock::new(records));
+        let completion_waiters: CompletionWaiters = Arc::new(Mutex::new(HashMap::new()));
+        let idempotency_keys: IdempotencyKeys = Arc::new(Mutex::new(HashMap::new()));
 
         let queue = ProcessingQueue {
             sender,
             status_map: status_map.clone(),
-            throttle_semaphore: throttle_semaphore.clone(),
+            completion_waiters: completion_waiters.clone(),
+            idempotency_keys: idempotency_keys.clone(),
+            throttle: throttle.clone(),
+            circuit_breaker,
+            config: Arc::new(ArcSwap::from_pointee(config.clone())),
+            store: store.clone(),
+            inspector,
+            llm_probe: Arc::new(LlmProbe::new(
+                config.llm_api_url.clone(),
+                PROBE_TIMEOUT,
+                PROBE_CACHE_TTL,
+            )),
+            worker_restarts: Arc::new(AtomicU64::new(0)),
+            seen_images,
+            draining: Arc::new(AtomicBool::new(false)),
+            active_jobs: Arc::new(AtomicUsize::new(0)),
+            processed_images: None,
+            rates: Arc::new(QueueRates::default()),
         };
 
-        // Start the worker task
+        // Start the worker task, restarting it should it panic; the receiver is shared
+        // so a restarted worker picks up the queued requests
         let worker_config = config.clone();
         let worker_status_map = status_map.clone();
-        let worker_throttle = throttle_semaphore.clone();
+        let worker_waiters = completion_waiters.clone();
+        let worker_throttle = throttle.clone();
+        let worker_store = store.clone();
+        let worker_restarts = queue.worker_restarts.clone();
+        let worker_rates = queue.rates.clone();
+        let worker_draining = queue.draining.clone();
+        let worker_active_jobs = queue.active_jobs.clone();
+        let retry_sender = queue.sender.clone();
+        let receiver = Arc::new(Mutex::new(receiver));
 
         tokio::spawn(async move {
-            Self::worker_task(receiver, worker_config, worker_status_map, worker_throttle).await;
+            loop {
+                let worker = tokio::spawn(Self::worker_task(
+                    receiver.clone(),
+                    retry_sender.clone(),
+                    validator.clone(),
+                    worker_config.clone(),
+                    worker_status_map.clone(),
+                    worker_waiters.clone(),
+                    worker_throttle.clone(),
+                    worker_store.clone(),
+                    worker_restarts.clone(),
+                    worker_rates.clone(),
+                    worker_draining.clone(),
+                    worker_active_jobs.clone(),
+                ));
+                match worker.await {
+                    Err(e) if e.is_panic() => {
+                        worker_restarts.fetch_add(1, Ordering::Relaxed);
+                        error!("Processing queue worker panicked; restarting it");
+                    }
+                    _ => break,
+                }
+            }
         });
 
         // Start cleanup task for expired records
         let cleanup_status_map = status_map.clone();
-        let cleanup_timeout = config.processing_timeout();
+        let cleanup_timeout = config.longest_processing_timeout();
+        let cleanup_retention = config.result_retention();
+        let idempotency_key_ttl = config.idempotency_key_ttl();
 
         tokio::spawn(async move {
-            Self::cleanup_task(cleanup_status_map, cleanup_timeout).await;
+            Self::cleanup_task(
+                cleanup_status_map,
+                cleanup_timeout,
+                cleanup_retention,
+                store,
+                idempotency_keys,
+                idempotency_key_ttl,
+            )
+            .await;
         });
 
         queue
     }
 
+    /// Generates the id for a new request
+    ///
+    /// Without `PROCESSING_ID_LENGTH` this is a UUID. Short ids are checked against the
+    /// tracked requests and regenerated on a collision, up to `MAX_ID_ATTEMPTS` times.
+    /// The id is not reserved, so a concurrent submission may still take it first; the
+    /// later submission of the two is then refused.
+    pub async fn new_processing_id(&self) -> Result<String, QueueError> {
+        let config = self.config();
+        let Some(length) = config.processing_id_length else {
+            return Ok(uuid::Uuid::new_v4().to_string());
+        };
+
+        let status_map = self.status_map.read().await;
+        for _ in 0..MAX_ID_ATTEMPTS {
+            let processing_id = generate_processing_id(length, config.processing_id_alphabet);
+            if !status_map.contains_key(&processing_id) {
+                return Ok(processing_id);
+            }
+            warn!(processing_id = %processing_id, "Generated processing id is taken; regenerating");
+        }
+        Err(QueueError::Internal(
+            "could not generate an unused processing id".to_string(),
+        ))
+    }
+
+    /// Associates an `Idempotency-Key` with a new request's processing id
+    ///
+    /// Returns the id and current status of the request the key was first used for,
+    /// if any, in which case nothing is recorded. Keys expire after
+    /// `IDEMPOTENCY_KEY_TTL_MINUTES`, when their submission fails, or when their request
+    /// is cleaned up or flushed.
+    pub async fn claim_idempotency_key(
+        &self,
+        key: &str,
+        processing_id: &str,
+    ) -> Option<(String, ProcessingStatus)> {
+        let mut keys = self.idempotency_keys.lock().await;
+        if let Some((original_id, claimed_at)) = keys.get(key) {
+            if claimed_at.elapsed() < self.config().idempotency_key_ttl() {
+                // An untracked id is a first submission still on its way into the queue
+                let status = match self.get_status(original_id).await {
+                    ProcessingStatus::NotFound => ProcessingStatus::Accepted,
+                    status => status,
+                };
+                return Some((original_id.clone(), status));
+            }
+        }
+
+        keys.insert(key.to_string(), (processing_id.to_string(), Instant::now()));
+        None
+    }
+
+    /// Forgets a key claimed for `processing_id`, e.g. because its submission failed
+    pub async fn release_idempotency_key(&self, key: &str, processing_id: &str) {
+        let mut keys = self.idempotency_keys.lock().await;
+        if keys.get(key).is_some_and(|(id, _)| id == processing_id) {
+            keys.remove(key);
+        }
+    }
+
     pub async fn submit_validation(&self, request: ProcessingRequest) -> Result<(), QueueError> {
-        // Check if queue is full
-        if self.sender.is_closed() {
+        if self.is_closed() {
             return Err(QueueError::QueueClosed);
         }
 
         // Add to status tracking
+        self.track(&request).await?;
+
+        // Send to processing queue
+        metrics::record_enqueued();
+        if self
+            .sender
+            .send(QueueItem::ValidationRequest(Box::new(request)))
+            .await
+            .is_err()
+        {
+            metrics::record_dequeued();
+            return Err(QueueError::QueueFull);
+        }
+        metrics::record_submission();
+        self.rates.submissions.record();
+
+        Ok(())
+    }
+
+    /// Submits a request without waiting for a free slot
+    ///
+    /// Fails with `QueueFull` when all `QUEUE_SIZE` slots are taken; the request is
+    /// then not tracked at all.
+    pub async fn try_submit_validation(
+        &self,
+        request: ProcessingRequest,
+    ) -> Result<(), QueueError> {
+        if self.is_closed() {
+            return Err(QueueError::QueueClosed);
+        }
+
+        let processing_id = request.processing_id.clone();
+        self.track(&request).await?;
+
+        metrics::record_enqueued();
+        let error = match self
+            .sender
+            .try_send(QueueItem::ValidationRequest(Box::new(request)))
+        {
+            Ok(()) => {
+                metrics::record_submission();
+                self.rates.submissions.record();
+                return Ok(());
+            }
+            Err(mpsc::error::TrySendError::Full(_)) => QueueError::QueueFull,
+            Err(mpsc::error::TrySendError::Closed(_)) => QueueError::QueueClosed,
+        };
+
+        metrics::record_dequeued();
+        self.status_map.write().await.remove(&processing_id);
+        if let Some(store) = &self.store {
+            if let Err(e) = store.remove(&processing_id).await {
+                warn!(processing_id = %processing_id, "Failed to remove persisted record: {}", e);
+            }
+        }
+        Err(error)
+    }
+
+    /// Starts tracking a new request as accepted
+    ///
+    /// Fails if the processing id is already tracked, e.g. when two concurrent
+    /// submissions drew the same short id, rather than overwriting the other request.
+    async fn track(&self, request: &ProcessingRequest) -> Result<(), QueueError> {
+        let record = ProcessingRecord::for_request(request);
         {
             let mut status_map = self.status_map.write().await;
-            status_map.insert(request.processing_id.clone(), ProcessingRecord::new());
+            match status_map.entry(request.processing_id.clone()) {
+                Entry::Occupied(_) => {
+                    warn!(processing_id = %request.processing_id, "Processing id is already in use");
+                    return Err(QueueError::Internal(
+                        "processing id is already in use".to_string(),
+                    ));
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(record.clone());
+                }
+            }
         }
+        Self::persist(&self.store, &request.processing_id, Some(record)).await;
+        Ok(())
+    }
 
-        // Send to processing queue
-        self.sender
-            .send(QueueItem::ValidationRequest(request))
+    /// Queues a finished request again under the same processing id
+    ///
+    /// The record starts over as accepted, dropping the earlier result. The original
+    /// deadline has usually passed by then, so the re-run gets `deadline` instead. Requests
+    /// loaded from `PERSISTENCE_PATH` cannot be re-run, since the original request is not
+    /// stored.
+    pub async fn rerun(
+        &self,
+        processing_id: &str,
+        deadline: Option<SystemTime>,
+    ) -> Result<(), QueueError> {
+        if self.is_closed() {
+            return Err(QueueError::QueueClosed);
+        }
+
+        let (request, record) = {
+            let mut status_map = self.status_map.write().await;
+            let record = status_map
+                .get_mut(processing_id)
+                .ok_or(QueueError::NotFound)?;
+            if !record.is_finished() {
+                return Err(QueueError::NotFinished);
+            }
+            let request = record
+                .request
+                .clone()
+                .ok_or(QueueError::RequestUnavailable)?
+                .with_deadline(deadline);
+
+            *record = ProcessingRecord::for_request(&request);
+            (request, record.clone())
+        };
+
+        info!(processing_id = %processing_id, "Re-running request");
+        Self::persist(&self.store, processing_id, Some(record)).await;
+
+        metrics::record_enqueued();
+        if self
+            .sender
+            .send(QueueItem::ValidationRequest(Box::new(request)))
             .await
-            .map_err(|_| QueueError::QueueFull)?;
+            .is_err()
+        {
+            metrics::record_dequeued();
+            return Err(QueueError::QueueClosed);
+        }
+        metrics::record_submission();
+        self.rates.submissions.record();
 
         Ok(())
     }
 
+    /// Submits a request and returns a receiver that resolves when processing finishes
+    ///
+    /// The receiver yields the response on success and `None` if processing failed or
+    /// timed out. The request is tracked like any other, so `/status` still works.
+    pub async fn submit_and_wait(
+        &self,
+        request: ProcessingRequest,
+    ) -> Result<oneshot::Receiver<Option<ValidationResponse>>, QueueError> {
+        let processing_id = request.processing_id.clone();
+        let (sender, receiver) = oneshot::channel();
+
+        // Register before submitting so a fast worker cannot finish unobserved
+        self.completion_waiters
+            .lock()
+            .await
+            .insert(processing_id.clone(), sender);
+
+        if let Err(e) = self.submit_validation(request).await {
+            self.completion_waiters.lock().await.remove(&processing_id);
+            return Err(e);
+        }
+
+        Ok(receiver)
+    }
+
+    /// The configuration the queue runs with, including reloaded settings
+    pub fn config(&self) -> Arc<Config> {
+        self.config.load_full()
+    }
+
+    /// Applies the reloadable settings of `reloaded` to the running queue
+    ///
+    /// The throttle takes the new rate at once, and jobs starting from now on use the
+    /// new default model and prompt template; in-flight jobs are not interrupted. Returns
+    /// the changed settings that need a restart, which are ignored.
+    pub fn reload(&self, reloaded: &Config) -> Result<Vec<&'static str>, ConfigError> {
+        let (config, ignored) = self.config().with_reloaded(reloaded);
+        let prompt_template = config.prompt_template().map_err(|e| {
+            ConfigError::Validation(format!(
+                "Prompt template {} is unusable: {}",
+                config.prompt_template_path.as_deref().unwrap_or_default(),
+                e
+            ))
+        })?;
+
+        self.throttle.set_rate(config.throttle_requests_per_minute);
+        self.inspector
+            .reload(config.llm_model_name.clone(), prompt_template);
+        self.config.store(Arc::new(config));
+        Ok(ignored)
+    }
+
+    /// Checks that the LLM API is reachable, returning why it is not
+    pub async fn check_llm_ready(&self) -> Result<(), String> {
+        self.llm_probe.check().await
+    }
+
+    /// Describes an image as the processor would read it, without queueing a job
+    pub async fn inspect(
+        &self,
+        request: &InspectRequest,
+    ) -> Result<ImageInspection, ProcessorError> {
+        self.inspector.inspect(request).await
+    }
+
     pub async fn get_status(&self, processing_id: &str) -> ProcessingStatus {
         let status_map = self.status_map.read().await;
 
@@ -137,6 +663,139 @@ impl ProcessingQueue {
             .unwrap_or(ProcessingStatus::NotFound)
     }
 
+    /// Statuses of several requests, in order, read under a single lock
+    ///
+    /// Unknown ids are reported as `not_found` with no attempts.
+    pub async fn get_statuses(&self, processing_ids: Vec<String>) -> Vec<StatusResponse> {
+        let status_map = self.status_map.read().await;
+
+        processing_ids
+            .into_iter()
+            .map(|processing_id| {
+                let (status, attempts, reason) = status_map
+                    .get(&processing_id)
+                    .map(|record| {
+                        (
+                            record.status.clone(),
+                            record.attempts,
+                            record.failure_reason.clone(),
+                        )
+                    })
+                    .unwrap_or((ProcessingStatus::NotFound, 0, None));
+                StatusResponse {
+                    processing_id,
+                    status,
+                    attempts,
+                    reason,
+                }
+            })
+            .collect()
+    }
+
+    /// Number of processing attempts made for the request so far, 0 if unknown
+    pub async fn get_attempts(&self, processing_id: &str) -> u32 {
+        let status_map = self.status_map.read().await;
+
+        status_map
+            .get(processing_id)
+            .map(|record| record.attempts)
+            .unwrap_or(0)
+    }
+
+    /// Why the request failed, if it failed for a known reason
+    pub async fn get_failure_reason(&self, processing_id: &str) -> Option<String> {
+        let status_map = self.status_map.read().await;
+
+        status_map
+            .get(processing_id)
+            .and_then(|record| record.failure_reason.clone())
+    }
+
+    /// Lists tracked requests, newest first, optionally filtered by status
+    ///
+    /// Returns one page of `limit` jobs starting at `offset` together with the
+    /// number of jobs matching the filter.
+    pub async fn list_jobs(
+        &self,
+        status: Option<&ProcessingStatus>,
+        limit: usize,
+        offset: usize,
+    ) -> (Vec<JobSummary>, usize) {
+        let mut jobs: Vec<(String, ProcessingStatus, Instant)> = {
+            let status_map = self.status_map.read().await;
+            status_map
+                .iter()
+                .filter(|(_, record)| status.is_none_or(|status| record.status == *status))
+                .map(|(id, record)| (id.clone(), record.status.clone(), record.submitted_at))
+                .collect()
+        };
+
+        jobs.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+        let total = jobs.len();
+
+        let now = chrono::Utc::now();
+        let page = jobs
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|(processing_id, status, submitted_at)| JobSummary {
+                processing_id,
+                status,
+                submitted_at_ms: (now
+                    - chrono::Duration::from_std(submitted_at.elapsed()).unwrap_or_default())
+                .timestamp_millis(),
+            })
+            .collect();
+
+        (page, total)
+    }
+
+    /// Waits up to `max_wait` for the request to finish and returns its status
+    ///
+    /// Returns immediately for unknown or already finished requests; otherwise the
+    /// status is whatever it is when the request finishes or the wait runs out.
+    pub async fn wait_for_completion(
+        &self,
+        processing_id: &str,
+        max_wait: Duration,
+    ) -> ProcessingStatus {
+        let finished = {
+            let status_map = self.status_map.read().await;
+            match status_map.get(processing_id) {
+                None => return ProcessingStatus::NotFound,
+                Some(record) if record.is_finished() => return record.status.clone(),
+                Some(record) => record.finished.clone(),
+            }
+        };
+
+        // Register for the wake-up before re-checking, so a request finishing in
+        // between cannot be missed
+        let notified = finished.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        let status = self.get_status(processing_id).await;
+        if matches!(
+            status,
+            ProcessingStatus::Accepted | ProcessingStatus::InProgress
+        ) {
+            let _ = timeout(max_wait, notified).await;
+            return self.get_status(processing_id).await;
+        }
+
+        status
+    }
+
+    /// Sub-check outcomes reported so far, while the request is in progress
+    pub async fn get_partial_results(&self, processing_id: &str) -> Option<PartialResults> {
+        let status_map = self.status_map.read().await;
+
+        status_map
+            .get(processing_id)
+            .filter(|record| record.status == ProcessingStatus::InProgress)
+            .map(|record| record.progress.snapshot())
+    }
+
     pub async fn get_result(&self, processing_id: &str) -> Option<ValidationResponse> {
         let status_map = self.status_map.read().await;
 
@@ -145,33 +804,212 @@ impl ProcessingQueue {
             .and_then(|record| record.result.clone())
     }
 
+    /// Cancels a request that has not finished yet
+    ///
+    /// Queued requests are skipped by the worker and in-flight ones are abandoned.
+    /// Cancelling an already cancelled request succeeds without side effects.
+    pub async fn cancel(&self, processing_id: &str) -> Result<(), QueueError> {
+        let cancelled = {
+            let mut status_map = self.status_map.write().await;
+            let record = status_map
+                .get_mut(processing_id)
+                .ok_or(QueueError::NotFound)?;
+
+            match record.status {
+                ProcessingStatus::Accepted | ProcessingStatus::InProgress => record.cancel(),
+                ProcessingStatus::Cancelled => return Ok(()),
+                _ => return Err(QueueError::AlreadyFinished),
+            }
+            record.clone()
+        };
+
+        info!(processing_id = %processing_id, "Cancelled request");
+        Self::persist(&self.store, processing_id, Some(cancelled)).await;
+        Self::notify_waiter(&self.completion_waiters, processing_id, None).await;
+
+        Ok(())
+    }
+
+    /// The image sent to the model for a request, while `DEBUG_RETAIN_PROCESSED_IMAGES`
+    /// keeps them and the request's results are retained
+    pub fn get_processed_image(&self, processing_id: &str) -> Option<ProcessedImage> {
+        self.processed_images.as_ref()?.get(processing_id)
+    }
+
+    /// Whether new submissions are refused, because shutdown started or the worker stopped
+    fn is_closed(&self) -> bool {
+        self.draining.load(Ordering::SeqCst) || self.sender.is_closed()
+    }
+
+    /// Refuses new submissions at once, then tells the worker to stop once it has
+    /// drained the requests already queued; status and result queries keep working
     pub async fn shutdown(&self) {
+        self.draining.store(true, Ordering::SeqCst);
         if let Err(e) = self.sender.send(QueueItem::Shutdown).await {
             warn!("Failed to send shutdown signal: {}", e);
         }
     }
 
+    /// Waits until no request is queued or being worked on, giving up after `timeout`
+    ///
+    /// Returns the number of requests still queued or unfinished when the timeout was
+    /// hit, or 0 once the queue is idle. Jobs waiting for a throttle token count as
+    /// unfinished; retries waiting out their backoff do not, as they are no longer
+    /// queued once shutdown starts.
+    pub async fn await_idle(&self, timeout: Duration) -> usize {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let pending = self.pending_count();
+            if pending == 0 || Instant::now() >= deadline {
+                return pending;
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            sleep(IDLE_POLL_INTERVAL.min(remaining)).await;
+        }
+    }
+
+    /// Requests in the channel, other than the shutdown signal, plus unfinished jobs
+    fn pending_count(&self) -> usize {
+        let queued = if self.sender.is_closed() {
+            // The worker has stopped, so nothing left in the channel will run
+            0
+        } else if self.draining.load(Ordering::SeqCst) {
+            (self.sender.max_capacity() - self.sender.capacity()).saturating_sub(1)
+        } else {
+            self.sender.max_capacity() - self.sender.capacity()
+        };
+        queued + self.active_jobs.load(Ordering::SeqCst)
+    }
+
+    /// Fails every request that has not finished, e.g. those left when the shutdown
+    /// grace period runs out or whose retry was not queued, so they are not reported
+    /// as accepted forever; returns how many were failed
+    pub async fn fail_unfinished(&self) -> usize {
+        let failed: Vec<(String, ProcessingRecord)> = {
+            let mut status_map = self.status_map.write().await;
+            status_map
+                .iter_mut()
+                .filter(|(_, record)| !record.is_finished())
+                .map(|(processing_id, record)| {
+                    record.cancel_token.cancel();
+                    record.fail_with_reason(SHUTDOWN_ABANDONED_REASON);
+                    (processing_id.clone(), record.clone())
+                })
+                .collect()
+        };
+
+        for (processing_id, record) in &failed {
+            warn!(processing_id = %processing_id, "Failing request abandoned at shutdown");
+            Self::persist(&self.store, processing_id, Some(record.clone())).await;
+            Self::notify_waiter(&self.completion_waiters, processing_id, None).await;
+        }
+        failed.len()
+    }
+
+    #[allow(clippy::too_many_arguments)]
     async fn worker_task(
-        mut receiver: mpsc::Receiver<QueueItem>,
+        receiver: Arc<Mutex<mpsc::Receiver<QueueItem>>>,
+        retry_sender: mpsc::Sender<QueueItem>,
+        validator: Arc<dyn RequestValidator>,
         config: Config,
         status_map: Arc<RwLock<HashMap<String, ProcessingRecord>>>,
-        throttle_semaphore: Arc<Semaphore>,
+        completion_waiters: CompletionWaiters,
+        throttle: Arc<TokenBucket>,
+        store: Option<RecordStore>,
+        worker_restarts: Arc<AtomicU64>,
+        rates: Arc<QueueRates>,
+        draining: Arc<AtomicBool>,
+        active_jobs: Arc<AtomicUsize>,
     ) {
-        info!("Processing queue worker started");
+        let mut receiver = receiver.lock().await;
+        info!(
+            concurrency = config.worker_concurrency,
+            "Processing queue worker started"
+        );
+
+        let slots = Arc::new(Semaphore::new(config.worker_concurrency));
+        let config = Arc::new(config);
 
-        let processor = ValidationProcessor::new(&config);
+        loop {
+            // Requests stay in the channel, and count towards the queue size, until a
+            // slot is free
+            let slot = slots
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("worker semaphore is never closed");
+            let Some(item) = receiver.recv().await else {
+                break;
+            };
 
-        while let Some(item) = receiver.recv().await {
             match item {
                 QueueItem::ValidationRequest(request) => {
-                    Self::process_validation_request(
-                        request,
-                        &processor,
-                        &config,
-                        &status_map,
-                        &throttle_semaphore,
-                    )
-                    .await;
+                    let active_job = ActiveJob::start(&active_jobs);
+                    // Log lines from the processor inherit the id through this span
+                    let span = info_span!("job", processing_id = %request.processing_id);
+                    let validator = validator.clone();
+                    let retry_sender = retry_sender.clone();
+                    let config = config.clone();
+                    let status_map = status_map.clone();
+                    let completion_waiters = completion_waiters.clone();
+                    let throttle = throttle.clone();
+                    let store = store.clone();
+                    let worker_restarts = worker_restarts.clone();
+                    let rates = rates.clone();
+                    let draining = draining.clone();
+
+                    tokio::spawn(
+                        async move {
+                            // Processing runs in its own task so a panic, e.g. in the
+                            // validator, fails the job instead of leaving it in progress
+                            let processing_id = request.processing_id.clone();
+                            let callback_url = request.callback_url.clone();
+                            let job = {
+                                let config = config.clone();
+                                let status_map = status_map.clone();
+                                let completion_waiters = completion_waiters.clone();
+                                let store = store.clone();
+                                tokio::spawn(
+                                    async move {
+                                        Self::process_validation_request(
+                                            *request,
+                                            validator.as_ref(),
+                                            &retry_sender,
+                                            &config,
+                                            &status_map,
+                                            &completion_waiters,
+                                            &throttle,
+                                            &store,
+                                            &rates,
+                                            &draining,
+                                        )
+                                        .await;
+                                    }
+                                    .in_current_span(),
+                                )
+                            };
+
+                            if let Err(e) = job.await {
+                                if e.is_panic() {
+                                    worker_restarts.fetch_add(1, Ordering::Relaxed);
+                                    Self::fail_panicked_request(
+                                        &processing_id,
+                                        callback_url,
+                                        &config,
+                                        &status_map,
+                                        &completion_waiters,
+                                        &store,
+                                    )
+                                    .await;
+                                }
+                            }
+                            drop(active_job);
+                            drop(slot);
+                        }
+                        .instrument(span),
+                    );
                 }
                 QueueItem::Shutdown => {
                     info!("Received shutdown signal, stopping worker");
@@ -186,130 +1024,515 @@ impl ProcessingQueue {
         info!("Processing queue worker stopped");
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn process_validation_request(
         request: ProcessingRequest,
-        processor: &ValidationProcessor,
+        validator: &dyn RequestValidator,
+        retry_sender: &mpsc::Sender<QueueItem>,
         config: &Config,
         status_map: &Arc<RwLock<HashMap<String, ProcessingRecord>>>,
-        throttle_semaphore: &Arc<Semaphore>,
+        completion_waiters: &CompletionWaiters,
+        throttle: &TokenBucket,
+        store: &Option<RecordStore>,
+        rates: &QueueRates,
+        draining: &Arc<AtomicBool>,
     ) {
         let processing_id = request.processing_id.clone();
+        metrics::record_dequeued();
+
+        // Wait for a throttle token while the request still counts as queued
+        let Some(queued_token) = status_map
+            .read()
+            .await
+            .get(&processing_id)
+            .map(|record| record.cancel_token.clone())
+        else {
+            info!(processing_id = %processing_id, "Skipping request that is no longer tracked");
+            return;
+        };
+        let throttle_wait = tokio::select! {
+            wait = throttle.acquire() => wait,
+            _ = queued_token.cancelled() => {
+                info!(processing_id = %processing_id, "Skipping cancelled request");
+                return;
+            }
+        };
+        metrics::record_throttle_wait(throttle_wait);
 
-        debug!("Starting processing for request: {}", processing_id);
+        debug!(processing_id = %processing_id, "Starting processing for request");
 
-        // Update status to in_progress
-        {
+        // Update status to in_progress, unless the request was cancelled while queued or
+        // its client no longer needs the result
+        let started = {
             let mut status_map = status_map.write().await;
-            if let Some(record) = status_map.get_mut(&processing_id) {
-                record.start_processing();
+            match status_map.get_mut(&processing_id) {
+                Some(record) if record.cancel_token.is_cancelled() => {
+                    info!(processing_id = %processing_id, "Skipping cancelled request");
+                    return;
+                }
+                Some(record) if record.time_to_deadline() == Some(Duration::ZERO) => {
+                    warn!(processing_id = %processing_id, "Deadline exceeded before processing");
+                    record.fail_with_reason(DEADLINE_EXCEEDED_REASON);
+                    Err(record.clone())
+                }
+                Some(record) => {
+                    record.start_processing();
+                    Ok(Some(record.clone()))
+                }
+                None => Ok(None),
             }
-        }
+        };
+        let started = match started {
+            Ok(started) => started,
+            Err(failed) => {
+                let callback = CallbackPayload {
+                    processing_id: processing_id.clone(),
+                    status: failed.status.clone(),
+                    results: None,
+                };
+                Self::persist(store, &processing_id, Some(failed)).await;
+                Self::notify_waiter(completion_waiters, &processing_id, None).await;
+                if let Some(callback_url) = request.callback_url {
+                    Self::send_callback(config, callback_url, callback);
+                }
+                return;
+            }
+        };
+        let (cancel_token, progress) = started
+            .as_ref()
+            .map(|record| (record.cancel_token.clone(), record.progress.clone()))
+            .unwrap_or_default();
+        let time_to_deadline = started
+            .as_ref()
+            .and_then(ProcessingRecord::time_to_deadline);
+        Self::persist(store, &processing_id, started).await;
 
-        // Acquire throttle permit
-        let _permit = throttle_semaphore
-            .acquire()
-            .await
-            .expect("Semaphore closed");
+        // Process with timeout, abandoning the work if the request is cancelled; there is
+        // no point working past the client's deadline
+        let processing_timeout = config.processing_timeout_for(request.timeout_seconds);
+        let processing_timeout = match time_to_deadline {
+            Some(remaining) if remaining < processing_timeout => {
+                debug!(processing_id = %processing_id, "Processing timeout clamped to the {:?} left before the deadline", remaining);
+                remaining
+            }
+            _ => processing_timeout,
+        };
+        let result = tokio::select! {
+            result = timeout(processing_timeout, validator.validate_request(request.clone(), &progress)) => result,
+            _ = cancel_token.cancelled() => {
+                info!(processing_id = %processing_id, "Abandoned cancelled request");
+                return;
+            }
+        };
 
-        // Process with timeout
-        let processing_timeout = config.processing_timeout();
-        let result = timeout(
-            processing_timeout,
-            processor.validate_request(request.clone()),
-        )
-        .await;
+        let retryable = match &result {
+            Ok(Err(e)) if e.is_retryable() => Some(e),
+            _ => None,
+        };
 
         // Update status with result
-        {
+        let (outcome, finished) = {
             let mut status_map = status_map.write().await;
-            if let Some(record) = status_map.get_mut(&processing_id) {
-                match result {
-                    Ok(Ok(validation_result)) => {
-                        let response = ValidationResponse {
-                            processing_id: processing_id.clone(),
-                            results: validation_result,
-                        };
-                        record.complete_with_result(response);
-                        info!("Completed processing for request: {}", processing_id);
-                    }
-                    Ok(Err(e)) => {
-                        error!("Processing failed for request {}: {}", processing_id, e);
-                        record.fail();
-                    }
-                    Err(_) => {
-                        warn!("Processing timed out for request: {}", processing_id);
-                        record.fail();
+            match (status_map.get_mut(&processing_id), retryable) {
+                // Cancelled after validation finished; the cancellation stands
+                (Some(record), _) if record.status == ProcessingStatus::Cancelled => (None, None),
+                // Transient infrastructure failure with attempts left: queue it again, unless
+                // shutdown has started and the retry would never run
+                (Some(record), Some(e))
+                    if record.attempts <= config.job_max_retries
+                        && !draining.load(Ordering::SeqCst) =>
+                {
+                    warn!(
+                        processing_id = %processing_id,
+                        attempt = record.attempts,
+                        "Processing failed: {}. Retrying",
+                        e
+                    );
+                    record.retry();
+                    let retrying = record.clone();
+                    drop(status_map);
+
+                    Self::persist(store, &processing_id, Some(retrying.clone())).await;
+                    Self::schedule_retry(
+                        request,
+                        config.job_retry_backoff(retrying.attempts),
+                        retrying.cancel_token,
+                        retry_sender.clone(),
+                        draining.clone(),
+                    );
+                    return;
+                }
+                (Some(record), _) => {
+                    match result {
+                        Ok(Ok(validation_result)) => {
+                            let results = if config.emit_legacy_reasons_key {
+                                validation_result.with_legacy_reasons_key()
+                            } else {
+                                validation_result
+                            };
+                            let response = ValidationResponse {
+                                processing_id: processing_id.clone(),
+                                results,
+                            };
+                            record.complete_with_result(response);
+                            rates.completions.record();
+                            info!(processing_id = %processing_id, "Completed processing for request");
+                        }
+                        Ok(Err(e)) => {
+                            error!(processing_id = %processing_id, "Processing failed: {}", e);
+                            record.fail();
+                        }
+                        Err(_) => {
+                            warn!(processing_id = %processing_id, "Processing timed out for request");
+                            record.fail();
+                        }
                     }
+                    (record.result.clone(), Some(record.clone()))
                 }
+                (None, _) => (None, None),
             }
-        }
+        };
+
+        let callback = finished.as_ref().map(|record| CallbackPayload {
+            processing_id: processing_id.clone(),
+            status: record.status.clone(),
+            results: record.result.as_ref().map(|result| result.results.clone()),
+        });
+        Self::persist(store, &processing_id, finished).await;
+        Self::notify_waiter(completion_waiters, &processing_id, outcome).await;
 
-        // Add delay for throttling
-        let throttle_interval = Duration::from_secs(60) / config.throttle_requests_per_minute;
-        sleep(throttle_interval).await;
+        if let (Some(callback_url), Some(payload)) = (request.callback_url, callback) {
+            Self::send_callback(config, callback_url, payload);
+        }
     }
 
-    async fn cleanup_task(
-        status_map: Arc<RwLock<HashMap<String, ProcessingRecord>>>,
-        timeout: Duration,
+    /// Marks a request whose processing panicked as failed, notifying its waiter and
+    /// callback as for any other failure
+    async fn fail_panicked_request(
+        processing_id: &str,
+        callback_url: Option<String>,
+        config: &Config,
+        status_map: &Arc<RwLock<HashMap<String, ProcessingRecord>>>,
+        completion_waiters: &CompletionWaiters,
+        store: &Option<RecordStore>,
     ) {
-        info!("Cleanup task started");
-
-        let mut cleanup_interval = tokio::time::interval(Duration::from_secs(300)); // Every 5 minutes
-
-        loop {
-            cleanup_interval.tick().await;
+        error!(processing_id = %processing_id, "Processing panicked; marking the request failed");
 
+        let failed = {
             let mut status_map = status_map.write().await;
-            let initial_count = status_map.len();
-
-            // Remove expired records
-            status_map.retain(|id, record| {
-                if record.is_expired(timeout) {
-                    debug!("Removing expired record: {}", id);
-                    false
-                } else {
-                    true
+            match status_map.get_mut(processing_id) {
+                Some(record) if !record.is_finished() => {
+                    record.fail();
+                    Some(record.clone())
                 }
-            });
-
-            let removed_count = initial_count - status_map.len();
-            if removed_count > 0 {
-                info!("Cleaned up {} expired records", removed_count);
+                _ => None,
             }
+        };
+        let Some(record) = failed else {
+            return;
+        };
+
+        let callback = CallbackPayload {
+            processing_id: processing_id.to_string(),
+            status: record.status.clone(),
+            results: None,
+        };
+        Self::persist(store, processing_id, Some(record)).await;
+        Self::notify_waiter(completion_waiters, processing_id, None).await;
+
+        if let Some(callback_url) = callback_url {
+            Self::send_callback(config, callback_url, callback);
         }
     }
 
-    pub async fn get_queue_stats(&self) -> QueueStats {
-        let status_map = self.status_map.read().await;
+    /// Re-submits a request to the worker after `backoff`, unless it is cancelled first
+    ///
+    /// Once shutdown has started the worker no longer takes requests, so the retry is
+    /// dropped and the request left for [`ProcessingQueue::fail_unfinished`].
+    fn schedule_retry(
+        request: ProcessingRequest,
+        backoff: Duration,
+        cancel_token: CancellationToken,
+        sender: mpsc::Sender<QueueItem>,
+        draining: Arc<AtomicBool>,
+    ) {
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = sleep(backoff) => {}
+                _ = cancel_token.cancelled() => return,
+            }
 
-        let mut stats = QueueStats::default();
+            let processing_id = request.processing_id.clone();
+            if draining.load(Ordering::SeqCst) {
+                warn!(processing_id = %processing_id, "Shutting down; not retrying request");
+                return;
+            }
+            metrics::record_enqueued();
+            if sender
+                .send(QueueItem::ValidationRequest(Box::new(request)))
+                .await
+                .is_err()
+            {
+                metrics::record_dequeued();
+                warn!(processing_id = %processing_id, "Queue closed before retrying request");
+            }
+        });
+    }
 
-        for record in status_map.values() {
+    /// Delivers the outcome to the request's callback URL in the background
+    ///
+    /// Retries can take a while, so delivery does not hold up the worker.
+    fn send_callback(config: &Config, callback_url: String, payload: CallbackPayload) {
+        let client = WebhookClient::new(config.callback_timeout());
+        tokio::spawn(async move {
+            if let Err(e) = client.deliver(&callback_url, &payload).await {
+                error!(
+                    processing_id = %payload.processing_id,
+                    "Failed to deliver callback to {}: {}",
+                    callback_url,
+                    e
+                );
+            }
+        });
+    }
+
+    /// Wakes a synchronous caller waiting on this request, if any
+    async fn notify_waiter(
+        completion_waiters: &CompletionWaiters,
+        processing_id: &str,
+        outcome: Option<ValidationResponse>,
+    ) {
+        if let Some(waiter) = completion_waiters.lock().await.remove(processing_id) {
+            // The caller may have given up already; nothing to do in that case
+            let _ = waiter.send(outcome);
+        }
+    }
+
+    /// Writes the record through to the persistent store, if one is configured
+    ///
+    /// Failures are logged rather than propagated: the in-memory state stays
+    /// authoritative for the running process.
+    async fn persist(
+        store: &Option<RecordStore>,
+        processing_id: &str,
+        record: Option<ProcessingRecord>,
+    ) {
+        if let (Some(store), Some(record)) = (store, record) {
+            if let Err(e) = store.save(processing_id, &record).await {
+                warn!(processing_id = %processing_id, "Failed to persist record: {}", e);
+            }
+        }
+    }
+
+    /// Removes records past their processing timeout or retention, returning their ids
+    fn evict_expired(
+        status_map: &mut HashMap<String, ProcessingRecord>,
+        timeout: Duration,
+        retention: Duration,
+    ) -> Vec<String> {
+        let mut expired = Vec::new();
+        status_map.retain(|id, record| {
+            if record.is_evictable(timeout, retention) {
+                debug!(processing_id = %id, "Removing expired record");
+                expired.push(id.clone());
+                false
+            } else {
+                true
+            }
+        });
+        expired
+    }
+
+    async fn cleanup_task(
+        status_map: Arc<RwLock<HashMap<String, ProcessingRecord>>>,
+        timeout: Duration,
+        retention: Duration,
+        store: Option<RecordStore>,
+        idempotency_keys: IdempotencyKeys,
+        idempotency_key_ttl: Duration,
+    ) {
+        info!("Cleanup task started");
+
+        let mut cleanup_interval = tokio::time::interval(Duration::from_secs(300)); // Every 5 minutes
+
+        loop {
+            cleanup_interval.tick().await;
+
+            let expired = Self::evict_expired(&mut *status_map.write().await, timeout, retention);
+
+            if let Some(store) = &store {
+                for id in &expired {
+                    if let Err(e) = store.remove(id).await {
+                        warn!(processing_id = %id, "Failed to remove persisted record: {}", e);
+                    }
+                }
+            }
+
+            if !expired.is_empty() {
+                info!("Cleaned up {} expired records", expired.len());
+            }
+
+            idempotency_keys.lock().await.retain(|_, (id, claimed_at)| {
+                claimed_at.elapsed() < idempotency_key_ttl && !expired.contains(id)
+            });
+        }
+    }
+
+    /// Snapshot of the queue internals for `GET /admin/queue`
+    pub async fn get_queue_snapshot(&self) -> QueueSnapshot {
+        let mut snapshot = QueueSnapshot {
+            queue_depth: self.sender.max_capacity() - self.sender.capacity(),
+            queue_capacity: self.sender.max_capacity(),
+            worker: WorkerStatus {
+                // The worker drops the receiving end when it stops
+                running: !self.sender.is_closed(),
+                concurrency: self.config().worker_concurrency,
+            },
+            available_tokens: self.throttle.available(),
+            ..QueueSnapshot::default()
+        };
+
+        let status_map = self.status_map.read().await;
+        for record in status_map.values() {
+            match record.status {
+                ProcessingStatus::Accepted => {
+                    snapshot.accepted += 1;
+                    let age_ms = elapsed_ms(record.submitted_at);
+                    snapshot.oldest_accepted_age_ms =
+                        snapshot.oldest_accepted_age_ms.max(Some(age_ms));
+                }
+                ProcessingStatus::InProgress => {
+                    snapshot.in_progress += 1;
+                    let age_ms = elapsed_ms(record.started_at.unwrap_or(record.submitted_at));
+                    snapshot.oldest_in_progress_age_ms =
+                        snapshot.oldest_in_progress_age_ms.max(Some(age_ms));
+                }
+                _ => {}
+            }
+        }
+
+        snapshot
+    }
+
+    /// Drops every accepted and in-progress request, returning how many were dropped
+    ///
+    /// Flushed requests are cancelled, so queued ones are skipped by the worker and
+    /// in-flight ones are abandoned, and then forgotten as if they had never been
+    /// submitted. Finished requests keep their results.
+    pub async fn flush(&self) -> usize {
+        let mut flushed = Vec::new();
+        self.status_map.write().await.retain(|id, record| {
+            if record.is_finished() {
+                return true;
+            }
+            record.cancel_token.cancel();
+            record.finished.notify_waiters();
+            flushed.push(id.clone());
+            false
+        });
+
+        self.idempotency_keys
+            .lock()
+            .await
+            .retain(|_, (id, _)| !flushed.contains(id));
+        for processing_id in &flushed {
+            if let Some(store) = &self.store {
+                if let Err(e) = store.remove(processing_id).await {
+                    warn!(processing_id = %processing_id, "Failed to remove persisted record: {}", e);
+                }
+            }
+            Self::notify_waiter(&self.completion_waiters, processing_id, None).await;
+        }
+
+        warn!(
+            "Flushed {} unfinished requests from the queue",
+            flushed.len()
+        );
+        flushed.len()
+    }
+
+    pub async fn get_queue_stats(&self) -> QueueStats {
+        let status_map = self.status_map.read().await;
+
+        let mut stats = QueueStats::default();
+
+        for record in status_map.values() {
             match record.status {
                 ProcessingStatus::Accepted => stats.accepted += 1,
                 ProcessingStatus::InProgress => stats.in_progress += 1,
                 ProcessingStatus::Completed => stats.completed += 1,
                 ProcessingStatus::Failed => stats.failed += 1,
+                ProcessingStatus::Cancelled => stats.cancelled += 1,
                 ProcessingStatus::NotFound => {} // Should not happen in the map
             }
         }
 
         stats.total = status_map.len();
-        stats.available_permits = self.throttle_semaphore.available_permits();
+        stats.available_tokens = self.throttle.available();
+        stats.throttle_requests_per_minute = self.throttle.rate_per_minute();
+        stats.llm_circuit = self.circuit_breaker.state();
+        stats.worker_restarts = self.worker_restarts.load(Ordering::Relaxed);
+        stats.seen_images = self.seen_images.len();
+        stats.submission_rate = self.rates.submissions.rates();
+        stats.completion_rate = self.rates.completions.rates();
 
         stats
     }
 }
 
-#[derive(Debug, Default, serde::Serialize)]
+#[derive(Debug, Default, serde::Serialize, utoipa::ToSchema)]
 pub struct QueueStats {
     pub total: usize,
     pub accepted: usize,
     pub in_progress: usize,
     pub completed: usize,
     pub failed: usize,
-    pub available_permits: usize,
+    pub cancelled: usize,
+    /// Requests that can start right away before throttling kicks in
+    pub available_tokens: usize,
+    /// Current throttle rate, which follows `THROTTLE_REQUESTS_PER_MINUTE` on reload
+    pub throttle_requests_per_minute: u32,
+    /// State of the circuit breaker guarding LLM calls
+    pub llm_circuit: CircuitState,
+    /// Times the worker recovered from a panic, failing the request it was processing
+    pub worker_restarts: u64,
+    /// Image hashes remembered for duplicate detection
+    pub seen_images: usize,
+    /// Requests submitted per minute over the last 1, 5 and 15 minutes
+    pub submission_rate: WindowRates,
+    /// Requests completed with results per minute over the last 1, 5 and 15 minutes
+    pub completion_rate: WindowRates,
+}
+
+/// Milliseconds since `instant`, saturating rather than wrapping
+fn elapsed_ms(instant: Instant) -> u64 {
+    u64::try_from(instant.elapsed().as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Queue internals reported by `GET /admin/queue`
+#[derive(Debug, Default, serde::Serialize, utoipa::ToSchema)]
+pub struct QueueSnapshot {
+    /// Requests waiting in the channel for a worker slot
+    pub queue_depth: usize,
+    /// `QUEUE_SIZE`, the most requests the channel holds
+    pub queue_capacity: usize,
+    pub accepted: usize,
+    pub in_progress: usize,
+    /// How long the oldest accepted request has waited since submission
+    pub oldest_accepted_age_ms: Option<u64>,
+    /// How long the oldest in-progress request has run since its attempt started
+    pub oldest_in_progress_age_ms: Option<u64>,
+    pub worker: WorkerStatus,
+    /// Requests that can start right away before throttling kicks in
+    pub available_tokens: usize,
+}
+
+#[derive(Debug, Default, serde::Serialize, utoipa::ToSchema)]
+pub struct WorkerStatus {
+    /// Whether the worker is still taking requests off the queue
+    pub running: bool,
+    /// `WORKER_CONCURRENCY`, the most requests processed at once
+    pub concurrency: usize,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -320,6 +1543,12 @@ pub enum QueueError {
     QueueClosed,
     #[error("Request not found")]
     NotFound,
+    #[error("Request already finished")]
+    AlreadyFinished,
+    #[error("Request not finished yet")]
+    NotFinished,
+    #[error("Original request no longer available")]
+    RequestUnavailable,
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -328,6 +1557,9 @@ pub enum QueueError {
 mod tests {
     use super::*;
     use crate::models::{Resolution, ValidationResults};
+    use crate::storage::StorageError;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicU32, Ordering};
 
     #[tokio::test]
     async fn test_processing_record_lifecycle() {
@@ -339,13 +1571,16 @@ mod tests {
         record.start_processing();
         assert_eq!(record.status, ProcessingStatus::InProgress);
         assert!(record.started_at.is_some());
+        assert_eq!(record.attempts, 1);
+
+        record.retry();
+        assert_eq!(record.status, ProcessingStatus::Accepted);
+        record.start_processing();
+        assert_eq!(record.attempts, 2);
 
         let response = ValidationResponse {
             processing_id: "test".to_string(),
-            results: ValidationResults {
-                resolution: Resolution::Accepted,
-                reasons: None,
-            },
+            results: ValidationResults::accepted(),
         };
 
         record.complete_with_result(response);
@@ -354,6 +1589,36 @@ mod tests {
         assert!(record.result.is_some());
     }
 
+    #[test]
+    fn test_processing_record_cancel() {
+        let mut record = ProcessingRecord::new();
+        let token = record.cancel_token.clone();
+
+        record.cancel();
+        assert_eq!(record.status, ProcessingStatus::Cancelled);
+        assert!(record.completed_at.is_some());
+        assert!(token.is_cancelled());
+        assert!(record.is_finished());
+    }
+
+    #[tokio::test]
+    async fn test_processing_record_notifies_when_finished() {
+        let mut record = ProcessingRecord::new();
+        let finished = record.finished.clone();
+        let notified = finished.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        record.start_processing();
+        assert!(!record.is_finished());
+
+        record.fail();
+        assert!(record.is_finished());
+        timeout(Duration::from_secs(1), notified)
+            .await
+            .expect("waiter was not notified");
+    }
+
     #[tokio::test]
     async fn test_processing_record_expiration() {
         let record = ProcessingRecord::new();
@@ -366,6 +1631,992 @@ mod tests {
         assert!(record.is_expired(Duration::from_millis(1)));
     }
 
+    #[test]
+    fn test_completed_records_are_kept_for_retention() {
+        let processing_timeout = Duration::from_secs(5);
+        let retention = Duration::from_secs(60);
+        let ago = |seconds: u64| Instant::now() - Duration::from_secs(seconds);
+
+        // Submitted 10 seconds ago and finished right away: past the processing
+        // timeout, but well within retention
+        let mut completed = ProcessingRecord::new();
+        completed.complete_with_result(ValidationResponse {
+            processing_id: "completed".to_string(),
+            results: ValidationResults::accepted(),
+        });
+        completed.submitted_at = ago(10);
+        completed.completed_at = Some(ago(10));
+
+        let mut failed = ProcessingRecord::new();
+        failed.fail();
+        failed.submitted_at = ago(90);
+        failed.completed_at = Some(ago(90));
+
+        let mut stuck = ProcessingRecord::new();
+        stuck.start_processing();
+        stuck.submitted_at = ago(10);
+
+        let mut status_map = HashMap::from([
+            ("completed".to_string(), completed),
+            ("failed".to_string(), failed),
+            ("stuck".to_string(), stuck),
+        ]);
+
+        let expired =
+            ProcessingQueue::evict_expired(&mut status_map, processing_timeout, retention);
+        assert_eq!(expired.len(), 2);
+        assert!(status_map.contains_key("completed"));
+
+        // Once retention has passed the completed record goes too
+        let completed = status_map.get_mut("completed").unwrap();
+        completed.completed_at = Some(ago(61));
+        let expired =
+            ProcessingQueue::evict_expired(&mut status_map, processing_timeout, retention);
+        assert_eq!(expired, vec!["completed".to_string()]);
+        assert!(status_map.is_empty());
+    }
+
+    fn submission(content: &str) -> ProcessingRequest {
+        ProcessingRequest::from_request(
+            serde_json::from_value(serde_json::json!({
+                "image-path": "/tmp/test.jpg",
+                "analysis-request": { "content": content }
+            }))
+            .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_await_idle_returns_once_job_completes() {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            ..Config::default()
+        };
+        let queue = ProcessingQueue::with_validator(&config, Arc::new(SlowValidator::default()));
+
+        // Nothing running: returns immediately
+        assert_eq!(queue.await_idle(Duration::from_secs(5)).await, 0);
+
+        // A job that finishes after 200ms
+        queue
+            .submit_validation(submission("Three birds on a wire"))
+            .await
+            .unwrap();
+        queue.shutdown().await;
+
+        let started = Instant::now();
+        assert_eq!(queue.await_idle(Duration::from_secs(10)).await, 0);
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(200));
+        assert!(
+            elapsed < Duration::from_secs(2),
+            "drained after {elapsed:?}"
+        );
+        assert_eq!(queue.get_queue_stats().await.completed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_await_idle_reports_abandoned_jobs() {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            throttle_requests_per_minute: 1,
+            worker_concurrency: 2,
+            ..Config::default()
+        };
+        let queue = ProcessingQueue::with_validator(&config, Arc::new(StalledValidator));
+
+        // One job stalls in progress, one waits for a throttle token and one stays queued
+        let mut ids = Vec::new();
+        for content in ["first", "second", "third"] {
+            let request = submission(content);
+            ids.push(request.processing_id.clone());
+            queue.submit_validation(request).await.unwrap();
+        }
+        queue.shutdown().await;
+
+        assert_eq!(queue.await_idle(Duration::from_millis(250)).await, 3);
+        assert_eq!(
+            queue.get_status(&ids[0]).await,
+            ProcessingStatus::InProgress
+        );
+        assert_eq!(queue.get_status(&ids[1]).await, ProcessingStatus::Accepted);
+
+        assert_eq!(queue.fail_unfinished().await, 3);
+        for processing_id in &ids {
+            assert_eq!(
+                queue.get_status(processing_id).await,
+                ProcessingStatus::Failed
+            );
+            assert_eq!(
+                queue.get_failure_reason(processing_id).await.as_deref(),
+                Some(SHUTDOWN_ABANDONED_REASON)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drops_pending_retries() {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            job_max_retries: 2,
+            job_retry_backoff_seconds: 1,
+            ..Config::default()
+        };
+        let validator = FlakyValidator::new(u32::MAX, transient_error);
+        let queue = ProcessingQueue::with_validator(&config, validator.clone());
+
+        let request = submission("Three birds on a wire");
+        let processing_id = request.processing_id.clone();
+        queue.submit_validation(request).await.unwrap();
+        // Wait for the first attempt to fail and its retry to be scheduled
+        while queue.get_attempts(&processing_id).await == 0
+            || queue.get_status(&processing_id).await != ProcessingStatus::Accepted
+        {
+            sleep(Duration::from_millis(10)).await;
+        }
+        queue.shutdown().await;
+
+        // The retry waiting out its backoff is not waited for, and never runs
+        assert_eq!(queue.await_idle(Duration::from_secs(5)).await, 0);
+        sleep(Duration::from_millis(1500)).await;
+        assert_eq!(validator.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            queue.get_status(&processing_id).await,
+            ProcessingStatus::Accepted
+        );
+
+        assert_eq!(queue.fail_unfinished().await, 1);
+        assert_eq!(
+            queue.get_status(&processing_id).await,
+            ProcessingStatus::Failed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_short_processing_ids_avoid_tracked_ids() {
+        use crate::models::IdAlphabet;
+
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            ..Config::default()
+        };
+        let queue = ProcessingQueue::new(&config);
+        assert_eq!(queue.new_processing_id().await.unwrap().len(), 36);
+
+        // With one-character ids, only "z" is still free
+        let queue = ProcessingQueue::new(&Config {
+            processing_id_length: Some(1),
+            processing_id_alphabet: IdAlphabet::Base58,
+            ..config
+        });
+        {
+            let mut status_map = queue.status_map.write().await;
+            for &c in IdAlphabet::Base58.chars().iter().filter(|&&c| c != b'z') {
+                status_map.insert((c as char).to_string(), ProcessingRecord::new());
+            }
+        }
+        // Each attempt hits the free id with probability 1/58, so most calls give up
+        let mut found = 0;
+        for _ in 0..200 {
+            match queue.new_processing_id().await {
+                Ok(processing_id) => {
+                    assert_eq!(processing_id, "z");
+                    found += 1;
+                }
+                Err(e) => assert!(matches!(e, QueueError::Internal(_))),
+            }
+        }
+        assert!(found < 200);
+
+        queue
+            .status_map
+            .write()
+            .await
+            .insert("z".to_string(), ProcessingRecord::new());
+        assert!(matches!(
+            queue.new_processing_id().await,
+            Err(QueueError::Internal(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_submission_refuses_tracked_processing_id() {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            ..Config::default()
+        };
+        let queue = ProcessingQueue::with_validator(&config, Arc::new(StalledValidator));
+
+        let request = |content: &str| {
+            ProcessingRequest::with_id(
+                serde_json::from_value(serde_json::json!({
+                    "image-path": "/tmp/test.jpg",
+                    "analysis-request": { "content": content }
+                }))
+                .unwrap(),
+                "abc".to_string(),
+            )
+        };
+        queue.submit_validation(request("first")).await.unwrap();
+
+        // Two submissions that drew the same short id must not share a record
+        assert!(matches!(
+            queue.submit_validation(request("second")).await,
+            Err(QueueError::Internal(_))
+        ));
+        assert!(matches!(
+            queue.try_submit_validation(request("third")).await,
+            Err(QueueError::Internal(_))
+        ));
+        let status_map = queue.status_map.read().await;
+        let tracked = status_map["abc"].request.as_ref().unwrap();
+        assert_eq!(tracked.analysis_request.content, "first".into());
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_keys() {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            ..Config::default()
+        };
+        let queue = ProcessingQueue::new(&config);
+        queue
+            .status_map
+            .write()
+            .await
+            .insert("first".to_string(), ProcessingRecord::new());
+
+        assert_eq!(queue.claim_idempotency_key("key", "first").await, None);
+        assert_eq!(
+            queue.claim_idempotency_key("key", "second").await,
+            Some(("first".to_string(), ProcessingStatus::Accepted))
+        );
+
+        // Releasing only applies to the id the key was claimed for
+        queue.release_idempotency_key("key", "second").await;
+        assert!(queue.claim_idempotency_key("key", "third").await.is_some());
+        queue.release_idempotency_key("key", "first").await;
+        assert_eq!(queue.claim_idempotency_key("key", "fourth").await, None);
+
+        // A key stays claimed while its request is being queued, before it is tracked
+        assert_eq!(
+            queue.claim_idempotency_key("key", "fifth").await,
+            Some(("fourth".to_string(), ProcessingStatus::Accepted))
+        );
+
+        // Expired keys are claimed afresh
+        let queue = ProcessingQueue::new(&Config {
+            idempotency_key_ttl_minutes: 0,
+            ..config
+        });
+        queue
+            .status_map
+            .write()
+            .await
+            .insert("first".to_string(), ProcessingRecord::new());
+        assert_eq!(queue.claim_idempotency_key("key", "first").await, None);
+        assert_eq!(queue.claim_idempotency_key("key", "second").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_list_jobs_filters_sorts_and_paginates() {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            ..Config::default()
+        };
+        let queue = ProcessingQueue::new(&config);
+
+        {
+            let mut status_map = queue.status_map.write().await;
+            let now = Instant::now();
+            for (age_secs, id) in [(40, "a"), (30, "b"), (20, "c"), (10, "d")] {
+                let mut record = ProcessingRecord::new();
+                record.submitted_at = now - Duration::from_secs(age_secs);
+                if id == "b" || id == "d" {
+                    record.fail();
+                }
+                status_map.insert(id.to_string(), record);
+            }
+        }
+
+        let ids = |jobs: Vec<JobSummary>| -> Vec<String> {
+            jobs.into_iter().map(|job| job.processing_id).collect()
+        };
+
+        let (jobs, total) = queue.list_jobs(None, 50, 0).await;
+        assert_eq!(total, 4);
+        assert!(jobs[0].submitted_at_ms > jobs[1].submitted_at_ms);
+        assert_eq!(ids(jobs), ["d", "c", "b", "a"]);
+
+        let (jobs, total) = queue.list_jobs(None, 2, 1).await;
+        assert_eq!(total, 4);
+        assert_eq!(ids(jobs), ["c", "b"]);
+
+        let (jobs, total) = queue
+            .list_jobs(Some(&ProcessingStatus::Failed), 50, 0)
+            .await;
+        assert_eq!(total, 2);
+        assert_eq!(ids(jobs), ["d", "b"]);
+
+        let (jobs, total) = queue.list_jobs(None, 50, 10).await;
+        assert_eq!(total, 4);
+        assert!(jobs.is_empty());
+    }
+
+    /// Fails the first `failures` calls with the error `make_error` builds, then accepts
+    struct FlakyValidator {
+        failures: u32,
+        make_error: fn() -> ProcessorError,
+        calls: AtomicU32,
+    }
+
+    impl FlakyValidator {
+        fn new(failures: u32, make_error: fn() -> ProcessorError) -> Arc<Self> {
+            Arc::new(Self {
+                failures,
+                make_error,
+                calls: AtomicU32::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl RequestValidator for FlakyValidator {
+        async fn validate_request(
+            &self,
+            _request: ProcessingRequest,
+            _progress: &Progress,
+        ) -> Result<ValidationResults, ProcessorError> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                Err((self.make_error)())
+            } else {
+                Ok(ValidationResults::accepted())
+            }
+        }
+    }
+
+    /// Panics on the first call, then accepts
+    #[derive(Default)]
+    struct PanickingValidator {
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl RequestValidator for PanickingValidator {
+        async fn validate_request(
+            &self,
+            _request: ProcessingRequest,
+            _progress: &Progress,
+        ) -> Result<ValidationResults, ProcessorError> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                panic!("validator bug");
+            }
+            Ok(ValidationResults::accepted())
+        }
+    }
+
+    /// Never completes a validation
+    struct StalledValidator;
+
+    #[async_trait]
+    impl RequestValidator for StalledValidator {
+        async fn validate_request(
+            &self,
+            _request: ProcessingRequest,
+            _progress: &Progress,
+        ) -> Result<ValidationResults, ProcessorError> {
+            std::future::pending().await
+        }
+    }
+
+    /// Reports a finished content check, then never completes
+    struct HalfDoneValidator;
+
+    #[async_trait]
+    impl RequestValidator for HalfDoneValidator {
+        async fn validate_request(
+            &self,
+            _request: ProcessingRequest,
+            progress: &Progress,
+        ) -> Result<ValidationResults, ProcessorError> {
+            progress.update(|partial| {
+                partial.content = Some(true);
+                partial.content_confidence = Some(0.9);
+            });
+            std::future::pending().await
+        }
+    }
+
+    /// Accepts after a short delay, tracking how many validations overlap
+    #[derive(Default)]
+    struct SlowValidator {
+        running: AtomicU32,
+        max_running: AtomicU32,
+    }
+
+    #[async_trait]
+    impl RequestValidator for SlowValidator {
+        async fn validate_request(
+            &self,
+            _request: ProcessingRequest,
+            _progress: &Progress,
+        ) -> Result<ValidationResults, ProcessorError> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(running, Ordering::SeqCst);
+            sleep(Duration::from_millis(200)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(ValidationResults::accepted())
+        }
+    }
+
+    fn transient_error() -> ProcessorError {
+        ProcessorError::Storage(StorageError::Io(std::io::Error::new(
+            std::io::ErrorKind::ConnectionReset,
+            "connection reset",
+        )))
+    }
+
+    async fn run_with_validator(validator: Arc<dyn RequestValidator>) -> (ProcessingQueue, String) {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            job_max_retries: 2,
+            job_retry_backoff_seconds: 0,
+            ..Config::default()
+        };
+        let queue = ProcessingQueue::with_validator(&config, validator);
+
+        let request = ProcessingRequest::from_request(
+            serde_json::from_value(serde_json::json!({
+                "image-path": "/tmp/test.jpg",
+                "analysis-request": { "content": "Three birds on a wire" }
+            }))
+            .unwrap(),
+        );
+        let processing_id = request.processing_id.clone();
+        queue.submit_validation(request).await.unwrap();
+        queue
+            .wait_for_completion(&processing_id, Duration::from_secs(5))
+            .await;
+
+        (queue, processing_id)
+    }
+
+    #[tokio::test]
+    async fn test_transient_failure_is_retried() {
+        let validator = FlakyValidator::new(1, transient_error);
+        let (queue, processing_id) = run_with_validator(validator.clone()).await;
+
+        assert_eq!(
+            queue.get_status(&processing_id).await,
+            ProcessingStatus::Completed
+        );
+        assert_eq!(queue.get_attempts(&processing_id).await, 2);
+        assert_eq!(validator.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_failure_is_not_retried() {
+        let validator = FlakyValidator::new(1, || {
+            ProcessorError::ImageNotFound("/tmp/test.jpg".to_string())
+        });
+        let (queue, processing_id) = run_with_validator(validator.clone()).await;
+
+        assert_eq!(
+            queue.get_status(&processing_id).await,
+            ProcessingStatus::Failed
+        );
+        assert_eq!(queue.get_attempts(&processing_id).await, 1);
+        assert_eq!(validator.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retries_are_bounded() {
+        let validator = FlakyValidator::new(u32::MAX, transient_error);
+        let (queue, processing_id) = run_with_validator(validator.clone()).await;
+
+        // The first attempt plus JOB_MAX_RETRIES retries
+        assert_eq!(
+            queue.get_status(&processing_id).await,
+            ProcessingStatus::Failed
+        );
+        assert_eq!(queue.get_attempts(&processing_id).await, 3);
+        assert_eq!(validator.calls.load(Ordering::SeqCst), 3);
+    }
+
+    /// Mock OpenAI-compatible LLM that answers 500 to the first `failures` calls, then
+    /// accepts every image
+    async fn spawn_flaky_llm(failures: u32) -> (String, Arc<AtomicU32>) {
+        use axum::{http::StatusCode, response::IntoResponse, routing::post, Json, Router};
+
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let app = Router::new().fallback(post(move || {
+            let counter = counter.clone();
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                    return (StatusCode::INTERNAL_SERVER_ERROR, "model crashed").into_response();
+                }
+                Json(serde_json::json!({
+                    "choices": [{ "message": { "content": "ACCEPTED: birds\nSCORE: 0.9" } }]
+                }))
+                .into_response()
+            }
+        }));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{addr}/v1/chat/completions"), calls)
+    }
+
+    #[tokio::test]
+    async fn test_transient_llm_failure_is_retried_by_processor() {
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use exif::Tag;
+
+        let (llm_api_url, calls) = spawn_flaky_llm(1).await;
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url,
+            llm_max_retries: 1,
+            job_max_retries: 2,
+            job_retry_backoff_seconds: 0,
+            throttle_requests_per_minute: 6000,
+            ..Config::default()
+        };
+        // The real processor, so the LLM error has to make it out of validation
+        let queue = ProcessingQueue::new(&config);
+
+        let image = write_jpeg_with_exif(&[ascii_field(Tag::ImageDescription, "birds")]);
+        let request = ProcessingRequest::from_request(
+            serde_json::from_value(serde_json::json!({
+                "image-path": image.path(),
+                "analysis-request": { "content": "Three birds on a wire" }
+            }))
+            .unwrap(),
+        );
+        let processing_id = request.processing_id.clone();
+        queue.submit_validation(request).await.unwrap();
+
+        let status = queue
+            .wait_for_completion(&processing_id, Duration::from_secs(10))
+            .await;
+        assert_eq!(status, ProcessingStatus::Completed);
+        assert_eq!(queue.get_attempts(&processing_id).await, 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        let response = queue.get_result(&processing_id).await.unwrap();
+        assert_eq!(response.results.resolution, Resolution::Accepted);
+    }
+
+    #[tokio::test]
+    async fn test_panicking_validator_fails_job_and_worker_recovers() {
+        let validator = Arc::new(PanickingValidator::default());
+        let (queue, processing_id) = run_with_validator(validator.clone()).await;
+
+        // Panics are not retried, since they would most likely panic again
+        assert_eq!(
+            queue.get_status(&processing_id).await,
+            ProcessingStatus::Failed
+        );
+        assert_eq!(queue.get_attempts(&processing_id).await, 1);
+        assert_eq!(queue.get_queue_stats().await.worker_restarts, 1);
+
+        let request = ProcessingRequest::from_request(
+            serde_json::from_value(serde_json::json!({
+                "image-path": "/tmp/test.jpg",
+                "analysis-request": { "content": "Three birds on a wire" }
+            }))
+            .unwrap(),
+        );
+        let next_id = request.processing_id.clone();
+        queue.submit_validation(request).await.unwrap();
+        queue
+            .wait_for_completion(&next_id, Duration::from_secs(5))
+            .await;
+
+        assert_eq!(
+            queue.get_status(&next_id).await,
+            ProcessingStatus::Completed
+        );
+        assert_eq!(validator.calls.load(Ordering::SeqCst), 2);
+        assert!(queue.get_queue_snapshot().await.worker.running);
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_overrides_processing_timeout() {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            processing_timeout_minutes: 5,
+            ..Config::default()
+        };
+        let queue = ProcessingQueue::with_validator(&config, Arc::new(StalledValidator));
+
+        let request = ProcessingRequest::from_request(
+            serde_json::from_value(serde_json::json!({
+                "image-path": "/tmp/test.jpg",
+                "analysis-request": { "content": "Three birds on a wire" },
+                "timeout-seconds": 1
+            }))
+            .unwrap(),
+        );
+        let processing_id = request.processing_id.clone();
+        queue.submit_validation(request).await.unwrap();
+
+        // Fails after a second rather than the global five minutes
+        let status = queue
+            .wait_for_completion(&processing_id, Duration::from_secs(5))
+            .await;
+        assert_eq!(status, ProcessingStatus::Failed);
+        assert_eq!(queue.get_attempts(&processing_id).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_passed_deadline_fails_without_processing() {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            ..Config::default()
+        };
+        // Would leave the request in progress if it were ever called
+        let queue = ProcessingQueue::with_validator(&config, Arc::new(StalledValidator));
+
+        let request = ProcessingRequest::from_request(
+            serde_json::from_value(serde_json::json!({
+                "image-path": "/tmp/test.jpg",
+                "analysis-request": { "content": "Three birds on a wire" }
+            }))
+            .unwrap(),
+        )
+        .with_deadline(Some(SystemTime::now() - Duration::from_secs(1)));
+        let processing_id = request.processing_id.clone();
+        queue.submit_validation(request).await.unwrap();
+
+        let status = queue
+            .wait_for_completion(&processing_id, Duration::from_secs(5))
+            .await;
+        assert_eq!(status, ProcessingStatus::Failed);
+        assert_eq!(queue.get_attempts(&processing_id).await, 0);
+        assert_eq!(
+            queue.get_failure_reason(&processing_id).await.as_deref(),
+            Some(DEADLINE_EXCEEDED_REASON)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rerun_drops_passed_deadline() {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            ..Config::default()
+        };
+        let validator = FlakyValidator::new(0, transient_error);
+        let queue = ProcessingQueue::with_validator(&config, validator.clone());
+
+        let request = ProcessingRequest::from_request(
+            serde_json::from_value(serde_json::json!({
+                "image-path": "/tmp/test.jpg",
+                "analysis-request": { "content": "Three birds on a wire" }
+            }))
+            .unwrap(),
+        )
+        .with_deadline(Some(SystemTime::now() - Duration::from_secs(1)));
+        let processing_id = request.processing_id.clone();
+        queue.submit_validation(request).await.unwrap();
+        let status = queue
+            .wait_for_completion(&processing_id, Duration::from_secs(5))
+            .await;
+        assert_eq!(status, ProcessingStatus::Failed);
+
+        queue.rerun(&processing_id, None).await.unwrap();
+        let status = queue
+            .wait_for_completion(&processing_id, Duration::from_secs(5))
+            .await;
+        assert_eq!(status, ProcessingStatus::Completed);
+        assert_eq!(validator.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(queue.get_failure_reason(&processing_id).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_near_deadline_clamps_processing_timeout() {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            processing_timeout_minutes: 5,
+            ..Config::default()
+        };
+        let queue = ProcessingQueue::with_validator(&config, Arc::new(StalledValidator));
+
+        let request = ProcessingRequest::from_request(
+            serde_json::from_value(serde_json::json!({
+                "image-path": "/tmp/test.jpg",
+                "analysis-request": { "content": "Three birds on a wire" },
+                "timeout-seconds": 60
+            }))
+            .unwrap(),
+        )
+        .with_deadline(Some(SystemTime::now() + Duration::from_millis(500)));
+        let processing_id = request.processing_id.clone();
+        let submitted = Instant::now();
+        queue.submit_validation(request).await.unwrap();
+
+        // Times out at the deadline rather than after the request's minute
+        let status = queue
+            .wait_for_completion(&processing_id, Duration::from_secs(5))
+            .await;
+        assert_eq!(status, ProcessingStatus::Failed);
+        assert!(submitted.elapsed() < Duration::from_secs(5));
+        assert_eq!(queue.get_attempts(&processing_id).await, 1);
+        assert_eq!(queue.get_failure_reason(&processing_id).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_try_submit_rejects_when_queue_full() {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            queue_size: 1,
+            ..Config::default()
+        };
+        // The worker holds on to the first request forever
+        let queue = ProcessingQueue::with_validator(&config, Arc::new(StalledValidator));
+
+        let request = |path: &str| {
+            ProcessingRequest::from_request(
+                serde_json::from_value(serde_json::json!({
+                    "image-path": path,
+                    "analysis-request": { "content": "Three birds on a wire" }
+                }))
+                .unwrap(),
+            )
+        };
+
+        let first = request("/tmp/first.jpg");
+        let first_id = first.processing_id.clone();
+        queue.try_submit_validation(first).await.unwrap();
+        while queue.get_status(&first_id).await != ProcessingStatus::InProgress {
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        // One slot in the channel, then full
+        queue
+            .try_submit_validation(request("/tmp/second.jpg"))
+            .await
+            .unwrap();
+        let third = request("/tmp/third.jpg");
+        let third_id = third.processing_id.clone();
+        assert!(matches!(
+            queue.try_submit_validation(third).await,
+            Err(QueueError::QueueFull)
+        ));
+        assert_eq!(
+            queue.get_status(&third_id).await,
+            ProcessingStatus::NotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reload_applies_throttle_rate_to_next_job() {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            throttle_requests_per_minute: 1,
+            ..Config::default()
+        };
+        let queue = ProcessingQueue::with_validator(&config, Arc::new(SlowValidator::default()));
+        let submit = || async {
+            let request = ProcessingRequest::from_request(
+                serde_json::from_value(serde_json::json!({
+                    "image-path": "/tmp/test.jpg",
+                    "analysis-request": { "content": "Three birds on a wire" }
+                }))
+                .unwrap(),
+            );
+            let processing_id = request.processing_id.clone();
+            queue.submit_validation(request).await.unwrap();
+            processing_id
+        };
+
+        // The first job takes the only token; the second waits a minute for the next
+        let first = submit().await;
+        queue
+            .wait_for_completion(&first, Duration::from_secs(5))
+            .await;
+        let second = submit().await;
+        sleep(Duration::from_millis(300)).await;
+        assert_eq!(queue.get_status(&second).await, ProcessingStatus::Accepted);
+        assert_eq!(
+            queue.get_queue_stats().await.throttle_requests_per_minute,
+            1
+        );
+
+        let ignored = queue
+            .reload(&Config {
+                throttle_requests_per_minute: 600,
+                llm_model_name: "llava:34b".to_string(),
+                port: config.port + 1,
+                ..config.clone()
+            })
+            .unwrap();
+        assert_eq!(ignored, vec!["PORT"]);
+        assert_eq!(
+            queue.get_queue_stats().await.throttle_requests_per_minute,
+            600
+        );
+        assert_eq!(queue.config().llm_model_name, "llava:34b");
+        assert_eq!(queue.config().port, config.port);
+
+        // At 600 per minute the waiting job gets a token within 100ms
+        queue
+            .wait_for_completion(&second, Duration::from_secs(2))
+            .await;
+        assert_eq!(queue.get_status(&second).await, ProcessingStatus::Completed);
+
+        // An unusable prompt template keeps the running configuration
+        let error = queue
+            .reload(&Config {
+                throttle_requests_per_minute: 5,
+                prompt_template_path: Some("/nonexistent/prompt.txt".to_string()),
+                ..config.clone()
+            })
+            .unwrap_err();
+        assert!(error.to_string().contains("Prompt template"), "{error}");
+        assert_eq!(
+            queue.get_queue_stats().await.throttle_requests_per_minute,
+            600
+        );
+    }
+
+    #[tokio::test]
+    async fn test_worker_concurrency_overlaps_jobs() {
+        for (worker_concurrency, expected_overlap) in [(1, 1), (3, 3)] {
+            let config = Config {
+                image_base_dir: "/tmp".to_string(),
+                llm_api_url: "http://localhost:8080".to_string(),
+                worker_concurrency,
+                ..Config::default()
+            };
+            let validator = Arc::new(SlowValidator::default());
+            let queue = ProcessingQueue::with_validator(&config, validator.clone());
+
+            let mut processing_ids = Vec::new();
+            for _ in 0..3 {
+                let request = ProcessingRequest::from_request(
+                    serde_json::from_value(serde_json::json!({
+                        "image-path": "/tmp/test.jpg",
+                        "analysis-request": { "content": "Three birds on a wire" }
+                    }))
+                    .unwrap(),
+                );
+                processing_ids.push(request.processing_id.clone());
+                queue.submit_validation(request).await.unwrap();
+            }
+
+            for processing_id in &processing_ids {
+                assert_eq!(
+                    queue
+                        .wait_for_completion(processing_id, Duration::from_secs(5))
+                        .await,
+                    ProcessingStatus::Completed
+                );
+            }
+            assert_eq!(
+                validator.max_running.load(Ordering::SeqCst),
+                expected_overlap
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_partial_results_while_in_progress() {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            ..Config::default()
+        };
+        let queue = ProcessingQueue::with_validator(&config, Arc::new(HalfDoneValidator));
+
+        let request = ProcessingRequest::from_request(
+            serde_json::from_value(serde_json::json!({
+                "image-path": "/tmp/test.jpg",
+                "analysis-request": { "content": "Three birds on a wire" }
+            }))
+            .unwrap(),
+        );
+        let processing_id = request.processing_id.clone();
+        queue.submit_validation(request).await.unwrap();
+
+        let mut partial = None;
+        for _ in 0..100 {
+            partial = queue
+                .get_partial_results(&processing_id)
+                .await
+                .filter(|partial| partial.content.is_some());
+            if partial.is_some() {
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        let partial = partial.expect("content check was never reported");
+        assert_eq!(partial.content, Some(true));
+        assert_eq!(partial.content_confidence, Some(0.9));
+        assert_eq!(partial.location, None);
+        assert!(queue.get_partial_results("unknown").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stats_report_rolling_rates() {
+        let config = Config {
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            worker_concurrency: 6,
+            throttle_requests_per_minute: 6000,
+            ..Config::default()
+        };
+        let queue = ProcessingQueue::with_validator(&config, Arc::new(SlowValidator::default()));
+        assert_eq!(
+            queue.get_queue_stats().await.submission_rate,
+            WindowRates::default()
+        );
+
+        let mut processing_ids = Vec::new();
+        for _ in 0..6 {
+            let request = ProcessingRequest::from_request(
+                serde_json::from_value(serde_json::json!({
+                    "image-path": "/tmp/test.jpg",
+                    "analysis-request": { "content": "Three birds on a wire" }
+                }))
+                .unwrap(),
+            );
+            processing_ids.push(request.processing_id.clone());
+            queue.submit_validation(request).await.unwrap();
+        }
+
+        // The burst counts in full over a minute and is spread over the longer windows
+        let stats = queue.get_queue_stats().await;
+        assert_eq!(stats.submission_rate.one_minute, 6.0);
+        assert_eq!(stats.submission_rate.five_minutes, 1.2);
+        assert_eq!(stats.submission_rate.fifteen_minutes, 0.4);
+
+        for processing_id in &processing_ids {
+            queue
+                .wait_for_completion(processing_id, Duration::from_secs(5))
+                .await;
+        }
+        let stats = queue.get_queue_stats().await;
+        assert_eq!(stats.completion_rate.one_minute, 6.0);
+        assert_eq!(stats.submission_rate.one_minute, 6.0);
+    }
+
     // Integration tests with full queue processing should be in tests/ directory
     // as they require more complex setup and coordination
 }
@@ -0,0 +1,137 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+/// Events remembered per counter; beyond this the oldest are dropped, so rates above
+/// roughly 6,600 per minute are under-reported over the 15-minute window
+const MAX_EVENTS: usize = 100_000;
+
+/// The longest window reported; older events are forgotten
+const LONGEST_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// Events per minute, averaged over the last 1, 5 and 15 minutes
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, ToSchema)]
+pub struct WindowRates {
+    #[serde(rename = "1m")]
+    pub one_minute: f64,
+    #[serde(rename = "5m")]
+    pub five_minutes: f64,
+    #[serde(rename = "15m")]
+    pub fifteen_minutes: f64,
+}
+
+/// Timestamps of recent events, kept in a bounded ring buffer, from which rolling
+/// rates are computed
+///
+/// Unlike the records in the queue, events are not evicted with their request, so
+/// the rates keep reflecting throughput however short `RESULT_RETENTION_MINUTES` is.
+#[derive(Debug, Default)]
+pub struct RollingRate {
+    events: Mutex<VecDeque<Instant>>,
+}
+
+impl RollingRate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records an event happening now
+    pub fn record(&self) {
+        self.record_at(Instant::now());
+    }
+
+    fn record_at(&self, at: Instant) {
+        let mut events = self.events.lock().expect("rolling rate lock poisoned");
+        Self::forget_expired(&mut events, at);
+        if events.len() >= MAX_EVENTS {
+            events.pop_front();
+        }
+        events.push_back(at);
+    }
+
+    /// Events per minute over each window ending now
+    pub fn rates(&self) -> WindowRates {
+        self.rates_at(Instant::now())
+    }
+
+    fn rates_at(&self, now: Instant) -> WindowRates {
+        let mut events = self.events.lock().expect("rolling rate lock poisoned");
+        Self::forget_expired(&mut events, now);
+
+        let per_minute = |minutes: u64| {
+            let window = Duration::from_secs(minutes * 60);
+            let count = events
+                .iter()
+                .rev()
+                .take_while(|event| now.saturating_duration_since(**event) <= window)
+                .count();
+            count as f64 / minutes as f64
+        };
+        WindowRates {
+            one_minute: per_minute(1),
+            five_minutes: per_minute(5),
+            fifteen_minutes: per_minute(15),
+        }
+    }
+
+    fn forget_expired(events: &mut VecDeque<Instant>, now: Instant) {
+        while events
+            .front()
+            .is_some_and(|event| now.saturating_duration_since(*event) > LONGEST_WINDOW)
+        {
+            events.pop_front();
+        }
+    }
+}
+
+/// Rolling submission and completion rates reported in the queue stats
+#[derive(Debug, Default)]
+pub struct QueueRates {
+    /// Requests accepted into the queue
+    pub submissions: RollingRate,
+    /// Requests that finished processing with results
+    pub completions: RollingRate,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolling_rates_over_windows() {
+        let rate = RollingRate::new();
+        assert_eq!(rate.rates(), WindowRates::default());
+
+        let now = Instant::now() + Duration::from_secs(20 * 60);
+        // Older than the longest window, so forgotten
+        rate.record_at(now - Duration::from_secs(16 * 60));
+        // Three minutes ago only counts towards the 5 and 15-minute windows
+        for _ in 0..15 {
+            rate.record_at(now - Duration::from_secs(3 * 60));
+        }
+        for _ in 0..30 {
+            rate.record_at(now);
+        }
+
+        let rates = rate.rates_at(now);
+        assert_eq!(rates.one_minute, 30.0);
+        assert_eq!(rates.five_minutes, 9.0);
+        assert_eq!(rates.fifteen_minutes, 3.0);
+        assert_eq!(rate.events.lock().unwrap().len(), 45);
+    }
+
+    #[test]
+    fn test_window_rates_serialization() {
+        let rates = WindowRates {
+            one_minute: 12.0,
+            five_minutes: 4.5,
+            fifteen_minutes: 1.5,
+        };
+        assert_eq!(
+            serde_json::to_value(rates).unwrap(),
+            serde_json::json!({ "1m": 12.0, "5m": 4.5, "15m": 1.5 })
+        );
+    }
+}
@@ -0,0 +1,354 @@
+use crate::config::LlmBackend;
+use reqwest::{Client, StatusCode, Url};
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+/// How long a probe may take before the LLM counts as unreachable
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long a probe result is reused before the LLM is probed again
+pub const PROBE_CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// How long the startup model check waits for the model list
+pub const MODEL_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Checks that the LLM API answers at all, for `GET /health/ready`
+///
+/// The probe is a `HEAD` request to `LLM_API_URL`. Any response below 500 counts as
+/// reachable, since chat endpoints typically reject `HEAD` with 404 or 405; connection
+/// failures, timeouts and server errors do not. Results are cached for a short while,
+/// and concurrent checks wait for a single probe, so load balancers polling every
+/// instance do not add load on the backend.
+pub struct LlmProbe {
+    client: Client,
+    url: String,
+    ttl: Duration,
+    last: Mutex<Option<(Instant, Result<(), String>)>>,
+}
+
+impl LlmProbe {
+    pub fn new(url: String, timeout: Duration, ttl: Duration) -> Self {
+        let client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            url,
+            ttl,
+            last: Mutex::new(None),
+        }
+    }
+
+    /// Returns why the LLM is unreachable, or `Ok` when it answered
+    pub async fn check(&self) -> Result<(), String> {
+        let mut last = self.last.lock().await;
+        if let Some((probed_at, result)) = last.as_ref() {
+            if probed_at.elapsed() < self.ttl {
+                return result.clone();
+            }
+        }
+
+        let result = self.probe().await;
+        match &result {
+            Ok(()) => debug!("LLM API at {} is reachable", self.url),
+            Err(e) => warn!("LLM API at {} is unreachable: {}", self.url, e),
+        }
+        *last = Some((Instant::now(), result.clone()));
+        result
+    }
+
+    async fn probe(&self) -> Result<(), String> {
+        let response = self
+            .client
+            .head(&self.url)
+            .send()
+            .await
+            .map_err(|e| format!("request failed: {e}"))?;
+
+        let status = response.status();
+        if status.is_server_error() {
+            return Err(format!("LLM API returned {status}"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ModelCheckError {
+    #[error("Invalid LLM API URL: {0}")]
+    InvalidUrl(String),
+    #[error("Listing models at {url} failed: {source}")]
+    Request { url: String, source: reqwest::Error },
+    #[error("Listing models at {url} returned {status}")]
+    Status { url: String, status: StatusCode },
+    #[error("Model '{model}' is not available at {url}; available models: {}", available.join(", "))]
+    Missing {
+        model: String,
+        url: String,
+        available: Vec<String>,
+    },
+}
+
+/// Model list of Ollama's `/api/tags`
+#[derive(Deserialize)]
+struct OllamaTags {
+    models: Vec<OllamaModel>,
+}
+
+#[derive(Deserialize)]
+struct OllamaModel {
+    name: String,
+}
+
+/// Model list of OpenAI-compatible `/v1/models`
+#[derive(Deserialize)]
+struct OpenAiModels {
+    data: Vec<OpenAiModel>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiModel {
+    id: String,
+}
+
+/// The endpoint listing the models served next to the chat endpoint `api_url`
+///
+/// Ollama lists them at `/api/tags`, OpenAI-compatible APIs at `/v1/models`. A path
+/// prefix in front of the chat endpoint, e.g. from a reverse proxy, is kept.
+pub fn models_url(api_url: &str, backend: LlmBackend) -> Result<Url, ModelCheckError> {
+    let mut url = Url::parse(api_url).map_err(|e| ModelCheckError::InvalidUrl(e.to_string()))?;
+    let (chat_path, models_path) = match backend {
+        LlmBackend::OpenAi => ("/chat/completions", "/models"),
+        LlmBackend::Ollama => ("/api/chat", "/api/tags"),
+    };
+    let path = match url.path().strip_suffix(chat_path) {
+        Some(prefix) => format!("{prefix}{models_path}"),
+        None if backend == LlmBackend::OpenAi => "/v1/models".to_string(),
+        None => models_path.to_string(),
+    };
+    url.set_path(&path);
+    url.set_query(None);
+    Ok(url)
+}
+
+/// Checks at startup that the backend at `api_url` serves `model`, so a misspelled
+/// `LLM_MODEL_NAME` fails fast instead of failing every job
+///
+/// A model named without a tag also matches its `:latest` tag, as Ollama lists it.
+pub async fn check_model_available(
+    api_url: &str,
+    backend: LlmBackend,
+    model: &str,
+    timeout: Duration,
+) -> Result<(), ModelCheckError> {
+    let url = models_url(api_url, backend)?;
+    let request_error = |source| ModelCheckError::Request {
+        url: url.to_string(),
+        source,
+    };
+
+    let client = Client::builder()
+        .timeout(timeout)
+        .build()
+        .expect("Failed to create HTTP client");
+    let response = client
+        .get(url.clone())
+        .send()
+        .await
+        .map_err(request_error)?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ModelCheckError::Status {
+            url: url.to_string(),
+            status,
+        });
+    }
+
+    let available: Vec<String> = match backend {
+        LlmBackend::OpenAi => response
+            .json::<OpenAiModels>()
+            .await
+            .map_err(request_error)?
+            .data
+            .into_iter()
+            .map(|model| model.id)
+            .collect(),
+        LlmBackend::Ollama => response
+            .json::<OllamaTags>()
+            .await
+            .map_err(request_error)?
+            .models
+            .into_iter()
+            .map(|model| model.name)
+            .collect(),
+    };
+
+    let latest = (!model.contains(':')).then(|| format!("{model}:latest"));
+    if available
+        .iter()
+        .any(|name| name == model || Some(name) == latest.as_ref())
+    {
+        return Ok(());
+    }
+    Err(ModelCheckError::Missing {
+        model: model.to_string(),
+        url: url.to_string(),
+        available,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use std::sync::atomic::{AtomicU16, AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    /// Serves every request with the status in `status`, counting requests
+    async fn spawn_llm(status: Arc<AtomicU16>) -> (String, Arc<AtomicU32>) {
+        let hits = Arc::new(AtomicU32::new(0));
+        let counter = hits.clone();
+        let app = axum::Router::new().fallback(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let status = StatusCode::from_u16(status.load(Ordering::SeqCst)).unwrap();
+            async move { status }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{addr}/v1/chat/completions"), hits)
+    }
+
+    #[tokio::test]
+    async fn test_probe_result_is_cached() {
+        let status = Arc::new(AtomicU16::new(405));
+        let (url, hits) = spawn_llm(status.clone()).await;
+        let probe = LlmProbe::new(url, PROBE_TIMEOUT, Duration::from_secs(60));
+
+        assert!(probe.check().await.is_ok());
+        status.store(503, Ordering::SeqCst);
+        assert!(probe.check().await.is_ok());
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_and_expired_cache() {
+        let status = Arc::new(AtomicU16::new(503));
+        let (url, hits) = spawn_llm(status.clone()).await;
+        let probe = LlmProbe::new(url, PROBE_TIMEOUT, Duration::ZERO);
+
+        let error = probe.check().await.unwrap_err();
+        assert!(error.contains("503"), "{error}");
+
+        status.store(200, Ordering::SeqCst);
+        assert!(probe.check().await.is_ok());
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    /// Serves `body` as the model list of both backends
+    async fn spawn_model_list(body: serde_json::Value) -> String {
+        let app = axum::Router::new().fallback(move |uri: axum::http::Uri| {
+            let body = body.clone();
+            async move {
+                match uri.path() {
+                    "/api/tags" | "/v1/models" => Ok(axum::Json(body)),
+                    _ => Err(StatusCode::NOT_FOUND),
+                }
+            }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    #[test]
+    fn test_models_url() {
+        let url = |api_url, backend| models_url(api_url, backend).unwrap().to_string();
+        assert_eq!(
+            url(
+                "http://localhost:11434/v1/chat/completions",
+                LlmBackend::OpenAi
+            ),
+            "http://localhost:11434/v1/models"
+        );
+        assert_eq!(
+            url(
+                "https://gateway.example.com/llm/v1/chat/completions?key=1",
+                LlmBackend::OpenAi
+            ),
+            "https://gateway.example.com/llm/v1/models"
+        );
+        assert_eq!(
+            url("http://localhost:11434/api/chat", LlmBackend::Ollama),
+            "http://localhost:11434/api/tags"
+        );
+        assert_eq!(
+            url("http://localhost:11434/", LlmBackend::Ollama),
+            "http://localhost:11434/api/tags"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_model_available_ollama() {
+        let server = spawn_model_list(serde_json::json!({
+            "models": [{ "name": "llava:7b" }, { "name": "bakllava:latest" }]
+        }))
+        .await;
+        let api_url = format!("{server}/api/chat");
+        let check =
+            |model| check_model_available(&api_url, LlmBackend::Ollama, model, PROBE_TIMEOUT);
+
+        assert!(check("llava:7b").await.is_ok());
+        assert!(check("bakllava").await.is_ok());
+
+        let error = check("llava:7bb").await.unwrap_err();
+        assert!(
+            matches!(&error, ModelCheckError::Missing { available, .. } if available.len() == 2)
+        );
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Model 'llava:7bb' is not available at {server}/api/tags; \
+                 available models: llava:7b, bakllava:latest"
+            )
+        );
+        assert!(check("llava").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_check_model_available_openai() {
+        let server = spawn_model_list(serde_json::json!({
+            "object": "list",
+            "data": [{ "id": "gpt-4o", "object": "model" }]
+        }))
+        .await;
+        let api_url = format!("{server}/v1/chat/completions");
+        let check =
+            |model| check_model_available(&api_url, LlmBackend::OpenAi, model, PROBE_TIMEOUT);
+
+        assert!(check("gpt-4o").await.is_ok());
+        assert!(matches!(
+            check("gpt-40").await,
+            Err(ModelCheckError::Missing { .. })
+        ));
+
+        // A backend without a model list fails the check rather than passing silently
+        let error = check_model_available(
+            &format!("{server}/proxy/v1/chat/completions"),
+            LlmBackend::OpenAi,
+            "gpt-4o",
+            PROBE_TIMEOUT,
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(error, ModelCheckError::Status { status, .. } if status == reqwest::StatusCode::NOT_FOUND)
+        );
+    }
+}
@@ -1,4 +1,9 @@
+use object_store::aws::AmazonS3Builder;
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::ObjectStore;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -9,15 +14,20 @@ pub enum StorageError {
     InvalidUri(String),
     #[error("Path does not exist: {0}")]
     PathNotFound(String),
+    #[error("Object of {size} bytes exceeds the {limit} byte limit")]
+    TooLarge { size: u64, limit: usize },
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("Object store error: {0}")]
+    ObjectStore(#[from] object_store::Error),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum StorageUri {
     LocalPath(PathBuf),
     FileUri(PathBuf),
-    // Future: S3Uri(String), GcsUri(String), etc.
+    S3Uri { bucket: String, key_prefix: String },
+    GcsUri { bucket: String, prefix: String },
 }
 
 impl StorageUri {
@@ -25,8 +35,13 @@ impl StorageUri {
     ///
     /// # Examples
     /// ```rust
+    /// use image_checker::storage::StorageUri;
+    ///
     /// let uri = StorageUri::parse("/tmp/images")?;
     /// let file_uri = StorageUri::parse("file:///tmp/images")?;
+    /// let s3_uri = StorageUri::parse("s3://my-bucket/images")?;
+    /// let gcs_uri = StorageUri::parse("gs://my-bucket/images")?;
+    /// # Ok::<(), image_checker::storage::StorageError>(())
     /// ```
     pub fn parse(uri: &str) -> Result<Self, StorageError> {
         if let Some(path_str) = uri.strip_prefix("file://") {
@@ -39,6 +54,32 @@ impl StorageUri {
             }
 
             Ok(StorageUri::FileUri(PathBuf::from(path_str)))
+        } else if let Some(location) = uri.strip_prefix("s3://") {
+            // Handle s3://bucket/key/prefix URI
+            let (bucket, key_prefix) = location.split_once('/').unwrap_or((location, ""));
+            if bucket.is_empty() {
+                return Err(StorageError::InvalidUri(format!(
+                    "s3:// URI must include a bucket name: {uri}"
+                )));
+            }
+
+            Ok(StorageUri::S3Uri {
+                bucket: bucket.to_string(),
+                key_prefix: key_prefix.trim_matches('/').to_string(),
+            })
+        } else if let Some(location) = uri.strip_prefix("gs://") {
+            // Handle gs://bucket/object/prefix URI
+            let (bucket, prefix) = location.split_once('/').unwrap_or((location, ""));
+            if bucket.is_empty() {
+                return Err(StorageError::InvalidUri(format!(
+                    "gs:// URI must include a bucket name: {uri}"
+                )));
+            }
+
+            Ok(StorageUri::GcsUri {
+                bucket: bucket.to_string(),
+                prefix: prefix.trim_matches('/').to_string(),
+            })
         } else if uri.contains("://") {
             // Other URI schemes - extract scheme name for better error reporting
             let scheme = uri
@@ -52,23 +93,34 @@ impl StorageUri {
         }
     }
 
-    /// Returns the local filesystem path for this URI as a string slice
-    /// Maintains backward compatibility by returning &str
+    /// Returns the local filesystem path for this URI as a string slice,
+    /// or `None` for remote storage that has no local representation
     #[must_use]
-    pub fn to_local_path(&self) -> &str {
+    pub fn to_local_path(&self) -> Option<&str> {
         match self {
             StorageUri::LocalPath(path) | StorageUri::FileUri(path) => {
                 // This is safe because we construct PathBuf from valid UTF-8 strings
-                path.to_str().expect("Path should be valid UTF-8")
+                Some(path.to_str().expect("Path should be valid UTF-8"))
             }
+            StorageUri::S3Uri { .. } | StorageUri::GcsUri { .. } => None,
         }
     }
 
+    /// Returns true when the URI points at remote object storage
+    #[must_use]
+    pub fn is_remote(&self) -> bool {
+        matches!(self, StorageUri::S3Uri { .. } | StorageUri::GcsUri { .. })
+    }
+
     /// Checks if the path exists on the filesystem
+    ///
+    /// Remote storage cannot be checked synchronously, so it is assumed to exist
+    /// and any missing object is reported when it is fetched.
     #[must_use]
     pub fn exists(&self) -> bool {
         match self {
             StorageUri::LocalPath(path) | StorageUri::FileUri(path) => path.exists(),
+            StorageUri::S3Uri { .. } | StorageUri::GcsUri { .. } => true,
         }
     }
 
@@ -77,39 +129,216 @@ impl StorageUri {
     ///
     /// # Examples
     /// ```rust
+    /// use image_checker::storage::StorageUri;
+    ///
     /// let uri = StorageUri::parse("/tmp/images")?;
     /// assert_eq!(uri.resolve_relative_path("test.jpg"), "/tmp/images/test.jpg");
     /// assert_eq!(uri.resolve_relative_path("/absolute/path.jpg"), "/absolute/path.jpg");
+    ///
+    /// let s3_uri = StorageUri::parse("s3://my-bucket/images")?;
+    /// assert_eq!(s3_uri.resolve_relative_path("test.jpg"), "s3://my-bucket/images/test.jpg");
+    /// # Ok::<(), image_checker::storage::StorageError>(())
     /// ```
     #[must_use]
     pub fn resolve_relative_path(&self, relative_path: &str) -> String {
         if relative_path.starts_with('/') {
             // Absolute path - return as-is
-            relative_path.to_string()
-        } else {
-            // Relative path - join with base using PathBuf for correctness
-            // then convert back to String for compatibility
-            let base_path = match self {
-                StorageUri::LocalPath(path) | StorageUri::FileUri(path) => path,
-            };
-
-            let resolved = base_path.join(relative_path);
-            resolved.to_string_lossy().into_owned()
+            return relative_path.to_string();
+        }
+
+        match self {
+            StorageUri::LocalPath(path) | StorageUri::FileUri(path) => {
+                // Relative path - join with base using PathBuf for correctness
+                // then convert back to String for compatibility
+                let resolved = path.join(relative_path);
+                resolved.to_string_lossy().into_owned()
+            }
+            StorageUri::S3Uri { bucket, key_prefix } => {
+                format!(
+                    "s3://{bucket}/{}",
+                    join_object_key(key_prefix, relative_path)
+                )
+            }
+            StorageUri::GcsUri { bucket, prefix } => {
+                format!("gs://{bucket}/{}", join_object_key(prefix, relative_path))
+            }
+        }
+    }
+
+    /// Checks that the storage location can be reached with the configured credentials
+    ///
+    /// Local paths must exist; for buckets, one listing request under the prefix is made.
+    pub async fn verify(&self) -> Result<(), StorageError> {
+        match self {
+            StorageUri::LocalPath(path) | StorageUri::FileUri(path) => {
+                if path.exists() {
+                    Ok(())
+                } else {
+                    Err(StorageError::PathNotFound(path.display().to_string()))
+                }
+            }
+            StorageUri::S3Uri { .. } | StorageUri::GcsUri { .. } => {
+                let prefix = self.as_path().to_string_lossy();
+                let prefix = (!prefix.is_empty()).then(|| object_store::path::Path::from(&*prefix));
+                self.object_store()?
+                    .list_with_delimiter(prefix.as_ref())
+                    .await?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Builds a client for the bucket behind a remote URI
+    fn object_store(&self) -> Result<Arc<dyn ObjectStore>, StorageError> {
+        match self {
+            StorageUri::S3Uri { bucket, .. } => Ok(Arc::new(
+                AmazonS3Builder::from_env()
+                    .with_bucket_name(bucket)
+                    .build()?,
+            )),
+            StorageUri::GcsUri { bucket, .. } => Ok(Arc::new(
+                GoogleCloudStorageBuilder::from_env()
+                    .with_bucket_name(bucket)
+                    .build()?,
+            )),
+            StorageUri::LocalPath(_) | StorageUri::FileUri(_) => Err(StorageError::InvalidUri(
+                format!("{self} is not an object store URI"),
+            )),
+        }
+    }
+
+    /// Identifies the bucket behind a remote URI, e.g. `s3://my-bucket`
+    fn bucket_uri(&self) -> Option<String> {
+        match self {
+            StorageUri::S3Uri { bucket, .. } => Some(format!("s3://{bucket}")),
+            StorageUri::GcsUri { bucket, .. } => Some(format!("gs://{bucket}")),
+            StorageUri::LocalPath(_) | StorageUri::FileUri(_) => None,
         }
     }
 
-    /// Internal method to get the PathBuf
+    /// Internal method to get the path component
+    ///
+    /// For remote URIs this is the object key prefix.
     #[must_use]
-    fn as_path_buf(&self) -> &PathBuf {
+    fn as_path(&self) -> &Path {
         match self {
-            StorageUri::LocalPath(path) | StorageUri::FileUri(path) => path,
+            StorageUri::LocalPath(path) | StorageUri::FileUri(path) => path.as_path(),
+            StorageUri::S3Uri { key_prefix, .. } => Path::new(key_prefix),
+            StorageUri::GcsUri { prefix, .. } => Path::new(prefix),
+        }
+    }
+}
+
+/// Fetches objects from storage URIs, refusing any larger than `max_bytes`
+///
+/// Bucket clients are built on first use and kept for later fetches, since building one
+/// reads credentials from the environment. S3 objects are downloaded using credentials
+/// from the standard AWS environment variables (`AWS_ACCESS_KEY_ID`,
+/// `AWS_SECRET_ACCESS_KEY`, `AWS_REGION`, ...) and GCS objects using
+/// `GOOGLE_APPLICATION_CREDENTIALS` or other `GOOGLE_*` variables.
+pub struct StorageFetcher {
+    max_bytes: usize,
+    /// Clients by bucket URI, e.g. `s3://my-bucket`
+    stores: Mutex<HashMap<String, Arc<dyn ObjectStore>>>,
+}
+
+impl StorageFetcher {
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            stores: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Fetches the bytes of an object relative to `uri`
+    ///
+    /// An empty `relative` path fetches the URI itself. Local paths are read from the
+    /// filesystem. The size is checked before anything is read, with a `head` request
+    /// for bucket objects.
+    pub async fn fetch(&self, uri: &StorageUri, relative: &str) -> Result<Vec<u8>, StorageError> {
+        match uri {
+            StorageUri::LocalPath(path) | StorageUri::FileUri(path) => {
+                let full_path = if relative.is_empty() {
+                    path.clone()
+                } else {
+                    PathBuf::from(uri.resolve_relative_path(relative))
+                };
+                let not_found = |e: std::io::Error| {
+                    if e.kind() == std::io::ErrorKind::NotFound {
+                        StorageError::PathNotFound(full_path.display().to_string())
+                    } else {
+                        StorageError::Io(e)
+                    }
+                };
+
+                let size = tokio::fs::metadata(&full_path)
+                    .await
+                    .map_err(not_found)?
+                    .len();
+                self.check_size(size)?;
+                tokio::fs::read(&full_path).await.map_err(not_found)
+            }
+            StorageUri::S3Uri { .. } | StorageUri::GcsUri { .. } => {
+                let store = self.store(uri)?;
+                let key = join_object_key(&uri.as_path().to_string_lossy(), relative);
+                let location = object_store::path::Path::from(key.as_str());
+                let not_found = |e| match e {
+                    object_store::Error::NotFound { .. } => {
+                        StorageError::PathNotFound(uri.resolve_relative_path(relative))
+                    }
+                    other => StorageError::ObjectStore(other),
+                };
+
+                let meta = store.head(&location).await.map_err(not_found)?;
+                self.check_size(meta.size as u64)?;
+                let object = store.get(&location).await.map_err(not_found)?;
+                Ok(object.bytes().await?.to_vec())
+            }
+        }
+    }
+
+    fn check_size(&self, size: u64) -> Result<(), StorageError> {
+        if size > self.max_bytes as u64 {
+            return Err(StorageError::TooLarge {
+                size,
+                limit: self.max_bytes,
+            });
+        }
+        Ok(())
+    }
+
+    /// The client for the bucket behind a remote URI, built on first use
+    fn store(&self, uri: &StorageUri) -> Result<Arc<dyn ObjectStore>, StorageError> {
+        let bucket_uri = uri
+            .bucket_uri()
+            .ok_or_else(|| StorageError::InvalidUri(format!("{uri} is not an object store URI")))?;
+        let mut stores = self
+            .stores
+            .lock()
+            .expect("object store cache lock poisoned");
+        if let Some(store) = stores.get(&bucket_uri) {
+            return Ok(store.clone());
         }
+
+        let store = uri.object_store()?;
+        stores.insert(bucket_uri, store.clone());
+        Ok(store)
+    }
+}
+
+/// Joins an object key prefix and a relative key with a single `/`
+fn join_object_key(prefix: &str, relative: &str) -> String {
+    let relative = relative.trim_start_matches('/');
+    match (prefix.is_empty(), relative.is_empty()) {
+        (true, _) => relative.to_string(),
+        (false, true) => prefix.to_string(),
+        (false, false) => format!("{prefix}/{relative}"),
     }
 }
 
 impl AsRef<Path> for StorageUri {
     fn as_ref(&self) -> &Path {
-        self.as_path_buf().as_path()
+        self.as_path()
     }
 }
 
@@ -118,6 +347,14 @@ impl std::fmt::Display for StorageUri {
         match self {
             StorageUri::LocalPath(path) => write!(f, "{}", path.display()),
             StorageUri::FileUri(path) => write!(f, "file://{}", path.display()),
+            StorageUri::S3Uri { bucket, key_prefix } if key_prefix.is_empty() => {
+                write!(f, "s3://{bucket}")
+            }
+            StorageUri::S3Uri { bucket, key_prefix } => write!(f, "s3://{bucket}/{key_prefix}"),
+            StorageUri::GcsUri { bucket, prefix } if prefix.is_empty() => {
+                write!(f, "gs://{bucket}")
+            }
+            StorageUri::GcsUri { bucket, prefix } => write!(f, "gs://{bucket}/{prefix}"),
         }
     }
 }
@@ -130,14 +367,14 @@ mod tests {
     fn test_parse_local_path() {
         let uri = StorageUri::parse("/tmp/images").unwrap();
         assert_eq!(uri, StorageUri::LocalPath(PathBuf::from("/tmp/images")));
-        assert_eq!(uri.to_local_path(), "/tmp/images");
+        assert_eq!(uri.to_local_path(), Some("/tmp/images"));
     }
 
     #[test]
     fn test_parse_file_uri() {
         let uri = StorageUri::parse("file:///tmp/images").unwrap();
         assert_eq!(uri, StorageUri::FileUri(PathBuf::from("/tmp/images")));
-        assert_eq!(uri.to_local_path(), "/tmp/images");
+        assert_eq!(uri.to_local_path(), Some("/tmp/images"));
     }
 
     #[test]
@@ -150,7 +387,7 @@ mod tests {
 
     #[test]
     fn test_parse_unsupported_scheme() {
-        let result = StorageUri::parse("s3://bucket/path");
+        let result = StorageUri::parse("ftp://bucket/path");
         assert!(result.is_err());
         assert!(matches!(
             result.unwrap_err(),
@@ -196,14 +433,14 @@ mod tests {
     fn test_parse_empty_string() {
         let uri = StorageUri::parse("").unwrap();
         assert_eq!(uri, StorageUri::LocalPath(PathBuf::from("")));
-        assert_eq!(uri.to_local_path(), "");
+        assert_eq!(uri.to_local_path(), Some(""));
     }
 
     #[test]
     fn test_parse_relative_local_path() {
         let uri = StorageUri::parse("relative/path").unwrap();
         assert_eq!(uri, StorageUri::LocalPath(PathBuf::from("relative/path")));
-        assert_eq!(uri.to_local_path(), "relative/path");
+        assert_eq!(uri.to_local_path(), Some("relative/path"));
     }
 
     #[test]
@@ -222,15 +459,15 @@ mod tests {
         let uri = StorageUri::parse("file:////tmp///images//").unwrap();
         assert_eq!(uri, StorageUri::FileUri(PathBuf::from("//tmp///images//")));
         // PathBuf preserves the exact string
-        assert_eq!(uri.to_local_path(), "//tmp///images//");
+        assert_eq!(uri.to_local_path(), Some("//tmp///images//"));
     }
 
     #[test]
     fn test_unsupported_scheme_error_message() {
-        let result = StorageUri::parse("s3://bucket/path");
+        let result = StorageUri::parse("ftp://bucket/path");
         match result {
             Err(StorageError::UnsupportedScheme(scheme)) => {
-                assert_eq!(scheme, "s3");
+                assert_eq!(scheme, "ftp");
             }
             _ => panic!("Expected UnsupportedScheme error"),
         }
@@ -354,4 +591,223 @@ mod tests {
             _ => panic!("Expected UnsupportedScheme error"),
         }
     }
+
+    #[test]
+    fn test_parse_s3_uri() {
+        let uri = StorageUri::parse("s3://my-bucket/images/2025").unwrap();
+        assert_eq!(
+            uri,
+            StorageUri::S3Uri {
+                bucket: "my-bucket".to_string(),
+                key_prefix: "images/2025".to_string(),
+            }
+        );
+        assert!(uri.is_remote());
+        assert!(uri.exists());
+        assert_eq!(uri.to_local_path(), None);
+    }
+
+    #[test]
+    fn test_parse_s3_uri_bucket_only() {
+        let uri = StorageUri::parse("s3://my-bucket").unwrap();
+        assert_eq!(
+            uri,
+            StorageUri::S3Uri {
+                bucket: "my-bucket".to_string(),
+                key_prefix: String::new(),
+            }
+        );
+
+        // Trailing slashes are normalized away
+        let uri = StorageUri::parse("s3://my-bucket/images/").unwrap();
+        assert_eq!(
+            uri,
+            StorageUri::S3Uri {
+                bucket: "my-bucket".to_string(),
+                key_prefix: "images".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_s3_uri_missing_bucket() {
+        let result = StorageUri::parse("s3:///images");
+        match result {
+            Err(StorageError::InvalidUri(msg)) => {
+                assert!(msg.contains("must include a bucket name"));
+            }
+            _ => panic!("Expected InvalidUri error"),
+        }
+    }
+
+    #[test]
+    fn test_s3_resolve_relative_path() {
+        let uri = StorageUri::parse("s3://my-bucket/images").unwrap();
+        assert_eq!(
+            uri.resolve_relative_path("test.jpg"),
+            "s3://my-bucket/images/test.jpg"
+        );
+
+        let bucket_only = StorageUri::parse("s3://my-bucket").unwrap();
+        assert_eq!(
+            bucket_only.resolve_relative_path("nested/test.jpg"),
+            "s3://my-bucket/nested/test.jpg"
+        );
+
+        // Resolving a full S3 URI round-trips to the same bucket and key
+        let resolved = StorageUri::parse(&uri.resolve_relative_path("test.jpg")).unwrap();
+        assert_eq!(
+            resolved,
+            StorageUri::S3Uri {
+                bucket: "my-bucket".to_string(),
+                key_prefix: "images/test.jpg".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_display_s3_uri() {
+        let uri = StorageUri::parse("s3://my-bucket/images").unwrap();
+        assert_eq!(format!("{}", uri), "s3://my-bucket/images");
+
+        let bucket_only = StorageUri::parse("s3://my-bucket").unwrap();
+        assert_eq!(format!("{}", bucket_only), "s3://my-bucket");
+    }
+
+    #[test]
+    fn test_parse_gcs_uri() {
+        let uri = StorageUri::parse("gs://my-bucket/images/2025/").unwrap();
+        assert_eq!(
+            uri,
+            StorageUri::GcsUri {
+                bucket: "my-bucket".to_string(),
+                prefix: "images/2025".to_string(),
+            }
+        );
+        assert!(uri.is_remote());
+        assert!(uri.exists());
+        assert_eq!(uri.to_local_path(), None);
+
+        let bucket_only = StorageUri::parse("gs://my-bucket").unwrap();
+        assert_eq!(
+            bucket_only,
+            StorageUri::GcsUri {
+                bucket: "my-bucket".to_string(),
+                prefix: String::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_gcs_uri_missing_bucket() {
+        for uri in ["gs://", "gs:///images"] {
+            match StorageUri::parse(uri) {
+                Err(StorageError::InvalidUri(msg)) => {
+                    assert!(msg.contains("gs:// URI must include a bucket name"));
+                }
+                other => panic!("Expected InvalidUri error for {uri}, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_gcs_resolve_relative_path_and_display() {
+        let uri = StorageUri::parse("gs://my-bucket/images").unwrap();
+        assert_eq!(
+            uri.resolve_relative_path("test.jpg"),
+            "gs://my-bucket/images/test.jpg"
+        );
+        assert_eq!(uri.to_string(), "gs://my-bucket/images");
+
+        let bucket_only = StorageUri::parse("gs://my-bucket").unwrap();
+        assert_eq!(
+            bucket_only.resolve_relative_path("nested/test.jpg"),
+            "gs://my-bucket/nested/test.jpg"
+        );
+        assert_eq!(bucket_only.to_string(), "gs://my-bucket");
+    }
+
+    #[tokio::test]
+    async fn test_verify_local_path() {
+        assert!(StorageUri::parse("/tmp").unwrap().verify().await.is_ok());
+        assert!(matches!(
+            StorageUri::parse("/nonexistent/image-checker")
+                .unwrap()
+                .verify()
+                .await,
+            Err(StorageError::PathNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_join_object_key() {
+        assert_eq!(join_object_key("images", "a.jpg"), "images/a.jpg");
+        assert_eq!(join_object_key("", "a.jpg"), "a.jpg");
+        assert_eq!(join_object_key("images", ""), "images");
+        assert_eq!(join_object_key("images", "/a.jpg"), "images/a.jpg");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_local_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("image.jpg"), b"image-bytes").unwrap();
+
+        let uri = StorageUri::parse(dir.path().to_str().unwrap()).unwrap();
+        let fetcher = StorageFetcher::new(1024);
+        let bytes = fetcher.fetch(&uri, "image.jpg").await.unwrap();
+        assert_eq!(bytes, b"image-bytes");
+
+        let missing = fetcher.fetch(&uri, "missing.jpg").await;
+        assert!(matches!(missing, Err(StorageError::PathNotFound(_))));
+
+        let too_large = StorageFetcher::new(4).fetch(&uri, "image.jpg").await;
+        assert!(matches!(
+            too_large,
+            Err(StorageError::TooLarge { size: 11, limit: 4 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_bucket_object_reuses_client() {
+        use object_store::memory::InMemory;
+
+        let store = Arc::new(InMemory::new());
+        store
+            .put(
+                &object_store::path::Path::from("images/image.jpg"),
+                b"image-bytes".to_vec().into(),
+            )
+            .await
+            .unwrap();
+        let fetcher = StorageFetcher::new(1024);
+        fetcher
+            .stores
+            .lock()
+            .unwrap()
+            .insert("s3://my-bucket".to_string(), store);
+
+        // Every URI in the bucket is served by the one client
+        let uri = StorageUri::parse("s3://my-bucket/images").unwrap();
+        let bytes = fetcher.fetch(&uri, "image.jpg").await.unwrap();
+        assert_eq!(bytes, b"image-bytes");
+        let uri = StorageUri::parse("s3://my-bucket/images/image.jpg").unwrap();
+        assert_eq!(fetcher.fetch(&uri, "").await.unwrap(), b"image-bytes");
+        assert_eq!(fetcher.stores.lock().unwrap().len(), 1);
+
+        assert!(matches!(
+            fetcher.fetch(&uri, "missing.jpg").await,
+            Err(StorageError::PathNotFound(_))
+        ));
+
+        let small = StorageFetcher::new(4);
+        small
+            .stores
+            .lock()
+            .unwrap()
+            .insert("s3://my-bucket".to_string(), fetcher.store(&uri).unwrap());
+        assert!(matches!(
+            small.fetch(&uri, "").await,
+            Err(StorageError::TooLarge { size: 11, limit: 4 })
+        ));
+    }
 }
@@ -0,0 +1,222 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::{sleep, Instant};
+
+/// Token-bucket rate limiter for LLM-bound work
+///
+/// The bucket holds up to `capacity` tokens and refills continuously at
+/// `capacity` tokens per minute, so idle periods allow bursts of up to a minute's
+/// worth of requests while the sustained rate never exceeds the configured one.
+/// The rate can be changed while the bucket is in use, e.g. on a config reload.
+#[derive(Debug)]
+pub struct TokenBucket {
+    state: Mutex<BucketState>,
+    /// Wakes waiting callers so they recompute their wait at the new rate
+    rate_changed: Notify,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    capacity: f64,
+    refill_per_second: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    /// Creates a full bucket allowing `requests_per_minute` requests per minute
+    pub fn per_minute(requests_per_minute: u32) -> Self {
+        let capacity = f64::from(requests_per_minute.max(1));
+        Self {
+            state: Mutex::new(BucketState {
+                capacity,
+                refill_per_second: capacity / 60.0,
+                tokens: capacity,
+                refilled_at: Instant::now(),
+            }),
+            rate_changed: Notify::new(),
+        }
+    }
+
+    /// Changes the rate to `requests_per_minute`, keeping the tokens already earned
+    ///
+    /// Waiting callers are woken and wait for the next token at the new rate.
+    pub fn set_rate(&self, requests_per_minute: u32) {
+        let capacity = f64::from(requests_per_minute.max(1));
+        {
+            let mut state = self.state.lock().expect("token bucket lock poisoned");
+            Self::refill(&mut state);
+            state.capacity = capacity;
+            state.refill_per_second = capacity / 60.0;
+            state.tokens = state.tokens.min(capacity);
+        }
+        self.rate_changed.notify_waiters();
+    }
+
+    /// The requests per minute currently allowed
+    pub fn rate_per_minute(&self) -> u32 {
+        self.state
+            .lock()
+            .expect("token bucket lock poisoned")
+            .capacity as u32
+    }
+
+    /// Takes a token, waiting for the bucket to refill if it is empty
+    ///
+    /// Returns how long the caller waited.
+    pub async fn acquire(&self) -> Duration {
+        let started = Instant::now();
+
+        loop {
+            // Created before reading the rate, so a change made meanwhile is not missed
+            let rate_changed = self.rate_changed.notified();
+            let wait = {
+                let mut state = self.state.lock().expect("token bucket lock poisoned");
+                Self::refill(&mut state);
+                if state.tokens >= 1.0 {
+                    state.tokens -= 1.0;
+                    return started.elapsed();
+                }
+                Duration::from_secs_f64((1.0 - state.tokens) / state.refill_per_second)
+            };
+            tokio::select! {
+                _ = sleep(wait) => {}
+                _ = rate_changed => {}
+            }
+        }
+    }
+
+    /// Takes a token if one is available, or returns how long until the next one
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        let mut state = self.state.lock().expect("token bucket lock poisoned");
+        Self::refill(&mut state);
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64(
+            (1.0 - state.tokens) / state.refill_per_second,
+        ))
+    }
+
+    /// Whether the bucket has refilled completely, i.e. behaves like a new one
+    pub fn is_full(&self) -> bool {
+        let mut state = self.state.lock().expect("token bucket lock poisoned");
+        Self::refill(&mut state);
+        state.tokens >= state.capacity
+    }
+
+    /// Whole tokens currently available without waiting
+    pub fn available(&self) -> usize {
+        let mut state = self.state.lock().expect("token bucket lock poisoned");
+        Self::refill(&mut state);
+        state.tokens as usize
+    }
+
+    fn refill(state: &mut BucketState) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.refilled_at).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * state.refill_per_second).min(state.capacity);
+        state.refilled_at = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_burst_up_to_capacity_without_waiting() {
+        let bucket = TokenBucket::per_minute(10);
+        assert_eq!(bucket.available(), 10);
+
+        for _ in 0..10 {
+            assert_eq!(bucket.acquire().await, Duration::ZERO);
+        }
+        assert_eq!(bucket.available(), 0);
+
+        // The next token arrives after 60s / 10
+        assert_eq!(bucket.acquire().await, Duration::from_secs(6));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_burst_rate_converges_to_configured_rate() {
+        let bucket = TokenBucket::per_minute(120);
+        let started = Instant::now();
+
+        // A burst of three minutes' worth of requests: the first 120 pass
+        // immediately, the remaining 240 are paced at 2 per second
+        for _ in 0..360 {
+            bucket.acquire().await;
+        }
+
+        let elapsed = started.elapsed().as_secs_f64();
+        assert!(
+            (elapsed - 120.0).abs() < 1.0,
+            "burst took {elapsed}s, expected about 120s"
+        );
+
+        // Sustained rate over the paced part of the window
+        let paced_started = Instant::now();
+        for _ in 0..60 {
+            bucket.acquire().await;
+        }
+        let per_minute = 60.0 / paced_started.elapsed().as_secs_f64() * 60.0;
+        assert!(
+            (per_minute - 120.0).abs() < 2.0,
+            "observed {per_minute} requests per minute"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_refill_is_capped_at_capacity() {
+        let bucket = TokenBucket::per_minute(6);
+        for _ in 0..6 {
+            bucket.acquire().await;
+        }
+
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert_eq!(bucket.available(), 1);
+
+        tokio::time::advance(Duration::from_secs(3600)).await;
+        assert_eq!(bucket.available(), 6);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_try_acquire_reports_wait() {
+        let bucket = TokenBucket::per_minute(2);
+        assert!(bucket.is_full());
+        assert_eq!(bucket.try_acquire(), Ok(()));
+        assert_eq!(bucket.try_acquire(), Ok(()));
+        assert_eq!(bucket.try_acquire(), Err(Duration::from_secs(30)));
+
+        tokio::time::advance(Duration::from_secs(20)).await;
+        assert_eq!(bucket.try_acquire(), Err(Duration::from_secs(10)));
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert_eq!(bucket.try_acquire(), Ok(()));
+        assert!(!bucket.is_full());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_can_change_while_in_use() {
+        let bucket = std::sync::Arc::new(TokenBucket::per_minute(1));
+        bucket.acquire().await;
+        assert_eq!(bucket.rate_per_minute(), 1);
+
+        // A caller already waiting a minute for the next token gets it after a second
+        let waiting = tokio::spawn({
+            let bucket = bucket.clone();
+            async move { bucket.acquire().await }
+        });
+        tokio::task::yield_now().await;
+        bucket.set_rate(60);
+        assert_eq!(bucket.rate_per_minute(), 60);
+        assert_eq!(waiting.await.unwrap(), Duration::from_secs(1));
+
+        // Lowering the rate caps the tokens already earned
+        tokio::time::advance(Duration::from_secs(3600)).await;
+        bucket.set_rate(5);
+        assert_eq!(bucket.available(), 5);
+    }
+}
@@ -1,5 +1,8 @@
-use crate::models::{DateTimeConstraint, LocationConstraint};
+use crate::models::{
+    AltitudeConstraint, DateTimeConstraint, DimensionsConstraint, DistanceUnit, LocationConstraint,
+};
 use chrono::{DateTime, FixedOffset};
+use std::time::Duration;
 
 const EARTH_RADIUS_KM: f64 = 6371.0;
 const EARTH_RADIUS_M: f64 = EARTH_RADIUS_KM * 1000.0;
@@ -26,6 +29,29 @@ pub fn haversine_distance(coord1: (f64, f64), coord2: (f64, f64)) -> f64 {
     EARTH_RADIUS_M * c
 }
 
+/// Initial compass bearing in degrees (0-360, clockwise from north) of the great-circle
+/// path from `from` to `to`
+pub fn bearing_degrees(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lon1) = from;
+    let (lat2, lon2) = to;
+
+    let lat1_rad = lat1.to_radians();
+    let lat2_rad = lat2.to_radians();
+    let delta_lon = (lon2 - lon1).to_radians();
+
+    let y = delta_lon.sin() * lat2_rad.cos();
+    let x = lat1_rad.cos() * lat2_rad.sin() - lat1_rad.sin() * lat2_rad.cos() * delta_lon.cos();
+
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+/// Map a bearing to an 8-point compass label, each covering 45° centred on its direction
+pub fn compass_point(bearing: f64) -> &'static str {
+    const POINTS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+    let sector = ((bearing.rem_euclid(360.0) + 22.5) / 45.0) as usize % 8;
+    POINTS[sector]
+}
+
 /// Validate if GPS coordinates are within the specified location constraint
 pub fn validate_location(
     actual_coords: (f64, f64),
@@ -44,10 +70,132 @@ pub fn validate_location(
         ));
     }
 
-    let expected_coords = (constraint.latitude, constraint.longitude);
-    let distance = haversine_distance(actual_coords, expected_coords);
+    match constraint {
+        LocationConstraint::Radius {
+            latitude,
+            longitude,
+            max_distance_meters,
+            tolerate_gps_precision,
+            ..
+        } => {
+            let distance = haversine_distance(actual_coords, (*latitude, *longitude));
+            let tolerance = if *tolerate_gps_precision {
+                estimate_gps_precision(actual_coords)
+            } else {
+                0.0
+            };
+            Ok(distance <= max_distance_meters + tolerance)
+        }
+        LocationConstraint::Polygon(vertices) => Ok(point_in_polygon(actual_coords, vertices)),
+        LocationConstraint::Place(name) => Err(format!("Place '{name}' has not been geocoded")),
+    }
+}
+
+/// Test whether a `(lat, long)` point lies inside a polygon using ray casting
+///
+/// Coordinates are treated as planar, which is accurate for areas the size of a
+/// park or property lot; polygons crossing the antimeridian are not supported.
+pub fn point_in_polygon(point: (f64, f64), vertices: &[(f64, f64)]) -> bool {
+    let (lat, lon) = point;
+    let mut inside = false;
+
+    // Count crossings of a ray cast from the point towards increasing longitude
+    let mut previous = match vertices.last() {
+        Some(&vertex) => vertex,
+        None => return false,
+    };
+    for &(lat_i, lon_i) in vertices {
+        let (lat_j, lon_j) = previous;
+        if (lat_i > lat) != (lat_j > lat) {
+            let crossing_lon = lon_i + (lat - lat_i) / (lat_j - lat_i) * (lon_j - lon_i);
+            if lon < crossing_lon {
+                inside = !inside;
+            }
+        }
+        previous = (lat_i, lon_i);
+    }
+
+    inside
+}
+
+/// Describe how an altitude falls outside the allowed range, or `None` if it is within it
+pub fn altitude_violation(altitude_meters: f64, constraint: &AltitudeConstraint) -> Option<String> {
+    if let Some(max) = constraint.max_meters.filter(|max| altitude_meters > *max) {
+        return Some(format!(
+            "image altitude {} exceeds allowed {}",
+            format_altitude(altitude_meters),
+            format_altitude(max)
+        ));
+    }
+    if let Some(min) = constraint.min_meters.filter(|min| altitude_meters < *min) {
+        return Some(format!(
+            "image altitude {} is below allowed {}",
+            format_altitude(altitude_meters),
+            format_altitude(min)
+        ));
+    }
+    None
+}
+
+/// Describe how an image size falls outside the allowed range, or `None` if it is within it
+pub fn dimensions_violation(
+    width: u32,
+    height: u32,
+    constraint: &DimensionsConstraint,
+) -> Option<String> {
+    let describe =
+        |width_bound: Option<u32>, height_bound: Option<u32>| match (width_bound, height_bound) {
+            (Some(width), Some(height)) => format!("{width}x{height}"),
+            (Some(width), None) => format!("width {width}"),
+            (None, Some(height)) => format!("height {height}"),
+            (None, None) => unreachable!("only called with a violated bound"),
+        };
+
+    let too_narrow = constraint.min_width.is_some_and(|min| width < min);
+    let too_short = constraint.min_height.is_some_and(|min| height < min);
+    if too_narrow || too_short {
+        return Some(format!(
+            "image {width}x{height} below minimum {}",
+            describe(constraint.min_width, constraint.min_height)
+        ));
+    }
+    let too_wide = constraint.max_width.is_some_and(|max| width > max);
+    let too_tall = constraint.max_height.is_some_and(|max| height > max);
+    if too_wide || too_tall {
+        return Some(format!(
+            "image {width}x{height} above maximum {}",
+            describe(constraint.max_width, constraint.max_height)
+        ));
+    }
+    None
+}
+
+/// Describe a capture speed above `max_kmh`, or `None` if the device moved slowly enough
+pub fn speed_violation(speed_kmh: f64, max_kmh: f64) -> Option<String> {
+    (speed_kmh > max_kmh).then(|| {
+        format!("image captured while moving at {speed_kmh:.0} km/h, over the allowed {max_kmh:.0} km/h")
+    })
+}
 
-    Ok(distance <= constraint.max_distance_meters)
+/// Check a "Make Model" camera name against an allowlist
+///
+/// Matching ignores case and runs of whitespace, since EXIF values are often padded.
+pub fn is_camera_allowed(camera: &str, allowed_cameras: &[String]) -> bool {
+    let normalize = |name: &str| {
+        name.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    };
+    let camera = normalize(camera);
+    allowed_cameras
+        .iter()
+        .any(|allowed| normalize(allowed) == camera)
+}
+
+/// Format altitude to the nearest meter
+pub fn format_altitude(altitude_meters: f64) -> String {
+    format!("{altitude_meters:.0}m")
 }
 
 /// Validate if a timestamp is within the specified datetime constraint
@@ -79,10 +227,29 @@ pub fn format_distance(distance_meters: f64) -> String {
     }
 }
 
+/// Format a distance in the unit it was requested in
+///
+/// Meters keep the automatic switch to kilometers of [`format_distance`].
+pub fn format_distance_in(distance_meters: f64, unit: DistanceUnit) -> String {
+    match unit {
+        DistanceUnit::Meters => format_distance(distance_meters),
+        DistanceUnit::Kilometers => format!("{:.2}km", distance_meters / unit.meters_per_unit()),
+        DistanceUnit::Miles => format!("{:.2}mi", distance_meters / unit.meters_per_unit()),
+    }
+}
+
 /// Validate that coordinates are reasonable (not obviously invalid)
 pub fn validate_coordinates(coords: (f64, f64)) -> Result<(), String> {
     let (lat, lon) = coords;
 
+    // NaN fails every range comparison, so name it explicitly rather than as out of range
+    if !lat.is_finite() {
+        return Err(format!("Latitude {lat} is not a finite number"));
+    }
+    if !lon.is_finite() {
+        return Err(format!("Longitude {lon} is not a finite number"));
+    }
+
     if !(-90.0..=90.0).contains(&lat) {
         return Err(format!("Latitude {lat} is out of valid range (-90 to 90)"));
     }
@@ -101,6 +268,83 @@ pub fn validate_coordinates(coords: (f64, f64)) -> Result<(), String> {
     Ok(())
 }
 
+/// Parse a short duration such as `30s`, `500ms` or `2m`; a bare number is seconds
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("Invalid duration: {value:?}"))?;
+
+    match unit {
+        "ms" => Ok(Duration::from_millis(amount)),
+        "" | "s" => Ok(Duration::from_secs(amount)),
+        "m" => Ok(Duration::from_secs(amount.saturating_mul(60))),
+        _ => Err(format!(
+            "Invalid duration unit in {value:?} (expected ms, s or m)"
+        )),
+    }
+}
+
+/// Parse an ISO-8601 duration such as `PT1H30M` or `P1DT12H`
+///
+/// Weeks, days, hours, minutes and whole seconds are supported. Years and months are
+/// rejected since their length depends on the calendar.
+pub fn parse_iso8601_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let invalid = || format!("Invalid ISO-8601 duration: {value:?}");
+
+    if value.starts_with('-') {
+        return Err(format!("Duration must be positive, got: {value:?}"));
+    }
+    let rest = value.strip_prefix('P').ok_or_else(invalid)?;
+    let (date_part, time_part) = match rest.split_once('T') {
+        Some((_, "")) => return Err(invalid()),
+        Some((date, time)) => (date, time),
+        None => (rest, ""),
+    };
+    if date_part.is_empty() && time_part.is_empty() {
+        return Err(invalid());
+    }
+
+    const DATE_UNITS: &[(char, u64)] = &[('W', 7 * 86_400), ('D', 86_400)];
+    const TIME_UNITS: &[(char, u64)] = &[('H', 3_600), ('M', 60), ('S', 1)];
+
+    let mut seconds: u64 = 0;
+    for (mut part, units) in [(date_part, DATE_UNITS), (time_part, TIME_UNITS)] {
+        // Units must appear in order, each at most once
+        for &(unit, unit_seconds) in units {
+            if let Some(index) = part.find(unit) {
+                let amount = &part[..index];
+                if amount.is_empty() || !amount.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(invalid());
+                }
+                let amount: u64 = amount.parse().map_err(|_| invalid())?;
+                seconds = amount
+                    .checked_mul(unit_seconds)
+                    .and_then(|unit_total| seconds.checked_add(unit_total))
+                    .ok_or_else(invalid)?;
+                part = &part[index + 1..];
+            }
+        }
+
+        if part.contains('Y') || (units == DATE_UNITS && part.contains('M')) {
+            return Err(format!(
+                "Years and months are not supported in durations: {value:?}"
+            ));
+        }
+        if !part.is_empty() {
+            return Err(invalid());
+        }
+    }
+
+    Ok(Duration::from_secs(seconds))
+}
+
 /// Calculate the precision/uncertainty of GPS coordinates based on EXIF limitations
 /// Returns approximate uncertainty in meters
 pub fn estimate_gps_precision(coords: (f64, f64)) -> f64 {
@@ -160,6 +404,38 @@ mod tests {
         assert!(distance > 200.0); // Should be around 250m
     }
 
+    #[test]
+    fn test_bearing_degrees_cardinal_directions() {
+        let origin = (51.5, -0.1);
+
+        // Due north along a meridian
+        assert!(bearing_degrees(origin, (51.6, -0.1)).abs() < 1e-9);
+        // Due south
+        assert!((bearing_degrees(origin, (51.4, -0.1)) - 180.0).abs() < 1e-9);
+        // East along the equator
+        assert!((bearing_degrees((0.0, 0.0), (0.0, 1.0)) - 90.0).abs() < 1e-9);
+        // West is reported as 270, not -90
+        assert!((bearing_degrees((0.0, 0.0), (0.0, -1.0)) - 270.0).abs() < 1e-9);
+
+        // London to Paris heads roughly south-east
+        let bearing = bearing_degrees((51.5074, -0.1278), (48.8566, 2.3522));
+        assert!((bearing - 148.1).abs() < 0.5, "bearing was {bearing}");
+    }
+
+    #[test]
+    fn test_compass_point_boundaries() {
+        assert_eq!(compass_point(0.0), "N");
+        assert_eq!(compass_point(22.49), "N");
+        assert_eq!(compass_point(22.5), "NE");
+        assert_eq!(compass_point(67.5), "E");
+        assert_eq!(compass_point(180.0), "S");
+        assert_eq!(compass_point(292.5), "NW");
+        assert_eq!(compass_point(337.49), "NW");
+        assert_eq!(compass_point(337.5), "N");
+        assert_eq!(compass_point(359.99), "N");
+        assert_eq!(compass_point(360.0), "N");
+    }
+
     #[test]
     fn test_haversine_distance_same_point() {
         let coord = (51.5074, -0.1278); // London
@@ -171,10 +447,12 @@ mod tests {
     #[test]
     fn test_validate_location_within_range() {
         let actual = (51.491079, -0.269590);
-        let constraint = LocationConstraint {
+        let constraint = LocationConstraint::Radius {
             max_distance_meters: 300.0,
             latitude: 51.492191,
             longitude: -0.266108,
+            distance_unit: DistanceUnit::Meters,
+            tolerate_gps_precision: false,
         };
 
         let result = validate_location(actual, &constraint).unwrap();
@@ -184,16 +462,203 @@ mod tests {
     #[test]
     fn test_validate_location_outside_range() {
         let actual = (51.491079, -0.269590);
-        let constraint = LocationConstraint {
+        let constraint = LocationConstraint::Radius {
             max_distance_meters: 100.0, // Very strict limit
             latitude: 51.492191,
             longitude: -0.266108,
+            distance_unit: DistanceUnit::Meters,
+            tolerate_gps_precision: false,
         };
 
         let result = validate_location(actual, &constraint).unwrap();
         assert!(!result); // Should be outside 100m range
     }
 
+    #[test]
+    fn test_validate_location_gps_precision_tolerance() {
+        // ≈111m north of the center, stored with 3 decimals (≈100m precision)
+        let coarse = (51.501, -0.12);
+        let constraint = |tolerate_gps_precision| LocationConstraint::Radius {
+            max_distance_meters: 100.0,
+            latitude: 51.5,
+            longitude: -0.12,
+            distance_unit: DistanceUnit::Meters,
+            tolerate_gps_precision,
+        };
+
+        assert!(!validate_location(coarse, &constraint(false)).unwrap());
+        assert!(validate_location(coarse, &constraint(true)).unwrap());
+
+        // A precise reading at the same distance gets no extra room
+        let precise = (51.5010004, -0.1200003);
+        assert!(!validate_location(precise, &constraint(true)).unwrap());
+    }
+
+    #[test]
+    fn test_point_in_polygon_square() {
+        let square = vec![
+            (51.50, -0.17),
+            (51.50, -0.15),
+            (51.51, -0.15),
+            (51.51, -0.17),
+        ];
+
+        assert!(point_in_polygon((51.505, -0.16), &square));
+        assert!(point_in_polygon((51.5001, -0.1699), &square));
+
+        assert!(!point_in_polygon((51.52, -0.16), &square)); // north
+        assert!(!point_in_polygon((51.505, -0.14), &square)); // east
+        assert!(!point_in_polygon((51.49, -0.18), &square)); // south-west
+        assert!(!point_in_polygon((51.505, -0.16), &[]));
+    }
+
+    #[test]
+    fn test_validate_location_polygon() {
+        // An L-shaped lot: the notch at the north-east corner is outside
+        let lot = LocationConstraint::Polygon(vec![
+            (51.50, -0.17),
+            (51.50, -0.15),
+            (51.505, -0.15),
+            (51.505, -0.16),
+            (51.51, -0.16),
+            (51.51, -0.17),
+        ]);
+
+        assert!(validate_location((51.502, -0.155), &lot).unwrap());
+        assert!(validate_location((51.508, -0.165), &lot).unwrap());
+        assert!(!validate_location((51.508, -0.155), &lot).unwrap());
+        assert!(validate_location((95.0, -0.16), &lot).is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("2m").unwrap(), Duration::from_secs(120));
+        assert_eq!(parse_duration("15").unwrap(), Duration::from_secs(15));
+
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("s").is_err());
+        assert!(parse_duration("-5s").is_err());
+        assert!(parse_duration("1.5s").is_err());
+        assert!(parse_duration("10h").is_err());
+    }
+
+    #[test]
+    fn test_parse_iso8601_duration() {
+        assert_eq!(
+            parse_iso8601_duration("PT90M").unwrap(),
+            Duration::from_secs(90 * 60)
+        );
+        assert_eq!(
+            parse_iso8601_duration("PT2H").unwrap(),
+            Duration::from_secs(2 * 3600)
+        );
+        assert_eq!(
+            parse_iso8601_duration("PT1H30M15S").unwrap(),
+            Duration::from_secs(5415)
+        );
+        assert_eq!(
+            parse_iso8601_duration("P1DT12H").unwrap(),
+            Duration::from_secs(36 * 3600)
+        );
+        assert_eq!(
+            parse_iso8601_duration("P1W").unwrap(),
+            Duration::from_secs(7 * 86_400)
+        );
+        assert_eq!(parse_iso8601_duration("PT0S").unwrap(), Duration::ZERO);
+
+        for invalid in [
+            "", "P", "PT", "90M", "PT1.5H", "PT30M1H", "PTH", "PT1X", "P+1D",
+        ] {
+            assert!(
+                parse_iso8601_duration(invalid).is_err(),
+                "{invalid:?} should be rejected"
+            );
+        }
+        assert!(parse_iso8601_duration("-PT1H")
+            .unwrap_err()
+            .contains("must be positive"));
+        assert!(parse_iso8601_duration("P1M")
+            .unwrap_err()
+            .contains("not supported"));
+        assert!(parse_iso8601_duration("P1Y")
+            .unwrap_err()
+            .contains("not supported"));
+    }
+
+    #[test]
+    fn test_is_camera_allowed() {
+        let allowed = vec!["Apple iPhone 15".to_string(), "Canon EOS R5".to_string()];
+
+        assert!(is_camera_allowed("Apple iPhone 15", &allowed));
+        assert!(is_camera_allowed("apple  iphone 15 ", &allowed));
+        assert!(!is_camera_allowed("Apple iPhone 14", &allowed));
+        assert!(!is_camera_allowed("Apple", &allowed));
+        assert!(!is_camera_allowed("Apple iPhone 15", &[]));
+    }
+
+    #[test]
+    fn test_altitude_violation() {
+        let constraint = AltitudeConstraint {
+            min_meters: Some(-10.0),
+            max_meters: Some(300.0),
+        };
+
+        assert_eq!(altitude_violation(120.0, &constraint), None);
+        assert_eq!(
+            altitude_violation(412.3, &constraint).unwrap(),
+            "image altitude 412m exceeds allowed 300m"
+        );
+        assert_eq!(
+            altitude_violation(-25.0, &constraint).unwrap(),
+            "image altitude -25m is below allowed -10m"
+        );
+
+        // Open-ended ranges only check the bound that is set
+        let max_only = AltitudeConstraint {
+            min_meters: None,
+            max_meters: Some(300.0),
+        };
+        assert_eq!(altitude_violation(-400.0, &max_only), None);
+    }
+
+    #[test]
+    fn test_dimensions_violation() {
+        let constraint = DimensionsConstraint {
+            min_width: Some(1024),
+            min_height: Some(768),
+            max_width: Some(8000),
+            max_height: None,
+        };
+
+        assert_eq!(dimensions_violation(4032, 3024, &constraint), None);
+        assert_eq!(
+            dimensions_violation(320, 240, &constraint).unwrap(),
+            "image 320x240 below minimum 1024x768"
+        );
+        // One axis is enough
+        assert_eq!(
+            dimensions_violation(2000, 700, &constraint).unwrap(),
+            "image 2000x700 below minimum 1024x768"
+        );
+        assert_eq!(
+            dimensions_violation(12000, 9000, &constraint).unwrap(),
+            "image 12000x9000 above maximum width 8000"
+        );
+    }
+
+    #[test]
+    fn test_speed_violation() {
+        assert_eq!(speed_violation(12.0, 30.0), None);
+        assert_eq!(speed_violation(30.0, 30.0), None);
+        assert_eq!(
+            speed_violation(92.4, 30.0).unwrap(),
+            "image captured while moving at 92 km/h, over the allowed 30 km/h"
+        );
+        assert!(speed_violation(0.5, 0.0).is_some());
+    }
+
     #[test]
     fn test_validate_datetime_within_window() {
         let start_time = FixedOffset::east_opt(0)
@@ -259,6 +724,18 @@ mod tests {
         assert!(validate_coordinates((0.0, 0.0)).is_err());
     }
 
+    #[test]
+    fn test_validate_coordinates_rejects_non_finite() {
+        let error = validate_coordinates((f64::NAN, -0.1278)).unwrap_err();
+        assert_eq!(error, "Latitude NaN is not a finite number");
+
+        let error = validate_coordinates((51.5074, f64::INFINITY)).unwrap_err();
+        assert_eq!(error, "Longitude inf is not a finite number");
+
+        let error = validate_coordinates((51.5074, f64::NEG_INFINITY)).unwrap_err();
+        assert_eq!(error, "Longitude -inf is not a finite number");
+    }
+
     #[test]
     fn test_coords_to_string() {
         let coords = (51.491079, -0.269590);
@@ -274,6 +751,16 @@ mod tests {
         assert_eq!(format_distance(999.9), "999.9m");
     }
 
+    #[test]
+    fn test_format_distance_in_unit() {
+        assert_eq!(format_distance_in(250.5, DistanceUnit::Meters), "250.5m");
+        assert_eq!(
+            format_distance_in(250.5, DistanceUnit::Kilometers),
+            "0.25km"
+        );
+        assert_eq!(format_distance_in(3218.688, DistanceUnit::Miles), "2.00mi");
+    }
+
     #[test]
     fn test_estimate_gps_precision() {
         // High precision coordinates
@@ -0,0 +1,125 @@
+use crate::validation::heic::has_ftyp_brand;
+
+use std::path::Path;
+use tempfile::NamedTempFile;
+use thiserror::Error;
+use tokio::process::Command;
+use tracing::debug;
+
+#[derive(Debug, Error)]
+pub enum AvifError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("AVIF transcode failed: {0}")]
+    Transcode(String),
+}
+
+/// `ftyp` brands identifying AVIF still images and image sequences
+const AVIF_BRANDS: &[&[u8; 4]] = &[b"avif", b"avis"];
+
+/// Returns true when the bytes start with an ISO-BMFF `ftyp` box carrying an AVIF brand
+pub fn is_avif(bytes: &[u8]) -> bool {
+    has_ftyp_brand(bytes, AVIF_BRANDS)
+}
+
+/// Converts an AVIF image to PNG with an external tool
+///
+/// The command is invoked as `<command> <input> <output.png>`, which matches
+/// libavif's `avifdec` as well as ImageMagick's `magick`.
+pub async fn transcode_to_png(input: &Path, command: &str) -> Result<Vec<u8>, AvifError> {
+    let output = NamedTempFile::with_suffix(".png")?;
+    debug!("Transcoding {:?} to PNG with {}", input, command);
+
+    let result = Command::new(command)
+        .arg(input)
+        .arg(output.path())
+        .output()
+        .await
+        .map_err(|e| AvifError::Transcode(format!("cannot run {command}: {e}")))?;
+
+    if !result.status.success() {
+        return Err(AvifError::Transcode(format!(
+            "{command} exited with {}: {}",
+            result.status,
+            String::from_utf8_lossy(&result.stderr).trim()
+        )));
+    }
+
+    let png = tokio::fs::read(output.path()).await?;
+    if !png.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Err(AvifError::Transcode(format!(
+            "{command} did not produce a PNG"
+        )));
+    }
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_avif_magic_bytes() {
+        // libavif output: major brand avif, compatible avif, mif1 and miaf
+        assert!(is_avif(b"\0\0\0\x20ftypavif\0\0\0\0avifmif1miafMA1B"));
+        // Animated AVIF
+        assert!(is_avif(b"\0\0\0\x1cftypavis\0\0\0\0avismsf1miaf"));
+        // Identified only by a compatible brand
+        assert!(is_avif(b"\0\0\0\x18ftypmif1\0\0\0\0mif1avif"));
+
+        assert!(!is_avif(b"\0\0\0\x18ftypheic\0\0\0\0mif1heic"));
+        assert!(!is_avif(b"\0\0\0\x18ftypmp42\0\0\0\0mp42isom"));
+        assert!(!is_avif(&[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, 0x4A, 0x46]));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_transcode_round_trip() {
+        use image::{ImageFormat, RgbImage};
+        use std::io::{Cursor, Write};
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+
+        // Stand-in for avifdec that writes a known PNG to the output path
+        let mut png = Cursor::new(Vec::new());
+        RgbImage::new(4, 3)
+            .write_to(&mut png, ImageFormat::Png)
+            .unwrap();
+        let png_path = dir.path().join("decoded.png");
+        std::fs::write(&png_path, png.get_ref()).unwrap();
+
+        let converter = dir.path().join("fake-avifdec");
+        let mut script = std::fs::File::create(&converter).unwrap();
+        writeln!(script, "#!/bin/sh\ncp {} \"$2\"", png_path.display()).unwrap();
+        drop(script);
+        std::fs::set_permissions(&converter, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let input = dir.path().join("photo.avif");
+        std::fs::write(&input, b"\0\0\0\x1cftypavif\0\0\0\0avifmif1miaf").unwrap();
+
+        let transcoded = transcode_to_png(&input, converter.to_str().unwrap())
+            .await
+            .unwrap();
+        let image = image::load_from_memory(&transcoded).unwrap();
+        assert_eq!((image.width(), image.height()), (4, 3));
+
+        // A converter producing something else is an error
+        let result = transcode_to_png(&input, "true").await;
+        assert!(
+            matches!(result, Err(AvifError::Transcode(message)) if message.contains("did not produce"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transcode_reports_missing_command() {
+        let result = transcode_to_png(
+            Path::new("/tmp/missing.avif"),
+            "image-checker-no-such-transcoder",
+        )
+        .await;
+        assert!(
+            matches!(result, Err(AvifError::Transcode(message)) if message.contains("cannot run"))
+        );
+    }
+}
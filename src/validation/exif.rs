@@ -1,16 +1,23 @@
-use chrono::{DateTime, FixedOffset, NaiveDateTime, Utc};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
 use exif::{In, Reader, Tag, Value};
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::BufReader;
-use std::path::Path;
+use std::io::{BufReader, Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum ExifError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
-    #[error("EXIF parsing error: {0}")]
-    Parsing(#[from] exif::Error),
+    #[error("Unsupported image container: {0}")]
+    UnsupportedContainer(String),
+    #[error("Corrupt EXIF data: {0}")]
+    Parsing(exif::Error),
+    #[error("No EXIF data in image")]
+    MissingExif,
     #[error("Missing GPS data in EXIF")]
     MissingGpsData,
     #[error("Missing timestamp data in EXIF")]
@@ -19,33 +26,481 @@ pub enum ExifError {
     InvalidGpsFormat(String),
     #[error("Invalid timestamp format: {0}")]
     InvalidTimestamp(String),
+    #[error("EXIF extraction timed out after {0:?}")]
+    Timeout(Duration),
 }
 
 #[derive(Debug, Clone)]
 pub struct ExifData {
     pub gps_coordinates: Option<(f64, f64)>, // (latitude, longitude)
+    pub altitude_meters: Option<f64>,        // negative when below sea level
+    /// `GPSSpeed` converted from its `GPSSpeedRef` unit: how fast the device was moving
+    pub speed_kmh: Option<f64>,
+    /// `DateTime`, also known as ModifyDate: when the file was last changed
     pub timestamp: Option<DateTime<FixedOffset>>,
     pub datetime_original: Option<DateTime<FixedOffset>>,
+    /// `DateTime` and `DateTimeOriginal` as written, e.g. `2025:08:01 15:40:00`, to
+    /// show how they were parsed
+    pub raw_timestamp: Option<String>,
+    pub raw_datetime_original: Option<String>,
+    /// `GPSDateStamp` and `GPSTimeStamp`, which are always UTC
+    pub gps_timestamp: Option<DateTime<FixedOffset>>,
+    /// Offset the timestamps were interpreted in
+    pub utc_offset: FixedOffset,
+    /// Whether `utc_offset` came from an EXIF offset tag rather than the default
+    pub offset_from_exif: bool,
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    /// EXIF `Orientation` (1-8); 1 is upright, others need rotating or flipping
+    pub orientation: Option<u16>,
+    /// EXIF `Software`: the firmware or program that last wrote the image
+    pub software: Option<String>,
+    /// `PixelXDimension` and `PixelYDimension`: the width and height of the image
+    pub pixel_dimensions: Option<(u32, u32)>,
 }
 
-pub fn extract_exif_metadata<P: AsRef<Path>>(image_path: P) -> Result<ExifData, ExifError> {
-    let file = File::open(&image_path)?;
-    let mut reader = BufReader::new(&file);
+/// Programs whose name in the `Software` tag marks an image as edited, lowercased
+const EDITING_SOFTWARE: &[&str] = &[
+    "photoshop",
+    "gimp",
+    "lightroom",
+    "affinity photo",
+    "pixelmator",
+    "paint.net",
+    "snapseed",
+    "facetune",
+];
 
-    let exif_reader = Reader::new();
-    let exif = exif_reader.read_from_container(&mut reader)?;
+/// How much later than `DateTimeOriginal` the ModifyDate may be before the image
+/// counts as edited; cameras write both at capture, phones within seconds
+const MAX_MODIFY_DATE_SKEW_SECONDS: i64 = 3600;
+
+impl ExifData {
+    /// Data for an image without EXIF, with timestamps interpreted at `utc_offset`
+    pub fn without_metadata(utc_offset: FixedOffset) -> Self {
+        Self {
+            gps_coordinates: None,
+            altitude_meters: None,
+            speed_kmh: None,
+            timestamp: None,
+            datetime_original: None,
+            raw_timestamp: None,
+            raw_datetime_original: None,
+            gps_timestamp: None,
+            utc_offset,
+            offset_from_exif: false,
+            camera_make: None,
+            camera_model: None,
+            orientation: None,
+            software: None,
+            pixel_dimensions: None,
+        }
+    }
+
+    /// Signs that the image was changed after it was taken, as rejection messages
+    ///
+    /// Flags a `Software` tag naming a known editor, and a ModifyDate more than an
+    /// hour after `DateTimeOriginal`.
+    pub fn edit_indicators(&self) -> Vec<String> {
+        let mut indicators = Vec::new();
+
+        if let Some(software) = &self.software {
+            let lowercase = software.to_lowercase();
+            if EDITING_SOFTWARE
+                .iter()
+                .any(|editor| lowercase.contains(editor))
+            {
+                indicators.push(format!("image edited with {software}"));
+            }
+        }
+
+        if let (Some(original), Some(modified)) = (self.datetime_original, self.timestamp) {
+            let skew = modified - original;
+            if skew.num_seconds() > MAX_MODIFY_DATE_SKEW_SECONDS {
+                indicators.push(format!(
+                    "image modified {} after it was taken ({})",
+                    format_skew(skew),
+                    modified.format("%Y-%m-%d %H:%M:%S %z")
+                ));
+            }
+        }
+
+        indicators
+    }
+
+    /// When the image was taken: `DateTimeOriginal`, else `DateTime`, else the GPS timestamp
+    pub fn capture_time(&self) -> Option<DateTime<FixedOffset>> {
+        self.datetime_original
+            .or(self.timestamp)
+            .or(self.gps_timestamp)
+    }
+
+    /// The EXIF string [`capture_time`](Self::capture_time) was parsed from; `None` when
+    /// it came from the GPS timestamp
+    pub fn raw_capture_time(&self) -> Option<&str> {
+        if self.datetime_original.is_some() {
+            self.raw_datetime_original.as_deref()
+        } else if self.timestamp.is_some() {
+            self.raw_timestamp.as_deref()
+        } else {
+            None
+        }
+    }
+
+    /// The camera as "Make Model", or whichever of the two is recorded
+    ///
+    /// Some manufacturers repeat the make in the model (`Canon` / `Canon EOS R5`),
+    /// in which case the model is used on its own.
+    pub fn camera(&self) -> Option<String> {
+        match (self.camera_make.as_deref(), self.camera_model.as_deref()) {
+            (Some(make), Some(model)) if model.to_lowercase().starts_with(&make.to_lowercase()) => {
+                Some(model.to_string())
+            }
+            (Some(make), Some(model)) => Some(format!("{make} {model}")),
+            (Some(name), None) | (None, Some(name)) => Some(name.to_string()),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Image file formats EXIF can be read from, identified by magic bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageContainer {
+    Jpeg,
+    /// TIFF and TIFF-based raws: DNG, CR2, NEF, ARW, ORF, RW2, ...
+    Tiff,
+    Png,
+    Webp,
+    Heif,
+    /// HEIF-based, so EXIF is read the same way when the encoder wrote it
+    Avif,
+}
+
+impl ImageContainer {
+    /// Identifies the container of the file at `path` from its magic bytes
+    pub fn of_file(path: &Path) -> Result<Option<Self>, ExifError> {
+        let mut header = Vec::with_capacity(64);
+        File::open(path)?.take(64).read_to_end(&mut header)?;
+        Ok(Self::detect(&header))
+    }
+
+    /// Whether cameras always write EXIF in this format, so its absence is suspicious
+    pub fn expects_exif(self) -> bool {
+        matches!(self, Self::Jpeg | Self::Tiff | Self::Heif)
+    }
+
+    /// Identifies the container from the first bytes of the file
+    pub fn detect(header: &[u8]) -> Option<Self> {
+        if header.starts_with(&[0xFF, 0xD8]) {
+            Some(Self::Jpeg)
+        } else if tiff_magic(header).is_some() {
+            Some(Self::Tiff)
+        } else if header.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Self::Png)
+        } else if header.len() >= 12 && header.starts_with(b"RIFF") && &header[8..12] == b"WEBP" {
+            Some(Self::Webp)
+        } else if crate::validation::avif::is_avif(header) {
+            Some(Self::Avif)
+        } else if crate::validation::heic::is_heif(header) {
+            Some(Self::Heif)
+        } else {
+            None
+        }
+    }
+}
+
+/// TIFF magic numbers: the standard 42 plus the variants used by Olympus ORF
+/// (`IIRO`, `IIRS`) and Panasonic RW2 (`IIU\0`) raws
+const TIFF_MAGICS: &[&[u8; 4]] = &[b"II*\0", b"MM\0*", b"IIRO", b"IIRS", b"IIU\0"];
+
+fn tiff_magic(header: &[u8]) -> Option<&'static [u8; 4]> {
+    TIFF_MAGICS
+        .iter()
+        .copied()
+        .find(|magic| header.starts_with(*magic))
+}
+
+/// Reads the EXIF block, choosing the entry point from the file's magic bytes
+fn read_exif(image_path: &Path) -> Result<exif::Exif, ExifError> {
+    let mut reader = BufReader::new(File::open(image_path)?);
+
+    // 64 bytes cover every magic number, including the HEIF `ftyp` brand list
+    let mut header = Vec::with_capacity(64);
+    reader.by_ref().take(64).read_to_end(&mut header)?;
+    reader.rewind()?;
+
+    let container = ImageContainer::detect(&header).ok_or_else(|| {
+        ExifError::UnsupportedContainer(
+            "expected JPEG, TIFF/DNG, PNG, WebP, HEIF or AVIF magic bytes".to_string(),
+        )
+    })?;
+
+    let result = match container {
+        ImageContainer::Tiff => {
+            // Raw files are TIFF structures, some with a vendor-specific magic number
+            // that the EXIF reader does not accept; parse them as plain TIFF
+            let mut tiff = Vec::new();
+            reader.read_to_end(&mut tiff)?;
+            if tiff.starts_with(b"II") {
+                tiff[2..4].copy_from_slice(&[0x2A, 0x00]);
+            }
+            Reader::new().read_raw(tiff)
+        }
+        _ => Reader::new().read_from_container(&mut reader),
+    };
+
+    result.map_err(|e| match e {
+        exif::Error::Io(e) => ExifError::Io(e),
+        exif::Error::NotFound(_) => ExifError::MissingExif,
+        // The reader only recognizes AVIF files that also declare a HEIF brand
+        exif::Error::InvalidFormat(_) if container == ImageContainer::Avif => {
+            ExifError::UnsupportedContainer("AVIF without a HEIF-compatible brand".to_string())
+        }
+        other => ExifError::Parsing(other),
+    })
+}
+
+/// Extracts GPS, timestamp and camera metadata from an image
+///
+/// EXIF timestamps carry no timezone, so `DateTimeOriginal` is read in the offset
+/// recorded by `OffsetTimeOriginal` and `DateTime` in that of `OffsetTime`. When only
+/// one offset tag is present it applies to both; with neither, `default_offset` is used.
+pub fn extract_exif_metadata<P: AsRef<Path>>(
+    image_path: P,
+    default_offset: FixedOffset,
+) -> Result<ExifData, ExifError> {
+    let exif = read_exif(image_path.as_ref())?;
 
     let gps_coordinates = extract_gps_coordinates(&exif)?;
-    let timestamp = extract_datetime(&exif, Tag::DateTime)?;
-    let datetime_original = extract_datetime(&exif, Tag::DateTimeOriginal)?;
+    let altitude_meters = extract_gps_altitude(&exif)?;
+    let speed_kmh = extract_gps_speed(&exif)?;
+
+    // Each timestamp has its own offset tag; when only one is recorded it applies to both
+    let original_offset = extract_offset(&exif, Tag::OffsetTimeOriginal)?;
+    let modified_offset = extract_offset(&exif, Tag::OffsetTime)?;
+    let recorded_offset = original_offset.or(modified_offset);
+    let utc_offset = recorded_offset.unwrap_or(default_offset);
+
+    let timestamp = extract_datetime(&exif, Tag::DateTime, modified_offset.unwrap_or(utc_offset))?;
+    let datetime_original = extract_datetime(&exif, Tag::DateTimeOriginal, utc_offset)?;
+    // Only a fallback, so a malformed GPS timestamp does not fail the whole image
+    let gps_timestamp = extract_gps_timestamp(&exif).unwrap_or(None);
+
+    let camera_make = extract_ascii(&exif, Tag::Make);
+    let camera_model = extract_ascii(&exif, Tag::Model);
+    let software = extract_ascii(&exif, Tag::Software);
+    let orientation = exif
+        .get_field(Tag::Orientation, In::PRIMARY)
+        .and_then(|field| field.value.get_uint(0))
+        .and_then(|value| u16::try_from(value).ok())
+        .filter(|value| (1..=8).contains(value));
+    let pixel_dimension = |tag| {
+        exif.get_field(tag, In::PRIMARY)
+            .and_then(|field| field.value.get_uint(0))
+    };
+    let pixel_dimensions = pixel_dimension(Tag::PixelXDimension)
+        .zip(pixel_dimension(Tag::PixelYDimension))
+        .filter(|&(width, height)| width > 0 && height > 0);
 
     Ok(ExifData {
         gps_coordinates,
+        altitude_meters,
+        speed_kmh,
+        raw_timestamp: timestamp.and(extract_ascii(&exif, Tag::DateTime)),
+        raw_datetime_original: datetime_original.and(extract_ascii(&exif, Tag::DateTimeOriginal)),
         timestamp,
         datetime_original,
+        gps_timestamp,
+        utc_offset,
+        offset_from_exif: recorded_offset.is_some(),
+        camera_make,
+        camera_model,
+        orientation,
+        software,
+        pixel_dimensions,
     })
 }
 
+/// Returns the JPEG thumbnail embedded in the image's EXIF, if it has one
+///
+/// Images without EXIF have no thumbnail either, so `MissingExif` is not an error here.
+pub fn extract_thumbnail<P: AsRef<Path>>(image_path: P) -> Result<Option<Vec<u8>>, ExifError> {
+    let exif = match read_exif(image_path.as_ref()) {
+        Ok(exif) => exif,
+        Err(ExifError::MissingExif) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    let location = |tag| {
+        exif.get_field(tag, In::THUMBNAIL)
+            .and_then(|field| field.value.get_uint(0))
+            .and_then(|value| usize::try_from(value).ok())
+    };
+    let (Some(offset), Some(length)) = (
+        location(Tag::JPEGInterchangeFormat),
+        location(Tag::JPEGInterchangeFormatLength),
+    ) else {
+        return Ok(None);
+    };
+
+    // The offset is relative to the start of the TIFF structure the reader kept
+    offset
+        .checked_add(length)
+        .and_then(|end| exif.buf().get(offset..end))
+        .filter(|thumbnail| !thumbnail.is_empty())
+        .map(|thumbnail| Some(thumbnail.to_vec()))
+        .ok_or(ExifError::Parsing(exif::Error::InvalidFormat(
+            "thumbnail lies outside the EXIF data",
+        )))
+}
+
+struct CachedExif {
+    file_size: u64,
+    modified: SystemTime,
+    default_offset: FixedOffset,
+    data: ExifData,
+    /// Value of the cache's use counter when the entry was last read or written
+    last_used: u64,
+}
+
+/// Parsed EXIF metadata of recently read images, evicting the least recently used
+///
+/// Entries are keyed by path and only reused while the file keeps its size and
+/// modification time, so a replaced image is read again. Failed reads are not cached.
+pub struct ExifCache {
+    capacity: usize,
+    /// Entries plus a counter bumped on every use, to find the least recently used
+    entries: Mutex<(HashMap<PathBuf, CachedExif>, u64)>,
+}
+
+impl ExifCache {
+    /// A cache holding up to `capacity` images; 0 disables caching
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new((HashMap::new(), 0)),
+        }
+    }
+
+    /// [`extract_exif_metadata`], served from the cache while the file is unchanged
+    pub fn extract(
+        &self,
+        image_path: &Path,
+        default_offset: FixedOffset,
+    ) -> Result<ExifData, ExifError> {
+        if self.capacity == 0 {
+            return extract_exif_metadata(image_path, default_offset);
+        }
+
+        let metadata = std::fs::metadata(image_path)?;
+        let Ok(modified) = metadata.modified() else {
+            return extract_exif_metadata(image_path, default_offset);
+        };
+        let file_size = metadata.len();
+
+        {
+            let mut guard = self.entries.lock().expect("EXIF cache lock poisoned");
+            let (entries, clock) = &mut *guard;
+            *clock += 1;
+            if let Some(entry) = entries.get_mut(image_path).filter(|entry| {
+                entry.file_size == file_size
+                    && entry.modified == modified
+                    && entry.default_offset == default_offset
+            }) {
+                entry.last_used = *clock;
+                return Ok(entry.data.clone());
+            }
+        }
+
+        let data = extract_exif_metadata(image_path, default_offset)?;
+
+        let mut guard = self.entries.lock().expect("EXIF cache lock poisoned");
+        let (entries, clock) = &mut *guard;
+        *clock += 1;
+        if entries.len() >= self.capacity && !entries.contains_key(image_path) {
+            let least_recent = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(path, _)| path.clone());
+            if let Some(path) = least_recent {
+                entries.remove(&path);
+            }
+        }
+        entries.insert(
+            image_path.to_path_buf(),
+            CachedExif {
+                file_size,
+                modified,
+                default_offset,
+                data: data.clone(),
+                last_used: *clock,
+            },
+        );
+        Ok(data)
+    }
+
+    /// Number of images currently cached
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .expect("EXIF cache lock poisoned")
+            .0
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Runs [`extract_exif_metadata`] on the blocking thread pool, giving up after `timeout`
+///
+/// Reads from slow storage or pathological files cannot stall the async runtime. On
+/// timeout the read carries on in the background but its result is discarded. With a
+/// `cache`, unchanged images are not parsed again.
+pub async fn extract_exif_metadata_with_timeout<P: AsRef<Path>>(
+    image_path: P,
+    default_offset: FixedOffset,
+    timeout: Duration,
+    cache: Option<&Arc<ExifCache>>,
+) -> Result<ExifData, ExifError> {
+    let image_path = image_path.as_ref().to_path_buf();
+    let cache = cache.cloned();
+    let extraction = tokio::task::spawn_blocking(move || match cache {
+        Some(cache) => cache.extract(&image_path, default_offset),
+        None => extract_exif_metadata(image_path, default_offset),
+    });
+
+    match tokio::time::timeout(timeout, extraction).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => Err(ExifError::Io(std::io::Error::other(format!(
+            "EXIF extraction task failed: {e}"
+        )))),
+        Err(_) => Err(ExifError::Timeout(timeout)),
+    }
+}
+
+/// Renders a time difference in whole minutes, hours or days
+fn format_skew(skew: chrono::Duration) -> String {
+    match (skew.num_days(), skew.num_hours(), skew.num_minutes()) {
+        (days, ..) if days >= 2 => format!("{days} days"),
+        (_, hours, _) if hours >= 2 => format!("{hours} hours"),
+        (.., minutes) => format!("{minutes} minutes"),
+    }
+}
+
+/// Reads a free-text ASCII tag, treating blank values as absent
+fn extract_ascii(exif: &exif::Exif, tag: Tag) -> Option<String> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    let Value::Ascii(ascii_values) = &field.value else {
+        return None;
+    };
+
+    let value = String::from_utf8_lossy(ascii_values.first()?);
+    let value = value.trim_end_matches('\0').trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
 fn extract_gps_coordinates(exif: &exif::Exif) -> Result<Option<(f64, f64)>, ExifError> {
     // Try to extract GPS latitude
     let lat_field = exif.get_field(Tag::GPSLatitude, In::PRIMARY);
@@ -78,12 +533,73 @@ fn extract_gps_coordinates(exif: &exif::Exif) -> Result<Option<(f64, f64)>, Exif
     let lon_ref = extract_gps_ref(&lon_ref_field.value)?;
 
     // Convert DMS to decimal degrees
-    let latitude = dms_to_decimal(lat_dms) * if lat_ref == "S" { -1.0 } else { 1.0 };
-    let longitude = dms_to_decimal(lon_dms) * if lon_ref == "W" { -1.0 } else { 1.0 };
+    let latitude = dms_to_decimal(lat_dms) * hemisphere_sign(&lat_ref, "latitude", 'N', 'S')?;
+    let longitude = dms_to_decimal(lon_dms) * hemisphere_sign(&lon_ref, "longitude", 'E', 'W')?;
 
     Ok(Some((latitude, longitude)))
 }
 
+fn extract_gps_altitude(exif: &exif::Exif) -> Result<Option<f64>, ExifError> {
+    let Some(altitude_field) = exif.get_field(Tag::GPSAltitude, In::PRIMARY) else {
+        return Ok(None);
+    };
+
+    let altitude = match &altitude_field.value {
+        Value::Rational(rationals) if rationals.len() == 1 => rationals[0].to_f64(),
+        Value::Rational(rationals) => {
+            return Err(ExifError::InvalidGpsFormat(format!(
+                "Expected 1 rational value for altitude, got {}",
+                rationals.len()
+            )))
+        }
+        _ => {
+            return Err(ExifError::InvalidGpsFormat(
+                "GPS altitude must be stored as a rational value".into(),
+            ))
+        }
+    };
+
+    // GPSAltitudeRef: 0 = above sea level (the default when absent), 1 = below
+    let below_sea_level = exif
+        .get_field(Tag::GPSAltitudeRef, In::PRIMARY)
+        .and_then(|field| field.value.get_uint(0))
+        == Some(1);
+
+    Ok(Some(if below_sea_level { -altitude } else { altitude }))
+}
+
+fn extract_gps_speed(exif: &exif::Exif) -> Result<Option<f64>, ExifError> {
+    let Some(speed_field) = exif.get_field(Tag::GPSSpeed, In::PRIMARY) else {
+        return Ok(None);
+    };
+
+    let speed = match &speed_field.value {
+        Value::Rational(rationals) if rationals.len() == 1 => rationals[0].to_f64(),
+        _ => {
+            return Err(ExifError::InvalidGpsFormat(
+                "GPS speed must be stored as a single rational value".into(),
+            ))
+        }
+    };
+
+    // GPSSpeedRef defaults to km/h when absent
+    let speed_ref = extract_ascii(exif, Tag::GPSSpeedRef).unwrap_or_else(|| "K".to_string());
+    speed_to_kmh(speed, &speed_ref).map(Some)
+}
+
+/// Converts a `GPSSpeed` value to km/h from its `GPSSpeedRef` unit: `K` for km/h, `M`
+/// for mph or `N` for knots
+fn speed_to_kmh(speed: f64, speed_ref: &str) -> Result<f64, ExifError> {
+    match speed_ref {
+        "K" => Ok(speed),
+        "M" => Ok(speed * 1.609_344),
+        "N" => Ok(speed * 1.852),
+        other => Err(ExifError::InvalidGpsFormat(format!(
+            "Unknown GPS speed unit: {other}"
+        ))),
+    }
+}
+
 fn extract_gps_dms(value: &Value) -> Result<(f64, f64, f64), ExifError> {
     match value {
         Value::Rational(rationals) => {
@@ -122,14 +638,72 @@ fn extract_gps_ref(value: &Value) -> Result<String, ExifError> {
     }
 }
 
+/// Sign of a coordinate from its hemisphere reference, ignoring case
+///
+/// Anything but the two hemispheres of the coordinate's own axis is an error, rather
+/// than silently placing the photo in the wrong hemisphere.
+fn hemisphere_sign(
+    reference: &str,
+    axis: &str,
+    positive: char,
+    negative: char,
+) -> Result<f64, ExifError> {
+    let normalized = reference.trim().to_ascii_uppercase();
+    let mut chars = normalized.chars();
+    match (chars.next(), chars.next()) {
+        (Some(hemisphere), None) if hemisphere == positive => Ok(1.0),
+        (Some(hemisphere), None) if hemisphere == negative => Ok(-1.0),
+        (Some('N' | 'S' | 'E' | 'W'), None) => Err(ExifError::InvalidGpsFormat(format!(
+            "GPS {} reference '{}' belongs to the other axis",
+            axis, reference
+        ))),
+        _ => Err(ExifError::InvalidGpsFormat(format!(
+            "Invalid GPS {} reference '{}', expected {} or {}",
+            axis, reference, positive, negative
+        ))),
+    }
+}
+
 fn dms_to_decimal(dms: (f64, f64, f64)) -> f64 {
     let (degrees, minutes, seconds) = dms;
     degrees + minutes / 60.0 + seconds / 3600.0
 }
 
+/// Reads an EXIF 2.31 offset tag such as `OffsetTimeOriginal` (`"+01:00"`)
+fn extract_offset(exif: &exif::Exif, tag: Tag) -> Result<Option<FixedOffset>, ExifError> {
+    let Some(field) = exif.get_field(tag, In::PRIMARY) else {
+        return Ok(None);
+    };
+
+    match &field.value {
+        Value::Ascii(ascii_values) => {
+            let Some(offset) = ascii_values.first() else {
+                return Ok(None);
+            };
+
+            let offset_str = String::from_utf8_lossy(offset);
+            let offset_str = offset_str.trim_end_matches('\0').trim();
+
+            // Cameras write blanks ("   :  ") when the offset is unknown
+            if offset_str.trim_matches([' ', ':']).is_empty() {
+                return Ok(None);
+            }
+
+            offset_str
+                .parse::<FixedOffset>()
+                .map(Some)
+                .map_err(|_| ExifError::InvalidTimestamp(format!("invalid offset: {offset_str}")))
+        }
+        _ => Err(ExifError::InvalidTimestamp(
+            "Offset time must be stored as ASCII value".into(),
+        )),
+    }
+}
+
 fn extract_datetime(
     exif: &exif::Exif,
     tag: Tag,
+    offset: FixedOffset,
 ) -> Result<Option<DateTime<FixedOffset>>, ExifError> {
     let field = exif.get_field(tag, In::PRIMARY);
 
@@ -152,11 +726,13 @@ fn extract_datetime(
             let naive_dt = NaiveDateTime::parse_from_str(datetime_str, "%Y:%m:%d %H:%M:%S")
                 .map_err(|_| ExifError::InvalidTimestamp(datetime_str.to_string()))?;
 
-            // Convert to UTC (EXIF timestamps are typically in local time, but we assume UTC for consistency)
-            let utc_dt = DateTime::<Utc>::from_naive_utc_and_offset(naive_dt, Utc);
-            let fixed_offset_dt = utc_dt.with_timezone(&FixedOffset::east_opt(0).unwrap());
+            // EXIF timestamps are wall-clock time in the camera's local offset
+            let local_dt = offset
+                .from_local_datetime(&naive_dt)
+                .single()
+                .ok_or_else(|| ExifError::InvalidTimestamp(datetime_str.to_string()))?;
 
-            Ok(Some(fixed_offset_dt))
+            Ok(Some(local_dt))
         }
         _ => Err(ExifError::InvalidTimestamp(
             "DateTime must be stored as ASCII value".into(),
@@ -164,9 +740,61 @@ fn extract_datetime(
     }
 }
 
+/// Combines `GPSDateStamp` (`"YYYY:MM:DD"`) and `GPSTimeStamp` (hours, minutes and
+/// seconds as rationals) into a UTC timestamp; both tags must be present
+fn extract_gps_timestamp(exif: &exif::Exif) -> Result<Option<DateTime<FixedOffset>>, ExifError> {
+    let (Some(date), Some(time_field)) = (
+        extract_ascii(exif, Tag::GPSDateStamp),
+        exif.get_field(Tag::GPSTimeStamp, In::PRIMARY),
+    ) else {
+        return Ok(None);
+    };
+
+    let date = NaiveDate::parse_from_str(&date, "%Y:%m:%d")
+        .map_err(|_| ExifError::InvalidTimestamp(format!("invalid GPS date: {date}")))?;
+
+    let time = match &time_field.value {
+        Value::Rational(rationals) if rationals.len() == 3 => {
+            let [hours, minutes, seconds] = [0, 1, 2].map(|i| rationals[i].to_f64());
+            let whole_seconds = seconds.trunc();
+            let nanos = ((seconds - whole_seconds) * 1e9).round();
+            [hours, minutes, whole_seconds]
+                .iter()
+                .all(|value| value.fract() == 0.0 && *value >= 0.0)
+                .then(|| {
+                    NaiveTime::from_hms_nano_opt(
+                        hours as u32,
+                        minutes as u32,
+                        whole_seconds as u32,
+                        nanos.min(999_999_999.0) as u32,
+                    )
+                })
+                .flatten()
+                .ok_or_else(|| {
+                    ExifError::InvalidTimestamp(format!(
+                        "invalid GPS time: {hours}:{minutes}:{seconds}"
+                    ))
+                })?
+        }
+        _ => {
+            return Err(ExifError::InvalidTimestamp(
+                "GPS time must be stored as 3 rational values".into(),
+            ))
+        }
+    };
+
+    Ok(Some(
+        Utc.from_utc_datetime(&date.and_time(time)).fixed_offset(),
+    ))
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
+    use exif::experimental::Writer;
+    use exif::{Field, Rational};
+    use std::io::{Cursor, Write};
+    use tempfile::NamedTempFile;
 
     #[test]
     fn test_dms_to_decimal_conversion() {
@@ -180,17 +808,771 @@ mod tests {
     fn test_dms_to_decimal_negative() {
         // Test with Southern/Western coordinates
         let dms = (0.0, 16.0, 9.324); // 0°16'9.324" = 0.2692567
-        let decimal = dms_to_decimal(dms) * -1.0; // Western longitude
+        let decimal = -dms_to_decimal(dms); // Western longitude
         assert!((decimal + 0.2692567).abs() < 0.000001);
     }
 
     #[test]
     fn test_extract_exif_metadata_missing_file() {
-        let result = extract_exif_metadata("nonexistent.jpg");
+        let result = extract_exif_metadata("nonexistent.jpg", FixedOffset::east_opt(0).unwrap());
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), ExifError::Io(_)));
     }
 
+    /// Writes a minimal JPEG whose APP1 segment carries the given EXIF fields
+    pub(crate) fn write_jpeg_with_exif(fields: &[Field]) -> NamedTempFile {
+        write_jpeg_with_thumbnail(fields, None)
+    }
+
+    /// Like [`write_jpeg_with_exif`], also embedding `thumbnail` in the EXIF
+    pub(crate) fn write_jpeg_with_thumbnail(
+        fields: &[Field],
+        thumbnail: Option<&[u8]>,
+    ) -> NamedTempFile {
+        let mut writer = Writer::new();
+        for field in fields {
+            writer.push_field(field);
+        }
+        if let Some(thumbnail) = thumbnail {
+            writer.set_jpeg(thumbnail, In::THUMBNAIL);
+        }
+        let mut tiff = Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+        let tiff = tiff.into_inner();
+
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        jpeg.extend(((tiff.len() + 8) as u16).to_be_bytes());
+        jpeg.extend(b"Exif\0\0");
+        jpeg.extend(tiff);
+        jpeg.extend([0xFF, 0xD9]);
+
+        let mut file = NamedTempFile::with_suffix(".jpg").unwrap();
+        file.write_all(&jpeg).unwrap();
+        file
+    }
+
+    pub(crate) fn ascii_field(tag: Tag, value: &str) -> Field {
+        Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value: Value::Ascii(vec![value.as_bytes().to_vec()]),
+        }
+    }
+
+    fn altitude_fields(meters: u32, altitude_ref: u8) -> [Field; 2] {
+        [
+            Field {
+                tag: Tag::GPSAltitude,
+                ifd_num: In::PRIMARY,
+                value: Value::Rational(vec![Rational::from((meters * 10, 10))]),
+            },
+            Field {
+                tag: Tag::GPSAltitudeRef,
+                ifd_num: In::PRIMARY,
+                value: Value::Byte(vec![altitude_ref]),
+            },
+        ]
+    }
+
+    fn coordinate_fields(latitude_ref: &str, longitude_ref: &str) -> [Field; 4] {
+        let dms = |degrees: u32| {
+            Value::Rational(vec![
+                Rational::from((degrees, 1)),
+                Rational::from((30, 1)),
+                Rational::from((0, 1)),
+            ])
+        };
+        [
+            Field {
+                tag: Tag::GPSLatitude,
+                ifd_num: In::PRIMARY,
+                value: dms(51),
+            },
+            ascii_field(Tag::GPSLatitudeRef, latitude_ref),
+            Field {
+                tag: Tag::GPSLongitude,
+                ifd_num: In::PRIMARY,
+                value: dms(0),
+            },
+            ascii_field(Tag::GPSLongitudeRef, longitude_ref),
+        ]
+    }
+
+    #[test]
+    fn test_gps_hemisphere_references() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let extract = |latitude_ref, longitude_ref| {
+            let image = write_jpeg_with_exif(&coordinate_fields(latitude_ref, longitude_ref));
+            extract_exif_metadata(image.path(), utc)
+        };
+
+        let exif_data = extract("S", "W").unwrap();
+        assert_eq!(exif_data.gps_coordinates, Some((-51.5, -0.5)));
+        // Lowercase references keep their hemisphere instead of turning positive
+        let exif_data = extract("s", "w").unwrap();
+        assert_eq!(exif_data.gps_coordinates, Some((-51.5, -0.5)));
+        let exif_data = extract("n", "e").unwrap();
+        assert_eq!(exif_data.gps_coordinates, Some((51.5, 0.5)));
+
+        assert!(matches!(
+            extract("X", "W"),
+            Err(ExifError::InvalidGpsFormat(message)) if message.contains("'X'")
+        ));
+        assert!(matches!(
+            extract("N", "S"),
+            Err(ExifError::InvalidGpsFormat(message)) if message.contains("other axis")
+        ));
+    }
+
+    #[test]
+    fn test_extract_gps_altitude() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+
+        let image = write_jpeg_with_exif(&altitude_fields(412, 0));
+        let exif_data = extract_exif_metadata(image.path(), utc).unwrap();
+        assert_eq!(exif_data.altitude_meters, Some(412.0));
+
+        let image = write_jpeg_with_exif(&altitude_fields(28, 1));
+        let exif_data = extract_exif_metadata(image.path(), utc).unwrap();
+        assert_eq!(exif_data.altitude_meters, Some(-28.0));
+
+        let image = write_jpeg_with_exif(&[ascii_field(Tag::DateTime, "2025:08:01 15:25:00")]);
+        let exif_data = extract_exif_metadata(image.path(), utc).unwrap();
+        assert_eq!(exif_data.altitude_meters, None);
+    }
+
+    #[test]
+    fn test_speed_units_convert_to_kmh() {
+        assert_eq!(speed_to_kmh(92.0, "K").unwrap(), 92.0);
+        assert!((speed_to_kmh(50.0, "M").unwrap() - 80.4672).abs() < 1e-9);
+        assert!((speed_to_kmh(10.0, "N").unwrap() - 18.52).abs() < 1e-9);
+        assert!(matches!(
+            speed_to_kmh(10.0, "X"),
+            Err(ExifError::InvalidGpsFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_extract_gps_speed() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let speed = |value: u32| Field {
+            tag: Tag::GPSSpeed,
+            ifd_num: In::PRIMARY,
+            value: Value::Rational(vec![Rational::from((value * 10, 10))]),
+        };
+
+        let image = write_jpeg_with_exif(&[speed(60), ascii_field(Tag::GPSSpeedRef, "M")]);
+        let exif_data = extract_exif_metadata(image.path(), utc).unwrap();
+        assert!((exif_data.speed_kmh.unwrap() - 96.56064).abs() < 1e-6);
+
+        // Without a unit the speed is in km/h
+        let image = write_jpeg_with_exif(&[speed(92)]);
+        let exif_data = extract_exif_metadata(image.path(), utc).unwrap();
+        assert_eq!(exif_data.speed_kmh, Some(92.0));
+
+        let image = write_jpeg_with_exif(&altitude_fields(412, 0));
+        let exif_data = extract_exif_metadata(image.path(), utc).unwrap();
+        assert_eq!(exif_data.speed_kmh, None);
+    }
+
+    #[test]
+    fn test_extract_datetime_with_offset_tag() {
+        let image = write_jpeg_with_exif(&[
+            ascii_field(Tag::DateTimeOriginal, "2025:08:01 15:25:00"),
+            ascii_field(Tag::OffsetTimeOriginal, "+02:00"),
+        ]);
+
+        // The recorded offset wins over the configured default
+        let default_offset = FixedOffset::east_opt(-5 * 3600).unwrap();
+        let exif_data = extract_exif_metadata(image.path(), default_offset).unwrap();
+
+        let expected = FixedOffset::east_opt(2 * 3600).unwrap();
+        assert_eq!(exif_data.utc_offset, expected);
+        assert!(exif_data.offset_from_exif);
+        assert_eq!(
+            exif_data.datetime_original.unwrap(),
+            DateTime::parse_from_rfc3339("2025-08-01T15:25:00+02:00").unwrap()
+        );
+        assert_eq!(
+            exif_data.raw_datetime_original.as_deref(),
+            Some("2025:08:01 15:25:00")
+        );
+        assert_eq!(exif_data.raw_capture_time(), Some("2025:08:01 15:25:00"));
+        assert_eq!(exif_data.raw_timestamp, None);
+    }
+
+    #[test]
+    fn test_extract_datetime_without_offset_tag_uses_default() {
+        let image =
+            write_jpeg_with_exif(&[ascii_field(Tag::DateTimeOriginal, "2025:08:01 15:25:00")]);
+
+        let default_offset = FixedOffset::east_opt(330 * 60).unwrap();
+        let exif_data = extract_exif_metadata(image.path(), default_offset).unwrap();
+
+        assert_eq!(exif_data.utc_offset, default_offset);
+        assert!(!exif_data.offset_from_exif);
+        assert_eq!(
+            exif_data.datetime_original.unwrap(),
+            DateTime::parse_from_rfc3339("2025-08-01T15:25:00+05:30").unwrap()
+        );
+        assert!(exif_data.timestamp.is_none());
+    }
+
+    #[test]
+    fn test_extract_datetime_offsets() {
+        let default_offset = FixedOffset::east_opt(0).unwrap();
+
+        // Tokyo: +09:00 recorded for the original capture time
+        let image = write_jpeg_with_exif(&[
+            ascii_field(Tag::DateTimeOriginal, "2025:08:01 09:00:00"),
+            ascii_field(Tag::OffsetTimeOriginal, "+09:00"),
+        ]);
+        let exif_data = extract_exif_metadata(image.path(), default_offset).unwrap();
+        let datetime_original = exif_data.datetime_original.unwrap();
+        assert_eq!(datetime_original.offset().local_minus_utc(), 9 * 3600);
+        assert_eq!(
+            datetime_original,
+            DateTime::parse_from_rfc3339("2025-08-01T00:00:00Z").unwrap()
+        );
+
+        // New York: only OffsetTime (-05:00) recorded, used for both timestamps
+        let image = write_jpeg_with_exif(&[
+            ascii_field(Tag::DateTime, "2025:01:15 08:30:00"),
+            ascii_field(Tag::DateTimeOriginal, "2025:01:15 08:29:00"),
+            ascii_field(Tag::OffsetTime, "-05:00"),
+        ]);
+        let exif_data = extract_exif_metadata(image.path(), default_offset).unwrap();
+        assert!(exif_data.offset_from_exif);
+        assert_eq!(
+            exif_data.timestamp.unwrap(),
+            DateTime::parse_from_rfc3339("2025-01-15T08:30:00-05:00").unwrap()
+        );
+        assert_eq!(
+            exif_data.datetime_original.unwrap(),
+            DateTime::parse_from_rfc3339("2025-01-15T08:29:00-05:00").unwrap()
+        );
+
+        // Edited in another timezone: each timestamp keeps its own offset
+        let image = write_jpeg_with_exif(&[
+            ascii_field(Tag::DateTime, "2025:01:16 10:00:00"),
+            ascii_field(Tag::OffsetTime, "+01:00"),
+            ascii_field(Tag::DateTimeOriginal, "2025:01:15 08:29:00"),
+            ascii_field(Tag::OffsetTimeOriginal, "-05:00"),
+        ]);
+        let exif_data = extract_exif_metadata(image.path(), default_offset).unwrap();
+        assert_eq!(
+            exif_data.timestamp.unwrap().offset().local_minus_utc(),
+            3600
+        );
+        assert_eq!(
+            exif_data
+                .datetime_original
+                .unwrap()
+                .offset()
+                .local_minus_utc(),
+            -5 * 3600
+        );
+        assert_eq!(exif_data.utc_offset.local_minus_utc(), -5 * 3600);
+
+        // No offset tag: the configured default applies
+        let image = write_jpeg_with_exif(&[ascii_field(Tag::DateTime, "2025:01:15 08:30:00")]);
+        let tokyo = FixedOffset::east_opt(9 * 3600).unwrap();
+        let exif_data = extract_exif_metadata(image.path(), tokyo).unwrap();
+        assert!(!exif_data.offset_from_exif);
+        assert_eq!(
+            exif_data.timestamp.unwrap(),
+            DateTime::parse_from_rfc3339("2025-01-15T08:30:00+09:00").unwrap()
+        );
+    }
+
+    fn gps_time_field(hours: u32, minutes: u32, seconds: (u32, u32)) -> Field {
+        Field {
+            tag: Tag::GPSTimeStamp,
+            ifd_num: In::PRIMARY,
+            value: Value::Rational(vec![
+                Rational::from((hours, 1)),
+                Rational::from((minutes, 1)),
+                Rational::from(seconds),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_extract_gps_timestamp() {
+        // GPS time is UTC whatever the default offset
+        let tokyo = FixedOffset::east_opt(9 * 3600).unwrap();
+        let image = write_jpeg_with_exif(&[
+            ascii_field(Tag::GPSDateStamp, "2025:08:01"),
+            gps_time_field(14, 25, (3050, 100)),
+        ]);
+        let exif_data = extract_exif_metadata(image.path(), tokyo).unwrap();
+        assert_eq!(
+            exif_data.gps_timestamp.unwrap(),
+            DateTime::parse_from_rfc3339("2025-08-01T14:25:30.5Z").unwrap()
+        );
+        assert!(exif_data.timestamp.is_none());
+
+        // Both tags are needed
+        let image = write_jpeg_with_exif(&[ascii_field(Tag::GPSDateStamp, "2025:08:01")]);
+        let exif_data = extract_exif_metadata(image.path(), tokyo).unwrap();
+        assert!(exif_data.gps_timestamp.is_none());
+
+        // Malformed stamps are ignored rather than failing the image
+        for fields in [
+            [
+                ascii_field(Tag::GPSDateStamp, "2025-08-01"),
+                gps_time_field(14, 25, (30, 1)),
+            ],
+            [
+                ascii_field(Tag::GPSDateStamp, "2025:08:01"),
+                gps_time_field(25, 0, (0, 1)),
+            ],
+        ] {
+            let image = write_jpeg_with_exif(&fields);
+            let exif_data = extract_exif_metadata(image.path(), tokyo).unwrap();
+            assert!(exif_data.gps_timestamp.is_none());
+        }
+    }
+
+    #[test]
+    fn test_capture_time_fallback_order() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let gps = [
+            ascii_field(Tag::GPSDateStamp, "2025:08:01"),
+            gps_time_field(12, 0, (0, 1)),
+        ];
+        let capture_time = |fields: &[Field]| {
+            let image = write_jpeg_with_exif(fields);
+            extract_exif_metadata(image.path(), utc)
+                .unwrap()
+                .capture_time()
+                .map(|time| time.to_rfc3339())
+        };
+
+        let mut fields = gps.to_vec();
+        assert_eq!(
+            capture_time(&fields).as_deref(),
+            Some("2025-08-01T12:00:00+00:00")
+        );
+
+        fields.push(ascii_field(Tag::DateTime, "2025:08:01 13:00:00"));
+        assert_eq!(
+            capture_time(&fields).as_deref(),
+            Some("2025-08-01T13:00:00+00:00")
+        );
+
+        fields.push(ascii_field(Tag::DateTimeOriginal, "2025:08:01 11:00:00"));
+        assert_eq!(
+            capture_time(&fields).as_deref(),
+            Some("2025-08-01T11:00:00+00:00")
+        );
+
+        assert_eq!(capture_time(&[ascii_field(Tag::Make, "Apple")]), None);
+    }
+
+    #[test]
+    fn test_blank_offset_tag_uses_default() {
+        let image = write_jpeg_with_exif(&[
+            ascii_field(Tag::DateTime, "2025:08:01 15:25:00"),
+            ascii_field(Tag::OffsetTime, "   :  "),
+        ]);
+
+        let default_offset = FixedOffset::east_opt(3600).unwrap();
+        let exif_data = extract_exif_metadata(image.path(), default_offset).unwrap();
+
+        assert_eq!(exif_data.utc_offset, default_offset);
+        assert!(!exif_data.offset_from_exif);
+    }
+
+    #[test]
+    fn test_extract_camera_make_and_model() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+
+        let image = write_jpeg_with_exif(&[
+            ascii_field(Tag::Make, "Apple"),
+            ascii_field(Tag::Model, "iPhone 15"),
+        ]);
+        let exif_data = extract_exif_metadata(image.path(), utc).unwrap();
+        assert_eq!(exif_data.camera_make.as_deref(), Some("Apple"));
+        assert_eq!(exif_data.camera_model.as_deref(), Some("iPhone 15"));
+        assert_eq!(exif_data.camera().as_deref(), Some("Apple iPhone 15"));
+
+        // The make is not repeated when the model already starts with it
+        let image = write_jpeg_with_exif(&[
+            ascii_field(Tag::Make, "Canon"),
+            ascii_field(Tag::Model, "Canon EOS R5"),
+        ]);
+        let exif_data = extract_exif_metadata(image.path(), utc).unwrap();
+        assert_eq!(exif_data.camera().as_deref(), Some("Canon EOS R5"));
+
+        let image = write_jpeg_with_exif(&[ascii_field(Tag::Make, "  ")]);
+        let exif_data = extract_exif_metadata(image.path(), utc).unwrap();
+        assert_eq!(exif_data.camera_make, None);
+        assert_eq!(exif_data.camera(), None);
+    }
+
+    #[test]
+    fn test_edit_indicators_software_tag() {
+        let indicators = |software: &str| {
+            let image = write_jpeg_with_exif(&[ascii_field(Tag::Software, software)]);
+            let exif =
+                extract_exif_metadata(image.path(), FixedOffset::east_opt(0).unwrap()).unwrap();
+            assert_eq!(exif.software.as_deref(), Some(software));
+            exif.edit_indicators()
+        };
+
+        assert_eq!(
+            indicators("Adobe Photoshop 25.0 (Macintosh)"),
+            vec!["image edited with Adobe Photoshop 25.0 (Macintosh)"]
+        );
+        assert_eq!(
+            indicators("GIMP 2.10.36"),
+            vec!["image edited with GIMP 2.10.36"]
+        );
+        // Camera firmware and phone OS versions are not editors
+        assert!(indicators("17.1.1").is_empty());
+        assert!(indicators("HDR+ 1.0.540104767zd").is_empty());
+    }
+
+    #[test]
+    fn test_edit_indicators_modify_date_skew() {
+        let indicators = |original: &str, modified: &str| {
+            let image = write_jpeg_with_exif(&[
+                ascii_field(Tag::DateTimeOriginal, original),
+                ascii_field(Tag::DateTime, modified),
+            ]);
+            extract_exif_metadata(image.path(), FixedOffset::east_opt(0).unwrap())
+                .unwrap()
+                .edit_indicators()
+        };
+
+        assert_eq!(
+            indicators("2025:08:01 10:00:00", "2025:08:04 12:00:00"),
+            vec!["image modified 3 days after it was taken (2025-08-04 12:00:00 +0000)"]
+        );
+        assert_eq!(
+            indicators("2025:08:01 10:00:00", "2025:08:01 15:30:00"),
+            vec!["image modified 5 hours after it was taken (2025-08-01 15:30:00 +0000)"]
+        );
+        assert_eq!(
+            indicators("2025:08:01 10:00:00", "2025:08:01 11:30:00"),
+            vec!["image modified 90 minutes after it was taken (2025-08-01 11:30:00 +0000)"]
+        );
+        // Written at capture, or shortly after by the phone's processing
+        assert!(indicators("2025:08:01 10:00:00", "2025:08:01 10:00:00").is_empty());
+        assert!(indicators("2025:08:01 10:00:00", "2025:08:01 10:45:00").is_empty());
+        // Only one of the two dates: nothing to compare
+        let image = write_jpeg_with_exif(&[ascii_field(Tag::DateTime, "2025:08:04 12:00:00")]);
+        let exif = extract_exif_metadata(image.path(), FixedOffset::east_opt(0).unwrap()).unwrap();
+        assert!(exif.edit_indicators().is_empty());
+    }
+
+    #[test]
+    fn test_extract_orientation() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let orientation = |value: u16| Field {
+            tag: Tag::Orientation,
+            ifd_num: In::PRIMARY,
+            value: Value::Short(vec![value]),
+        };
+
+        let image = write_jpeg_with_exif(&[orientation(6)]);
+        let exif_data = extract_exif_metadata(image.path(), utc).unwrap();
+        assert_eq!(exif_data.orientation, Some(6));
+
+        // Out-of-range values are ignored
+        let image = write_jpeg_with_exif(&[orientation(0)]);
+        let exif_data = extract_exif_metadata(image.path(), utc).unwrap();
+        assert_eq!(exif_data.orientation, None);
+
+        let image = write_jpeg_with_exif(&[ascii_field(Tag::Make, "Apple")]);
+        let exif_data = extract_exif_metadata(image.path(), utc).unwrap();
+        assert_eq!(exif_data.orientation, None);
+    }
+
+    #[test]
+    fn test_extract_pixel_dimensions() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let dimension = |tag, value: u32| Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value: Value::Long(vec![value]),
+        };
+
+        let image = write_jpeg_with_exif(&[
+            dimension(Tag::PixelXDimension, 4032),
+            dimension(Tag::PixelYDimension, 3024),
+        ]);
+        let exif_data = extract_exif_metadata(image.path(), utc).unwrap();
+        assert_eq!(exif_data.pixel_dimensions, Some((4032, 3024)));
+
+        // Both are needed
+        let image = write_jpeg_with_exif(&[dimension(Tag::PixelXDimension, 4032)]);
+        let exif_data = extract_exif_metadata(image.path(), utc).unwrap();
+        assert_eq!(exif_data.pixel_dimensions, None);
+    }
+
+    #[test]
+    fn test_extract_thumbnail() {
+        let thumbnail = [0xFF, 0xD8, 0xFF, 0xDB, 0x01, 0x02, 0x03, 0xFF, 0xD9];
+        let image = write_jpeg_with_thumbnail(
+            &[ascii_field(Tag::Make, "Apple")],
+            Some(thumbnail.as_slice()),
+        );
+        assert_eq!(
+            extract_thumbnail(image.path()).unwrap(),
+            Some(thumbnail.to_vec())
+        );
+
+        // EXIF without a thumbnail, and no EXIF at all
+        let image = write_jpeg_with_exif(&[ascii_field(Tag::Make, "Apple")]);
+        assert_eq!(extract_thumbnail(image.path()).unwrap(), None);
+        let mut file = NamedTempFile::with_suffix(".jpg").unwrap();
+        file.write_all(&[0xFF, 0xD8, 0xFF, 0xD9]).unwrap();
+        assert_eq!(extract_thumbnail(file.path()).unwrap(), None);
+
+        assert!(matches!(
+            extract_thumbnail("nonexistent.jpg"),
+            Err(ExifError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_extract_exif_metadata_from_heic() {
+        // Minimal HEIC from the kamadak-exif test suite; its EXIF holds only ExifVersion
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/exif.heic");
+        let exif_data = extract_exif_metadata(path, FixedOffset::east_opt(0).unwrap()).unwrap();
+
+        assert!(exif_data.gps_coordinates.is_none());
+        assert!(exif_data.datetime_original.is_none());
+        assert!(exif_data.camera().is_none());
+    }
+
+    #[test]
+    fn test_detect_image_container() {
+        assert_eq!(
+            ImageContainer::detect(&[0xFF, 0xD8, 0xFF, 0xE1]),
+            Some(ImageContainer::Jpeg)
+        );
+        assert_eq!(
+            ImageContainer::detect(b"II*\0\x08\0\0\0"),
+            Some(ImageContainer::Tiff)
+        );
+        assert_eq!(
+            ImageContainer::detect(b"MM\0*\0\0\0\x08"),
+            Some(ImageContainer::Tiff)
+        );
+        // Olympus ORF and Panasonic RW2 raws
+        assert_eq!(
+            ImageContainer::detect(b"IIRO\x08\0\0\0"),
+            Some(ImageContainer::Tiff)
+        );
+        assert_eq!(
+            ImageContainer::detect(b"IIU\0\x08\0\0\0"),
+            Some(ImageContainer::Tiff)
+        );
+        assert_eq!(
+            ImageContainer::detect(b"\x89PNG\r\n\x1a\n"),
+            Some(ImageContainer::Png)
+        );
+        assert_eq!(
+            ImageContainer::detect(b"RIFF\0\0\0\0WEBPVP8X"),
+            Some(ImageContainer::Webp)
+        );
+        assert_eq!(
+            ImageContainer::detect(b"\0\0\0\x18ftypheic\0\0\0\0mif1heic"),
+            Some(ImageContainer::Heif)
+        );
+
+        assert_eq!(
+            ImageContainer::detect(b"\0\0\0\x1cftypavif\0\0\0\0avifmif1miaf"),
+            Some(ImageContainer::Avif)
+        );
+
+        assert_eq!(ImageContainer::detect(b"GIF89a"), None);
+        assert_eq!(ImageContainer::detect(b"II"), None);
+        assert_eq!(ImageContainer::detect(b""), None);
+    }
+
+    /// Writes a bare TIFF (as a DNG or other raw would be) carrying the given fields
+    fn write_tiff_with_exif(fields: &[Field], suffix: &str, magic: &[u8; 4]) -> NamedTempFile {
+        let mut writer = Writer::new();
+        for field in fields {
+            writer.push_field(field);
+        }
+        let mut tiff = Cursor::new(Vec::new());
+        writer.write(&mut tiff, true).unwrap();
+        let mut tiff = tiff.into_inner();
+        tiff[..4].copy_from_slice(magic);
+
+        let mut file = NamedTempFile::with_suffix(suffix).unwrap();
+        file.write_all(&tiff).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_extract_exif_metadata_from_tiff_and_raws() {
+        let fields = [
+            ascii_field(Tag::Make, "Olympus"),
+            ascii_field(Tag::DateTimeOriginal, "2025:08:01 15:25:00"),
+        ];
+        let utc = FixedOffset::east_opt(0).unwrap();
+
+        for (suffix, magic) in [(".tif", b"II*\0"), (".dng", b"II*\0"), (".orf", b"IIRO")] {
+            let image = write_tiff_with_exif(&fields, suffix, magic);
+            let exif_data = extract_exif_metadata(image.path(), utc)
+                .unwrap_or_else(|e| panic!("{suffix}: {e}"));
+            assert_eq!(exif_data.camera_make.as_deref(), Some("Olympus"));
+            assert!(exif_data.datetime_original.is_some());
+        }
+
+        // Big-endian TIFF from the kamadak-exif test suite
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/exif.tif");
+        assert!(extract_exif_metadata(path, utc).is_ok());
+    }
+
+    #[test]
+    fn test_extract_exif_metadata_from_avif_fails_gracefully() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+
+        // HEIF-compatible AVIF without a metadata box
+        let mut avif = NamedTempFile::with_suffix(".avif").unwrap();
+        avif.write_all(b"\0\0\0\x1cftypavif\0\0\0\0avifmif1miaf")
+            .unwrap();
+        let error = extract_exif_metadata(avif.path(), utc).unwrap_err();
+        assert!(matches!(error, ExifError::MissingExif), "{error}");
+
+        // Without mif1 the EXIF reader does not recognize the container
+        let mut avif = NamedTempFile::with_suffix(".avif").unwrap();
+        avif.write_all(b"\0\0\0\x18ftypavif\0\0\0\0avifmiaf")
+            .unwrap();
+        let error = extract_exif_metadata(avif.path(), utc).unwrap_err();
+        assert!(
+            matches!(error, ExifError::UnsupportedContainer(_)),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_exif_errors_distinguish_container_from_corruption() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+
+        let mut gif = NamedTempFile::with_suffix(".gif").unwrap();
+        gif.write_all(b"GIF89a\x01\0\x01\0\0\0\0;").unwrap();
+        let error = extract_exif_metadata(gif.path(), utc).unwrap_err();
+        assert!(matches!(error, ExifError::UnsupportedContainer(_)));
+        assert!(error.to_string().contains("Unsupported image container"));
+
+        // TIFF magic followed by an IFD offset pointing past the end of the file
+        let mut tiff = NamedTempFile::with_suffix(".tif").unwrap();
+        tiff.write_all(b"II*\0\xff\xff\0\0").unwrap();
+        let error = extract_exif_metadata(tiff.path(), utc).unwrap_err();
+        assert!(matches!(error, ExifError::Parsing(_)));
+        assert!(error.to_string().contains("Corrupt EXIF data"));
+
+        // A JPEG without an APP1 segment
+        let mut jpeg = NamedTempFile::with_suffix(".jpg").unwrap();
+        jpeg.write_all(&[0xFF, 0xD8, 0xFF, 0xD9]).unwrap();
+        let error = extract_exif_metadata(jpeg.path(), utc).unwrap_err();
+        assert!(matches!(error, ExifError::MissingExif));
+    }
+
+    #[test]
+    fn test_exif_cache_reuses_unchanged_images() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let cache = ExifCache::new(8);
+        let image = write_jpeg_with_exif(&[ascii_field(Tag::Make, "Canon")]);
+        let first = cache.extract(image.path(), utc).unwrap();
+        assert_eq!(first.camera_make.as_deref(), Some("Canon"));
+
+        // Overwrite the file with junk of the same size and restore its mtime: the
+        // cached data is returned without parsing the file again
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(image.path())
+            .unwrap();
+        let modified = file.metadata().unwrap().modified().unwrap();
+        let size = file.metadata().unwrap().len() as usize;
+        (&file).write_all(&vec![0u8; size]).unwrap();
+        file.set_modified(modified).unwrap();
+        let cached = cache.extract(image.path(), utc).unwrap();
+        assert_eq!(cached.camera_make.as_deref(), Some("Canon"));
+        assert_eq!(cache.len(), 1);
+
+        // A new mtime invalidates the entry, so the junk is read
+        file.set_modified(modified + Duration::from_secs(1))
+            .unwrap();
+        assert!(cache.extract(image.path(), utc).is_err());
+
+        // Deleted files are not served from the cache
+        let path = image.path().to_path_buf();
+        drop(image);
+        assert!(matches!(
+            cache.extract(&path, utc).unwrap_err(),
+            ExifError::Io(_)
+        ));
+    }
+
+    #[test]
+    fn test_exif_cache_evicts_least_recently_used() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let cache = ExifCache::new(2);
+        let canon = write_jpeg_with_exif(&[ascii_field(Tag::Make, "Canon")]);
+        let nikon = write_jpeg_with_exif(&[ascii_field(Tag::Make, "Nikon")]);
+        let sony = write_jpeg_with_exif(&[ascii_field(Tag::Make, "Sony")]);
+
+        cache.extract(canon.path(), utc).unwrap();
+        cache.extract(nikon.path(), utc).unwrap();
+        // Using Canon again leaves Nikon as the least recently used
+        cache.extract(canon.path(), utc).unwrap();
+        cache.extract(sony.path(), utc).unwrap();
+        assert_eq!(cache.len(), 2);
+
+        let entries = cache.entries.lock().unwrap();
+        assert!(entries.0.contains_key(canon.path()));
+        assert!(!entries.0.contains_key(nikon.path()));
+        assert!(entries.0.contains_key(sony.path()));
+        drop(entries);
+
+        // A capacity of 0 caches nothing
+        let disabled = ExifCache::new(0);
+        disabled.extract(canon.path(), utc).unwrap();
+        assert!(disabled.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_extraction_times_out_on_blocking_reads() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let image = write_jpeg_with_exif(&[ascii_field(Tag::Make, "Canon")]);
+        let exif_data =
+            extract_exif_metadata_with_timeout(image.path(), utc, Duration::from_secs(5), None)
+                .await
+                .unwrap();
+        assert_eq!(exif_data.camera_make.as_deref(), Some("Canon"));
+
+        // Opening a FIFO blocks until something opens it for writing
+        let dir = tempfile::tempdir().unwrap();
+        let fifo = dir.path().join("stalled.jpg");
+        let status = std::process::Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let timeout = Duration::from_millis(100);
+        let error = extract_exif_metadata_with_timeout(&fifo, utc, timeout, None)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, ExifError::Timeout(t) if t == timeout),
+            "{error}"
+        );
+        assert!(error.to_string().contains("timed out"));
+
+        // Unblock the abandoned read so the runtime can shut down
+        drop(std::fs::OpenOptions::new().write(true).open(&fifo).unwrap());
+    }
+
     // Note: Integration tests with real images should be in the tests/ directory
     // since we need actual image files with EXIF data for testing
 }
@@ -0,0 +1,112 @@
+use crate::validation::avif::is_avif;
+
+use std::path::Path;
+use tempfile::NamedTempFile;
+use thiserror::Error;
+use tokio::process::Command;
+use tracing::debug;
+
+#[derive(Debug, Error)]
+pub enum HeicError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("HEIC transcode failed: {0}")]
+    Transcode(String),
+}
+
+/// `ftyp` brands identifying HEIF still images, including HEVC-coded HEIC
+const HEIF_BRANDS: &[&[u8; 4]] = &[b"heic", b"heix", b"heif", b"mif1"];
+
+/// Returns true when the bytes start with an ISO-BMFF `ftyp` box carrying a HEIF brand
+///
+/// iPhones write `heic` as the major brand; other encoders may only list `mif1`
+/// among the compatible brands, so those are checked as well. AVIF shares the
+/// container and the `mif1` brand, but is not HEIF as far as decoders are concerned.
+pub fn is_heif(bytes: &[u8]) -> bool {
+    has_ftyp_brand(bytes, HEIF_BRANDS) && !is_avif(bytes)
+}
+
+/// Returns true when the bytes start with an ISO-BMFF `ftyp` box listing one of
+/// `brands`, as the major brand or among the compatible brands
+pub(crate) fn has_ftyp_brand(bytes: &[u8], brands: &[&[u8; 4]]) -> bool {
+    if bytes.len() < 12 || bytes[4..8] != *b"ftyp" {
+        return false;
+    }
+
+    let box_size = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+    let ftyp = &bytes[..box_size.clamp(12, bytes.len())];
+
+    // Major brand, then (after the 4-byte minor version) the compatible brands
+    std::iter::once(&ftyp[8..12])
+        .chain(ftyp.get(16..).unwrap_or_default().chunks_exact(4))
+        .any(|brand| brands.iter().any(|wanted| brand == *wanted))
+}
+
+/// Converts a HEIC/HEIF image to JPEG with an external tool
+///
+/// The command is invoked as `<command> <input> <output.jpg>`, which matches
+/// libheif's `heif-convert` as well as ImageMagick's `magick`.
+pub async fn transcode_to_jpeg(input: &Path, command: &str) -> Result<Vec<u8>, HeicError> {
+    let output = NamedTempFile::with_suffix(".jpg")?;
+    debug!("Transcoding {:?} to JPEG with {}", input, command);
+
+    let result = Command::new(command)
+        .arg(input)
+        .arg(output.path())
+        .output()
+        .await
+        .map_err(|e| HeicError::Transcode(format!("cannot run {command}: {e}")))?;
+
+    if !result.status.success() {
+        return Err(HeicError::Transcode(format!(
+            "{command} exited with {}: {}",
+            result.status,
+            String::from_utf8_lossy(&result.stderr).trim()
+        )));
+    }
+
+    let jpeg = tokio::fs::read(output.path()).await?;
+    if !jpeg.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Err(HeicError::Transcode(format!(
+            "{command} did not produce a JPEG"
+        )));
+    }
+    Ok(jpeg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_heif_magic_bytes() {
+        // iPhone HEIC: major brand heic, compatible mif1 and heic
+        assert!(is_heif(b"\0\0\0\x18ftypheic\0\0\0\0mif1heic"));
+        assert!(is_heif(b"\0\0\0\x18ftypheix\0\0\0\0mif1heix"));
+        // Generic HEIF identified only by a compatible brand
+        assert!(is_heif(b"\0\0\0\x18ftypXXXX\0\0\0\0XXXXmif1"));
+        assert!(is_heif(b"\0\0\0\x14ftypmif1\0\0\0\0mif1"));
+
+        // AVIF lists mif1 too, but is detected separately
+        assert!(!is_heif(b"\0\0\0\x1cftypavif\0\0\0\0avifmif1miaf"));
+
+        // MP4 video is also ISO-BMFF, but not HEIF
+        assert!(!is_heif(b"\0\0\0\x18ftypmp42\0\0\0\0mp42isom"));
+        // Brands beyond the ftyp box do not count
+        assert!(!is_heif(b"\0\0\0\x14ftypmp42\0\0\0\0mp42mif1"));
+        assert!(!is_heif(&[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, 0x4A, 0x46]));
+        assert!(!is_heif(b"ftyp"));
+    }
+
+    #[tokio::test]
+    async fn test_transcode_reports_missing_command() {
+        let result = transcode_to_jpeg(
+            Path::new("/tmp/missing.heic"),
+            "image-checker-no-such-transcoder",
+        )
+        .await;
+        assert!(
+            matches!(result, Err(HeicError::Transcode(message)) if message.contains("cannot run"))
+        );
+    }
+}
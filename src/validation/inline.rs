@@ -0,0 +1,191 @@
+use crate::validation::avif::is_avif;
+use crate::validation::heic::is_heif;
+
+use base64::{engine::general_purpose, Engine as _};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum InlineImageError {
+    #[error("Invalid base64 image data: {0}")]
+    InvalidBase64(#[from] base64::DecodeError),
+    #[error("Unsupported inline image type: {0}")]
+    UnsupportedType(String),
+    #[error("Inline image is {size} bytes, exceeding the {limit} byte limit")]
+    TooLarge { size: usize, limit: usize },
+}
+
+/// An image submitted inline in the request body rather than referenced by path
+#[derive(Debug, Clone)]
+pub struct InlineImage {
+    pub bytes: Vec<u8>,
+    /// File extension matching the detected format, used when staging to disk
+    pub extension: &'static str,
+}
+
+/// Returns true when the `image` field carries image data rather than a path
+///
+/// Data URLs (`data:image/png;base64,...`) are always inline. Raw base64 is only
+/// treated as inline when its first bytes decode to a known image signature, so
+/// existing callers passing a path in `image` keep working.
+#[must_use]
+pub fn is_inline_image(value: &str) -> bool {
+    if value.starts_with("data:") {
+        return true;
+    }
+
+    // 16 base64 characters decode to exactly 12 bytes without padding,
+    // which is enough to cover every signature we recognize
+    value
+        .get(..16)
+        .and_then(|prefix| general_purpose::STANDARD.decode(prefix).ok())
+        .is_some_and(|bytes| detect_extension(&bytes).is_some())
+}
+
+/// Decodes an inline image, enforcing `max_bytes` on the decoded size
+///
+/// The size is estimated from the encoded length before decoding so oversized
+/// payloads are rejected without allocating the full buffer.
+pub fn decode_inline_image(value: &str, max_bytes: usize) -> Result<InlineImage, InlineImageError> {
+    let (declared_type, encoded) = match value.strip_prefix("data:") {
+        Some(data_url) => {
+            let (header, data) = data_url.split_once(',').ok_or_else(|| {
+                InlineImageError::UnsupportedType("data URL is missing ',' separator".into())
+            })?;
+            let media_type = header.strip_suffix(";base64").ok_or_else(|| {
+                InlineImageError::UnsupportedType(format!(
+                    "data URL must be base64 encoded: {header}"
+                ))
+            })?;
+            (Some(media_type), data)
+        }
+        None => (None, value),
+    };
+
+    let encoded = encoded.trim();
+    let estimated_size = encoded.len() / 4 * 3;
+    if estimated_size > max_bytes + 2 {
+        return Err(InlineImageError::TooLarge {
+            size: estimated_size,
+            limit: max_bytes,
+        });
+    }
+
+    let bytes = general_purpose::STANDARD.decode(encoded)?;
+    if bytes.len() > max_bytes {
+        return Err(InlineImageError::TooLarge {
+            size: bytes.len(),
+            limit: max_bytes,
+        });
+    }
+
+    let extension = detect_extension(&bytes).ok_or_else(|| {
+        InlineImageError::UnsupportedType(
+            declared_type
+                .unwrap_or("unrecognized image signature")
+                .to_string(),
+        )
+    })?;
+
+    Ok(InlineImage { bytes, extension })
+}
+
+/// Maps the leading magic bytes of an image to its file extension
+pub(crate) fn detect_extension(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("jpg")
+    } else if bytes.starts_with(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some("png")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("gif")
+    } else if bytes.starts_with(b"BM") {
+        Some("bmp")
+    } else if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && bytes[8..12] == *b"WEBP" {
+        Some("webp")
+    } else if is_heif(bytes) {
+        Some("heic")
+    } else if is_avif(bytes) {
+        Some("avif")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1x1 transparent PNG
+    const PNG_BASE64: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==";
+
+    #[test]
+    fn test_decode_png_data_url() {
+        let data_url = format!("data:image/png;base64,{PNG_BASE64}");
+
+        assert!(is_inline_image(&data_url));
+        let image = decode_inline_image(&data_url, 1024).unwrap();
+        assert_eq!(image.extension, "png");
+        assert!(image.bytes.starts_with(&[0x89, 0x50, 0x4E, 0x47]));
+    }
+
+    #[test]
+    fn test_decode_raw_base64() {
+        assert!(is_inline_image(PNG_BASE64));
+        let image = decode_inline_image(PNG_BASE64, 1024).unwrap();
+        assert_eq!(image.extension, "png");
+    }
+
+    #[test]
+    fn test_decode_heic() {
+        let heic = general_purpose::STANDARD.encode(b"\0\0\0\x18ftypheic\0\0\0\0mif1heic");
+
+        assert!(is_inline_image(&heic));
+        let image = decode_inline_image(&heic, 1024).unwrap();
+        assert_eq!(image.extension, "heic");
+    }
+
+    #[test]
+    fn test_decode_avif() {
+        let avif = general_purpose::STANDARD.encode(b"\0\0\0\x1cftypavif\0\0\0\0avifmif1miaf");
+
+        assert!(is_inline_image(&avif));
+        let image = decode_inline_image(&avif, 1024).unwrap();
+        assert_eq!(image.extension, "avif");
+    }
+
+    #[test]
+    fn test_paths_are_not_inline() {
+        assert!(!is_inline_image("/tmp/image.jpg"));
+        assert!(!is_inline_image("photos/sunset.jpg"));
+        assert!(!is_inline_image("file:///tmp/image.jpg"));
+        assert!(!is_inline_image("abcd"));
+    }
+
+    #[test]
+    fn test_oversized_payload_rejected() {
+        let data_url = format!("data:image/png;base64,{PNG_BASE64}");
+
+        let result = decode_inline_image(&data_url, 16);
+        assert!(matches!(
+            result,
+            Err(InlineImageError::TooLarge { limit: 16, .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_base64_rejected() {
+        let result = decode_inline_image("data:image/png;base64,not-base64!!", 1024);
+        assert!(matches!(result, Err(InlineImageError::InvalidBase64(_))));
+    }
+
+    #[test]
+    fn test_unrecognized_image_rejected() {
+        let encoded = general_purpose::STANDARD.encode(b"plain text, not an image");
+        let result = decode_inline_image(&format!("data:text/plain;base64,{encoded}"), 1024);
+        match result {
+            Err(InlineImageError::UnsupportedType(media_type)) => {
+                assert_eq!(media_type, "text/plain")
+            }
+            other => panic!("Expected UnsupportedType error, got {other:?}"),
+        }
+    }
+}
@@ -1,10 +1,19 @@
-use base64::{engine::general_purpose, Engine as _};
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
-use std::error::Error as StdError;
+use crate::circuit_breaker::CircuitBreaker;
+use crate::config::{LlmBackend, LlmResponseParser};
+use crate::metrics;
+use crate::validation::avif::{is_avif, transcode_to_png, AvifError};
+use crate::validation::exif::{extract_exif_metadata, extract_thumbnail, ImageContainer};
+use crate::validation::heic::{is_heif, transcode_to_jpeg, HeicError};
+use crate::validation::resize::{prepare_for_model, prepare_thumbnail};
+use crate::validation::vision::{create_backend, GenerationOptions, PoolOptions, VisionBackend};
+
+use chrono::FixedOffset;
+use rand::Rng;
+use regex::Regex;
 use std::fs;
 use std::path::Path;
-use std::time::Duration;
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
 use thiserror::Error;
 use tokio::time::sleep;
 use tracing::{debug, error, warn};
@@ -25,96 +34,484 @@ pub enum LlmError {
     Timeout,
     #[error("Maximum retries exceeded")]
     MaxRetriesExceeded,
+    #[error("LLM circuit breaker is open")]
+    CircuitOpen,
+    #[error("Invalid prompt template: {0}")]
+    InvalidPromptTemplate(String),
+    #[error("HEIC image error: {0}")]
+    Heic(#[from] HeicError),
+    #[error("AVIF image error: {0}")]
+    Avif(#[from] AvifError),
+    #[error("Image format not supported by the vision model: {0}")]
+    UnsupportedByBackend(String),
 }
 
-#[derive(Debug, Serialize)]
-struct ChatCompletionRequest {
-    model: String,
-    messages: Vec<Message>,
-    stream: bool,
-    max_tokens: u32,
-    temperature: f32,
+/// Placeholder replaced with the content description when rendering a prompt
+pub const CONTENT_PLACEHOLDER: &str = "{content}";
+
+const DEFAULT_PROMPT_TEMPLATE: &str = "You are an image validation assistant. Please analyze this image and determine if it matches the following description: \"{content}\"\n\n\
+    Respond with either:\n\
+    - \"ACCEPTED\" if the image clearly matches the description\n\
+    - \"REJECTED: [reason]\" if the image does not match, followed by a brief explanation\n\n\
+    On the next line, give your confidence in that verdict as a number between 0 and 1, formatted as \"SCORE: 0.87\".\n\n\
+    Be precise and focus on the key elements mentioned in the description. If the description mentions specific objects, locations, or characteristics, verify their presence in the image.";
+
+/// The instruction sent to the model alongside each image
+///
+/// Custom templates may use any language or phrasing, but with the default `prefix`
+/// response parser replies are parsed by [`ContentVerdict::parse`], so a template must
+/// still ask the model to start its reply with `ACCEPTED` or `REJECTED: reason` and
+/// may ask for a `SCORE: 0.87` line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptTemplate {
+    template: String,
 }
 
-#[derive(Debug, Serialize)]
-struct Message {
-    role: String,
-    content: Vec<Content>,
+impl Default for PromptTemplate {
+    fn default() -> Self {
+        Self {
+            template: DEFAULT_PROMPT_TEMPLATE.to_string(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct Content {
-    #[serde(rename = "type")]
-    content_type: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    text: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    image_url: Option<ImageUrl>,
+impl PromptTemplate {
+    /// Wraps a template, which must contain the `{content}` placeholder
+    pub fn new(template: String) -> Result<Self, LlmError> {
+        if !template.contains(CONTENT_PLACEHOLDER) {
+            return Err(LlmError::InvalidPromptTemplate(format!(
+                "template must contain the {CONTENT_PLACEHOLDER} placeholder"
+            )));
+        }
+
+        let upper = template.to_uppercase();
+        if !upper.contains("ACCEPTED") || !upper.contains("REJECTED") {
+            warn!("Prompt template does not mention ACCEPTED/REJECTED; replies may not parse");
+        }
+
+        Ok(Self { template })
+    }
+
+    /// Reads a template from `PROMPT_TEMPLATE_PATH`
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, LlmError> {
+        Self::new(fs::read_to_string(path)?)
+    }
+
+    pub fn render(&self, content_description: &str) -> String {
+        self.template
+            .replace(CONTENT_PLACEHOLDER, content_description)
+    }
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct ImageUrl {
-    url: String,
+/// The model's decision on whether an image matches its description
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentVerdict {
+    pub accepted: bool,
+    /// Model-reported confidence in `[0.0, 1.0]`
+    pub confidence: f32,
+    pub explanation: String,
 }
 
-#[derive(Debug, Deserialize)]
-struct ChatCompletionResponse {
-    choices: Vec<Choice>,
+/// Delay before the first retry of a failed LLM call, doubled after each further failure
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest delay between retries of a failed LLM call, jitter included
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Placeholder replaced with the model's first reply in the judge prompt
+const REPLY_PLACEHOLDER: &str = "{reply}";
+
+/// Second question asked by the `judge` parser about a reply without a verdict prefix
+const JUDGE_PROMPT_TEMPLATE: &str = "An assistant was asked whether this image matches the description \"{content}\" and replied:\n\n\
+    {reply}\n\n\
+    Did the assistant say that the image matches the description? Respond with only \"ACCEPTED\" or \"REJECTED\".";
+
+/// Prose that says the image does not match; checked before [`PROSE_MATCH`]
+static PROSE_MISMATCH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)^\W*no\b|\b(?:does|do|did|is|are|was|were)(?:\s+not|n['’]t)\s+(?:match|show|contain|depict|include|appear|correspond)|\b(?:cannot|can['’]t)\s+see\b|\bnot\s+(?:a\s+)?match|\bmismatch|\bno\s+(?:match|sign|evidence)\b",
+    )
+    .expect("valid mismatch pattern")
+});
+
+/// Prose that says the image matches
+static PROSE_MATCH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^\W*(?:yes|correct)\b|\bmatch(?:es|ing)?\b|\b(?:shows|depicts|contains)\b")
+        .expect("valid match pattern")
+});
+
+/// Words a reply gives its verdict with, e.g. `PASS` / `FAIL` for models told to use them
+///
+/// Matching ignores case and only counts whole words. By default a keyword must open
+/// the reply; with [`anywhere`](Self::anywhere) it may appear at any position, and
+/// reject keywords are looked for first. Replies with no keyword get the default
+/// verdict, which rejects unless set otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerdictKeywords {
+    accept: Vec<String>,
+    reject: Vec<String>,
+    anywhere: bool,
+    default_accepted: bool,
 }
 
-#[derive(Debug, Deserialize)]
-struct Choice {
-    message: ResponseMessage,
+impl Default for VerdictKeywords {
+    /// `ACCEPTED` and `REJECTED` opening the reply, as the default prompt asks for
+    fn default() -> Self {
+        Self::new(vec!["ACCEPTED".to_string()], vec!["REJECTED".to_string()])
+    }
+}
+
+impl VerdictKeywords {
+    pub fn new(accept: Vec<String>, reject: Vec<String>) -> Self {
+        Self {
+            accept,
+            reject,
+            anywhere: false,
+            default_accepted: false,
+        }
+    }
+
+    /// Looks for keywords anywhere in the reply rather than only at its start
+    #[must_use]
+    pub fn anywhere(mut self) -> Self {
+        self.anywhere = true;
+        self
+    }
+
+    /// Accepts replies without a keyword instead of rejecting them
+    #[must_use]
+    pub fn with_default_verdict(mut self, accepted: bool) -> Self {
+        self.default_accepted = accepted;
+        self
+    }
+
+    /// The verdict a keyword in the reply gives, if any
+    fn verdict(&self, response: &str) -> Option<bool> {
+        let response = response.trim_start();
+        let found = |keywords: &[String]| {
+            keywords.iter().any(|keyword| {
+                if self.anywhere {
+                    word_starts(response)
+                        .any(|start| strip_keyword(&response[start..], keyword).is_some())
+                } else {
+                    strip_keyword(response, keyword).is_some()
+                }
+            })
+        };
+
+        if found(&self.reject) {
+            Some(false)
+        } else if found(&self.accept) {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    /// The rest of a line that opens with a keyword
+    fn strip_prefix<'a>(&self, line: &'a str) -> Option<&'a str> {
+        self.accept
+            .iter()
+            .chain(&self.reject)
+            .find_map(|keyword| strip_keyword(line, keyword))
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct ResponseMessage {
-    content: String,
+/// Byte offsets where words start, so keywords are not found inside other words
+fn word_starts(text: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut previous = None;
+    text.char_indices().filter_map(move |(index, c)| {
+        let starts_word = c.is_alphanumeric() && !previous.is_some_and(char::is_alphanumeric);
+        previous = Some(c);
+        starts_word.then_some(index)
+    })
 }
 
-#[derive(Debug, Clone)]
+/// `text` after a leading `keyword`, compared ignoring case, when it ends a word there
+fn strip_keyword<'a>(text: &'a str, keyword: &str) -> Option<&'a str> {
+    let mut chars = text.chars();
+    for expected in keyword.chars() {
+        let actual = chars.next()?;
+        if !actual.to_lowercase().eq(expected.to_lowercase()) {
+            return None;
+        }
+    }
+    let rest = chars.as_str();
+    (!keyword.is_empty() && !rest.starts_with(char::is_alphanumeric)).then_some(rest)
+}
+
+impl ContentVerdict {
+    /// Parses a reply of the form `ACCEPTED` / `REJECTED: reason` with an optional
+    /// `SCORE: 0.87` line
+    ///
+    /// A missing or unparseable score defaults to 1.0 when accepted and 0.0 when
+    /// rejected; out-of-range scores are clamped.
+    pub fn parse(response: &str) -> Self {
+        Self::parse_with(response, &VerdictKeywords::default())
+    }
+
+    /// Parses a reply giving its verdict with `keywords` instead of `ACCEPTED` / `REJECTED`
+    pub fn parse_with(response: &str, keywords: &VerdictKeywords) -> Self {
+        let accepted = keywords
+            .verdict(response)
+            .unwrap_or(keywords.default_accepted);
+        Self::from_reply(accepted, response, keywords)
+    }
+
+    /// Parses a reply that may answer in prose instead of `ACCEPTED` / `REJECTED`
+    ///
+    /// Replies with a verdict prefix are parsed by [`ContentVerdict::parse`]. Otherwise
+    /// the reply is accepted when it says yes or that the image matches or shows the
+    /// description, unless it also says no or that the image does not match; replies
+    /// that say neither are rejected.
+    pub fn parse_prose(response: &str) -> Self {
+        Self::parse_prose_with(response, &VerdictKeywords::default())
+    }
+
+    /// Like [`ContentVerdict::parse_prose`], with `keywords` read before the prose and
+    /// their default verdict given to replies that say neither
+    pub fn parse_prose_with(response: &str, keywords: &VerdictKeywords) -> Self {
+        if keywords.verdict(response).is_some() {
+            return Self::parse_with(response, keywords);
+        }
+
+        let accepted = if PROSE_MISMATCH.is_match(response) {
+            false
+        } else if PROSE_MATCH.is_match(response) {
+            true
+        } else {
+            keywords.default_accepted
+        };
+        Self::from_reply(accepted, response, keywords)
+    }
+
+    /// Reads the score and explanation of a reply whose verdict is already known
+    fn from_reply(accepted: bool, response: &str, keywords: &VerdictKeywords) -> Self {
+        let mut confidence = None;
+        let mut explanation = Vec::new();
+
+        for line in response.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let upper = line.to_uppercase();
+            if let Some(score) = upper.strip_prefix("SCORE:") {
+                confidence = confidence.or_else(|| {
+                    score
+                        .trim()
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|score| score.is_finite())
+                        .map(|score| score.clamp(0.0, 1.0))
+                });
+            } else if let Some(rest) = keywords.strip_prefix(line) {
+                let reason = rest.trim_start_matches([':', ' ', '-']);
+                if !reason.is_empty() {
+                    explanation.push(reason);
+                }
+            } else {
+                explanation.push(line);
+            }
+        }
+
+        Self {
+            accepted,
+            confidence: confidence.unwrap_or(if accepted { 1.0 } else { 0.0 }),
+            explanation: explanation.join(" "),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct LlmClient {
-    client: Client,
-    api_url: String,
-    model_name: String,
+    backend: Arc<dyn VisionBackend>,
+    /// Model asked instead of the backend's configured one, chosen per request
+    model: Option<String>,
     max_retries: u32,
+    prompt_template: PromptTemplate,
+    /// Command used to convert HEIC images to JPEG; `None` sends them unchanged
+    heic_transcode_command: Option<String>,
+    /// Command used to convert AVIF images to PNG; `None` sends them unchanged
+    avif_transcode_command: Option<String>,
+    /// Longest side, in pixels, of images sent to the model; `None` sends them at full size
+    max_image_dimension: Option<u32>,
+    /// Rotate images upright according to their EXIF orientation before sending them
+    auto_orient: bool,
+    /// Smallest longer side, in pixels, of an embedded EXIF thumbnail sent instead of
+    /// the image; `None` always sends the image itself
+    exif_thumbnail_min_dimension: Option<u32>,
+    /// Stops calling the backend after repeated failures
+    circuit_breaker: Arc<CircuitBreaker>,
+    /// How replies are turned into a verdict
+    response_parser: LlmResponseParser,
+    /// Words replies give their verdict with
+    verdict_keywords: VerdictKeywords,
 }
 
 impl LlmClient {
+    /// Creates a client for an OpenAI-compatible chat completions endpoint
     pub fn new(api_url: String, model_name: String, timeout: Duration) -> Self {
-        let client = Client::builder()
-            .timeout(timeout)
-            .build()
-            .expect("Failed to create HTTP client");
-
-        Self {
-            client,
+        Self::with_backend(create_backend(
+            LlmBackend::OpenAi,
             api_url,
             model_name,
+            timeout,
+            GenerationOptions::default(),
+            PoolOptions::default(),
+        ))
+    }
+
+    /// Creates a client that sends requests through the given backend
+    pub fn with_backend(backend: Arc<dyn VisionBackend>) -> Self {
+        Self {
+            backend,
+            model: None,
             max_retries: 3,
+            prompt_template: PromptTemplate::default(),
+            heic_transcode_command: None,
+            avif_transcode_command: None,
+            max_image_dimension: None,
+            auto_orient: false,
+            exif_thumbnail_min_dimension: None,
+            circuit_breaker: Arc::new(CircuitBreaker::default()),
+            response_parser: LlmResponseParser::default(),
+            verdict_keywords: VerdictKeywords::default(),
         }
     }
 
-    pub async fn validate_image_content<P: AsRef<Path>>(
+    /// Attempts each LLM call up to `max_retries` times before giving up
+    #[must_use]
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Asks `model` instead of the model the backend was created with
+    #[must_use]
+    pub fn with_model(mut self, model: String) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    /// Guards backend calls with `circuit_breaker`, which may be shared with other clients
+    #[must_use]
+    pub fn with_circuit_breaker(mut self, circuit_breaker: Arc<CircuitBreaker>) -> Self {
+        self.circuit_breaker = circuit_breaker;
+        self
+    }
+
+    /// Transcodes HEIC images to JPEG with `command` before sending them, since most
+    /// vision models cannot read HEIC
+    #[must_use]
+    pub fn with_heic_transcoding(mut self, command: String) -> Self {
+        self.heic_transcode_command = Some(command);
+        self
+    }
+
+    /// Transcodes AVIF images to PNG with `command` before sending them, since many
+    /// vision models cannot read AVIF
+    #[must_use]
+    pub fn with_avif_transcoding(mut self, command: String) -> Self {
+        self.avif_transcode_command = Some(command);
+        self
+    }
+
+    /// Downscales images whose longer side exceeds `max_dimension` pixels before sending
+    /// them, keeping request bodies and model latency down for large photos
+    #[must_use]
+    pub fn with_max_image_dimension(mut self, max_dimension: u32) -> Self {
+        self.max_image_dimension = Some(max_dimension);
+        self
+    }
+
+    /// Rotates and flips photos by their EXIF orientation before sending them, since
+    /// vision models ignore the tag and would otherwise see them sideways
+    #[must_use]
+    pub fn with_auto_orient(mut self) -> Self {
+        self.auto_orient = true;
+        self
+    }
+
+    /// Sends the image's embedded EXIF thumbnail instead of the image when its longer
+    /// side is at least `min_dimension` pixels, which is often enough for a coarse
+    /// content match and much faster for the model
+    #[must_use]
+    pub fn with_exif_thumbnail(mut self, min_dimension: u32) -> Self {
+        self.exif_thumbnail_min_dimension = Some(min_dimension);
+        self
+    }
+
+    /// Reads replies with `response_parser` instead of requiring a verdict prefix
+    #[must_use]
+    pub fn with_response_parser(mut self, response_parser: LlmResponseParser) -> Self {
+        self.response_parser = response_parser;
+        self
+    }
+
+    /// Reads verdicts given with `verdict_keywords` instead of `ACCEPTED` / `REJECTED`
+    #[must_use]
+    pub fn with_verdict_keywords(mut self, verdict_keywords: VerdictKeywords) -> Self {
+        self.verdict_keywords = verdict_keywords;
+        self
+    }
+
+    /// Replaces the default content-check prompt
+    #[must_use]
+    pub fn with_prompt_template(mut self, prompt_template: PromptTemplate) -> Self {
+        self.prompt_template = prompt_template;
+        self
+    }
+
+    /// Asks the model about the image and turns its reply into a verdict
+    ///
+    /// With the `judge` parser, a reply without a verdict prefix is sent back to the
+    /// model together with the image, asking it to classify the reply; the verdict
+    /// comes from that answer and the score and explanation from the original reply.
+    pub async fn content_verdict<P: AsRef<Path>>(
         &self,
         image_path: P,
         content_description: &str,
-    ) -> Result<String, LlmError> {
+    ) -> Result<ContentVerdict, LlmError> {
+        self.content_verdict_with_image(image_path, content_description)
+            .await
+            .map(|(verdict, _)| verdict)
+    }
+
+    /// Like [`LlmClient::content_verdict`], also returning the image bytes sent to the
+    /// model after transcoding, orientation and downscaling
+    pub async fn content_verdict_with_image<P: AsRef<Path>>(
+        &self,
+        image_path: P,
+        content_description: &str,
+    ) -> Result<(ContentVerdict, Vec<u8>), LlmError> {
         debug!("Validating image content for: {}", content_description);
 
-        let image_data = self.read_and_encode_image(&image_path).await?;
+        let image_data = self.read_image(&image_path).await?;
         let prompt = self.construct_validation_prompt(content_description);
-
         let response = self.call_llm_with_retry(&prompt, &image_data).await?;
-
         debug!("LLM response received: {} chars", response.len());
-        Ok(response)
+
+        let keywords = &self.verdict_keywords;
+        let verdict = match self.response_parser {
+            LlmResponseParser::Prefix => ContentVerdict::parse_with(&response, keywords),
+            LlmResponseParser::Keyword => ContentVerdict::parse_prose_with(&response, keywords),
+            LlmResponseParser::Judge if keywords.verdict(&response).is_some() => {
+                ContentVerdict::parse_with(&response, keywords)
+            }
+            LlmResponseParser::Judge => {
+                let judge_prompt = JUDGE_PROMPT_TEMPLATE
+                    .replace(CONTENT_PLACEHOLDER, content_description)
+                    .replace(REPLY_PLACEHOLDER, response.trim());
+                let judgement = self.call_llm_with_retry(&judge_prompt, &image_data).await?;
+                debug!("Judge classified the reply as: {}", judgement.trim());
+                // The judge prompt asks for ACCEPTED or REJECTED whatever the keywords
+                let accepted = ContentVerdict::parse(&judgement).accepted;
+                ContentVerdict::from_reply(accepted, &response, keywords)
+            }
+        };
+
+        debug!("Content validation result: {} -> {:?}", response, verdict);
+        Ok((verdict, image_data))
     }
 
-    async fn read_and_encode_image<P: AsRef<Path>>(
-        &self,
-        image_path: P,
-    ) -> Result<String, LlmError> {
+    async fn read_image<P: AsRef<Path>>(&self, image_path: P) -> Result<Vec<u8>, LlmError> {
         let path = image_path.as_ref();
 
         // Validate file exists
@@ -128,77 +525,141 @@ impl LlmClient {
         let image_bytes = fs::read(path)?;
 
         // Validate image format by checking file extension and magic bytes
-        self.validate_image_format(path, &image_bytes)?;
+        validate_image_format(path, &image_bytes)?;
 
-        // Encode to base64 (will be embedded in data URL for /v1/chat/completions)
-        let base64_data = general_purpose::STANDARD.encode(&image_bytes);
+        // TIFF and raw files carry EXIF we can validate, but vision models only take
+        // web image formats
+        if ImageContainer::detect(&image_bytes) == Some(ImageContainer::Tiff) {
+            return Err(LlmError::UnsupportedByBackend(
+                "TIFF/DNG images must be converted to JPEG before the content check".into(),
+            ));
+        }
 
-        Ok(base64_data)
-    }
+        if let Some(min_dimension) = self.exif_thumbnail_min_dimension {
+            if let Some(thumbnail) = self.exif_thumbnail(path, min_dimension).await {
+                return Ok(thumbnail);
+            }
+        }
 
-    fn validate_image_format<P: AsRef<Path>>(&self, path: P, bytes: &[u8]) -> Result<(), LlmError> {
-        let path = path.as_ref();
-        let extension = path
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .unwrap_or("")
-            .to_lowercase();
+        let image_bytes = match (&self.heic_transcode_command, &self.avif_transcode_command) {
+            (Some(command), _) if is_heif(&image_bytes) => transcode_to_jpeg(path, command).await?,
+            (_, Some(command)) if is_avif(&image_bytes) => transcode_to_png(path, command).await?,
+            _ => image_bytes,
+        };
 
-        // Check file extension
-        if !matches!(
-            extension.as_str(),
-            "jpg" | "jpeg" | "png" | "gif" | "bmp" | "webp"
-        ) {
-            return Err(LlmError::InvalidImage(format!(
-                "Unsupported image extension: {extension}"
-            )));
+        if self.max_image_dimension.is_none() && !self.auto_orient {
+            return Ok(image_bytes);
         }
+        Ok(self.prepare(path, image_bytes).await)
+    }
 
-        // Check magic bytes for common formats
-        if bytes.len() < 8 {
-            return Err(LlmError::InvalidImage("Image file too small".into()));
+    /// The image's EXIF thumbnail, if it has one of at least `min_dimension` pixels
+    ///
+    /// Read on a blocking thread; any failure falls back to sending the image itself.
+    async fn exif_thumbnail(&self, path: &Path, min_dimension: u32) -> Option<Vec<u8>> {
+        let image_path = path.to_path_buf();
+        let auto_orient = self.auto_orient;
+        let thumbnail = tokio::task::spawn_blocking(move || {
+            let Some(bytes) = extract_thumbnail(&image_path).map_err(|e| e.to_string())? else {
+                return Ok(None);
+            };
+            let orientation = auto_orient
+                .then(|| extract_exif_metadata(&image_path, FixedOffset::east_opt(0)?).ok())
+                .flatten()
+                .and_then(|exif_data| exif_data.orientation);
+            prepare_thumbnail(&bytes, min_dimension, orientation).map_err(|e| e.to_string())
+        })
+        .await;
+
+        match thumbnail {
+            Ok(Ok(Some(thumbnail))) => {
+                debug!(
+                    "Sending the {} byte EXIF thumbnail of {:?} to the model",
+                    thumbnail.len(),
+                    path
+                );
+                Some(thumbnail)
+            }
+            Ok(Ok(None)) => {
+                debug!("No usable EXIF thumbnail in {:?}", path);
+                None
+            }
+            Ok(Err(e)) => {
+                debug!("Cannot read the EXIF thumbnail of {:?}: {}", path, e);
+                None
+            }
+            Err(e) => {
+                warn!("Reading the EXIF thumbnail of {:?} panicked: {}", path, e);
+                None
+            }
         }
+    }
 
-        let is_valid = match extension.as_str() {
-            "jpg" | "jpeg" => bytes.starts_with(&[0xFF, 0xD8, 0xFF]),
-            "png" => bytes.starts_with(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]),
-            "gif" => bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a"),
-            "bmp" => bytes.starts_with(b"BM"),
-            "webp" => bytes[8..12] == *b"WEBP",
-            _ => true, // Allow other formats to pass through
-        };
+    /// Orients and downscales on a blocking thread, falling back to the original bytes
+    /// for formats the decoder cannot read
+    async fn prepare(&self, path: &Path, image_bytes: Vec<u8>) -> Vec<u8> {
+        let original = Arc::new(image_bytes);
+        let bytes = original.clone();
+        let (max_dimension, auto_orient) = (self.max_image_dimension, self.auto_orient);
+        let resized = tokio::task::spawn_blocking(move || {
+            prepare_for_model(&bytes, max_dimension, auto_orient)
+        })
+        .await;
 
-        if !is_valid {
-            return Err(LlmError::InvalidImage(format!(
-                "Invalid {extension} file format"
-            )));
+        match resized {
+            Ok(Ok(Some(resized))) => {
+                debug!(
+                    "Prepared {:?} for the model, {} to {} bytes",
+                    path,
+                    original.len(),
+                    resized.len()
+                );
+                resized
+            }
+            Ok(Ok(None)) => Arc::unwrap_or_clone(original),
+            Ok(Err(e)) => {
+                warn!("Cannot prepare {:?}, sending it unchanged: {}", path, e);
+                Arc::unwrap_or_clone(original)
+            }
+            Err(e) => {
+                warn!("Preparing {:?} panicked, sending it unchanged: {}", path, e);
+                Arc::unwrap_or_clone(original)
+            }
         }
-
-        Ok(())
     }
 
     fn construct_validation_prompt(&self, content_description: &str) -> String {
-        format!(
-            "You are an image validation assistant. Please analyze this image and determine if it matches the following description: \"{content_description}\"\n\n\
-            Respond with either:\n\
-            - \"ACCEPTED\" if the image clearly matches the description\n\
-            - \"REJECTED: [reason]\" if the image does not match, followed by a brief explanation\n\n\
-            Be precise and focus on the key elements mentioned in the description. If the description mentions specific objects, locations, or characteristics, verify their presence in the image."
-        )
+        self.prompt_template.render(content_description)
     }
 
     async fn call_llm_with_retry(
         &self,
         prompt: &str,
-        image_data: &str,
+        image_data: &[u8],
     ) -> Result<String, LlmError> {
         let mut attempt = 0;
-        let mut delay = Duration::from_millis(1000); // Start with 1 second
+        let mut delay = INITIAL_RETRY_DELAY;
 
         while attempt < self.max_retries {
-            match self.call_llm(prompt, image_data).await {
-                Ok(response) => return Ok(response),
+            if !self.circuit_breaker.try_acquire() {
+                warn!("LLM circuit is open; not calling the backend");
+                return Err(LlmError::CircuitOpen);
+            }
+
+            let started = Instant::now();
+            let response = self
+                .backend
+                .analyze(prompt, image_data, self.model.as_deref())
+                .await;
+            metrics::record_llm_latency(started.elapsed());
+
+            match response {
+                Ok(response) => {
+                    self.circuit_breaker.record_success();
+                    return Ok(response);
+                }
                 Err(e) => {
+                    self.circuit_breaker.record_failure();
                     attempt += 1;
 
                     if attempt >= self.max_retries {
@@ -206,146 +667,97 @@ impl LlmClient {
                         return Err(LlmError::MaxRetriesExceeded);
                     }
 
+                    let wait = jittered(delay);
                     warn!(
                         "LLM call failed (attempt {}): {}. Retrying in {:?}",
-                        attempt, e, delay
+                        attempt, e, wait
                     );
-                    sleep(delay).await;
+                    sleep(wait).await;
 
-                    // Exponential backoff with jitter
-                    delay = std::cmp::min(delay * 2, Duration::from_secs(30));
+                    delay = std::cmp::min(delay * 2, MAX_RETRY_DELAY);
                 }
             }
         }
 
         Err(LlmError::MaxRetriesExceeded)
     }
+}
 
-    async fn call_llm(&self, prompt: &str, image_data: &str) -> Result<String, LlmError> {
-        let request = ChatCompletionRequest {
-            model: self.model_name.clone(),
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: vec![
-                    Content {
-                        content_type: "text".to_string(),
-                        text: Some(prompt.to_string()),
-                        image_url: None,
-                    },
-                    Content {
-                        content_type: "image_url".to_string(),
-                        text: None,
-                        image_url: Some(ImageUrl {
-                            url: format!("data:image/jpeg;base64,{image_data}"),
-                        }),
-                    },
-                ],
-            }],
-            stream: false,
-            max_tokens: 500,
-            temperature: 0.1,
-        };
-
-        // Debug logging: print request URL and payload
-        println!("=== OLLAMA REQUEST DEBUG ===");
-        println!("URL: {}", self.api_url);
-        println!("Headers: Content-Type: application/json");
-        println!("Request payload:");
-
-        // Create a debug version of the request with image data replaced
-        let debug_request = ChatCompletionRequest {
-            model: request.model.clone(),
-            messages: vec![Message {
-                role: request.messages[0].role.clone(),
-                content: request.messages[0]
-                    .content
-                    .iter()
-                    .map(|c| match c.content_type.as_str() {
-                        "text" => Content {
-                            content_type: "text".to_string(),
-                            text: c.text.clone(),
-                            image_url: None,
-                        },
-                        "image_url" => Content {
-                            content_type: "image_url".to_string(),
-                            text: None,
-                            image_url: Some(ImageUrl {
-                                url: "<image data>".to_string(),
-                            }),
-                        },
-                        _ => c.clone(),
-                    })
-                    .collect(),
-            }],
-            stream: request.stream,
-            max_tokens: request.max_tokens,
-            temperature: request.temperature,
-        };
-
-        println!(
-            "{}",
-            serde_json::to_string_pretty(&debug_request)
-                .unwrap_or_else(|_| "Failed to serialize request".to_string())
-        );
-        println!("Image data length: {} chars", image_data.len());
-        println!(
-            "Image data preview (first 100 chars): {}",
-            &image_data[..image_data.len().min(100)]
-        );
-        println!("============================");
-
-        debug!("Sending request to LLM API: {}", self.api_url);
-
-        let response = self.client.post(&self.api_url).json(&request).send().await;
-
-        match &response {
-            Ok(resp) => println!("✅ HTTP request successful, status: {}", resp.status()),
-            Err(e) => {
-                println!("❌ HTTP request failed: {e}");
-                if let Some(source) = StdError::source(e) {
-                    println!("   Source error: {source}");
-                }
-            }
-        }
+/// Spreads a retry delay randomly over 50% to 150% of itself, capped at [`MAX_RETRY_DELAY`]
+///
+/// Without jitter, jobs whose calls failed together retry together and hit a
+/// recovering backend in lockstep.
+fn jittered(delay: Duration) -> Duration {
+    let factor = rand::thread_rng().gen_range(0.5..=1.5);
+    std::cmp::min(delay.mul_f64(factor), MAX_RETRY_DELAY)
+}
 
-        let response = response?;
+/// Checks that the file extension is a supported image type and matches the magic bytes
+pub fn validate_image_format<P: AsRef<Path>>(path: P, bytes: &[u8]) -> Result<(), LlmError> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("")
+        .to_lowercase();
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            println!("❌ HTTP error response:");
-            println!("   Status: {status}");
-            println!("   Body: {error_text}");
-            return Err(LlmError::Api(format!("HTTP {status}: {error_text}")));
-        }
+    // Check file extension
+    if !matches!(
+        extension.as_str(),
+        "jpg"
+            | "jpeg"
+            | "png"
+            | "gif"
+            | "bmp"
+            | "webp"
+            | "heic"
+            | "heif"
+            | "avif"
+            | "tif"
+            | "tiff"
+            | "dng"
+    ) {
+        return Err(LlmError::InvalidImage(format!(
+            "Unsupported image extension: {extension}"
+        )));
+    }
 
-        let completion: ChatCompletionResponse = response.json().await?;
+    // Check magic bytes for common formats
+    if bytes.len() < 8 {
+        return Err(LlmError::InvalidImage("Image file too small".into()));
+    }
 
-        if completion.choices.is_empty() {
-            return Err(LlmError::Api(
-                "No choices returned from LLM API".to_string(),
-            ));
-        }
+    let is_valid = match extension.as_str() {
+        "jpg" | "jpeg" => bytes.starts_with(&[0xFF, 0xD8, 0xFF]),
+        "png" => bytes.starts_with(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]),
+        "gif" => bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a"),
+        "bmp" => bytes.starts_with(b"BM"),
+        "webp" => bytes[8..12] == *b"WEBP",
+        "heic" | "heif" => is_heif(bytes),
+        "avif" => is_avif(bytes),
+        "tif" | "tiff" | "dng" => ImageContainer::detect(bytes) == Some(ImageContainer::Tiff),
+        _ => true, // Allow other formats to pass through
+    };
 
-        Ok(completion.choices[0].message.content.trim().to_string())
+    if !is_valid {
+        return Err(LlmError::InvalidImage(format!(
+            "Invalid {extension} file format"
+        )));
     }
+
+    Ok(())
 }
 
+/// Asks the model whether the image matches the description, reading the reply with
+/// the client's response parser
 pub async fn validate_image_content<P: AsRef<Path>>(
     client: &LlmClient,
     image_path: P,
     content_description: &str,
-) -> Result<bool, LlmError> {
-    let response = client
-        .validate_image_content(image_path, content_description)
-        .await?;
-
-    // Parse the response to determine if validation passed
-    let is_accepted = response.to_uppercase().starts_with("ACCEPTED");
-
-    debug!("Content validation result: {} -> {}", response, is_accepted);
-
-    Ok(is_accepted)
+) -> Result<ContentVerdict, LlmError> {
+    client
+        .content_verdict(image_path, content_description)
+        .await
 }
 
 #[cfg(test)]
@@ -367,36 +779,557 @@ mod tests {
         assert!(prompt.contains("Three birds on a wire"));
         assert!(prompt.contains("ACCEPTED"));
         assert!(prompt.contains("REJECTED"));
+        assert!(prompt.contains("SCORE:"));
     }
 
-    #[tokio::test]
-    async fn test_validate_image_format() {
+    #[test]
+    fn test_custom_prompt_template_from_file() {
+        let mut template_file = NamedTempFile::new().unwrap();
+        write!(
+            template_file,
+            "Analysiere das Bild. Zeigt es: {{content}}?\nAntworte mit ACCEPTED oder REJECTED: Grund."
+        )
+        .unwrap();
+
+        let template = PromptTemplate::from_file(template_file.path()).unwrap();
         let client = LlmClient::new(
             "http://localhost:8080".into(),
             "llava:7b".into(),
             Duration::from_secs(30),
+        )
+        .with_prompt_template(template);
+
+        assert_eq!(
+            client.construct_validation_prompt("drei Vögel auf einer Leitung"),
+            "Analysiere das Bild. Zeigt es: drei Vögel auf einer Leitung?\nAntworte mit ACCEPTED oder REJECTED: Grund."
         );
+    }
+
+    #[test]
+    fn test_prompt_template_requires_placeholder() {
+        let result =
+            PromptTemplate::new("Does this image match? Reply ACCEPTED or REJECTED.".into());
+        assert!(matches!(result, Err(LlmError::InvalidPromptTemplate(_))));
+
+        let result = PromptTemplate::from_file("/nonexistent/prompt.txt");
+        assert!(matches!(result, Err(LlmError::Io(_))));
+    }
+
+    #[test]
+    fn test_parse_verdict_with_score() {
+        let verdict = ContentVerdict::parse("ACCEPTED\nSCORE: 0.87");
+        assert!(verdict.accepted);
+        assert_eq!(verdict.confidence, 0.87);
+        assert_eq!(verdict.explanation, "");
+
+        let verdict = ContentVerdict::parse("REJECTED: only two birds are visible\nSCORE: 0.35");
+        assert!(!verdict.accepted);
+        assert_eq!(verdict.confidence, 0.35);
+        assert_eq!(verdict.explanation, "only two birds are visible");
+    }
+
+    #[test]
+    fn test_parse_verdict_without_score_uses_defaults() {
+        let verdict = ContentVerdict::parse("ACCEPTED");
+        assert!(verdict.accepted);
+        assert_eq!(verdict.confidence, 1.0);
+
+        let verdict = ContentVerdict::parse("rejected: no wire in the image");
+        assert!(!verdict.accepted);
+        assert_eq!(verdict.confidence, 0.0);
+        assert_eq!(verdict.explanation, "no wire in the image");
+    }
+
+    #[test]
+    fn test_parse_verdict_malformed_score() {
+        // Unparseable scores fall back to the verdict default
+        let verdict = ContentVerdict::parse("ACCEPTED\nSCORE: very high");
+        assert!(verdict.accepted);
+        assert_eq!(verdict.confidence, 1.0);
 
+        let verdict = ContentVerdict::parse("REJECTED: blurry\nSCORE: NaN");
+        assert_eq!(verdict.confidence, 0.0);
+
+        // Out-of-range scores are clamped
+        let verdict = ContentVerdict::parse("ACCEPTED\nscore: 1.7");
+        assert_eq!(verdict.confidence, 1.0);
+
+        // Replies that are neither verdict are rejected
+        let verdict = ContentVerdict::parse("I cannot tell what this image shows.");
+        assert!(!verdict.accepted);
+        assert_eq!(verdict.explanation, "I cannot tell what this image shows.");
+    }
+
+    #[test]
+    fn test_parse_prose_replies() {
+        let replies = [
+            (
+                "Yes, this image shows three birds perched on a power line.",
+                true,
+            ),
+            (
+                "The image matches the description: three birds are sitting on a wire.",
+                true,
+            ),
+            (
+                "This photo depicts three small birds on an electrical wire against a blue sky.",
+                true,
+            ),
+            ("No, the image shows a cat sitting on a fence.", false),
+            (
+                "The image does not match the description. It contains two dogs in a park.",
+                false,
+            ),
+            (
+                "Unfortunately, the picture doesn't show any birds; it is a city skyline.",
+                false,
+            ),
+            ("There is no sign of a wire in this image.", false),
+            (
+                "I'm sorry, I can't see the image clearly enough to tell.",
+                false,
+            ),
+            ("NO. Only two birds are visible.", false),
+            ("It is hard to say what this is.", false),
+        ];
+        for (reply, accepted) in replies {
+            let verdict = ContentVerdict::parse_prose(reply);
+            assert_eq!(verdict.accepted, accepted, "{reply}");
+            assert_eq!(verdict.explanation, reply);
+            // The prefix parser rejects every one of them
+            assert!(!ContentVerdict::parse(reply).accepted);
+        }
+
+        // Scores and verdict prefixes are still honoured
+        let verdict = ContentVerdict::parse_prose("yes, three birds\nSCORE: 0.7");
+        assert!(verdict.accepted);
+        assert_eq!(verdict.confidence, 0.7);
+        let verdict = ContentVerdict::parse_prose("REJECTED: the image shows two birds");
+        assert!(!verdict.accepted);
+        assert_eq!(verdict.explanation, "the image shows two birds");
+    }
+
+    #[test]
+    fn test_custom_verdict_keywords() {
+        let keywords = VerdictKeywords::new(
+            vec!["PASS".to_string(), "OUI".to_string()],
+            vec!["FAIL".to_string(), "NON".to_string()],
+        );
+
+        let verdict = ContentVerdict::parse_with("pass: three birds\nSCORE: 0.8", &keywords);
+        assert!(verdict.accepted);
+        assert_eq!(verdict.confidence, 0.8);
+        assert_eq!(verdict.explanation, "three birds");
+        assert!(ContentVerdict::parse_with("Oui - trois oiseaux", &keywords).accepted);
+        let verdict = ContentVerdict::parse_with("FAIL: only two birds", &keywords);
+        assert!(!verdict.accepted);
+        assert_eq!(verdict.explanation, "only two birds");
+
+        // The default keywords no longer count, and keywords must be whole words
+        assert!(!ContentVerdict::parse_with("ACCEPTED", &keywords).accepted);
+        assert!(!ContentVerdict::parse_with("PASSABLE likeness", &keywords).accepted);
+
+        // Only the start of the reply is read unless keywords may appear anywhere
+        let reply = "The image shows three birds. Verdict: PASS";
+        assert!(!ContentVerdict::parse_with(reply, &keywords).accepted);
+        let anywhere = keywords.clone().anywhere();
+        assert!(ContentVerdict::parse_with(reply, &anywhere).accepted);
+        // Reject keywords win when both appear
+        let reply = "Verdict: FAIL, it does not PASS";
+        assert!(!ContentVerdict::parse_with(reply, &anywhere).accepted);
+        assert!(!ContentVerdict::parse_with("IMPASSE", &anywhere).accepted);
+    }
+
+    #[test]
+    fn test_ambiguous_reply_gets_default_verdict() {
+        let reply = "It is hard to say what this is.";
+        assert!(!ContentVerdict::parse(reply).accepted);
+
+        let lenient = VerdictKeywords::default().with_default_verdict(true);
+        let verdict = ContentVerdict::parse_with(reply, &lenient);
+        assert!(verdict.accepted);
+        assert_eq!(verdict.confidence, 1.0);
+        assert_eq!(verdict.explanation, reply);
+        // A keyword still decides
+        assert!(!ContentVerdict::parse_with("REJECTED: blurry", &lenient).accepted);
+
+        // Prose is read before falling back to the default
+        assert!(ContentVerdict::parse_prose_with(reply, &lenient).accepted);
+        assert!(!ContentVerdict::parse_prose_with("No, it is a cat.", &lenient).accepted);
+        assert!(!ContentVerdict::parse_prose(reply).accepted);
+    }
+
+    /// Backend answering with `reply`, and classifying it when asked to judge a reply
+    struct ProseBackend {
+        reply: String,
+        prompts: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl VisionBackend for ProseBackend {
+        async fn analyze(
+            &self,
+            prompt: &str,
+            _image_bytes: &[u8],
+            _model: Option<&str>,
+        ) -> Result<String, LlmError> {
+            self.prompts.lock().unwrap().push(prompt.to_string());
+            if !prompt.contains("Did the assistant say") {
+                return Ok(self.reply.clone());
+            }
+            let judged = if prompt.contains("three birds perched") {
+                "ACCEPTED"
+            } else {
+                "REJECTED"
+            };
+            Ok(judged.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_judge_parser_classifies_prose_replies() {
+        use image::{ImageFormat, RgbImage};
+        use std::io::Cursor;
+
+        let mut jpeg = Cursor::new(Vec::new());
+        RgbImage::new(32, 24)
+            .write_to(&mut jpeg, ImageFormat::Jpeg)
+            .unwrap();
+        let mut image = NamedTempFile::with_suffix(".jpg").unwrap();
+        image.write_all(jpeg.get_ref()).unwrap();
+
+        let verdict = |reply: &str| {
+            let backend = Arc::new(ProseBackend {
+                reply: reply.to_string(),
+                prompts: Default::default(),
+            });
+            let client = LlmClient::with_backend(backend.clone())
+                .with_response_parser(LlmResponseParser::Judge);
+            let path = image.path().to_path_buf();
+            async move {
+                let verdict = client
+                    .content_verdict(path, "Three birds on a wire")
+                    .await
+                    .unwrap();
+                let prompts = backend.prompts.lock().unwrap().clone();
+                (verdict, prompts)
+            }
+        };
+
+        let (accepted, prompts) = verdict("Sure! I can see three birds perched on a wire.").await;
+        assert!(accepted.accepted);
+        assert_eq!(
+            accepted.explanation,
+            "Sure! I can see three birds perched on a wire."
+        );
+        assert_eq!(prompts.len(), 2);
+        assert!(prompts[1].contains("\"Three birds on a wire\""));
+        assert!(prompts[1].contains("Sure! I can see three birds perched on a wire."));
+
+        let (rejected, _) = verdict("Sure! There is a squirrel on a branch.").await;
+        assert!(!rejected.accepted);
+
+        // Replies with a verdict prefix are not judged
+        let (verdict, prompts) = verdict("REJECTED: only two birds\nSCORE: 0.6").await;
+        assert!(!verdict.accepted);
+        assert_eq!(verdict.confidence, 0.6);
+        assert_eq!(prompts.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_validate_image_format() {
         // Create a temporary file with JPEG magic bytes
         let mut temp_file = NamedTempFile::with_suffix(".jpg").unwrap();
         temp_file
             .write_all(&[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, 0x4A, 0x46])
             .unwrap();
 
-        let result = client.validate_image_format(
+        let result = validate_image_format(
             temp_file.path(),
             &[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, 0x4A, 0x46],
         );
         assert!(result.is_ok());
 
         // Test invalid magic bytes
-        let result = client.validate_image_format(
+        let result = validate_image_format(
             temp_file.path(),
             &[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
         );
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_read_heic_image_without_transcoding() {
+        let client = LlmClient::new(
+            "http://localhost:8080".into(),
+            "llava:7b".into(),
+            Duration::from_secs(30),
+        );
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/exif.heic");
+
+        // Recognized by extension and ftyp brand, and sent unchanged when transcoding is off
+        let bytes = client.read_image(path).await.unwrap();
+        assert!(is_heif(&bytes));
+
+        // A HEIC extension on non-HEIF bytes is rejected
+        let result = validate_image_format("photo.heic", &[0xFF, 0xD8, 0xFF, 0xE0, 0, 0, 0, 0]);
+        assert!(matches!(result, Err(LlmError::InvalidImage(_))));
+
+        // With transcoding on, a missing converter surfaces as an error
+        let client = client.with_heic_transcoding("image-checker-no-such-transcoder".into());
+        let result = client.read_image(path).await;
+        assert!(matches!(result, Err(LlmError::Heic(_))));
+    }
+
+    #[tokio::test]
+    async fn test_read_avif_image() {
+        let client = LlmClient::new(
+            "http://localhost:8080".into(),
+            "llava:7b".into(),
+            Duration::from_secs(30),
+        );
+        let mut avif = NamedTempFile::with_suffix(".avif").unwrap();
+        avif.write_all(b"\0\0\0\x1cftypavif\0\0\0\0avifmif1miaf")
+            .unwrap();
+
+        // Recognized by extension and ftyp brand, and sent unchanged when transcoding is off
+        let bytes = client.read_image(avif.path()).await.unwrap();
+        assert!(is_avif(&bytes));
+
+        // An AVIF extension on HEIC bytes is rejected
+        let result = validate_image_format("photo.avif", b"\0\0\0\x18ftypheic\0\0\0\0mif1heic");
+        assert!(matches!(result, Err(LlmError::InvalidImage(_))));
+
+        // HEIC transcoding leaves AVIF alone; AVIF transcoding picks it up
+        let client = client.with_heic_transcoding("image-checker-no-such-transcoder".into());
+        assert!(client.read_image(avif.path()).await.is_ok());
+        let client = client.with_avif_transcoding("image-checker-no-such-transcoder".into());
+        let result = client.read_image(avif.path()).await;
+        assert!(matches!(result, Err(LlmError::Avif(_))));
+    }
+
+    #[tokio::test]
+    async fn test_read_tiff_and_dng_images_rejected_before_llm() {
+        let client = LlmClient::new(
+            "http://localhost:8080".into(),
+            "llava:7b".into(),
+            Duration::from_secs(30),
+        );
+
+        let tiff = b"II*\0\x08\0\0\0";
+        assert!(validate_image_format("scan.tif", tiff).is_ok());
+        assert!(validate_image_format("scan.TIFF", tiff).is_ok());
+        assert!(validate_image_format("raw.dng", b"MM\0*\0\0\0\x08").is_ok());
+        assert!(matches!(
+            validate_image_format("raw.dng", &[0xFF, 0xD8, 0xFF, 0xE0, 0, 0, 0, 0]),
+            Err(LlmError::InvalidImage(_))
+        ));
+
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/exif.tif");
+        let result = client.read_image(path).await;
+        assert!(
+            matches!(&result, Err(LlmError::UnsupportedByBackend(message)) if message.contains("JPEG"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_image_downscales_large_images() {
+        use image::{ImageFormat, RgbImage};
+        use std::io::Cursor;
+
+        let mut jpeg = Cursor::new(Vec::new());
+        RgbImage::new(480, 360)
+            .write_to(&mut jpeg, ImageFormat::Jpeg)
+            .unwrap();
+        let mut file = tempfile::NamedTempFile::with_suffix(".jpg").unwrap();
+        file.write_all(jpeg.get_ref()).unwrap();
+
+        let client = LlmClient::new(
+            "http://localhost:8080".into(),
+            "llava:7b".into(),
+            Duration::from_secs(30),
+        );
+
+        // Unlimited by default
+        let bytes = client.read_image(file.path()).await.unwrap();
+        assert_eq!(bytes, *jpeg.get_ref());
+
+        let client = client.with_max_image_dimension(240);
+        let bytes = client.read_image(file.path()).await.unwrap();
+        let image = image::load_from_memory(&bytes).unwrap();
+        assert_eq!((image.width(), image.height()), (240, 180));
+
+        // The file EXIF is read from is left alone
+        assert_eq!(std::fs::read(file.path()).unwrap(), *jpeg.get_ref());
+    }
+
+    #[tokio::test]
+    async fn test_read_image_prefers_large_enough_exif_thumbnail() {
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_thumbnail};
+        use exif::Tag;
+        use image::{ImageFormat, RgbImage};
+        use std::io::Cursor;
+
+        let mut thumbnail = Cursor::new(Vec::new());
+        RgbImage::new(160, 120)
+            .write_to(&mut thumbnail, ImageFormat::Jpeg)
+            .unwrap();
+        let thumbnail = thumbnail.into_inner();
+        let with_thumbnail = write_jpeg_with_thumbnail(
+            &[ascii_field(Tag::Make, "Apple")],
+            Some(thumbnail.as_slice()),
+        );
+        let without_thumbnail = write_jpeg_with_thumbnail(&[ascii_field(Tag::Make, "Apple")], None);
+
+        let client = LlmClient::new(
+            "http://localhost:8080".into(),
+            "llava:7b".into(),
+            Duration::from_secs(30),
+        );
+
+        // Off by default
+        let bytes = client.read_image(with_thumbnail.path()).await.unwrap();
+        assert_eq!(bytes, std::fs::read(with_thumbnail.path()).unwrap());
+
+        let client = client.with_exif_thumbnail(160);
+        let bytes = client.read_image(with_thumbnail.path()).await.unwrap();
+        assert_eq!(bytes, thumbnail);
+
+        // Falls back to the image without a thumbnail or with one that is too small
+        let bytes = client.read_image(without_thumbnail.path()).await.unwrap();
+        assert_eq!(bytes, std::fs::read(without_thumbnail.path()).unwrap());
+        let client = client.with_exif_thumbnail(320);
+        let bytes = client.read_image(with_thumbnail.path()).await.unwrap();
+        assert_eq!(bytes, std::fs::read(with_thumbnail.path()).unwrap());
+    }
+
     // Integration tests with real LLM API should be in tests/ directory
     // as they require a running LLaVa service
+
+    #[tokio::test]
+    async fn test_max_retries_bounds_llm_attempts() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let attempts = Arc::new(AtomicU32::new(0));
+        let counter = attempts.clone();
+        let app = axum::Router::new().fallback(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async { (axum::http::StatusCode::SERVICE_UNAVAILABLE, "model loading") }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = LlmClient::new(
+            format!("http://{addr}/v1/chat/completions"),
+            "llava:7b".into(),
+            Duration::from_secs(5),
+        )
+        .with_max_retries(2);
+
+        let result = client.call_llm_with_retry("Describe", b"image-bytes").await;
+        assert!(matches!(result, Err(LlmError::MaxRetriesExceeded)));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_retry_delay_jitter() {
+        let base = Duration::from_secs(4);
+        let delays: Vec<Duration> = (0..200).map(|_| jittered(base)).collect();
+        for delay in &delays {
+            assert!(
+                (Duration::from_secs(2)..=Duration::from_secs(6)).contains(delay),
+                "{delay:?}"
+            );
+        }
+        // Random, not a fixed multiple of the base delay
+        assert!(delays.iter().any(|delay| *delay != delays[0]));
+
+        for _ in 0..200 {
+            assert!(jittered(MAX_RETRY_DELAY) <= MAX_RETRY_DELAY);
+        }
+    }
+
+    /// Backend that fails until switched to healthy, counting the calls it receives
+    #[derive(Default)]
+    struct FlakyBackend {
+        healthy: std::sync::atomic::AtomicBool,
+        calls: std::sync::atomic::AtomicU32,
+    }
+
+    #[async_trait::async_trait]
+    impl VisionBackend for FlakyBackend {
+        async fn analyze(
+            &self,
+            _prompt: &str,
+            _image_bytes: &[u8],
+            _model: Option<&str>,
+        ) -> Result<String, LlmError> {
+            use std::sync::atomic::Ordering;
+
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.healthy.load(Ordering::SeqCst) {
+                Ok("ACCEPTED".to_string())
+            } else {
+                Err(LlmError::Api("HTTP 503: model loading".to_string()))
+            }
+        }
+    }
+
+    /// Backend that replies with the model it was asked to use
+    struct EchoModelBackend;
+
+    #[async_trait::async_trait]
+    impl VisionBackend for EchoModelBackend {
+        async fn analyze(
+            &self,
+            _prompt: &str,
+            _image_bytes: &[u8],
+            model: Option<&str>,
+        ) -> Result<String, LlmError> {
+            Ok(model.unwrap_or("default").to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_with_model_overrides_backend_model() {
+        let client = LlmClient::with_backend(Arc::new(EchoModelBackend));
+        let reply = client.call_llm_with_retry("Describe", b"image-bytes").await;
+        assert_eq!(reply.unwrap(), "default");
+
+        let client = client.with_model("llava:34b".to_string());
+        let reply = client.call_llm_with_retry("Describe", b"image-bytes").await;
+        assert_eq!(reply.unwrap(), "llava:34b");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_circuit_breaker_fails_fast_and_recovers() {
+        use crate::circuit_breaker::CircuitState;
+        use std::sync::atomic::Ordering;
+
+        let backend = Arc::new(FlakyBackend::default());
+        let breaker = Arc::new(CircuitBreaker::new(2, Duration::from_secs(30)));
+        let client = LlmClient::with_backend(backend.clone())
+            .with_max_retries(1)
+            .with_circuit_breaker(breaker.clone());
+
+        for _ in 0..2 {
+            let result = client.call_llm_with_retry("Describe", b"image-bytes").await;
+            assert!(matches!(result, Err(LlmError::MaxRetriesExceeded)));
+        }
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // The open circuit fails without reaching the backend
+        let result = client.call_llm_with_retry("Describe", b"image-bytes").await;
+        assert!(matches!(result, Err(LlmError::CircuitOpen)));
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 2);
+
+        // After the cooldown a probe reaches the recovered backend and closes the circuit
+        tokio::time::advance(Duration::from_secs(30)).await;
+        backend.healthy.store(true, Ordering::SeqCst);
+        let result = client.call_llm_with_retry("Describe", b"image-bytes").await;
+        assert_eq!(result.unwrap(), "ACCEPTED");
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 3);
+    }
 }
@@ -1,7 +1,25 @@
+pub mod avif;
 pub mod exif;
+pub mod heic;
+pub mod inline;
 pub mod llm;
+pub mod phash;
 pub mod processor;
+pub mod remote;
+pub mod resize;
+pub mod retained;
+pub mod vision;
 
-pub use exif::{extract_exif_metadata, ExifData, ExifError};
-pub use llm::{validate_image_content, LlmClient, LlmError};
-pub use processor::{ProcessorError, ValidationProcessor};
+pub use exif::{
+    extract_exif_metadata, extract_exif_metadata_with_timeout, ExifCache, ExifData, ExifError,
+};
+pub use inline::{decode_inline_image, InlineImage, InlineImageError};
+pub use llm::{
+    validate_image_content, validate_image_format, ContentVerdict, LlmClient, LlmError,
+    VerdictKeywords,
+};
+pub use phash::{dhash, hamming_distance, similarity, SeenImages};
+pub use processor::{ProcessorError, Progress, RequestValidator, ValidationProcessor};
+pub use remote::{is_image_url, ImageDownloader, RemoteImageError};
+pub use retained::{ProcessedImage, ProcessedImages};
+pub use vision::{GenerationOptions, OllamaBackend, OpenAiBackend, PoolOptions, VisionBackend};
@@ -0,0 +1,236 @@
+use image::imageops::FilterType;
+use image::{ImageError, ImageReader};
+use std::collections::VecDeque;
+use std::io::Cursor;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Most bits two hashes may differ in for the images to count as the same photo
+///
+/// Re-encoding, resizing or slight colour changes flip a few bits; different photos
+/// typically differ in 20 or more of the 64.
+pub const DUPLICATE_HAMMING_THRESHOLD: u32 = 8;
+
+/// Lowest [`similarity`] to a reference image that passes when a request sets none
+///
+/// Allows 16 of the 64 bits to differ: enough for another shot of the same scene from a
+/// similar angle, while unrelated photos typically differ in 25 or more.
+pub const DEFAULT_SIMILARITY_THRESHOLD: f32 = 0.75;
+
+/// Hashes kept in memory; the oldest are dropped first when the store fills up
+const MAX_SEEN_IMAGES: usize = 100_000;
+
+/// Computes the 64-bit difference hash (dHash) of an encoded image
+///
+/// The image is shrunk to 9x8 grayscale pixels and each bit records whether a pixel
+/// is brighter than its right neighbour, so the hash survives re-encoding and
+/// resizing but not cropping or rotation.
+pub fn dhash(bytes: &[u8]) -> Result<u64, ImageError> {
+    let image = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()?
+        .decode()?;
+    let pixels = image.resize_exact(9, 8, FilterType::Triangle).into_luma8();
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let brighter = pixels.get_pixel(x, y)[0] > pixels.get_pixel(x + 1, y)[0];
+            hash = (hash << 1) | u64::from(brighter);
+        }
+    }
+    Ok(hash)
+}
+
+/// Number of bits in which two hashes differ
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// How alike two hashed images are, from 0.0 (every bit differs) to 1.0 (identical)
+pub fn similarity(a: u64, b: u64) -> f32 {
+    1.0 - hamming_distance(a, b) as f32 / 64.0
+}
+
+struct SeenImage {
+    hash: u64,
+    /// Request the image belonged to; `None` for images validated outside a request
+    processing_id: Option<String>,
+    seen_at: Instant,
+}
+
+/// Perceptual hashes of recently validated images, for `reject_duplicate_within`
+///
+/// Hashes are kept in memory for `retention`, so they are forgotten on restart.
+pub struct SeenImages {
+    retention: Duration,
+    entries: Mutex<VecDeque<SeenImage>>,
+}
+
+impl SeenImages {
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            retention,
+            entries: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Records an image and returns how long ago a near-identical one was last seen
+    ///
+    /// Only images seen within `window` count, and never those of the same request,
+    /// so retries and re-runs do not reject themselves. Without a `window` the image
+    /// is only recorded.
+    pub fn record(
+        &self,
+        hash: u64,
+        processing_id: Option<&str>,
+        window: Option<Duration>,
+    ) -> Option<Duration> {
+        let mut entries = self.entries.lock().expect("seen images lock poisoned");
+        while entries.front().is_some_and(|entry| {
+            entries.len() >= MAX_SEEN_IMAGES || entry.seen_at.elapsed() > self.retention
+        }) {
+            entries.pop_front();
+        }
+
+        let duplicate_age = window.and_then(|window| {
+            entries
+                .iter()
+                .rev()
+                .take_while(|entry| entry.seen_at.elapsed() <= window)
+                .filter(|entry| {
+                    processing_id.is_none() || entry.processing_id.as_deref() != processing_id
+                })
+                .find(|entry| hamming_distance(entry.hash, hash) <= DUPLICATE_HAMMING_THRESHOLD)
+                .map(|entry| entry.seen_at.elapsed())
+        });
+
+        entries.push_back(SeenImage {
+            hash,
+            processing_id: processing_id.map(str::to_string),
+            seen_at: Instant::now(),
+        });
+        duplicate_age
+    }
+
+    /// Number of hashes currently held
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .expect("seen images lock poisoned")
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, RgbImage};
+
+    fn encode(image: &RgbImage, format: ImageFormat) -> Vec<u8> {
+        let mut bytes = Cursor::new(Vec::new());
+        image.write_to(&mut bytes, format).unwrap();
+        bytes.into_inner()
+    }
+
+    /// A horizontal gradient with a bright square, as a stand-in for a photo
+    fn photo(width: u32, height: u32) -> RgbImage {
+        RgbImage::from_fn(width, height, |x, y| {
+            let in_square =
+                (width / 4..width / 2).contains(&x) && (height / 4..height / 2).contains(&y);
+            let level = if in_square {
+                255
+            } else {
+                (x * 200 / width) as u8
+            };
+            image::Rgb([level, level, level])
+        })
+    }
+
+    /// Alternating bright and dark vertical stripes, which share no structure with `photo`
+    fn stripes(width: u32, height: u32) -> RgbImage {
+        RgbImage::from_fn(width, height, |x, _| {
+            let level = if (x * 9 / width).is_multiple_of(2) {
+                230
+            } else {
+                20
+            };
+            image::Rgb([level, 0, 255 - level])
+        })
+    }
+
+    #[test]
+    fn test_identical_images_hash_equal() {
+        let original = photo(320, 240);
+        let png = dhash(&encode(&original, ImageFormat::Png)).unwrap();
+        assert_eq!(png, dhash(&encode(&original, ImageFormat::Png)).unwrap());
+
+        // Re-encoded and resized copies stay within the threshold
+        let jpeg = dhash(&encode(&original, ImageFormat::Jpeg)).unwrap();
+        assert!(hamming_distance(png, jpeg) <= DUPLICATE_HAMMING_THRESHOLD);
+        let smaller = dhash(&encode(&photo(160, 120), ImageFormat::Png)).unwrap();
+        assert!(hamming_distance(png, smaller) <= DUPLICATE_HAMMING_THRESHOLD);
+    }
+
+    #[test]
+    fn test_different_images_do_not_collide() {
+        let photo = dhash(&encode(&photo(320, 240), ImageFormat::Png)).unwrap();
+        let stripes = dhash(&encode(&stripes(320, 240), ImageFormat::Png)).unwrap();
+        assert!(
+            hamming_distance(photo, stripes) > DUPLICATE_HAMMING_THRESHOLD,
+            "distance {}",
+            hamming_distance(photo, stripes)
+        );
+
+        assert!(dhash(b"not an image").is_err());
+    }
+
+    #[test]
+    fn test_similarity_scores() {
+        let original = dhash(&encode(&photo(320, 240), ImageFormat::Png)).unwrap();
+        assert_eq!(similarity(original, original), 1.0);
+        assert_eq!(similarity(0, u64::MAX), 0.0);
+        assert_eq!(similarity(0, 0xFFFF), 0.75);
+
+        // A re-encoded, smaller copy passes the default threshold; stripes do not
+        let copy = dhash(&encode(&photo(160, 120), ImageFormat::Jpeg)).unwrap();
+        assert!(similarity(original, copy) >= DEFAULT_SIMILARITY_THRESHOLD);
+        let stripes = dhash(&encode(&stripes(320, 240), ImageFormat::Png)).unwrap();
+        assert!(similarity(original, stripes) < DEFAULT_SIMILARITY_THRESHOLD);
+    }
+
+    #[test]
+    fn test_seen_images_window_and_owner() {
+        let seen = SeenImages::new(Duration::from_secs(3600));
+        let window = Some(Duration::from_secs(60));
+
+        assert_eq!(seen.record(0xFF00, Some("first"), window), None);
+        // Not a duplicate of itself when the same request is retried
+        assert_eq!(seen.record(0xFF00, Some("first"), window), None);
+        // Recorded without a window, but still remembered
+        assert_eq!(seen.record(0x00FF_0000, Some("other"), None), None);
+
+        // A near-identical hash from another request is a duplicate
+        assert!(seen.record(0xFF01, Some("second"), window).is_some());
+        // Hashes outside the window do not count
+        assert_eq!(
+            seen.record(0xFF00, Some("third"), Some(Duration::ZERO)),
+            None
+        );
+        assert_eq!(seen.len(), 5);
+    }
+
+    #[test]
+    fn test_seen_images_expire() {
+        let seen = SeenImages::new(Duration::ZERO);
+        seen.record(0xFF00, Some("first"), None);
+        std::thread::sleep(Duration::from_millis(5));
+
+        let window = Some(Duration::from_secs(60));
+        assert_eq!(seen.record(0xFF00, Some("second"), window), None);
+        assert_eq!(seen.len(), 1);
+    }
+}
@@ -1,11 +1,42 @@
-use crate::config::Config;
-use crate::models::{ProcessingRequest, Resolution, ValidationContext, ValidationResults};
-use crate::storage::StorageUri;
-use crate::utils::{coords_to_string, format_distance, validate_datetime, validate_location};
-use crate::validation::exif::{extract_exif_metadata, ExifError};
-use crate::validation::llm::{validate_image_content, LlmClient, LlmError};
-
+use crate::circuit_breaker::CircuitBreaker;
+use crate::config::{Config, ContentAlternativesStrategy};
+use crate::geocoder::Geocoder;
+use crate::metrics;
+use crate::models::{
+    CheckOutcome, CheckState, ImageInspection, ImageResult, InspectRequest, LocationConstraint,
+    MatchMode, MissingMetadataPolicy, PartialResults, ProcessingRequest, ReasonCode,
+    RejectionReason, Resolution, Severity, ValidationContext, ValidationResults,
+};
+use crate::storage::{StorageError, StorageFetcher, StorageUri};
+use crate::throttle::TokenBucket;
+use crate::utils::{
+    altitude_violation, bearing_degrees, compass_point, coords_to_string, dimensions_violation,
+    estimate_gps_precision, format_distance_in, haversine_distance, is_camera_allowed,
+    speed_violation, validate_datetime, validate_location,
+};
+use crate::validation::exif::{
+    extract_exif_metadata_with_timeout, ExifCache, ExifData, ExifError, ImageContainer,
+};
+use crate::validation::heic::is_heif;
+use crate::validation::inline::{
+    decode_inline_image, detect_extension, InlineImage, InlineImageError,
+};
+use crate::validation::llm::{
+    validate_image_format, ContentVerdict, LlmClient, LlmError, PromptTemplate,
+};
+use crate::validation::phash::{dhash, similarity, SeenImages, DEFAULT_SIMILARITY_THRESHOLD};
+use crate::validation::remote::{is_image_url, ImageDownloader, RemoteImageError};
+use crate::validation::retained::ProcessedImages;
+use crate::validation::vision::create_backend;
+
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
+use chrono::FixedOffset;
+use std::io::{Cursor, Write};
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::NamedTempFile;
 use thiserror::Error;
 use tokio::try_join;
 use tracing::{debug, error, info, warn};
@@ -22,224 +53,1165 @@ pub enum ProcessorError {
     Config(String),
     #[error("Validation context error: {0}")]
     ValidationContext(String),
+    #[error("Storage error: {0}")]
+    Storage(#[from] StorageError),
+    #[error("Inline image error: {0}")]
+    InlineImage(#[from] InlineImageError),
+    #[error("Remote image error: {0}")]
+    RemoteImage(#[from] RemoteImageError),
     #[error("Internal error: {0}")]
     Internal(String),
 }
 
+impl ProcessorError {
+    /// Whether the failure is transient infrastructure trouble worth re-running the job for
+    ///
+    /// Missing images, malformed requests and bad configuration fail the same way on
+    /// every attempt, so only IO, storage and LLM transport errors qualify.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ProcessorError::Llm(e) => matches!(
+                e,
+                LlmError::Http(_)
+                    | LlmError::Io(_)
+                    | LlmError::Api(_)
+                    | LlmError::Timeout
+                    | LlmError::MaxRetriesExceeded
+                    | LlmError::CircuitOpen
+            ),
+            ProcessorError::Storage(StorageError::Io(_)) => true,
+            ProcessorError::Storage(StorageError::ObjectStore(e)) => {
+                !matches!(e, object_store::Error::NotFound { .. })
+            }
+            ProcessorError::Exif(ExifError::Io(_) | ExifError::Timeout(_)) => true,
+            ProcessorError::RemoteImage(e) => e.is_transient(),
+            _ => false,
+        }
+    }
+
+    /// Whether staging failed because the image does not exist where it was looked for
+    fn is_image_missing(&self) -> bool {
+        match self {
+            ProcessorError::Storage(StorageError::PathNotFound(_)) => true,
+            ProcessorError::RemoteImage(e) => e.is_not_found(),
+            _ => false,
+        }
+    }
+}
+
+/// Validates queued requests; implemented by [`ValidationProcessor`]
+///
+/// The queue worker only depends on this trait, so tests can substitute a validator
+/// that fails on demand.
+#[async_trait]
+pub trait RequestValidator: Send + Sync {
+    /// Validates a request, reporting each sub-check to `progress` as it finishes
+    async fn validate_request(
+        &self,
+        request: ProcessingRequest,
+        progress: &Progress,
+    ) -> Result<ValidationResults, ProcessorError>;
+}
+
+/// Shared view of a request's partial results, updated while it is validated
+#[derive(Debug, Clone, Default)]
+pub struct Progress(Arc<std::sync::Mutex<PartialResults>>);
+
+impl Progress {
+    /// The sub-check outcomes reported so far
+    pub fn snapshot(&self) -> PartialResults {
+        self.0.lock().expect("progress lock poisoned").clone()
+    }
+
+    /// Forgets earlier outcomes, e.g. before a retry or the next image
+    pub fn reset(&self) {
+        self.update(|partial| *partial = PartialResults::default());
+    }
+
+    /// Records finished sub-checks
+    pub fn update(&self, apply: impl FnOnce(&mut PartialResults)) {
+        apply(&mut self.0.lock().expect("progress lock poisoned"));
+    }
+}
+
+/// An image that can be read from the local filesystem
+///
+/// Images fetched from remote storage or sent inline are written to a temporary file
+/// that lives as long as this value, so EXIF extraction and the LLM client can read
+/// them by path.
+struct LocalImage {
+    path: String,
+    _temp_file: Option<NamedTempFile>,
+}
+
 pub struct ValidationProcessor {
-    llm_client: LlmClient,
+    /// Swapped on a config reload; each content check uses the client current when it starts
+    llm_client: ArcSwap<LlmClient>,
     storage_uri: StorageUri,
+    /// Fetches bucket images, reusing one client per bucket
+    storage: StorageFetcher,
+    max_inline_image_bytes: usize,
+    /// Fetches `http(s)://` image paths from the hosts in `ALLOWED_IMAGE_HOSTS`
+    downloader: ImageDownloader,
+    exif_default_offset: FixedOffset,
+    exif_timeout: Duration,
+    /// Parsed EXIF of recently validated images, reused while the file is unchanged
+    exif_cache: Arc<ExifCache>,
+    /// Rate limit applied to each image of a multi-image request after the first, and to
+    /// each alternative content description after the first
+    throttle: Arc<TokenBucket>,
+    content_alternatives_strategy: ContentAlternativesStrategy,
+    /// Whether results carry the model's explanation, from `INCLUDE_LLM_EXPLANATION`
+    include_llm_explanation: bool,
+    /// Resolves `place_name` locations; `None` when `GEOCODER_URL` is unset
+    geocoder: Option<Geocoder>,
+    /// Hashes of recently validated images, checked for `reject_duplicate_within`
+    seen_images: Arc<SeenImages>,
+    /// Images as sent to the model; `None` unless `DEBUG_RETAIN_PROCESSED_IMAGES` is set
+    processed_images: Option<Arc<ProcessedImages>>,
 }
 
 impl ValidationProcessor {
     pub fn new(config: &Config) -> Self {
-        let llm_client = LlmClient::new(
+        let mut llm_client = LlmClient::with_backend(create_backend(
+            config.llm_backend,
             config.llm_api_url.clone(),
             config.llm_model_name.clone(),
             config.request_timeout(),
+            config.generation_options(),
+            config.pool_options(),
+        ))
+        .with_max_retries(config.llm_max_retries)
+        .with_response_parser(config.llm_response_parser)
+        .with_verdict_keywords(config.verdict_keywords())
+        .with_circuit_breaker(Arc::new(CircuitBreaker::new(
+            config.llm_circuit_failure_threshold,
+            config.llm_circuit_cooldown(),
+        )))
+        .with_prompt_template(
+            config
+                .prompt_template()
+                .expect("Invalid prompt template in config"),
         );
+        if config.transcode_heic {
+            llm_client = llm_client.with_heic_transcoding(config.heic_transcode_command.clone());
+        }
+        if config.transcode_avif {
+            llm_client = llm_client.with_avif_transcoding(config.avif_transcode_command.clone());
+        }
+        if let Some(max_dimension) = config.max_image_dimension {
+            llm_client = llm_client.with_max_image_dimension(max_dimension);
+        }
+        if config.auto_orient {
+            llm_client = llm_client.with_auto_orient();
+        }
+        if config.use_exif_thumbnail_if_present {
+            llm_client = llm_client.with_exif_thumbnail(config.exif_thumbnail_min_dimension);
+        }
 
         let storage_uri = config
             .get_storage_uri()
             .expect("Invalid storage URI in config");
+        let geocoder = config.geocoder_url.as_deref().map(|url| {
+            let url = url.parse().expect("Invalid geocoder URL in config");
+            Geocoder::new(url, config.request_timeout())
+        });
 
         Self {
-            llm_client,
+            llm_client: ArcSwap::from_pointee(llm_client),
             storage_uri,
+            storage: StorageFetcher::new(config.max_image_download_bytes),
+            max_inline_image_bytes: config.max_inline_image_bytes,
+            downloader: ImageDownloader::new(
+                config.allowed_image_hosts(),
+                config.max_image_download_bytes,
+                config.image_download_timeout(),
+            ),
+            exif_default_offset: config.exif_default_offset(),
+            exif_timeout: config.exif_timeout(),
+            exif_cache: Arc::new(ExifCache::new(config.exif_cache_entries)),
+            throttle: Arc::new(TokenBucket::per_minute(config.throttle_requests_per_minute)),
+            content_alternatives_strategy: config.content_alternatives_strategy,
+            include_llm_explanation: config.include_llm_explanation,
+            geocoder,
+            seen_images: Arc::new(SeenImages::new(config.duplicate_retention())),
+            processed_images: None,
         }
     }
 
+    /// Shares the queue's rate limiter, so extra images draw from the same budget
+    pub fn with_throttle(mut self, throttle: Arc<TokenBucket>) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    /// Shares the queue's store of image hashes, so its size can be reported
+    pub fn with_seen_images(mut self, seen_images: Arc<SeenImages>) -> Self {
+        self.seen_images = seen_images;
+        self
+    }
+
+    /// Keeps the image sent to the model for each request in `processed_images`
+    pub fn with_processed_images(mut self, processed_images: Arc<ProcessedImages>) -> Self {
+        self.processed_images = Some(processed_images);
+        self
+    }
+
+    /// Shares the queue's LLM circuit breaker, so its state can be reported
+    pub fn with_circuit_breaker(self, circuit_breaker: Arc<CircuitBreaker>) -> Self {
+        let llm_client = LlmClient::clone(&self.llm_client.load());
+        self.llm_client
+            .store(Arc::new(llm_client.with_circuit_breaker(circuit_breaker)));
+        self
+    }
+
+    /// Switches the default model and the prompt for content checks that start from now on
+    pub fn reload(&self, model: String, prompt_template: PromptTemplate) {
+        let llm_client = LlmClient::clone(&self.llm_client.load())
+            .with_model(model)
+            .with_prompt_template(prompt_template);
+        self.llm_client.store(Arc::new(llm_client));
+    }
+
     pub async fn validate_request(
         &self,
         request: ProcessingRequest,
+        progress: &Progress,
     ) -> Result<ValidationResults, ProcessorError> {
-        info!("Starting validation for request: {}", request.processing_id);
+        info!(processing_id = %request.processing_id, "Starting validation for request");
+
+        // Inline images are decoded to a temp file; paths are resolved and fetched
+        if let Some(inline_image) = request.get_inline_image() {
+            let image = decode_inline_image(inline_image, self.max_inline_image_bytes)?;
+            let local_image = Self::stage_inline_image(&image)?;
+            let context = self.prepare_context(&request).await?;
+            return self
+                .validate_local_image(&local_image.path, &context, progress)
+                .await;
+        }
+
+        let image_paths = request.get_image_paths();
+        if image_paths.len() > 1 {
+            let context = self.prepare_context(&request).await?;
+            let match_mode = request.analysis_request.match_mode;
+            return self
+                .validate_multiple_images(&image_paths, match_mode, &context, progress)
+                .await;
+        }
 
-        // Determine image path before moving analysis_request
-        let image_path = self.resolve_image_path(&request)?;
+        let image_path = self.resolve_image_path(request.get_image_path())?;
+        let context = self.prepare_context(&request).await?;
+        self.validate_image_path(&image_path, &context, progress)
+            .await
+    }
 
-        // Parse validation context
-        let context = ValidationContext::try_from(request.analysis_request)
-            .map_err(ProcessorError::ValidationContext)?;
+    /// Reports the format, dimensions and EXIF metadata of an image without the LLM
+    pub async fn inspect(
+        &self,
+        request: &InspectRequest,
+    ) -> Result<ImageInspection, ProcessorError> {
+        let (local_image, resolved_path) = match request.get_inline_image() {
+            Some(inline_image) => {
+                let image = decode_inline_image(inline_image, self.max_inline_image_bytes)?;
+                (Self::stage_inline_image(&image)?, None)
+            }
+            None => {
+                let image_path = self.resolve_image_path(request.get_image_path())?;
+                let local_image = match self.stage_image(&image_path).await {
+                    Ok(local_image) if Path::new(&local_image.path).exists() => local_image,
+                    Err(e) if !e.is_image_missing() => return Err(e),
+                    _ => return Err(ProcessorError::ImageNotFound(image_path)),
+                };
+                (local_image, Some(image_path))
+            }
+        };
 
-        // Validate image file exists
-        if !Path::new(&image_path).exists() {
-            warn!("Image file not found: {}", image_path);
-            return Ok(ValidationResults {
-                resolution: Resolution::Rejected,
-                reasons: Some(vec!["cannot locate image".to_string()]),
-            });
+        let bytes = tokio::fs::read(&local_image.path)
+            .await
+            .map_err(StorageError::Io)?;
+        validate_image_format(&local_image.path, &bytes)?;
+
+        let format = image::guess_format(&bytes)
+            .map(|format| format!("{format:?}").to_lowercase())
+            .ok()
+            .or_else(|| is_heif(&bytes).then(|| "heif".to_string()));
+        let dimensions = image::ImageReader::new(Cursor::new(&bytes))
+            .with_guessed_format()
+            .ok()
+            .and_then(|reader| reader.into_dimensions().ok());
+
+        // Images without EXIF, or without these tags, are reported rather than rejected
+        let exif_data = extract_exif_metadata_with_timeout(
+            &local_image.path,
+            self.exif_default_offset,
+            self.exif_timeout,
+            Some(&self.exif_cache),
+        )
+        .await
+        .ok();
+
+        Ok(ImageInspection {
+            resolved_path,
+            format,
+            width: dimensions.map(|(width, _)| width),
+            height: dimensions.map(|(_, height)| height),
+            has_gps: exif_data
+                .as_ref()
+                .is_some_and(|exif| exif.gps_coordinates.is_some()),
+            has_timestamp: exif_data
+                .as_ref()
+                .is_some_and(|exif| exif.capture_time().is_some()),
+        })
+    }
+
+    /// Parses the request's constraints and hashes its reference image, if any
+    async fn prepare_context(
+        &self,
+        request: &ProcessingRequest,
+    ) -> Result<ValidationContext, ProcessorError> {
+        let mut context = Self::parse_context(request)?;
+        let Some(reference_path) = context.reference_image_path.clone() else {
+            return Ok(context);
+        };
+
+        let reference_path = self.resolve_path(reference_path);
+        let reference = match self.stage_image(&reference_path).await {
+            Ok(reference) if Path::new(&reference.path).exists() => reference,
+            Err(e) if !e.is_image_missing() => return Err(e),
+            _ => {
+                return Err(ProcessorError::ImageNotFound(format!(
+                    "reference image {reference_path}"
+                )))
+            }
+        };
+        let hash = image_hash(&reference.path).await.map_err(|e| {
+            ProcessorError::ValidationContext(format!(
+                "cannot read reference image {reference_path}: {e}"
+            ))
+        })?;
+        context.reference_hash = Some(hash);
+        Ok(context)
+    }
+
+    fn parse_context(request: &ProcessingRequest) -> Result<ValidationContext, ProcessorError> {
+        ValidationContext::try_from(request.analysis_request.clone())
+            .map(|context| context.with_processing_id(request.processing_id.clone()))
+            .map_err(ProcessorError::ValidationContext)
+    }
+
+    /// Validates each image in turn, pausing between them to respect the throttle
+    async fn validate_multiple_images(
+        &self,
+        image_paths: &[String],
+        match_mode: MatchMode,
+        context: &ValidationContext,
+        progress: &Progress,
+    ) -> Result<ValidationResults, ProcessorError> {
+        let mut images = Vec::with_capacity(image_paths.len());
+
+        for (index, image_path) in image_paths.iter().enumerate() {
+            if index > 0 {
+                self.throttle.acquire().await;
+            }
+
+            let resolved_path = self.resolve_path(image_path.clone());
+            let results = self
+                .validate_image_path(&resolved_path, context, progress)
+                .await?;
+            let accepted = results.resolution == Resolution::Accepted;
+            images.push(ImageResult::new(image_path.clone(), results));
+
+            if accepted && match_mode == MatchMode::Any {
+                debug!("Image {} passed; skipping remaining images", image_path);
+                break;
+            }
+        }
+
+        Ok(ValidationResults::combine(match_mode, images))
+    }
+
+    /// Fetches a resolved image path and validates it, rejecting images that cannot be found
+    async fn validate_image_path(
+        &self,
+        image_path: &str,
+        context: &ValidationContext,
+        progress: &Progress,
+    ) -> Result<ValidationResults, ProcessorError> {
+        // Fetch remote images locally, then validate the image file exists
+        let local_image = match self.stage_image(image_path).await {
+            Ok(local_image) if Path::new(&local_image.path).exists() => local_image,
+            Err(ProcessorError::RemoteImage(RemoteImageError::HostNotAllowed(host))) => {
+                warn!("Image host not allowed: {}", image_path);
+                metrics::record_rejection(metrics::RejectionReason::Image);
+                return Ok(ValidationResults::rejected(vec![RejectionReason::new(
+                    ReasonCode::ImageHostNotAllowed,
+                    format!("image host '{host}' is not allowed"),
+                )])
+                .with_checks(skipped_checks(context)));
+            }
+            Err(e) if !e.is_image_missing() => return Err(e),
+            _ => {
+                warn!("Image file not found: {}", image_path);
+                metrics::record_rejection(metrics::RejectionReason::Image);
+                return Ok(ValidationResults::rejected(vec![RejectionReason::new(
+                    ReasonCode::ImageNotFound,
+                    "cannot locate image",
+                )])
+                .with_checks(skipped_checks(context)));
+            }
+        };
+
+        self.validate_local_image(&local_image.path, context, progress)
+            .await
+    }
+
+    /// Validates a staged image
+    ///
+    /// Failed checks, and errors that would recur on every attempt, reject the image.
+    /// Transient errors such as an unreachable LLM are returned instead, so the queue
+    /// can retry the job.
+    async fn validate_local_image(
+        &self,
+        image_path: &str,
+        context: &ValidationContext,
+        progress: &Progress,
+    ) -> Result<ValidationResults, ProcessorError> {
+        progress.reset();
+        // Decoding the whole image is only worth it for a duplicate or reference check
+        let hash = if context.reject_duplicate_within.is_some() || context.reference_hash.is_some()
+        {
+            Some(image_hash(image_path).await)
+        } else {
+            None
+        };
+        if let Some(reason) = hash
+            .as_ref()
+            .and_then(|hash| self.check_duplicate(image_path, hash, context))
+        {
+            info!(
+                "Duplicate image rejected: {} - {}",
+                image_path, reason.message
+            );
+            metrics::record_rejection(metrics::RejectionReason::Duplicate);
+            return Ok(
+                ValidationResults::rejected(vec![reason]).with_checks(skipped_checks(context))
+            );
+        }
+        if let Some(reason) = self.check_dimensions(image_path, context).await? {
+            info!("Image size rejected: {} - {}", image_path, reason.message);
+            metrics::record_rejection(metrics::RejectionReason::Dimensions);
+            return Ok(
+                ValidationResults::rejected(vec![reason]).with_checks(skipped_checks(context))
+            );
         }
+        let similarity = context
+            .reference_hash
+            .zip(hash)
+            .map(|(reference, hash)| hash.map(|hash| similarity(hash, reference)));
 
         // Perform parallel validation of content and metadata
         let validation_result = self
-            .perform_parallel_validation(&image_path, &context)
+            .perform_parallel_validation(image_path, context, progress)
             .await;
 
         match validation_result {
-            Ok((content_valid, location_valid, datetime_valid, reasons)) => {
-                let overall_valid = content_valid && location_valid && datetime_valid;
+            Ok(CheckResults {
+                content: content_verdict,
+                location,
+                datetime,
+                camera_valid,
+                unedited,
+                mut reasons,
+            }) => {
+                let location_valid = !location.is_failed();
+                let datetime_valid = !datetime.is_failed();
+                let content = if content_verdict.accepted {
+                    CheckState::Passed
+                } else {
+                    CheckState::Failed
+                };
+                let checks = context
+                    .verbose
+                    .then(|| CheckOutcome::list(content, location, datetime));
+                // Weighted checks pass or fail together, depending on the score
+                let (severity, score, scored) = match (context.weights, context.accept_threshold) {
+                    (Some(weights), Some(threshold)) => {
+                        let content_credit = if content_verdict.accepted {
+                            content_verdict.confidence
+                        } else {
+                            0.0
+                        };
+                        let score = weights.score(
+                            content_credit,
+                            location_valid,
+                            datetime_valid,
+                            camera_valid,
+                            unedited,
+                        );
+                        let scored = score >= threshold;
+                        if !scored {
+                            let message = format!(
+                                "weighted score of the checks is {score:.2}, \
+                                 below the {threshold:.2} threshold"
+                            );
+                            reasons.push(RejectionReason::new(
+                                ReasonCode::ScoreBelowThreshold,
+                                message,
+                            ));
+                        }
+                        let weighted = if scored {
+                            Severity::Soft
+                        } else {
+                            Severity::Hard
+                        };
+                        (
+                            weights.apply_to(context.severity, weighted),
+                            Some(score),
+                            scored,
+                        )
+                    }
+                    _ => (context.severity, None, true),
+                };
+                let similar = match &similarity {
+                    None => true,
+                    Some(Ok(score)) => {
+                        let threshold = context
+                            .similarity_threshold
+                            .unwrap_or(DEFAULT_SIMILARITY_THRESHOLD);
+                        if *score < threshold {
+                            reasons.push(RejectionReason::new(
+                                ReasonCode::ReferenceMismatch,
+                                format!(
+                                    "image similarity to the reference image is {score:.2}, below the {threshold:.2} threshold"
+                                ),
+                            ));
+                        }
+                        *score >= threshold
+                    }
+                    Some(Err(e)) => {
+                        reasons.push(RejectionReason::new(
+                            ReasonCode::ValidationError,
+                            format!("cannot compare image with the reference image: {e}"),
+                        ));
+                        false
+                    }
+                };
+
+                // Only hard failures reject the image; soft ones are reported as warnings
+                let mut overall_valid = true;
+                for (valid, check_severity, reason) in [
+                    (
+                        content_verdict.accepted,
+                        severity.content,
+                        metrics::RejectionReason::Content,
+                    ),
+                    (
+                        location_valid,
+                        severity.location,
+                        metrics::RejectionReason::Location,
+                    ),
+                    (
+                        datetime_valid,
+                        severity.datetime,
+                        metrics::RejectionReason::Datetime,
+                    ),
+                    (
+                        camera_valid,
+                        severity.camera,
+                        metrics::RejectionReason::Camera,
+                    ),
+                    (unedited, severity.edited, metrics::RejectionReason::Edited),
+                    (
+                        similar,
+                        Severity::Hard,
+                        metrics::RejectionReason::Similarity,
+                    ),
+                    (scored, Severity::Hard, metrics::RejectionReason::Score),
+                ] {
+                    if !valid && check_severity == Severity::Hard {
+                        overall_valid = false;
+                        metrics::record_rejection(reason);
+                    }
+                }
+
+                let reasons: Vec<RejectionReason> = reasons
+                    .into_iter()
+                    .map(|reason| match severity.for_code(reason.code) {
+                        Severity::Soft => reason.as_warning(),
+                        Severity::Hard => reason,
+                    })
+                    .collect();
 
                 let result = if overall_valid {
                     info!("Validation passed for image: {}", image_path);
-                    ValidationResults {
-                        resolution: Resolution::Accepted,
-                        reasons: None,
+                    if !reasons.is_empty() {
+                        info!("Soft-failed checks for {}: {:?}", image_path, reasons);
                     }
+                    ValidationResults::accepted_with_warnings(reasons)
                 } else {
                     info!(
                         "Validation failed for image: {} - reasons: {:?}",
                         image_path, reasons
                     );
-                    ValidationResults {
-                        resolution: Resolution::Rejected,
-                        reasons: Some(reasons),
-                    }
+                    ValidationResults::rejected(reasons)
                 };
 
-                Ok(result)
+                let explanation = self
+                    .include_llm_explanation
+                    .then_some(content_verdict.explanation);
+                Ok(result
+                    .with_content_confidence(content_verdict.confidence)
+                    .with_similarity(similarity.and_then(Result::ok))
+                    .with_score(score)
+                    .with_checks(checks)
+                    .with_explanation(explanation))
+            }
+            Err(e) if e.is_retryable() => {
+                warn!("Transient error validating image {}: {}", image_path, e);
+                Err(e)
             }
             Err(e) => {
                 error!("Validation error for image {}: {}", image_path, e);
-                Ok(ValidationResults {
-                    resolution: Resolution::Rejected,
-                    reasons: Some(vec![format!("validation error: {}", e)]),
-                })
+                Ok(ValidationResults::rejected(vec![RejectionReason::new(
+                    ReasonCode::ValidationError,
+                    format!("validation error: {}", e),
+                )])
+                .with_checks(skipped_checks(context)))
             }
         }
     }
 
-    fn resolve_image_path(&self, request: &ProcessingRequest) -> Result<String, ProcessorError> {
-        let image_path = request
-            .get_image_path()
+    /// Records the image's perceptual hash, rejecting it when a near-identical image
+    /// was validated within the request's `reject_duplicate_within`
+    ///
+    /// Only requests with `reject_duplicate_within` record their images. Images the
+    /// `image` crate cannot decode, such as HEIC, are neither recorded nor rejected.
+    fn check_duplicate(
+        &self,
+        image_path: &str,
+        hash: &Result<u64, String>,
+        context: &ValidationContext,
+    ) -> Option<RejectionReason> {
+        context.reject_duplicate_within?;
+        let hash = match hash {
+            Ok(hash) => *hash,
+            Err(e) => {
+                warn!("Cannot check {} for duplicates: {}", image_path, e);
+                return None;
+            }
+        };
+
+        let age = self.seen_images.record(
+            hash,
+            context.processing_id.as_deref(),
+            context.duplicate_window(),
+        )?;
+        let when = match age.as_secs() / 60 {
+            0 => "less than a minute ago".to_string(),
+            1 => "1 minute ago".to_string(),
+            minutes => format!("{minutes} minutes ago"),
+        };
+        Some(RejectionReason::new(
+            ReasonCode::DuplicateImage,
+            format!("a near-identical image was validated {when}"),
+        ))
+    }
+
+    /// Rejects images outside the request's `dimensions` before the model is called
+    ///
+    /// Only the image header is read. Formats the `image` crate cannot read, such as
+    /// HEIC, fall back to the EXIF `PixelXDimension` and `PixelYDimension`; a timed out
+    /// or failed EXIF read is returned as an error, since it may succeed on a retry.
+    async fn check_dimensions(
+        &self,
+        image_path: &str,
+        context: &ValidationContext,
+    ) -> Result<Option<RejectionReason>, ProcessorError> {
+        let Some(constraint) = context.dimensions_constraint.as_ref() else {
+            return Ok(None);
+        };
+
+        let path = image_path.to_string();
+        let header_dimensions = tokio::task::spawn_blocking(move || {
+            image::ImageReader::open(&path)?
+                .with_guessed_format()?
+                .into_dimensions()
+                .map_err(std::io::Error::other)
+        })
+        .await
+        .ok()
+        .and_then(Result::ok);
+        let dimensions = match header_dimensions {
+            Some(dimensions) => Some(dimensions),
+            None => match extract_exif_metadata_with_timeout(
+                image_path,
+                self.exif_default_offset,
+                self.exif_timeout,
+                Some(&self.exif_cache),
+            )
+            .await
+            {
+                Ok(exif) => exif.pixel_dimensions,
+                Err(e @ (ExifError::Io(_) | ExifError::Timeout(_))) => return Err(e.into()),
+                Err(_) => None,
+            },
+        };
+
+        let Some((width, height)) = dimensions else {
+            return Ok(Some(RejectionReason::new(
+                ReasonCode::ValidationError,
+                "cannot read image dimensions",
+            )));
+        };
+        debug!("Image dimensions: {}x{}", width, height);
+        Ok(dimensions_violation(width, height, constraint)
+            .map(|reason| RejectionReason::new(ReasonCode::DimensionsOutOfRange, reason)))
+    }
+
+    fn resolve_image_path(&self, image_path: Option<String>) -> Result<String, ProcessorError> {
+        let image_path = image_path
             .ok_or_else(|| ProcessorError::ImageNotFound("no image path provided".to_string()))?;
 
+        Ok(self.resolve_path(image_path))
+    }
+
+    fn resolve_path(&self, image_path: String) -> String {
         // Handle different path formats
         if image_path.contains("://") {
             // Full URI (e.g., file:///path, s3://bucket/path) - parse and extract local path
             match crate::storage::StorageUri::parse(&image_path) {
-                Ok(uri) => Ok(uri.to_local_path().to_string()),
+                Ok(uri) => uri
+                    .to_local_path()
+                    .map(str::to_string)
+                    .unwrap_or(image_path),
                 Err(_) => {
                     // If URI parsing fails, treat as absolute path for backward compatibility
-                    Ok(image_path)
+                    image_path
                 }
             }
         } else if image_path.starts_with('/') {
             // Absolute path - return as-is
-            Ok(image_path)
-        } else if image_path.starts_with("$image_base_dir/") {
-            // Legacy format with $image_base_dir prefix
-            let relative_path = image_path.strip_prefix("$image_base_dir/").unwrap();
-            Ok(self.storage_uri.resolve_relative_path(relative_path))
+            image_path
+        } else if let Some(relative_path) = image_path.strip_prefix("$image_base_dir/") {
+            // Legacy format with $image_base_dir prefix; extra slashes after the token
+            // must not turn the path absolute and escape the base
+            self.storage_uri
+                .resolve_relative_path(relative_path.trim_start_matches('/'))
         } else {
             // Relative path - resolve against storage URI
-            Ok(self.storage_uri.resolve_relative_path(&image_path))
+            self.storage_uri.resolve_relative_path(&image_path)
         }
     }
 
+    /// Makes the resolved image readable from the local filesystem
+    ///
+    /// Local paths are used as-is; remote URIs (e.g. `s3://`) are downloaded into a
+    /// temporary file that keeps the original extension for format detection, and
+    /// `http(s)://` URLs into one named by the downloaded image's format.
+    async fn stage_image(&self, image_path: &str) -> Result<LocalImage, ProcessorError> {
+        if is_image_url(image_path) {
+            debug!("Downloading image: {}", image_path);
+            let bytes = self.downloader.download(image_path).await?;
+            let extension = detect_extension(&bytes)
+                .or_else(|| url_extension(image_path))
+                .unwrap_or_default();
+            return Ok(Self::write_temp_image(&bytes, extension)?);
+        }
+
+        let remote_uri = match StorageUri::parse(image_path) {
+            Ok(uri) if uri.is_remote() => uri,
+            _ => {
+                return Ok(LocalImage {
+                    path: image_path.to_string(),
+                    _temp_file: None,
+                })
+            }
+        };
+
+        debug!("Fetching remote image: {}", remote_uri);
+        let bytes = self.storage.fetch(&remote_uri, "").await?;
+
+        let extension = Path::new(image_path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default();
+        Ok(Self::write_temp_image(&bytes, extension)?)
+    }
+
+    /// Writes a decoded inline image to a temporary file named by its detected format
+    fn stage_inline_image(image: &InlineImage) -> Result<LocalImage, ProcessorError> {
+        debug!(
+            "Staging inline {} image ({} bytes)",
+            image.extension,
+            image.bytes.len()
+        );
+        Ok(Self::write_temp_image(&image.bytes, image.extension)?)
+    }
+
+    fn write_temp_image(bytes: &[u8], extension: &str) -> Result<LocalImage, StorageError> {
+        let suffix = if extension.is_empty() {
+            String::new()
+        } else {
+            format!(".{extension}")
+        };
+        let mut temp_file = tempfile::Builder::new()
+            .prefix("image-checker-")
+            .suffix(&suffix)
+            .tempfile()?;
+        temp_file.write_all(bytes)?;
+
+        Ok(LocalImage {
+            path: temp_file.path().to_string_lossy().into_owned(),
+            _temp_file: Some(temp_file),
+        })
+    }
+
     async fn perform_parallel_validation(
         &self,
         image_path: &str,
         context: &ValidationContext,
-    ) -> Result<(bool, bool, bool, Vec<String>), ProcessorError> {
+        progress: &Progress,
+    ) -> Result<CheckResults, ProcessorError> {
         debug!("Performing parallel validation for: {}", image_path);
 
-        // Perform content validation and EXIF extraction in parallel
-        let (content_result, exif_result) = try_join!(
-            self.validate_content(image_path, &context.content_check),
-            self.extract_and_validate_metadata(image_path, context)
-        )?;
+        // Perform content validation and EXIF extraction in parallel, publishing each
+        // outcome as soon as it is known
+        let content = async {
+            let mut verdict = self.validate_content_checks(image_path, context).await?;
+            let keyword_failures = keyword_failures(&verdict.explanation, context);
+            if !keyword_failures.is_empty() {
+                verdict.accepted = false;
+            }
+            progress.update(|partial| {
+                partial.content = Some(verdict.accepted);
+                partial.content_confidence = Some(verdict.confidence);
+            });
+            Ok::<_, ProcessorError>((verdict, keyword_failures))
+        };
+        let metadata = async {
+            let checks = self
+                .extract_and_validate_metadata(image_path, context)
+                .await?;
+            let (location_valid, datetime_valid, camera_valid, ..) = &checks;
+            progress.update(|partial| {
+                partial.location = Some(*location_valid);
+                partial.datetime = Some(*datetime_valid);
+                partial.camera = Some(*camera_valid);
+            });
+            Ok(checks)
+        };
+        let (content_result, exif_result) = try_join!(content, metadata)?;
 
         let mut reasons = Vec::new();
 
-        // Process content validation result
-        let content_valid = content_result;
-        if !content_valid {
-            reasons.push(format!(
-                "image content does not match description: '{}'",
-                context.content_check
+        // Process content validation result; keyword failures explain themselves
+        let (content_verdict, keyword_failures) = content_result;
+        if !content_verdict.accepted && keyword_failures.is_empty() {
+            reasons.push(RejectionReason::new(
+                ReasonCode::ContentMismatch,
+                content_mismatch_message(context),
             ));
         }
+        reasons.extend(
+            keyword_failures
+                .into_iter()
+                .map(|failure| RejectionReason::new(ReasonCode::ContentMismatch, failure)),
+        );
 
         // Process metadata validation result
-        let (location_valid, datetime_valid, mut meta_reasons) = exif_result;
+        let (location_valid, datetime_valid, camera_valid, unedited, mut meta_reasons) =
+            exif_result;
         reasons.append(&mut meta_reasons);
 
         debug!(
-            "Validation results - content: {}, location: {}, datetime: {}",
-            content_valid, location_valid, datetime_valid
+            "Validation results - content: {} ({:.2}), location: {}, datetime: {}, camera: {}, unedited: {}",
+            content_verdict.accepted,
+            content_verdict.confidence,
+            location_valid,
+            datetime_valid,
+            camera_valid,
+            unedited
         );
 
-        Ok((content_valid, location_valid, datetime_valid, reasons))
+        let location_constrained = context.location_constraint.is_some()
+            || context.altitude_constraint.is_some()
+            || context.max_speed_kmh.is_some()
+            || context.forbid_gps;
+        let datetime_constrained =
+            context.datetime_constraint.is_some() || context.forbid_timestamp;
+        Ok(CheckResults {
+            content: content_verdict,
+            location: CheckState::of(location_constrained, location_valid),
+            datetime: CheckState::of(datetime_constrained, datetime_valid),
+            camera_valid,
+            unedited,
+            reasons,
+        })
+    }
+
+    /// Checks the image against the request's content descriptions, combining the
+    /// verdicts of several alternatives by `content_match_mode`
+    async fn validate_content_checks(
+        &self,
+        image_path: &str,
+        context: &ValidationContext,
+    ) -> Result<ContentVerdict, ProcessorError> {
+        let model = context.model.as_deref();
+        let processing_id = context.processing_id.as_deref();
+        let descriptions = &context.content_checks;
+        if let [description] = descriptions.as_slice() {
+            return self
+                .validate_content(image_path, description, model, processing_id)
+                .await;
+        }
+
+        if self.content_alternatives_strategy == ContentAlternativesStrategy::Combined {
+            let joined = descriptions.join("; ");
+            let description = match context.content_match_mode {
+                MatchMode::Any => format!("at least one of: {}", joined),
+                MatchMode::All => format!("all of: {}", joined),
+            };
+            return self
+                .validate_content(image_path, &description, model, processing_id)
+                .await;
+        }
+
+        let mut verdicts: Vec<ContentVerdict> = Vec::with_capacity(descriptions.len());
+        for (index, description) in descriptions.iter().enumerate() {
+            if index > 0 {
+                self.throttle.acquire().await;
+            }
+
+            let verdict = self
+                .validate_content(image_path, description, model, processing_id)
+                .await?;
+            let decided = match context.content_match_mode {
+                MatchMode::Any => verdict.accepted,
+                MatchMode::All => !verdict.accepted,
+            };
+            verdicts.push(verdict);
+            if decided {
+                break;
+            }
+        }
+
+        let accepted = match context.content_match_mode {
+            MatchMode::Any => verdicts.iter().any(|verdict| verdict.accepted),
+            MatchMode::All => verdicts.iter().all(|verdict| verdict.accepted),
+        };
+        // The deciding verdicts set the confidence: the best match when any may pass,
+        // the weakest when all must
+        let confidences = verdicts.iter().map(|verdict| verdict.confidence);
+        let confidence = match context.content_match_mode {
+            MatchMode::Any => confidences.fold(0.0, f32::max),
+            MatchMode::All => confidences.fold(1.0, f32::min),
+        };
+        let explanation = verdicts
+            .iter()
+            .map(|verdict| verdict.explanation.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Ok(ContentVerdict {
+            accepted,
+            confidence,
+            explanation,
+        })
     }
 
     async fn validate_content(
         &self,
         image_path: &str,
         content_description: &str,
-    ) -> Result<bool, ProcessorError> {
+        model: Option<&str>,
+        processing_id: Option<&str>,
+    ) -> Result<ContentVerdict, ProcessorError> {
         debug!("Validating image content: {}", content_description);
 
-        let is_valid =
-            validate_image_content(&self.llm_client, image_path, content_description).await?;
+        let mut llm_client = self.llm_client.load_full();
+        if let Some(model) = model {
+            llm_client = Arc::new(LlmClient::clone(&llm_client).with_model(model.to_string()));
+        }
+        let (verdict, image_data) = llm_client
+            .content_verdict_with_image(image_path, content_description)
+            .await?;
+        if let (Some(processed_images), Some(processing_id)) =
+            (&self.processed_images, processing_id)
+        {
+            processed_images.insert(processing_id, image_data);
+        }
 
-        debug!("Content validation result: {}", is_valid);
-        Ok(is_valid)
+        debug!(
+            "Content validation result: {} (confidence {:.2})",
+            verdict.accepted, verdict.confidence
+        );
+        Ok(verdict)
     }
 
     async fn extract_and_validate_metadata(
         &self,
         image_path: &str,
         context: &ValidationContext,
-    ) -> Result<(bool, bool, Vec<String>), ProcessorError> {
-        debug!("Extracting and validating metadata");
+    ) -> Result<(bool, bool, bool, bool, Vec<RejectionReason>), ProcessorError> {
+        // Without metadata constraints an unreadable EXIF block does not matter
+        if !context.needs_metadata() {
+            debug!("No metadata constraints; skipping EXIF extraction");
+            return Ok((true, true, true, true, Vec::new()));
+        }
 
-        // Extract EXIF data
-        let exif_data = extract_exif_metadata(image_path)?;
+        debug!("Extracting and validating metadata");
         let mut reasons = Vec::new();
 
+        // Extract EXIF data; with `reject_edited`, a photo format without any EXIF is
+        // itself a sign of editing rather than an error
+        let exif_data = match extract_exif_metadata_with_timeout(
+            image_path,
+            self.exif_default_offset,
+            self.exif_timeout,
+            Some(&self.exif_cache),
+        )
+        .await
+        {
+            Err(ExifError::MissingExif)
+                if context.reject_edited && Self::expects_exif(image_path) =>
+            {
+                reasons.push(RejectionReason::new(
+                    ReasonCode::ImageEdited,
+                    "image has no EXIF metadata, which cameras always write",
+                ));
+                ExifData::without_metadata(self.exif_default_offset)
+            }
+            // Lenient requests read an image without EXIF as one missing every tag
+            Err(ExifError::MissingExif)
+                if context.on_missing_metadata != MissingMetadataPolicy::Reject =>
+            {
+                ExifData::without_metadata(self.exif_default_offset)
+            }
+            result => result?,
+        };
+        let policy = context.on_missing_metadata;
+        debug!(
+            "EXIF timestamps interpreted at UTC{} ({})",
+            exif_data.utc_offset,
+            if exif_data.offset_from_exif {
+                "from EXIF offset tag"
+            } else {
+                "configured default"
+            }
+        );
+
         // Validate location constraint if present
         let location_valid = if let Some(location_constraint) = &context.location_constraint {
-            match exif_data.gps_coordinates {
-                Some(coords) => {
+            match (
+                self.resolve_location(location_constraint).await,
+                exif_data.gps_coordinates,
+            ) {
+                (Err(reason), _) => {
+                    reasons.push(RejectionReason::new(ReasonCode::PlaceNotResolved, reason));
+                    false
+                }
+                (Ok(resolved_constraint), Some(coords)) => {
                     debug!("Found GPS coordinates: {}", coords_to_string(coords));
 
-                    match validate_location(coords, location_constraint) {
+                    match validate_location(coords, &resolved_constraint) {
                         Ok(valid) => {
                             if !valid {
-                                let expected_coords =
-                                    (location_constraint.latitude, location_constraint.longitude);
-                                let actual_distance =
-                                    crate::utils::haversine_distance(coords, expected_coords);
-                                reasons.push(format!(
-                                    "image location {} is {} from expected location {}, exceeding {} limit",
-                                    coords_to_string(coords),
-                                    format_distance(actual_distance),
-                                    coords_to_string(expected_coords),
-                                    format_distance(location_constraint.max_distance_meters)
+                                reasons.push(RejectionReason::new(
+                                    ReasonCode::LocationOutOfRange,
+                                    location_rejection_reason(coords, location_constraint),
                                 ));
+                            } else if let Some(note) =
+                                gps_precision_note(coords, location_constraint)
+                            {
+                                reasons.push(
+                                    RejectionReason::new(ReasonCode::LocationOutOfRange, note)
+                                        .as_warning(),
+                                );
                             }
                             valid
                         }
                         Err(e) => {
-                            reasons.push(format!("location validation error: {e}"));
+                            reasons.push(RejectionReason::new(
+                                ReasonCode::ValidationError,
+                                format!("location validation error: {e}"),
+                            ));
                             false
                         }
                     }
                 }
-                None => {
-                    reasons.push("image does not contain GPS coordinates".to_string());
-                    false
-                }
+                (Ok(_), None) => missing_metadata(
+                    policy,
+                    &mut reasons,
+                    ReasonCode::GpsMissing,
+                    "image does not contain GPS coordinates",
+                ),
             }
         } else {
             true // No location constraint, so it passes
         };
 
+        // Validate altitude constraint if present; it counts towards the location check
+        let altitude_valid = match (&context.altitude_constraint, exif_data.altitude_meters) {
+            (Some(constraint), Some(altitude)) => {
+                debug!("Found GPS altitude: {:.1}m", altitude);
+                match altitude_violation(altitude, constraint) {
+                    Some(reason) => {
+                        reasons.push(RejectionReason::new(ReasonCode::AltitudeOutOfRange, reason));
+                        false
+                    }
+                    None => true,
+                }
+            }
+            (Some(_), None) => missing_metadata(
+                policy,
+                &mut reasons,
+                ReasonCode::AltitudeMissing,
+                "image does not contain GPS altitude",
+            ),
+            (None, _) => true,
+        };
+
+        // So does the speed the device recorded while taking the image
+        let speed_valid = match (context.max_speed_kmh, exif_data.speed_kmh) {
+            (Some(max_speed), Some(speed)) => {
+                debug!("Found GPS speed: {:.1} km/h", speed);
+                match speed_violation(speed, max_speed) {
+                    Some(reason) => {
+                        reasons.push(RejectionReason::new(ReasonCode::SpeedOutOfRange, reason));
+                        false
+                    }
+                    None => true,
+                }
+            }
+            (Some(_), None) => missing_metadata(
+                policy,
+                &mut reasons,
+                ReasonCode::SpeedMissing,
+                "image does not contain GPS speed",
+            ),
+            (None, _) => true,
+        };
+        // Some workflows need GPS stripped before images are submitted
+        let gps_absent = match exif_data.gps_coordinates {
+            Some(coords) if context.forbid_gps => {
+                debug!(
+                    "Found forbidden GPS coordinates: {}",
+                    coords_to_string(coords)
+                );
+                reasons.push(RejectionReason::new(
+                    ReasonCode::GpsPresent,
+                    "image unexpectedly contains GPS coordinates",
+                ));
+                false
+            }
+            _ => true,
+        };
+        let location_valid = location_valid && altitude_valid && speed_valid && gps_absent;
+
         // Validate datetime constraint if present
         let datetime_valid = if let Some(datetime_constraint) = &context.datetime_constraint {
-            // Try to use DateTimeOriginal first, then DateTime
-            let image_timestamp = exif_data.datetime_original.or(exif_data.timestamp);
+            // DateTimeOriginal first, then DateTime, then the GPS timestamp
+            let image_timestamp = exif_data.capture_time();
 
             match image_timestamp {
                 Some(timestamp) => {
@@ -260,7 +1232,7 @@ impl ValidationProcessor {
                                     )
                                 };
 
-                                reasons.push(format!(
+                                let mut message = format!(
                                     "image timestamp {} is {}, outside allowed time range {} to {}",
                                     timestamp.format("%Y-%m-%d %H:%M:%S %z"),
                                     time_diff,
@@ -268,104 +1240,440 @@ impl ValidationProcessor {
                                         .start_time
                                         .format("%Y-%m-%d %H:%M:%S %z"),
                                     datetime_constraint.end_time.format("%Y-%m-%d %H:%M:%S %z")
+                                );
+                                // Shows which offset the wall-clock EXIF time was read in
+                                if let Some(raw) = exif_data.raw_capture_time() {
+                                    message.push_str(&format!(
+                                        "; raw EXIF '{raw}' parsed as {}",
+                                        timestamp.format("%Y-%m-%d %H:%M:%S %:z")
+                                    ));
+                                }
+                                reasons.push(RejectionReason::new(
+                                    ReasonCode::TimestampOutOfRange,
+                                    message,
                                 ));
                             }
                             valid
                         }
                         Err(e) => {
-                            reasons.push(format!("datetime validation error: {e}"));
+                            reasons.push(RejectionReason::new(
+                                ReasonCode::ValidationError,
+                                format!("datetime validation error: {e}"),
+                            ));
                             false
                         }
                     }
                 }
-                None => {
-                    reasons.push("image does not contain timestamp information".to_string());
+                None => missing_metadata(
+                    policy,
+                    &mut reasons,
+                    ReasonCode::TimestampMissing,
+                    "image does not contain timestamp information",
+                ),
+            }
+        } else if context.forbid_timestamp {
+            match exif_data.capture_time() {
+                Some(timestamp) => {
+                    debug!("Found forbidden image timestamp: {}", timestamp);
+                    reasons.push(RejectionReason::new(
+                        ReasonCode::TimestampPresent,
+                        "image unexpectedly contains a timestamp",
+                    ));
                     false
                 }
+                None => true,
             }
         } else {
             true // No datetime constraint, so it passes
         };
 
+        // Validate camera allowlist if present
+        let camera_valid = if let Some(allowed_cameras) = &context.allowed_cameras {
+            match exif_data.camera() {
+                Some(camera) => {
+                    debug!("Found camera: {}", camera);
+                    let allowed = is_camera_allowed(&camera, allowed_cameras);
+                    if !allowed {
+                        reasons.push(RejectionReason::new(
+                            ReasonCode::CameraNotAllowed,
+                            format!("camera '{camera}' not in allowed list"),
+                        ));
+                    }
+                    allowed
+                }
+                None => missing_metadata(
+                    policy,
+                    &mut reasons,
+                    ReasonCode::CameraMissing,
+                    "image does not contain camera make and model",
+                ),
+            }
+        } else {
+            true // No camera constraint, so it passes
+        };
+
+        // Look for signs of editing if asked to
+        let unedited = if context.reject_edited {
+            let indicators = exif_data.edit_indicators();
+            for indicator in &indicators {
+                debug!("Edit indicator: {}", indicator);
+            }
+            reasons.extend(
+                indicators
+                    .into_iter()
+                    .map(|indicator| RejectionReason::new(ReasonCode::ImageEdited, indicator)),
+            );
+            !reasons
+                .iter()
+                .any(|reason| reason.code == ReasonCode::ImageEdited)
+        } else {
+            true
+        };
+
         debug!(
-            "Metadata validation results - location: {}, datetime: {}",
-            location_valid, datetime_valid
+            "Metadata validation results - location: {}, datetime: {}, camera: {}, unedited: {}",
+            location_valid, datetime_valid, camera_valid, unedited
         );
 
-        Ok((location_valid, datetime_valid, reasons))
+        Ok((
+            location_valid,
+            datetime_valid,
+            camera_valid,
+            unedited,
+            reasons,
+        ))
+    }
+
+    /// Whether the image is in a format cameras always write EXIF to
+    fn expects_exif(image_path: &str) -> bool {
+        ImageContainer::of_file(Path::new(image_path))
+            .ok()
+            .flatten()
+            .is_some_and(ImageContainer::expects_exif)
+    }
+
+    /// Geocodes a `place_name` constraint into its bounding polygon
+    ///
+    /// Other constraints are returned unchanged. The error is the rejection message.
+    async fn resolve_location(
+        &self,
+        constraint: &LocationConstraint,
+    ) -> Result<LocationConstraint, String> {
+        let LocationConstraint::Place(place_name) = constraint else {
+            return Ok(constraint.clone());
+        };
+
+        let Some(geocoder) = &self.geocoder else {
+            return Err(format!(
+                "cannot resolve place '{place_name}': no geocoder is configured"
+            ));
+        };
+        match geocoder.resolve(place_name).await {
+            Ok(polygon) => Ok(LocationConstraint::Polygon(polygon)),
+            Err(e) => {
+                warn!("Failed to geocode {:?}: {}", place_name, e);
+                Err(format!("cannot resolve place '{place_name}': {e}"))
+            }
+        }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::models::{AnalysisRequest, DateTimeRequest, LocationRequest};
+/// Outcome of the content and metadata checks for one image
+struct CheckResults {
+    content: ContentVerdict,
+    /// Location, altitude and speed checks together
+    location: CheckState,
+    datetime: CheckState,
+    camera_valid: bool,
+    unedited: bool,
+    reasons: Vec<RejectionReason>,
+}
 
-    fn create_test_config() -> Config {
-        Config {
-            host: "127.0.0.1".to_string(),
-            port: 3000,
-            image_base_dir: "/tmp".to_string(),
-            llm_api_url: "http://localhost:8080".to_string(),
-            llm_model_name: "llava:7b".to_string(),
-            request_timeout_seconds: 30,
-            processing_timeout_minutes: 5,
-            queue_size: 100,
-            throttle_requests_per_minute: 60,
+/// Outcome of a constrained check whose metadata the image lacks, following the
+/// request's `on_missing_metadata`
+fn missing_metadata(
+    policy: MissingMetadataPolicy,
+    reasons: &mut Vec<RejectionReason>,
+    code: ReasonCode,
+    message: &str,
+) -> bool {
+    match policy {
+        MissingMetadataPolicy::Reject => {
+            reasons.push(RejectionReason::new(code, message));
+            false
+        }
+        MissingMetadataPolicy::Warn => {
+            reasons.push(RejectionReason::new(code, message).as_warning());
+            true
         }
+        MissingMetadataPolicy::Pass => true,
     }
+}
 
-    #[test]
-    fn test_resolve_image_path_local() {
-        let config = create_test_config();
-        let processor = ValidationProcessor::new(&config);
+/// Verbose check list for an image rejected before its checks ran
+fn skipped_checks(context: &ValidationContext) -> Option<Vec<CheckOutcome>> {
+    context.verbose.then(|| {
+        CheckOutcome::list(
+            CheckState::Skipped,
+            CheckState::Skipped,
+            CheckState::Skipped,
+        )
+    })
+}
 
-        // Test absolute path
-        let request = ProcessingRequest {
-            processing_id: "test".to_string(),
-            image_path: Some("/absolute/path/image.jpg".to_string()),
-            image: None,
-            analysis_request: AnalysisRequest {
-                image_path: None,
-                content: "test".to_string(),
-                location: None,
-                datetime: None,
-            },
-        };
+/// File extension at the end of a URL's path, ignoring any query or fragment
+fn url_extension(url: &str) -> Option<&str> {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    Path::new(path).extension().and_then(|ext| ext.to_str())
+}
 
-        let resolved = processor.resolve_image_path(&request).unwrap();
-        assert_eq!(resolved, "/absolute/path/image.jpg");
+/// Rejection message for a failed content check, naming every description
+fn content_mismatch_message(context: &ValidationContext) -> String {
+    if let [description] = context.content_checks.as_slice() {
+        return format!(
+            "image content does not match description: '{}'",
+            description
+        );
+    }
 
-        // Test relative path with $image_base_dir
+    let descriptions = context
+        .content_checks
+        .iter()
+        .map(|description| format!("'{}'", description))
+        .collect::<Vec<_>>()
+        .join(", ");
+    match context.content_match_mode {
+        MatchMode::Any => format!(
+            "image content matches none of the descriptions: {}",
+            descriptions
+        ),
+        MatchMode::All => format!(
+            "image content does not match all of the descriptions: {}",
+            descriptions
+        ),
+    }
+}
+
+/// Checks the model's explanation against the request's keyword lists
+///
+/// A forbidden keyword overrides an accepting verdict, and a required one must be
+/// mentioned for the content check to pass. Returns one message per failed keyword.
+fn keyword_failures(explanation: &str, context: &ValidationContext) -> Vec<String> {
+    let forbidden = context
+        .forbidden_keywords
+        .iter()
+        .filter(|keyword| mentions_keyword(explanation, keyword))
+        .map(|keyword| format!("model explanation mentions forbidden keyword '{keyword}'"));
+    let missing = context
+        .required_keywords
+        .iter()
+        .filter(|keyword| !mentions_keyword(explanation, keyword))
+        .map(|keyword| format!("model explanation does not mention required keyword '{keyword}'"));
+    forbidden.chain(missing).collect()
+}
+
+/// Whether `text` contains `keyword` as whole words, ignoring case
+fn mentions_keyword(text: &str, keyword: &str) -> bool {
+    let text = text.to_lowercase();
+    let keyword = keyword.to_lowercase();
+    text.match_indices(&keyword).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + keyword.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// Perceptual hash of an image file, computed on the blocking thread pool
+///
+/// Fails for formats the `image` crate cannot decode, such as HEIC.
+async fn image_hash(image_path: &str) -> Result<u64, String> {
+    let path = image_path.to_string();
+    tokio::task::spawn_blocking(move || {
+        let bytes = std::fs::read(&path).map_err(|e| e.to_string())?;
+        dhash(&bytes).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|hash| hash)
+}
+
+/// Explain why GPS coordinates fall outside the location constraint
+fn location_rejection_reason(coords: (f64, f64), constraint: &LocationConstraint) -> String {
+    match constraint {
+        LocationConstraint::Radius {
+            latitude,
+            longitude,
+            max_distance_meters,
+            distance_unit,
+            tolerate_gps_precision,
+        } => {
+            let expected_coords = (*latitude, *longitude);
+            let limit = if *tolerate_gps_precision {
+                format!(
+                    "{} limit plus ≈{} GPS precision",
+                    format_distance_in(*max_distance_meters, *distance_unit),
+                    format_distance_in(estimate_gps_precision(coords), *distance_unit)
+                )
+            } else {
+                format!(
+                    "{} limit",
+                    format_distance_in(*max_distance_meters, *distance_unit)
+                )
+            };
+            format!(
+                "image location {} is ≈{} to the {} from expected location {}, exceeding {}",
+                coords_to_string(coords),
+                format_distance_in(haversine_distance(coords, expected_coords), *distance_unit),
+                compass_point(bearing_degrees(expected_coords, coords)),
+                coords_to_string(expected_coords),
+                limit
+            )
+        }
+        LocationConstraint::Polygon(_) => format!(
+            "image location {} is outside the allowed area",
+            coords_to_string(coords)
+        ),
+        LocationConstraint::Place(place_name) => format!(
+            "image location {} is outside {}",
+            coords_to_string(coords),
+            place_name
+        ),
+    }
+}
+
+/// Note for coordinates accepted only because `tolerate_gps_precision` widened the radius
+fn gps_precision_note(coords: (f64, f64), constraint: &LocationConstraint) -> Option<String> {
+    let LocationConstraint::Radius {
+        latitude,
+        longitude,
+        max_distance_meters,
+        distance_unit,
+        tolerate_gps_precision: true,
+    } = constraint
+    else {
+        return None;
+    };
+    let expected_coords = (*latitude, *longitude);
+    let distance = haversine_distance(coords, expected_coords);
+    (distance > *max_distance_meters).then(|| {
+        format!(
+            "image location {} is ≈{} from expected location {}, over the {} limit but within the ≈{} precision of its GPS coordinates",
+            coords_to_string(coords),
+            format_distance_in(distance, *distance_unit),
+            coords_to_string(expected_coords),
+            format_distance_in(*max_distance_meters, *distance_unit),
+            format_distance_in(estimate_gps_precision(coords), *distance_unit)
+        )
+    })
+}
+
+#[async_trait]
+impl RequestValidator for ValidationProcessor {
+    async fn validate_request(
+        &self,
+        request: ProcessingRequest,
+        progress: &Progress,
+    ) -> Result<ValidationResults, ProcessorError> {
+        ValidationProcessor::validate_request(self, request, progress).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        AnalysisRequest, ContentRequest, DateTimeDuration, DateTimeRequest, DistanceUnit,
+        LocationRequest,
+    };
+
+    fn create_test_config() -> Config {
+        Config {
+            host: "127.0.0.1".to_string(),
+            port: 3000,
+            image_base_dir: "/tmp".to_string(),
+            llm_api_url: "http://localhost:8080".to_string(),
+            llm_model_name: "llava:7b".to_string(),
+            request_timeout_seconds: 30,
+            processing_timeout_minutes: 5,
+            queue_size: 100,
+            throttle_requests_per_minute: 60,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_retryable_errors() {
+        let io_error = || std::io::Error::new(std::io::ErrorKind::TimedOut, "timed out");
+
+        assert!(ProcessorError::Llm(LlmError::Timeout).is_retryable());
+        assert!(ProcessorError::Llm(LlmError::MaxRetriesExceeded).is_retryable());
+        assert!(ProcessorError::Llm(LlmError::CircuitOpen).is_retryable());
+        assert!(ProcessorError::Storage(StorageError::Io(io_error())).is_retryable());
+        assert!(ProcessorError::Exif(ExifError::Io(io_error())).is_retryable());
+        assert!(ProcessorError::Exif(ExifError::Timeout(Duration::from_secs(10))).is_retryable());
+
+        assert!(!ProcessorError::ImageNotFound("missing.jpg".to_string()).is_retryable());
+        assert!(
+            !ProcessorError::Storage(StorageError::PathNotFound("missing.jpg".into()))
+                .is_retryable()
+        );
+        assert!(!ProcessorError::ValidationContext("bad polygon".to_string()).is_retryable());
+        assert!(
+            !ProcessorError::Llm(LlmError::UnsupportedByBackend("TIFF".to_string())).is_retryable()
+        );
+    }
+
+    #[test]
+    fn test_resolve_image_path_local() {
+        let config = create_test_config();
+        let processor = ValidationProcessor::new(&config);
+
+        // Test absolute path
         let request = ProcessingRequest {
             processing_id: "test".to_string(),
-            image_path: Some("$image_base_dir/image.jpg".to_string()),
+            image_path: Some("/absolute/path/image.jpg".to_string().into()),
             image: None,
-            analysis_request: AnalysisRequest {
-                image_path: None,
-                content: "test".to_string(),
-                location: None,
-                datetime: None,
-            },
+            callback_url: None,
+            timeout_seconds: None,
+            deadline: None,
+            analysis_request: AnalysisRequest::new("test"),
         };
 
-        let resolved = processor.resolve_image_path(&request).unwrap();
+        let resolved = processor
+            .resolve_image_path(request.get_image_path())
+            .unwrap();
+        assert_eq!(resolved, "/absolute/path/image.jpg");
+
+        // Test relative path with $image_base_dir
+        let request = ProcessingRequest {
+            processing_id: "test".to_string(),
+            image_path: Some("$image_base_dir/image.jpg".to_string().into()),
+            image: None,
+            callback_url: None,
+            timeout_seconds: None,
+            deadline: None,
+            analysis_request: AnalysisRequest::new("test"),
+        };
+
+        let resolved = processor
+            .resolve_image_path(request.get_image_path())
+            .unwrap();
         assert_eq!(resolved, "/tmp/image.jpg");
 
         // Test simple relative path
         let request = ProcessingRequest {
             processing_id: "test".to_string(),
-            image_path: Some("image.jpg".to_string()),
+            image_path: Some("image.jpg".to_string().into()),
             image: None,
-            analysis_request: AnalysisRequest {
-                image_path: None,
-                content: "test".to_string(),
-                location: None,
-                datetime: None,
-            },
+            callback_url: None,
+            timeout_seconds: None,
+            deadline: None,
+            analysis_request: AnalysisRequest::new("test"),
         };
 
-        let resolved = processor.resolve_image_path(&request).unwrap();
+        let resolved = processor
+            .resolve_image_path(request.get_image_path())
+            .unwrap();
         assert_eq!(resolved, "/tmp/image.jpg");
     }
 
@@ -382,58 +1690,93 @@ mod tests {
             processing_timeout_minutes: 5,
             queue_size: 100,
             throttle_requests_per_minute: 60,
+            ..Config::default()
         };
         let processor = ValidationProcessor::new(&config);
 
         // Test absolute path (should be returned as-is)
         let request = ProcessingRequest {
             processing_id: "test".to_string(),
-            image_path: Some("/absolute/path/image.jpg".to_string()),
+            image_path: Some("/absolute/path/image.jpg".to_string().into()),
             image: None,
-            analysis_request: AnalysisRequest {
-                image_path: None,
-                content: "test".to_string(),
-                location: None,
-                datetime: None,
-            },
+            callback_url: None,
+            timeout_seconds: None,
+            deadline: None,
+            analysis_request: AnalysisRequest::new("test"),
         };
 
-        let resolved = processor.resolve_image_path(&request).unwrap();
+        let resolved = processor
+            .resolve_image_path(request.get_image_path())
+            .unwrap();
         assert_eq!(resolved, "/absolute/path/image.jpg");
 
         // Test relative path with $image_base_dir (legacy support)
         let request = ProcessingRequest {
             processing_id: "test".to_string(),
-            image_path: Some("$image_base_dir/image.jpg".to_string()),
+            image_path: Some("$image_base_dir/image.jpg".to_string().into()),
             image: None,
-            analysis_request: AnalysisRequest {
-                image_path: None,
-                content: "test".to_string(),
-                location: None,
-                datetime: None,
-            },
+            callback_url: None,
+            timeout_seconds: None,
+            deadline: None,
+            analysis_request: AnalysisRequest::new("test"),
         };
 
-        let resolved = processor.resolve_image_path(&request).unwrap();
+        let resolved = processor
+            .resolve_image_path(request.get_image_path())
+            .unwrap();
         assert_eq!(resolved, "/tmp/image.jpg");
 
         // Test simple relative path
         let request = ProcessingRequest {
             processing_id: "test".to_string(),
-            image_path: Some("image.jpg".to_string()),
+            image_path: Some("image.jpg".to_string().into()),
             image: None,
-            analysis_request: AnalysisRequest {
-                image_path: None,
-                content: "test".to_string(),
-                location: None,
-                datetime: None,
-            },
+            callback_url: None,
+            timeout_seconds: None,
+            deadline: None,
+            analysis_request: AnalysisRequest::new("test"),
         };
 
-        let resolved = processor.resolve_image_path(&request).unwrap();
+        let resolved = processor
+            .resolve_image_path(request.get_image_path())
+            .unwrap();
         assert_eq!(resolved, "/tmp/image.jpg");
     }
 
+    #[test]
+    fn test_resolve_path_with_trailing_slash_bases() {
+        let resolve = |image_base_dir: &str, image_path: &str| {
+            let config = Config {
+                image_base_dir: image_base_dir.to_string(),
+                ..create_test_config()
+            };
+            ValidationProcessor::new(&config).resolve_path(image_path.to_string())
+        };
+
+        for base in ["/tmp/images/", "file:///tmp/images/"] {
+            assert_eq!(resolve(base, "image.jpg"), "/tmp/images/image.jpg");
+            assert_eq!(
+                resolve(base, "$image_base_dir/image.jpg"),
+                "/tmp/images/image.jpg"
+            );
+            assert_eq!(resolve(base, "/absolute/image.jpg"), "/absolute/image.jpg");
+        }
+        assert_eq!(
+            resolve("s3://my-bucket/images/", "$image_base_dir/image.jpg"),
+            "s3://my-bucket/images/image.jpg"
+        );
+
+        // Doubled slashes after the token stay under the base
+        assert_eq!(
+            resolve("/tmp/images", "$image_base_dir//image.jpg"),
+            "/tmp/images/image.jpg"
+        );
+        assert_eq!(
+            resolve("s3://my-bucket/images", "$image_base_dir//image.jpg"),
+            "s3://my-bucket/images/image.jpg"
+        );
+    }
+
     #[test]
     fn test_resolve_image_path_no_path() {
         let config = create_test_config();
@@ -444,15 +1787,13 @@ mod tests {
             processing_id: "test".to_string(),
             image_path: None,
             image: None,
-            analysis_request: AnalysisRequest {
-                image_path: None,
-                content: "test".to_string(),
-                location: None,
-                datetime: None,
-            },
+            callback_url: None,
+            timeout_seconds: None,
+            deadline: None,
+            analysis_request: AnalysisRequest::new("test"),
         };
 
-        let result = processor.resolve_image_path(&request);
+        let result = processor.resolve_image_path(request.get_image_path());
         assert!(result.is_err());
         assert!(result
             .unwrap_err()
@@ -468,63 +1809,143 @@ mod tests {
         // Test request with full file:// URI - should extract the path directly
         let request = ProcessingRequest {
             processing_id: "test".to_string(),
-            image_path: Some("file:///tmp/image-test/IMG_7910s.jpeg".to_string()),
+            image_path: Some("file:///tmp/image-test/IMG_7910s.jpeg".to_string().into()),
             image: None,
-            analysis_request: AnalysisRequest {
-                image_path: None,
-                content: "test".to_string(),
-                location: None,
-                datetime: None,
-            },
+            callback_url: None,
+            timeout_seconds: None,
+            deadline: None,
+            analysis_request: AnalysisRequest::new("test"),
         };
 
-        let resolved = processor.resolve_image_path(&request).unwrap();
+        let resolved = processor
+            .resolve_image_path(request.get_image_path())
+            .unwrap();
         assert_eq!(resolved, "/tmp/image-test/IMG_7910s.jpeg");
 
         // Test with unsupported URI scheme - should return as-is for backward compatibility
         let request = ProcessingRequest {
             processing_id: "test".to_string(),
-            image_path: Some("http://example.com/image.jpg".to_string()),
+            image_path: Some("http://example.com/image.jpg".to_string().into()),
             image: None,
-            analysis_request: AnalysisRequest {
-                image_path: None,
-                content: "test".to_string(),
-                location: None,
-                datetime: None,
-            },
+            callback_url: None,
+            timeout_seconds: None,
+            deadline: None,
+            analysis_request: AnalysisRequest::new("test"),
         };
 
-        let resolved = processor.resolve_image_path(&request).unwrap();
+        let resolved = processor
+            .resolve_image_path(request.get_image_path())
+            .unwrap();
         assert_eq!(resolved, "http://example.com/image.jpg");
     }
 
+    #[test]
+    fn test_resolve_image_path_s3_base() {
+        let config = Config {
+            image_base_dir: "s3://my-bucket/images".to_string(),
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+
+        // Relative paths resolve to objects under the bucket prefix
+        let request = ProcessingRequest {
+            processing_id: "test".to_string(),
+            image_path: Some("image.jpg".to_string().into()),
+            image: None,
+            callback_url: None,
+            timeout_seconds: None,
+            deadline: None,
+            analysis_request: AnalysisRequest::new("test"),
+        };
+
+        let resolved = processor
+            .resolve_image_path(request.get_image_path())
+            .unwrap();
+        assert_eq!(resolved, "s3://my-bucket/images/image.jpg");
+
+        // Full S3 URIs in the request are kept for fetching
+        let request = ProcessingRequest {
+            processing_id: "test".to_string(),
+            image_path: Some("s3://other-bucket/photo.jpg".to_string().into()),
+            image: None,
+            callback_url: None,
+            timeout_seconds: None,
+            deadline: None,
+            analysis_request: AnalysisRequest::new("test"),
+        };
+
+        let resolved = processor
+            .resolve_image_path(request.get_image_path())
+            .unwrap();
+        assert_eq!(resolved, "s3://other-bucket/photo.jpg");
+    }
+
+    #[tokio::test]
+    async fn test_stage_image_local_path_is_untouched() {
+        let config = create_test_config();
+        let processor = ValidationProcessor::new(&config);
+
+        let local_image = processor.stage_image("/tmp/image.jpg").await.unwrap();
+        assert_eq!(local_image.path, "/tmp/image.jpg");
+        assert!(local_image._temp_file.is_none());
+    }
+
+    #[test]
+    fn test_stage_inline_png_data_url() {
+        let data_url = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==";
+        let image = decode_inline_image(data_url, 1024).unwrap();
+
+        let local_image = ValidationProcessor::stage_inline_image(&image).unwrap();
+        assert!(local_image.path.ends_with(".png"));
+        assert_eq!(std::fs::read(&local_image.path).unwrap(), image.bytes);
+
+        // The temp file is removed once the staged image is dropped
+        let path = local_image.path.clone();
+        drop(local_image);
+        assert!(!Path::new(&path).exists());
+    }
+
     #[test]
     fn test_validation_context_creation() {
         let analysis_request = AnalysisRequest {
-            image_path: None,
-            content: "Three birds on a wire".to_string(),
             location: Some(LocationRequest {
-                long: -0.266108,
-                lat: 51.492191,
-                max_distance: 100.0,
+                long: Some(-0.266108),
+                lat: Some(51.492191),
+                max_distance: Some(100.0),
+                polygon: None,
+                place_name: None,
+                distance_unit: None,
+                altitude: None,
+                tolerate_gps_precision: None,
             }),
             datetime: Some(DateTimeRequest {
                 start: Some("2025-08-01T15:23:00+01:00".to_string()),
                 end: None,
-                duration: Some(10), // 10 minutes
+                duration: Some(DateTimeDuration::Minutes(10)), // 10 minutes
+                local_date: None,
+                timezone: None,
             }),
+            ..AnalysisRequest::new("Three birds on a wire")
         };
 
         let context = ValidationContext::try_from(analysis_request).unwrap();
 
-        assert_eq!(context.content_check, "Three birds on a wire");
+        assert_eq!(context.content_checks, vec!["Three birds on a wire"]);
         assert!(context.location_constraint.is_some());
         assert!(context.datetime_constraint.is_some());
 
-        let location = context.location_constraint.unwrap();
-        assert_eq!(location.max_distance_meters, 100.0);
-        assert!((location.latitude - 51.492191).abs() < 0.000001);
-        assert!((location.longitude + 0.266108).abs() < 0.000001);
+        let Some(LocationConstraint::Radius {
+            latitude,
+            longitude,
+            max_distance_meters,
+            ..
+        }) = context.location_constraint
+        else {
+            panic!("expected a radius constraint");
+        };
+        assert_eq!(max_distance_meters, 100.0);
+        assert!((latitude - 51.492191).abs() < 0.000001);
+        assert!((longitude + 0.266108).abs() < 0.000001);
 
         let datetime = context.datetime_constraint.unwrap();
         assert_eq!(
@@ -537,5 +1958,1674 @@ mod tests {
         );
     }
 
+    /// Serves an OpenAI-compatible API that accepts only images whose bytes mention birds
+    async fn spawn_birds_llm() -> String {
+        use axum::{routing::post, Json, Router};
+        use base64::{engine::general_purpose, Engine as _};
+        use serde_json::{json, Value};
+
+        let app = Router::new().fallback(post(|Json(body): Json<Value>| async move {
+            let url = body["messages"][0]["content"][1]["image_url"]["url"]
+                .as_str()
+                .unwrap_or_default();
+            let data = url
+                .split_once(',')
+                .map(|(_, data)| data)
+                .unwrap_or_default();
+            let image = general_purpose::STANDARD.decode(data).unwrap_or_default();
+            let reply = if image.windows(5).any(|window| window == b"birds") {
+                "ACCEPTED: three birds perched on a wire\nSCORE: 0.9"
+            } else {
+                "REJECTED: no birds\nSCORE: 0.8"
+            };
+            Json(json!({ "choices": [{ "message": { "content": reply } }] }))
+        }));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}/v1/chat/completions")
+    }
+
+    #[tokio::test]
+    async fn test_validate_multiple_images_match_mode() {
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use exif::Tag;
+
+        let config = Config {
+            llm_api_url: spawn_birds_llm().await,
+            throttle_requests_per_minute: 6000,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+
+        let empty_wire = write_jpeg_with_exif(&[ascii_field(Tag::ImageDescription, "wire")]);
+        let birds = write_jpeg_with_exif(&[ascii_field(Tag::ImageDescription, "three birds")]);
+        let image_paths = vec![
+            empty_wire.path().to_string_lossy().into_owned(),
+            birds.path().to_string_lossy().into_owned(),
+        ];
+
+        let request = |match_mode| ProcessingRequest {
+            processing_id: "test".to_string(),
+            image_path: Some(image_paths.clone().into()),
+            image: None,
+            callback_url: None,
+            timeout_seconds: None,
+            deadline: None,
+            analysis_request: AnalysisRequest {
+                match_mode,
+                ..AnalysisRequest::new("Three birds on a wire")
+            },
+        };
+
+        // One of the two images matches, so `any` passes
+        let results = processor
+            .validate_request(request(MatchMode::Any), &Progress::default())
+            .await
+            .unwrap();
+        assert_eq!(results.resolution, Resolution::Accepted);
+        let images = results.images.unwrap();
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[0].resolution, Resolution::Rejected);
+        assert_eq!(images[1].image_path, image_paths[1]);
+        assert_eq!(images[1].resolution, Resolution::Accepted);
+
+        // ...but `all` does not, and says which image failed
+        let results = processor
+            .validate_request(request(MatchMode::All), &Progress::default())
+            .await
+            .unwrap();
+        assert_eq!(results.resolution, Resolution::Rejected);
+        assert_eq!(
+            results.reasons.unwrap(),
+            vec![RejectionReason::new(
+                ReasonCode::ContentMismatch,
+                format!(
+                    "{}: image content does not match description: 'Three birds on a wire'",
+                    image_paths[0]
+                )
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_allowed_cameras() {
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use exif::Tag;
+
+        let config = Config {
+            llm_api_url: spawn_birds_llm().await,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+
+        let iphone = write_jpeg_with_exif(&[
+            ascii_field(Tag::ImageDescription, "three birds"),
+            ascii_field(Tag::Make, "Apple"),
+            ascii_field(Tag::Model, "iPhone 15"),
+        ]);
+        let unknown = write_jpeg_with_exif(&[ascii_field(Tag::ImageDescription, "three birds")]);
+
+        let context = |cameras: &[&str]| {
+            ValidationContext::try_from(AnalysisRequest {
+                allowed_cameras: Some(cameras.iter().map(|c| c.to_string()).collect()),
+                ..AnalysisRequest::new("Three birds on a wire")
+            })
+            .unwrap()
+        };
+        let iphone_path = iphone.path().to_str().unwrap();
+
+        let results = processor
+            .validate_local_image(
+                iphone_path,
+                &context(&["Apple iPhone 15"]),
+                &Progress::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.resolution, Resolution::Accepted);
+
+        let results = processor
+            .validate_local_image(
+                iphone_path,
+                &context(&["Google Pixel 8"]),
+                &Progress::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            results.reasons.unwrap(),
+            vec![RejectionReason::new(
+                ReasonCode::CameraNotAllowed,
+                "camera 'Apple iPhone 15' not in allowed list"
+            )]
+        );
+
+        let results = processor
+            .validate_local_image(
+                unknown.path().to_str().unwrap(),
+                &context(&["Apple iPhone 15"]),
+                &Progress::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            results.reasons.unwrap(),
+            vec![RejectionReason::new(
+                ReasonCode::CameraMissing,
+                "image does not contain camera make and model"
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reject_edited_images() {
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use exif::Tag;
+
+        let config = Config {
+            llm_api_url: spawn_birds_llm().await,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+
+        let edited = write_jpeg_with_exif(&[
+            ascii_field(Tag::ImageDescription, "three birds"),
+            ascii_field(Tag::Software, "Adobe Photoshop 25.0 (Windows)"),
+        ]);
+        let from_phone = write_jpeg_with_exif(&[
+            ascii_field(Tag::ImageDescription, "three birds"),
+            ascii_field(Tag::Software, "17.1.1"),
+        ]);
+        // A JPEG whose only metadata is a comment
+        let mut stripped = NamedTempFile::with_suffix(".jpg").unwrap();
+        let comment = b"three birds";
+        stripped.write_all(&[0xFF, 0xD8, 0xFF, 0xFE, 0x00]).unwrap();
+        stripped.write_all(&[comment.len() as u8 + 2]).unwrap();
+        stripped.write_all(comment).unwrap();
+        stripped.write_all(&[0xFF, 0xD9]).unwrap();
+
+        let context = |reject_edited: bool| {
+            ValidationContext::try_from(AnalysisRequest {
+                reject_edited,
+                ..AnalysisRequest::new("Three birds on a wire")
+            })
+            .unwrap()
+        };
+        let validate = |image: &NamedTempFile, reject_edited: bool| {
+            let path = image.path().to_str().unwrap().to_string();
+            let context = context(reject_edited);
+            let processor = &processor;
+            async move {
+                processor
+                    .validate_local_image(&path, &context, &Progress::default())
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // Off by default
+        let results = validate(&edited, false).await;
+        assert_eq!(results.resolution, Resolution::Accepted);
+
+        let results = validate(&edited, true).await;
+        assert_eq!(
+            results.reasons.unwrap(),
+            vec![RejectionReason::new(
+                ReasonCode::ImageEdited,
+                "image edited with Adobe Photoshop 25.0 (Windows)"
+            )]
+        );
+
+        let results = validate(&from_phone, true).await;
+        assert_eq!(results.resolution, Resolution::Accepted);
+
+        let results = validate(&stripped, true).await;
+        assert_eq!(
+            results.reasons.unwrap(),
+            vec![RejectionReason::new(
+                ReasonCode::ImageEdited,
+                "image has no EXIF metadata, which cameras always write"
+            )]
+        );
+        // Without the flag, or any other metadata constraint, EXIF is not read at all
+        let results = validate(&stripped, false).await;
+        assert_eq!(results.resolution, Resolution::Accepted);
+    }
+
+    #[tokio::test]
+    async fn test_reject_duplicate_images() {
+        use image::{ImageFormat, RgbImage};
+
+        let config = Config {
+            llm_api_url: spawn_birds_llm().await,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+
+        // Brightening and darkening gradients, as far apart as two hashes can be
+        let write_png = |level: fn(u32) -> u8| {
+            let image = RgbImage::from_fn(64, 48, |x, _| {
+                let level = level(x);
+                image::Rgb([level, level, level])
+            });
+            let file = NamedTempFile::with_suffix(".png").unwrap();
+            image
+                .save_with_format(file.path(), ImageFormat::Png)
+                .unwrap();
+            file
+        };
+        let photo = write_png(|x| (x * 4) as u8);
+        let other = write_png(|x| 255 - (x * 4) as u8);
+
+        let validate = |image: &NamedTempFile, processing_id: &str, window: Option<u64>| {
+            let path = image.path().to_str().unwrap().to_string();
+            let context = ValidationContext::try_from(AnalysisRequest {
+                reject_duplicate_within: window,
+                ..AnalysisRequest::new("Three birds on a wire")
+            })
+            .unwrap()
+            .with_processing_id(processing_id);
+            let processor = &processor;
+            async move {
+                processor
+                    .validate_local_image(&path, &context, &Progress::default())
+                    .await
+                    .unwrap()
+            }
+        };
+        let is_duplicate = |results: &ValidationResults| {
+            results
+                .reasons
+                .iter()
+                .flatten()
+                .any(|reason| reason.code == ReasonCode::DuplicateImage)
+        };
+
+        assert!(!is_duplicate(&validate(&photo, "first", Some(60)).await));
+        // A retry of the same request is not a duplicate of itself
+        assert!(!is_duplicate(&validate(&photo, "first", Some(60)).await));
+        // Neither is a different image, nor a request that does not ask for the check,
+        // whose image is not even recorded
+        assert!(!is_duplicate(&validate(&other, "second", Some(60)).await));
+        assert_eq!(processor.seen_images.len(), 3);
+        assert!(!is_duplicate(&validate(&photo, "third", None).await));
+        assert_eq!(processor.seen_images.len(), 3);
+
+        let results = validate(&photo, "fourth", Some(60)).await;
+        assert_eq!(results.resolution, Resolution::Rejected);
+        assert_eq!(
+            results.reasons.unwrap(),
+            vec![RejectionReason::new(
+                ReasonCode::DuplicateImage,
+                "a near-identical image was validated less than a minute ago"
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_keywords_checked_against_explanation() {
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use exif::Tag;
+
+        let config = Config {
+            llm_api_url: spawn_birds_llm().await,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+
+        // The model accepts this image, explaining it sees three birds perched on a wire
+        let image = write_jpeg_with_exif(&[ascii_field(Tag::ImageDescription, "three birds")]);
+        let image_path = image.path().to_str().unwrap();
+
+        let validate = |required: &[&str], forbidden: &[&str]| {
+            let keywords = |words: &[&str]| Some(words.iter().map(|w| w.to_string()).collect());
+            let context = ValidationContext::try_from(AnalysisRequest {
+                required_keywords: keywords(required),
+                forbidden_keywords: keywords(forbidden),
+                ..AnalysisRequest::new("Three birds on a wire")
+            })
+            .unwrap();
+            let processor = &processor;
+            async move {
+                processor
+                    .validate_local_image(image_path, &context, &Progress::default())
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // Matching ignores case and only counts whole words
+        let results = validate(&["Birds", " wire "], &["bird", ""]).await;
+        assert_eq!(results.resolution, Resolution::Accepted);
+
+        // A required keyword the explanation does not mention
+        let results = validate(&["birds", "pigeon"], &[]).await;
+        assert_eq!(results.resolution, Resolution::Rejected);
+        assert_eq!(
+            results.reasons.unwrap(),
+            vec![RejectionReason::new(
+                ReasonCode::ContentMismatch,
+                "model explanation does not mention required keyword 'pigeon'"
+            )]
+        );
+
+        // A forbidden keyword rejects the image although the model accepted it
+        let results = validate(&[], &["perched on"]).await;
+        assert_eq!(results.resolution, Resolution::Rejected);
+        assert_eq!(
+            results.reasons.unwrap(),
+            vec![RejectionReason::new(
+                ReasonCode::ContentMismatch,
+                "model explanation mentions forbidden keyword 'perched on'"
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_soft_content_mismatch_is_a_warning() {
+        use crate::models::{ConstraintSeverity, Severity};
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use exif::Tag;
+
+        let config = Config {
+            llm_api_url: spawn_birds_llm().await,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+
+        // The image shows no birds and was taken with an unlisted camera
+        let image = write_jpeg_with_exif(&[
+            ascii_field(Tag::ImageDescription, "an empty wire"),
+            ascii_field(Tag::Make, "Apple"),
+            ascii_field(Tag::Model, "iPhone 15"),
+        ]);
+        let image_path = image.path().to_str().unwrap();
+
+        let context = |allowed_cameras: Option<Vec<String>>| {
+            ValidationContext::try_from(AnalysisRequest {
+                allowed_cameras,
+                severity: Some(ConstraintSeverity {
+                    content: Severity::Soft,
+                    ..ConstraintSeverity::default()
+                }),
+                ..AnalysisRequest::new("Three birds on a wire")
+            })
+            .unwrap()
+        };
+
+        // A soft content mismatch alone still accepts, but reports the mismatch
+        let results = processor
+            .validate_local_image(image_path, &context(None), &Progress::default())
+            .await
+            .unwrap();
+        assert_eq!(results.resolution, Resolution::Accepted);
+        let reasons = results.reasons.unwrap();
+        assert_eq!(reasons.len(), 1);
+        assert_eq!(reasons[0].code, ReasonCode::ContentMismatch);
+        assert!(reasons[0].warning);
+
+        let json = serde_json::to_value(&reasons[0]).unwrap();
+        assert_eq!(json["warning"], true);
+
+        // A hard failure still rejects, with the soft one flagged among the reasons
+        let results = processor
+            .validate_local_image(
+                image_path,
+                &context(Some(vec!["Canon EOS R5".into()])),
+                &Progress::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.resolution, Resolution::Rejected);
+        let reasons = results.reasons.unwrap();
+        assert_eq!(reasons.len(), 2);
+        let camera = reasons
+            .iter()
+            .find(|reason| reason.code == ReasonCode::CameraNotAllowed)
+            .unwrap();
+        assert!(!camera.warning);
+        let content = reasons
+            .iter()
+            .find(|reason| reason.code == ReasonCode::ContentMismatch)
+            .unwrap();
+        assert!(content.warning);
+        assert!(serde_json::to_value(camera)
+            .unwrap()
+            .get("warning")
+            .is_none());
+    }
+
+    /// A photo of three birds geotagged at 51°30'N 0°10'W, central London
+    fn london_birds_photo() -> NamedTempFile {
+        birds_photo_at((51, 30), (0, 10))
+    }
+
+    /// A photo of three birds geotagged at the given north latitude and west longitude,
+    /// in degrees and minutes
+    fn birds_photo_at(latitude: (u32, u32), longitude: (u32, u32)) -> NamedTempFile {
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use exif::{Field, In, Rational, Tag, Value};
+
+        let dms = |degrees: u32, minutes: u32| {
+            Value::Rational(vec![
+                Rational::from((degrees, 1)),
+                Rational::from((minutes, 1)),
+                Rational::from((0, 1)),
+            ])
+        };
+        let gps_field = |tag, value| Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value,
+        };
+        write_jpeg_with_exif(&[
+            ascii_field(Tag::ImageDescription, "three birds"),
+            gps_field(Tag::GPSLatitude, dms(latitude.0, latitude.1)),
+            ascii_field(Tag::GPSLatitudeRef, "N"),
+            gps_field(Tag::GPSLongitude, dms(longitude.0, longitude.1)),
+            ascii_field(Tag::GPSLongitudeRef, "W"),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_location_out_of_range_reason_code() {
+        let config = Config {
+            llm_api_url: spawn_birds_llm().await,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+        let image = london_birds_photo();
+
+        // Expected in Paris, well beyond the allowed radius
+        let context = ValidationContext::try_from(AnalysisRequest {
+            location: Some(LocationRequest {
+                lat: Some(48.8566),
+                long: Some(2.3522),
+                max_distance: Some(1000.0),
+                polygon: None,
+                place_name: None,
+                distance_unit: None,
+                altitude: None,
+                tolerate_gps_precision: None,
+            }),
+            ..AnalysisRequest::new("Three birds on a wire")
+        })
+        .unwrap();
+
+        let results = processor
+            .validate_local_image(
+                image.path().to_str().unwrap(),
+                &context,
+                &Progress::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.resolution, Resolution::Rejected);
+        let reasons = results.reasons.unwrap();
+        assert_eq!(reasons.len(), 1);
+        assert_eq!(reasons[0].code, ReasonCode::LocationOutOfRange);
+        // London lies north-west of Paris
+        assert!(reasons[0]
+            .message
+            .contains("km to the NW from expected location 48.856600°N"));
+
+        let json = serde_json::to_value(&reasons[0]).unwrap();
+        assert_eq!(json["code"], "LOCATION_OUT_OF_RANGE");
+    }
+
+    #[tokio::test]
+    async fn test_location_within_gps_precision() {
+        let config = Config {
+            llm_api_url: spawn_birds_llm().await,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+        // 51.5°N 0.1°W carries a single decimal, so its precision is only ≈1km
+        let image = birds_photo_at((51, 30), (0, 6));
+
+        let validate = |tolerate_gps_precision| {
+            // ≈1.06km north of the image, just over the 1km limit
+            let context = ValidationContext::try_from(AnalysisRequest {
+                location: Some(LocationRequest {
+                    lat: Some(51.5095),
+                    long: Some(-0.1),
+                    max_distance: Some(1.0),
+                    polygon: None,
+                    place_name: None,
+                    distance_unit: Some(DistanceUnit::Kilometers),
+                    altitude: None,
+                    tolerate_gps_precision: Some(tolerate_gps_precision),
+                }),
+                ..AnalysisRequest::new("Three birds on a wire")
+            })
+            .unwrap();
+            let processor = &processor;
+            let path = image.path().to_str().unwrap().to_string();
+            async move {
+                processor
+                    .validate_local_image(&path, &context, &Progress::default())
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let strict = validate(false).await;
+        assert_eq!(strict.resolution, Resolution::Rejected);
+        assert_eq!(
+            strict.reasons.unwrap()[0].code,
+            ReasonCode::LocationOutOfRange
+        );
+
+        let tolerant = validate(true).await;
+        assert_eq!(tolerant.resolution, Resolution::Accepted);
+        let reasons = tolerant.reasons.unwrap();
+        assert_eq!(reasons.len(), 1);
+        assert_eq!(reasons[0].code, ReasonCode::LocationOutOfRange);
+        assert!(reasons[0].warning);
+        assert!(
+            reasons[0]
+                .message
+                .contains("over the 1.00km limit but within the ≈1.00km precision"),
+            "{}",
+            reasons[0].message
+        );
+    }
+
+    #[tokio::test]
+    async fn test_weighted_score_outweighs_failed_check() {
+        use crate::models::CheckWeights;
+
+        let config = Config {
+            llm_api_url: spawn_birds_llm().await,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+        let image = london_birds_photo();
+
+        let validate = |accept_threshold| {
+            // Expected in Paris, so the location check fails
+            let context = ValidationContext::try_from(AnalysisRequest {
+                location: Some(LocationRequest {
+                    lat: Some(48.8566),
+                    long: Some(2.3522),
+                    max_distance: Some(1000.0),
+                    polygon: None,
+                    place_name: None,
+                    distance_unit: None,
+                    altitude: None,
+                    tolerate_gps_precision: None,
+                }),
+                weights: Some(CheckWeights {
+                    content: Some(0.6),
+                    location: Some(0.3),
+                    datetime: Some(0.1),
+                    ..CheckWeights::default()
+                }),
+                accept_threshold: Some(accept_threshold),
+                ..AnalysisRequest::new("Three birds on a wire")
+            })
+            .unwrap();
+            let processor = &processor;
+            let path = image.path().to_str().unwrap().to_string();
+            async move {
+                processor
+                    .validate_local_image(&path, &context, &Progress::default())
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // Content at 0.9 confidence earns 0.54 and the unconstrained datetime 0.1
+        let accepted = validate(0.6).await;
+        assert_eq!(accepted.resolution, Resolution::Accepted);
+        let score = accepted.confidence.unwrap().score.unwrap();
+        assert!((score - 0.64).abs() < 1e-4, "{score}");
+        let reasons = accepted.reasons.unwrap();
+        assert_eq!(reasons.len(), 1);
+        assert_eq!(reasons[0].code, ReasonCode::LocationOutOfRange);
+        assert!(reasons[0].warning);
+
+        let rejected = validate(0.7).await;
+        assert_eq!(rejected.resolution, Resolution::Rejected);
+        let reasons = rejected.reasons.unwrap();
+        let codes: Vec<_> = reasons.iter().map(|reason| reason.code).collect();
+        assert_eq!(
+            codes,
+            [
+                ReasonCode::LocationOutOfRange,
+                ReasonCode::ScoreBelowThreshold
+            ]
+        );
+        assert!(reasons.iter().all(|reason| !reason.warning));
+        assert!(
+            reasons[1]
+                .message
+                .contains("0.64, below the 0.70 threshold"),
+            "{}",
+            reasons[1].message
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timestamp_reason_shows_raw_exif() {
+        use crate::models::DateTimeRequest;
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use exif::Tag;
+
+        let config = Config {
+            llm_api_url: spawn_birds_llm().await,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+        let image = write_jpeg_with_exif(&[
+            ascii_field(Tag::ImageDescription, "three birds"),
+            ascii_field(Tag::DateTimeOriginal, "2025:08:01 15:40:00"),
+        ]);
+
+        let context = ValidationContext::try_from(AnalysisRequest {
+            datetime: Some(DateTimeRequest {
+                start: Some("2025-08-01T15:23:00+00:00".to_string()),
+                end: Some("2025-08-01T15:33:00+00:00".to_string()),
+                duration: None,
+                local_date: None,
+                timezone: None,
+            }),
+            ..AnalysisRequest::new("Three birds on a wire")
+        })
+        .unwrap();
+        let result = processor
+            .validate_local_image(
+                image.path().to_str().unwrap(),
+                &context,
+                &Progress::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.resolution, Resolution::Rejected);
+        let reasons = result.reasons.unwrap();
+        assert_eq!(reasons.len(), 1);
+        assert_eq!(reasons[0].code, ReasonCode::TimestampOutOfRange);
+        assert_eq!(
+            reasons[0].message,
+            "image timestamp 2025-08-01 15:40:00 +0000 is 7 minutes after allowed end time, \
+             outside allowed time range 2025-08-01 15:23:00 +0000 to 2025-08-01 15:33:00 +0000; \
+             raw EXIF '2025:08:01 15:40:00' parsed as 2025-08-01 15:40:00 +00:00"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verbose_results_list_every_check() {
+        use crate::models::{Check, DateTimeRequest};
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use exif::Tag;
+
+        let config = Config {
+            llm_api_url: spawn_birds_llm().await,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+        let image = write_jpeg_with_exif(&[
+            ascii_field(Tag::ImageDescription, "three birds"),
+            ascii_field(Tag::DateTimeOriginal, "2025:08:01 15:40:00"),
+        ]);
+
+        let request = |verbose| AnalysisRequest {
+            datetime: Some(DateTimeRequest {
+                start: Some("2025-08-01T15:23:00+00:00".to_string()),
+                end: Some("2025-08-01T15:33:00+00:00".to_string()),
+                duration: None,
+                local_date: None,
+                timezone: None,
+            }),
+            verbose,
+            ..AnalysisRequest::new("Three birds on a wire")
+        };
+        let validate = |verbose| {
+            let context = ValidationContext::try_from(request(verbose)).unwrap();
+            let processor = &processor;
+            let path = image.path().to_str().unwrap().to_string();
+            async move {
+                processor
+                    .validate_local_image(&path, &context, &Progress::default())
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let terse = validate(false).await;
+        assert_eq!(terse.resolution, Resolution::Rejected);
+        assert!(terse.checks.is_none());
+
+        let verbose = validate(true).await;
+        assert_eq!(verbose.resolution, Resolution::Rejected);
+        assert_eq!(verbose.reasons.unwrap().len(), 1);
+        assert_eq!(
+            verbose.checks.unwrap(),
+            CheckOutcome::list(CheckState::Passed, CheckState::Skipped, CheckState::Failed)
+        );
+
+        // Checks that never ran are skipped, even though the image was rejected
+        let not_found = processor
+            .validate_image_path(
+                "/nonexistent/image.jpg",
+                &ValidationContext::try_from(request(true)).unwrap(),
+                &Progress::default(),
+            )
+            .await
+            .unwrap();
+        let checks = not_found.checks.unwrap();
+        assert_eq!(
+            checks
+                .iter()
+                .map(|outcome| outcome.check)
+                .collect::<Vec<_>>(),
+            [Check::Content, Check::Location, Check::Datetime]
+        );
+        assert!(checks
+            .iter()
+            .all(|outcome| outcome.state == CheckState::Skipped));
+    }
+
+    #[tokio::test]
+    async fn test_max_speed_rejects_moving_capture() {
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use exif::{Field, In, Rational, Tag, Value};
+
+        let config = Config {
+            llm_api_url: spawn_birds_llm().await,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+        // 57 mph, about 92 km/h
+        let moving = write_jpeg_with_exif(&[
+            ascii_field(Tag::ImageDescription, "three birds"),
+            Field {
+                tag: Tag::GPSSpeed,
+                ifd_num: In::PRIMARY,
+                value: Value::Rational(vec![Rational::from((57, 1))]),
+            },
+            ascii_field(Tag::GPSSpeedRef, "M"),
+        ]);
+        let still = london_birds_photo();
+
+        let validate = |image: &NamedTempFile, max_speed_kmh| {
+            let context = ValidationContext::try_from(AnalysisRequest {
+                max_speed_kmh: Some(max_speed_kmh),
+                ..AnalysisRequest::new("Three birds on a wire")
+            })
+            .unwrap();
+            let processor = &processor;
+            let path = image.path().to_str().unwrap().to_string();
+            async move {
+                processor
+                    .validate_local_image(&path, &context, &Progress::default())
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let too_fast = validate(&moving, 30.0).await;
+        assert_eq!(too_fast.resolution, Resolution::Rejected);
+        let reasons = too_fast.reasons.unwrap();
+        assert_eq!(reasons.len(), 1);
+        assert_eq!(reasons[0].code, ReasonCode::SpeedOutOfRange);
+        assert_eq!(
+            reasons[0].message,
+            "image captured while moving at 92 km/h, over the allowed 30 km/h"
+        );
+
+        let within = validate(&moving, 100.0).await;
+        assert_eq!(within.resolution, Resolution::Accepted);
+
+        let unknown = validate(&still, 30.0).await;
+        assert_eq!(unknown.resolution, Resolution::Rejected);
+        assert_eq!(unknown.reasons.unwrap()[0].code, ReasonCode::SpeedMissing);
+    }
+
+    #[tokio::test]
+    async fn test_content_only_request_ignores_missing_exif() {
+        use std::io::Write;
+
+        let config = Config {
+            llm_api_url: spawn_birds_llm().await,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+        // A JPEG with a comment segment but no EXIF block
+        let mut image = NamedTempFile::with_suffix(".jpg").unwrap();
+        let comment = b"three birds";
+        image.write_all(&[0xFF, 0xD8, 0xFF, 0xFE]).unwrap();
+        image
+            .write_all(&(comment.len() as u16 + 2).to_be_bytes())
+            .unwrap();
+        image.write_all(comment).unwrap();
+        image.write_all(&[0xFF, 0xD9]).unwrap();
+
+        let request = |datetime| AnalysisRequest {
+            datetime,
+            ..AnalysisRequest::new("Three birds on a wire")
+        };
+        let path = image.path().to_str().unwrap();
+
+        let context = ValidationContext::try_from(request(None)).unwrap();
+        let result = processor
+            .validate_local_image(path, &context, &Progress::default())
+            .await
+            .unwrap();
+        assert_eq!(result.resolution, Resolution::Accepted);
+        assert!(result.reasons.is_none());
+
+        // Still an error when a constraint needs the metadata
+        let context = ValidationContext::try_from(request(Some(crate::models::DateTimeRequest {
+            start: Some("2025-08-01T15:23:00+00:00".to_string()),
+            end: Some("2025-08-01T15:33:00+00:00".to_string()),
+            duration: None,
+            local_date: None,
+            timezone: None,
+        })))
+        .unwrap();
+        let result = processor
+            .validate_local_image(path, &context, &Progress::default())
+            .await
+            .unwrap();
+        assert_eq!(result.resolution, Resolution::Rejected);
+        assert_eq!(result.reasons.unwrap()[0].code, ReasonCode::ValidationError);
+    }
+
+    #[tokio::test]
+    async fn test_forbid_gps_and_timestamp() {
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use exif::Tag;
+
+        let config = Config {
+            llm_api_url: spawn_birds_llm().await,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+        let geotagged = london_birds_photo();
+        let timestamped = write_jpeg_with_exif(&[
+            ascii_field(Tag::ImageDescription, "three birds"),
+            ascii_field(Tag::DateTimeOriginal, "2025:08:01 15:40:00"),
+        ]);
+        let stripped = write_jpeg_with_exif(&[ascii_field(Tag::ImageDescription, "three birds")]);
+
+        let validate = |image: &NamedTempFile, forbid_gps, forbid_timestamp| {
+            let context = ValidationContext::try_from(AnalysisRequest {
+                forbid_gps,
+                forbid_timestamp,
+                ..AnalysisRequest::new("Three birds on a wire")
+            })
+            .unwrap();
+            let processor = &processor;
+            let path = image.path().to_str().unwrap().to_string();
+            async move {
+                processor
+                    .validate_local_image(&path, &context, &Progress::default())
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let with_gps = validate(&geotagged, true, false).await;
+        assert_eq!(with_gps.resolution, Resolution::Rejected);
+        let reasons = with_gps.reasons.unwrap();
+        assert_eq!(reasons.len(), 1);
+        assert_eq!(reasons[0].code, ReasonCode::GpsPresent);
+        assert_eq!(
+            reasons[0].message,
+            "image unexpectedly contains GPS coordinates"
+        );
+        let without_gps = validate(&timestamped, true, false).await;
+        assert_eq!(without_gps.resolution, Resolution::Accepted);
+
+        let with_timestamp = validate(&timestamped, false, true).await;
+        assert_eq!(with_timestamp.resolution, Resolution::Rejected);
+        let reasons = with_timestamp.reasons.unwrap();
+        assert_eq!(reasons.len(), 1);
+        assert_eq!(reasons[0].code, ReasonCode::TimestampPresent);
+        assert_eq!(
+            reasons[0].message,
+            "image unexpectedly contains a timestamp"
+        );
+
+        let neither = validate(&stripped, true, true).await;
+        assert_eq!(neither.resolution, Resolution::Accepted);
+        assert!(neither.reasons.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_dimensions_constraint() {
+        use crate::models::DimensionsRequest;
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use exif::{Field, In, Tag, Value};
+
+        let config = Config {
+            llm_api_url: spawn_birds_llm().await,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+
+        // The EXIF-only test photos have no image data, so their EXIF size is used
+        let photo_of_size = |width: u32, height: u32| {
+            let dimension = |tag, value| Field {
+                tag,
+                ifd_num: In::PRIMARY,
+                value: Value::Long(vec![value]),
+            };
+            write_jpeg_with_exif(&[
+                ascii_field(Tag::ImageDescription, "three birds"),
+                dimension(Tag::PixelXDimension, width),
+                dimension(Tag::PixelYDimension, height),
+            ])
+        };
+        let mut encoded = NamedTempFile::with_suffix(".jpg").unwrap();
+        image::RgbImage::new(320, 240)
+            .write_to(&mut encoded, image::ImageFormat::Jpeg)
+            .unwrap();
+
+        let validate = |image: &NamedTempFile, dimensions: DimensionsRequest| {
+            let context = ValidationContext::try_from(AnalysisRequest {
+                dimensions: Some(dimensions),
+                ..AnalysisRequest::new("Three birds on a wire")
+            })
+            .unwrap();
+            let processor = &processor;
+            let path = image.path().to_str().unwrap().to_string();
+            async move {
+                processor
+                    .validate_local_image(&path, &context, &Progress::default())
+                    .await
+                    .unwrap()
+            }
+        };
+        let minimum = || DimensionsRequest {
+            min_width: Some(1024),
+            min_height: Some(768),
+            max_width: None,
+            max_height: None,
+        };
+
+        let thumbnail = validate(&photo_of_size(320, 240), minimum()).await;
+        assert_eq!(thumbnail.resolution, Resolution::Rejected);
+        let reasons = thumbnail.reasons.unwrap();
+        assert_eq!(reasons.len(), 1);
+        assert_eq!(reasons[0].code, ReasonCode::DimensionsOutOfRange);
+        assert_eq!(reasons[0].message, "image 320x240 below minimum 1024x768");
+
+        let full_size = validate(&photo_of_size(4032, 3024), minimum()).await;
+        assert_eq!(full_size.resolution, Resolution::Accepted);
+
+        // Decodable images are measured from their header
+        let oversized = validate(
+            &encoded,
+            DimensionsRequest {
+                min_width: None,
+                min_height: None,
+                max_width: Some(200),
+                max_height: None,
+            },
+        )
+        .await;
+        assert_eq!(oversized.resolution, Resolution::Rejected);
+        let reasons = oversized.reasons.unwrap();
+        assert_eq!(reasons[0].code, ReasonCode::DimensionsOutOfRange);
+        assert_eq!(reasons[0].message, "image 320x240 above maximum width 200");
+
+        let unknown = validate(&london_birds_photo(), minimum()).await;
+        assert_eq!(
+            unknown.reasons.unwrap()[0].message,
+            "cannot read image dimensions"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reference_image_similarity() {
+        use crate::models::{ConstraintSeverity, Severity};
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use exif::Tag;
+        use image::{ImageFormat, RgbImage};
+        use std::io::{Cursor, Write};
+
+        let config = Config {
+            llm_api_url: spawn_birds_llm().await,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+
+        // A gradient scene, a smaller re-encoded shot of it and its mirror image
+        let scene = |width: u32, height: u32, flipped: bool| {
+            RgbImage::from_fn(width, height, move |x, _| {
+                let x = if flipped { width - 1 - x } else { x };
+                let level = (x * 255 / width) as u8;
+                image::Rgb([level, level / 2, 255 - level])
+            })
+        };
+        // Photos are JPEGs with an EXIF segment, since images without EXIF are rejected
+        let exif = write_jpeg_with_exif(&[ascii_field(Tag::Make, "Canon")]);
+        let exif = std::fs::read(exif.path()).unwrap();
+        let write = |image: RgbImage| {
+            let mut encoded = Cursor::new(Vec::new());
+            image.write_to(&mut encoded, ImageFormat::Jpeg).unwrap();
+            let encoded = encoded.into_inner();
+            let mut file = NamedTempFile::with_suffix(".jpg").unwrap();
+            file.write_all(&encoded[..2]).unwrap();
+            file.write_all(&exif[2..exif.len() - 2]).unwrap();
+            file.write_all(&encoded[2..]).unwrap();
+            file
+        };
+        let reference = write(scene(320, 240, false));
+        let same_scene = write(scene(160, 120, false));
+        let other_scene = write(scene(320, 240, true));
+
+        let request =
+            |image: &NamedTempFile, reference: &str, threshold: Option<f32>| ProcessingRequest {
+                processing_id: "similarity".to_string(),
+                image_path: Some(image.path().to_str().unwrap().to_string().into()),
+                image: None,
+                callback_url: None,
+                timeout_seconds: None,
+                deadline: None,
+                analysis_request: AnalysisRequest {
+                    severity: Some(ConstraintSeverity {
+                        content: Severity::Soft,
+                        ..ConstraintSeverity::default()
+                    }),
+                    reference_image_path: Some(reference.to_string()),
+                    similarity_threshold: threshold,
+                    ..AnalysisRequest::new("A colour gradient")
+                },
+            };
+        let reference_path = reference.path().to_str().unwrap();
+
+        // The content check fails softly, so only the similarity decides
+        let results = processor
+            .validate_request(
+                request(&same_scene, reference_path, None),
+                &Progress::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.resolution, Resolution::Accepted);
+        let score = results.confidence.unwrap().similarity.unwrap();
+        assert!(score >= DEFAULT_SIMILARITY_THRESHOLD, "{score}");
+
+        let results = processor
+            .validate_request(
+                request(&other_scene, reference_path, None),
+                &Progress::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.resolution, Resolution::Rejected);
+        let score = results.confidence.unwrap().similarity.unwrap();
+        assert!(score < DEFAULT_SIMILARITY_THRESHOLD, "{score}");
+        let reasons = results.reasons.unwrap();
+        let mismatch = reasons
+            .iter()
+            .find(|reason| reason.code == ReasonCode::ReferenceMismatch)
+            .unwrap();
+        assert!(!mismatch.warning);
+        assert!(mismatch.message.contains("below the 0.75 threshold"));
+
+        // A threshold of 1.0 only passes an identical hash
+        let results = processor
+            .validate_request(
+                request(&reference, reference_path, Some(1.0)),
+                &Progress::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.resolution, Resolution::Accepted);
+        assert_eq!(results.confidence.unwrap().similarity, Some(1.0));
+
+        // A missing reference fails the request rather than the image
+        let error = processor
+            .validate_request(
+                request(&same_scene, "/nonexistent/reference.jpg", None),
+                &Progress::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ProcessorError::ImageNotFound(_)), "{error}");
+    }
+
+    /// Serves a geocoder that knows London and Paris and fails for anything else
+    async fn spawn_geocoder() -> String {
+        use axum::extract::Query;
+        use axum::http::StatusCode;
+        use axum::response::IntoResponse;
+        use axum::{routing::get, Json};
+        use serde_json::json;
+        use std::collections::HashMap;
+
+        let app = axum::Router::new().route(
+            "/search",
+            get(|Query(params): Query<HashMap<String, String>>| async move {
+                let bbox = match params["q"].as_str() {
+                    "London" => ["51.28", "51.69", "-0.51", "0.33"],
+                    "Paris" => ["48.81", "48.90", "2.22", "2.47"],
+                    _ => return StatusCode::SERVICE_UNAVAILABLE.into_response(),
+                };
+                Json(json!([{ "boundingbox": bbox }])).into_response()
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}/search")
+    }
+
+    #[tokio::test]
+    async fn test_place_name_location() {
+        let config = Config {
+            llm_api_url: spawn_birds_llm().await,
+            geocoder_url: Some(spawn_geocoder().await),
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+        let image = london_birds_photo();
+        let image_path = image.path().to_str().unwrap();
+
+        let context = |place_name: &str| {
+            ValidationContext::try_from(AnalysisRequest {
+                location: Some(LocationRequest {
+                    lat: None,
+                    long: None,
+                    max_distance: None,
+                    distance_unit: None,
+                    polygon: None,
+                    place_name: Some(place_name.to_string()),
+                    altitude: None,
+                    tolerate_gps_precision: None,
+                }),
+                ..AnalysisRequest::new("Three birds on a wire")
+            })
+            .unwrap()
+        };
+
+        let results = processor
+            .validate_local_image(image_path, &context("London"), &Progress::default())
+            .await
+            .unwrap();
+        assert_eq!(results.resolution, Resolution::Accepted);
+
+        let results = processor
+            .validate_local_image(image_path, &context("Paris"), &Progress::default())
+            .await
+            .unwrap();
+        let reasons = results.reasons.unwrap();
+        assert_eq!(reasons[0].code, ReasonCode::LocationOutOfRange);
+        assert!(reasons[0].message.ends_with("is outside Paris"));
+
+        // A failed lookup rejects the image instead of skipping the check
+        let results = processor
+            .validate_local_image(image_path, &context("Atlantis"), &Progress::default())
+            .await
+            .unwrap();
+        assert_eq!(results.resolution, Resolution::Rejected);
+        let reasons = results.reasons.unwrap();
+        assert_eq!(reasons[0].code, ReasonCode::PlaceNotResolved);
+        assert!(reasons[0]
+            .message
+            .contains("cannot resolve place 'Atlantis'"));
+
+        // Without a geocoder, place names cannot be checked at all
+        let processor = ValidationProcessor::new(&Config {
+            geocoder_url: None,
+            ..config
+        });
+        let results = processor
+            .validate_local_image(image_path, &context("London"), &Progress::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            results.reasons.unwrap()[0].code,
+            ReasonCode::PlaceNotResolved
+        );
+    }
+
+    /// Serves an LLM that accepts prompts mentioning birds, counting the calls it answers
+    async fn spawn_birds_prompt_llm() -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        use axum::{routing::post, Json, Router};
+        use serde_json::{json, Value};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let app = Router::new().fallback(post(move |Json(body): Json<Value>| {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let prompt = body["messages"][0]["content"][0]["text"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_lowercase();
+                let reply = if prompt.contains("birds") {
+                    "ACCEPTED: three birds perched on a wire\nSCORE: 0.9"
+                } else {
+                    "REJECTED: no cat in sight\nSCORE: 0.7"
+                };
+                Json(json!({ "choices": [{ "message": { "content": reply } }] }))
+            }
+        }));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{addr}/v1/chat/completions"), calls)
+    }
+
+    fn alternatives_request(content_match_mode: MatchMode) -> ValidationContext {
+        ValidationContext::try_from(AnalysisRequest {
+            content_match_mode,
+            ..AnalysisRequest::new(ContentRequest::Alternatives(vec![
+                "A cat".to_string(),
+                "Three birds".to_string(),
+            ]))
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_content_alternatives_separate_calls() {
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use exif::Tag;
+        use std::sync::atomic::Ordering;
+
+        let (llm_api_url, calls) = spawn_birds_prompt_llm().await;
+        let config = Config {
+            llm_api_url,
+            throttle_requests_per_minute: 6000,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+        let image = write_jpeg_with_exif(&[ascii_field(Tag::ImageDescription, "wire")]);
+        let path = image.path().to_str().unwrap();
+
+        // One of the two descriptions matching is enough in any mode
+        let result = processor
+            .validate_local_image(
+                path,
+                &alternatives_request(MatchMode::Any),
+                &Progress::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.resolution, Resolution::Accepted);
+        assert_eq!(result.confidence.unwrap().content, 0.9);
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 2);
+
+        // All mode stops at the first mismatch and names every description
+        let result = processor
+            .validate_local_image(
+                path,
+                &alternatives_request(MatchMode::All),
+                &Progress::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.resolution, Resolution::Rejected);
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 1);
+        let reasons = result.reasons.unwrap();
+        assert_eq!(reasons[0].code, ReasonCode::ContentMismatch);
+        assert_eq!(
+            reasons[0].message,
+            "image content does not match all of the descriptions: 'A cat', 'Three birds'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_content_alternatives_combined_call() {
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use exif::Tag;
+        use std::sync::atomic::Ordering;
+
+        let (llm_api_url, calls) = spawn_birds_prompt_llm().await;
+        let config = Config {
+            llm_api_url,
+            content_alternatives_strategy: ContentAlternativesStrategy::Combined,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+        let image = write_jpeg_with_exif(&[ascii_field(Tag::ImageDescription, "wire")]);
+
+        let result = processor
+            .validate_local_image(
+                image.path().to_str().unwrap(),
+                &alternatives_request(MatchMode::Any),
+                &Progress::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.resolution, Resolution::Accepted);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_validate_image_url() {
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use axum::{routing::get, Router};
+        use exif::Tag;
+
+        let birds = write_jpeg_with_exif(&[ascii_field(Tag::ImageDescription, "three birds")]);
+        let bytes = std::fs::read(birds.path()).unwrap();
+        let images = Router::new().route("/photo", get(move || async move { bytes }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, images).await.unwrap() });
+
+        let config = Config {
+            llm_api_url: spawn_birds_llm().await,
+            allowed_image_hosts: Some("127.0.0.1".to_string()),
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+        let request = |image_path: String| ProcessingRequest {
+            processing_id: "test".to_string(),
+            image_path: Some(image_path.into()),
+            image: None,
+            callback_url: None,
+            timeout_seconds: None,
+            deadline: None,
+            analysis_request: AnalysisRequest::new("Three birds on a wire"),
+        };
+
+        // Downloaded and named by its detected format, though the URL has no extension
+        let results = processor
+            .validate_request(
+                request(format!("http://{addr}/photo")),
+                &Progress::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.resolution, Resolution::Accepted);
+
+        let results = processor
+            .validate_request(
+                request(format!("http://{addr}/missing.jpg")),
+                &Progress::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.reasons.unwrap()[0].code, ReasonCode::ImageNotFound);
+
+        let results = processor
+            .validate_request(
+                request("https://images.example.com/photo.jpg".to_string()),
+                &Progress::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.resolution, Resolution::Rejected);
+        let reasons = results.reasons.unwrap();
+        assert_eq!(reasons[0].code, ReasonCode::ImageHostNotAllowed);
+        assert_eq!(
+            reasons[0].message,
+            "image host 'images.example.com' is not allowed"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_results_include_llm_explanation_when_enabled() {
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use exif::Tag;
+
+        let llm_api_url = spawn_birds_llm().await;
+        let birds = write_jpeg_with_exif(&[ascii_field(Tag::ImageDescription, "three birds")]);
+        let path = birds.path().to_str().unwrap();
+        let context = alternatives_request(MatchMode::Any);
+
+        let config = Config {
+            llm_api_url,
+            throttle_requests_per_minute: 6000,
+            ..create_test_config()
+        };
+        let result = ValidationProcessor::new(&config)
+            .validate_local_image(path, &context, &Progress::default())
+            .await
+            .unwrap();
+        assert_eq!(result.resolution, Resolution::Accepted);
+        assert_eq!(result.explanation, None);
+        let json = serde_json::to_value(&result).unwrap();
+        assert!(json.get("explanation").is_none());
+
+        let config = Config {
+            include_llm_explanation: true,
+            ..config
+        };
+        let result = ValidationProcessor::new(&config)
+            .validate_local_image(path, &context, &Progress::default())
+            .await
+            .unwrap();
+        assert_eq!(result.resolution, Resolution::Accepted);
+        // The verdict and score lines are left out of the explanation
+        assert_eq!(
+            result.explanation.as_deref(),
+            Some("three birds perched on a wire")
+        );
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["explanation"], "three birds perched on a wire");
+    }
+
+    #[tokio::test]
+    async fn test_on_missing_metadata_policies() {
+        use image::{ImageFormat, RgbImage};
+
+        let (llm_api_url, _) = spawn_birds_prompt_llm().await;
+        let config = Config {
+            llm_api_url,
+            throttle_requests_per_minute: 6000,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+
+        // A freshly encoded JPEG carries no EXIF at all
+        let image = tempfile::NamedTempFile::with_suffix(".jpg").unwrap();
+        RgbImage::new(8, 8)
+            .save_with_format(image.path(), ImageFormat::Jpeg)
+            .unwrap();
+        let path = image.path().to_str().unwrap();
+
+        let validate = |policy: &str| {
+            let context = ValidationContext::try_from(
+                serde_json::from_value::<AnalysisRequest>(serde_json::json!({
+                    "content": "Three birds on a wire",
+                    "location": { "lat": 51.492191, "long": -0.266108, "max_distance": 100.0 },
+                    "datetime": { "start": "2025-08-01T15:23:00+01:00", "duration": 10 },
+                    "on_missing_metadata": policy
+                }))
+                .unwrap(),
+            )
+            .unwrap();
+            let processor = &processor;
+            async move {
+                processor
+                    .validate_local_image(path, &context, &Progress::default())
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let rejected = validate("reject").await;
+        assert_eq!(rejected.resolution, Resolution::Rejected);
+        let reasons = rejected.reasons.unwrap();
+        assert_eq!(reasons.len(), 1);
+        assert_eq!(reasons[0].code, ReasonCode::ValidationError);
+
+        let passed = validate("pass").await;
+        assert_eq!(passed.resolution, Resolution::Accepted);
+        assert!(passed.reasons.is_none());
+
+        let warned = validate("warn").await;
+        assert_eq!(warned.resolution, Resolution::Accepted);
+        let reasons = warned.reasons.unwrap();
+        let codes: Vec<_> = reasons.iter().map(|reason| reason.code).collect();
+        assert_eq!(
+            codes,
+            [ReasonCode::GpsMissing, ReasonCode::TimestampMissing]
+        );
+        assert!(reasons.iter().all(|reason| reason.warning));
+    }
+
+    #[tokio::test]
+    async fn test_on_missing_metadata_with_partial_exif() {
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use exif::Tag;
+
+        let (llm_api_url, _) = spawn_birds_prompt_llm().await;
+        let config = Config {
+            llm_api_url,
+            throttle_requests_per_minute: 6000,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+        // EXIF with a description but neither GPS nor timestamp
+        let image = write_jpeg_with_exif(&[ascii_field(Tag::ImageDescription, "wire")]);
+        let path = image.path().to_str().unwrap();
+
+        let mut results = Vec::new();
+        for policy in ["reject", "pass", "warn"] {
+            let context = ValidationContext::try_from(
+                serde_json::from_value::<AnalysisRequest>(serde_json::json!({
+                    "content": "Three birds on a wire",
+                    "location": { "lat": 51.492191, "long": -0.266108, "max_distance": 100.0 },
+                    "on_missing_metadata": policy
+                }))
+                .unwrap(),
+            )
+            .unwrap();
+            results.push(
+                processor
+                    .validate_local_image(path, &context, &Progress::default())
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        let reasons = |result: &ValidationResults| {
+            result
+                .reasons
+                .iter()
+                .flatten()
+                .map(|reason| (reason.code, reason.warning))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(results[0].resolution, Resolution::Rejected);
+        assert_eq!(reasons(&results[0]), [(ReasonCode::GpsMissing, false)]);
+        assert_eq!(results[1].resolution, Resolution::Accepted);
+        assert_eq!(reasons(&results[1]), []);
+        assert_eq!(results[2].resolution, Resolution::Accepted);
+        assert_eq!(reasons(&results[2]), [(ReasonCode::GpsMissing, true)]);
+    }
+
+    #[tokio::test]
+    async fn test_open_circuit_fails_request_as_retryable() {
+        use crate::validation::exif::tests::{ascii_field, write_jpeg_with_exif};
+        use exif::Tag;
+
+        // Nothing listens on port 1, so the first call fails and opens the circuit
+        let config = Config {
+            llm_api_url: "http://127.0.0.1:1/v1/chat/completions".to_string(),
+            llm_max_retries: 1,
+            llm_circuit_failure_threshold: 1,
+            llm_circuit_cooldown_seconds: 60,
+            throttle_requests_per_minute: 6000,
+            ..create_test_config()
+        };
+        let processor = ValidationProcessor::new(&config);
+        let image = write_jpeg_with_exif(&[ascii_field(Tag::ImageDescription, "birds")]);
+        let request = || {
+            ProcessingRequest::from_request(
+                serde_json::from_value(serde_json::json!({
+                    "image-path": image.path(),
+                    "analysis-request": { "content": "Three birds on a wire" }
+                }))
+                .unwrap(),
+            )
+        };
+
+        let error = processor
+            .validate_request(request(), &Progress::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            ProcessorError::Llm(LlmError::MaxRetriesExceeded)
+        ));
+
+        // Later jobs fail fast, and are left for the queue to retry rather than rejected
+        let error = processor
+            .validate_request(request(), &Progress::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ProcessorError::Llm(LlmError::CircuitOpen)));
+        assert!(error.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_exif_timeout_fails_request_as_retryable() {
+        use crate::models::DimensionsRequest;
+        use std::io::Write as _;
+
+        let mut processor = ValidationProcessor::new(&create_test_config());
+        processor.exif_timeout = Duration::from_millis(100);
+
+        // Reading a FIFO blocks until something writes to it, like a stalled storage read
+        let dir = tempfile::tempdir().unwrap();
+        let fifo = dir.path().join("stalled.jpg");
+        let status = std::process::Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let path = fifo.to_str().unwrap();
+        let feed = |bytes: &'static [u8]| {
+            let fifo = fifo.clone();
+            std::thread::spawn(move || {
+                let mut writer = std::fs::OpenOptions::new().write(true).open(fifo).unwrap();
+                // The reader may give up before everything is written
+                let _ = writer.write_all(bytes);
+            })
+        };
+
+        // The header read gets bytes it cannot decode, so the EXIF read is tried and stalls
+        let writer = feed(b"not an image");
+        let context = ValidationContext::try_from(AnalysisRequest {
+            dimensions: Some(DimensionsRequest {
+                min_width: Some(1024),
+                min_height: None,
+                max_width: None,
+                max_height: None,
+            }),
+            ..AnalysisRequest::new("Three birds on a wire")
+        })
+        .unwrap();
+        let error = processor
+            .check_dimensions(path, &context)
+            .await
+            .unwrap_err();
+        writer.join().unwrap();
+        assert!(matches!(error, ProcessorError::Exif(ExifError::Timeout(_))));
+        assert!(error.is_retryable());
+
+        // The same goes for the metadata checks
+        let context = ValidationContext::try_from(AnalysisRequest {
+            forbid_gps: true,
+            ..AnalysisRequest::new("Three birds on a wire")
+        })
+        .unwrap();
+        let error = processor
+            .extract_and_validate_metadata(path, &context)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ProcessorError::Exif(ExifError::Timeout(_))));
+
+        // Let both stalled reads see the end of the file, so the runtime can shut down
+        feed(b"").join().unwrap();
+    }
+
     // Integration tests with real image files and LLM API should be in tests/ directory
 }
@@ -0,0 +1,216 @@
+use reqwest::{redirect, StatusCode, Url};
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RemoteImageError {
+    #[error("Invalid image URL: {0}")]
+    InvalidUrl(String),
+    #[error("Image host '{0}' is not in ALLOWED_IMAGE_HOSTS")]
+    HostNotAllowed(String),
+    #[error("Image download answered {0}")]
+    Status(StatusCode),
+    #[error("Image download exceeds the {limit} byte limit")]
+    TooLarge { limit: usize },
+    #[error("Image download timed out after {0:?}")]
+    Timeout(Duration),
+    #[error("Image download failed: {0}")]
+    Http(#[from] reqwest::Error),
+}
+
+impl RemoteImageError {
+    /// Whether the server reported that the image does not exist
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            Self::Status(status) => *status == StatusCode::NOT_FOUND || *status == StatusCode::GONE,
+            _ => false,
+        }
+    }
+
+    /// Whether downloading again later may succeed
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Status(status) => status.is_server_error(),
+            Self::Timeout(_) | Self::Http(_) => true,
+            Self::InvalidUrl(_) | Self::HostNotAllowed(_) | Self::TooLarge { .. } => false,
+        }
+    }
+}
+
+/// Returns true when an image path is an `http://` or `https://` URL to download
+#[must_use]
+pub fn is_image_url(image_path: &str) -> bool {
+    let scheme = image_path
+        .split_once("://")
+        .map(|(scheme, _)| scheme)
+        .unwrap_or_default();
+    scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https")
+}
+
+/// Downloads images given by URL from the hosts in `ALLOWED_IMAGE_HOSTS`
+///
+/// Redirects are not followed, so a listed host cannot send the service elsewhere.
+#[derive(Debug, Clone)]
+pub struct ImageDownloader {
+    client: reqwest::Client,
+    /// Lowercase host names; empty refuses every URL
+    allowed_hosts: Vec<String>,
+    max_bytes: usize,
+    timeout: Duration,
+}
+
+impl ImageDownloader {
+    pub fn new(allowed_hosts: Vec<String>, max_bytes: usize, timeout: Duration) -> Self {
+        let client = reqwest::Client::builder()
+            .redirect(redirect::Policy::none())
+            .build()
+            .expect("Failed to build image download client");
+        Self {
+            client,
+            allowed_hosts: allowed_hosts
+                .into_iter()
+                .map(|host| host.to_ascii_lowercase())
+                .collect(),
+            max_bytes,
+            timeout,
+        }
+    }
+
+    /// Parses `url`, refusing other schemes and hosts missing from the allowlist
+    pub fn check_url(&self, url: &str) -> Result<Url, RemoteImageError> {
+        let parsed = Url::parse(url).map_err(|e| RemoteImageError::InvalidUrl(e.to_string()))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(RemoteImageError::InvalidUrl(format!(
+                "unsupported scheme '{}'",
+                parsed.scheme()
+            )));
+        }
+
+        let host = parsed
+            .host_str()
+            .ok_or_else(|| RemoteImageError::InvalidUrl(format!("no host in {url}")))?
+            .to_ascii_lowercase();
+        if !self.allowed_hosts.contains(&host) {
+            return Err(RemoteImageError::HostNotAllowed(host));
+        }
+        Ok(parsed)
+    }
+
+    /// Downloads the image at `url`, giving up after the timeout or once the body
+    /// grows past the size limit
+    pub async fn download(&self, url: &str) -> Result<Vec<u8>, RemoteImageError> {
+        let url = self.check_url(url)?;
+        tokio::time::timeout(self.timeout, self.fetch(url))
+            .await
+            .map_err(|_| RemoteImageError::Timeout(self.timeout))?
+    }
+
+    async fn fetch(&self, url: Url) -> Result<Vec<u8>, RemoteImageError> {
+        let mut response = self.client.get(url).send().await?;
+        if !response.status().is_success() {
+            return Err(RemoteImageError::Status(response.status()));
+        }
+
+        let limit = self.max_bytes;
+        if response
+            .content_length()
+            .is_some_and(|length| length > limit as u64)
+        {
+            return Err(RemoteImageError::TooLarge { limit });
+        }
+
+        // The declared length may be missing or wrong, so the body is checked as it arrives
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if bytes.len() + chunk.len() > limit {
+                return Err(RemoteImageError::TooLarge { limit });
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+
+    async fn spawn_image_server() -> String {
+        let app = Router::new()
+            .route(
+                "/photo.jpg",
+                get(|| async { vec![0xFFu8, 0xD8, 0xFF, 0xD9] }),
+            )
+            .route("/large.jpg", get(|| async { vec![0u8; 1024] }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    fn downloader(max_bytes: usize) -> ImageDownloader {
+        ImageDownloader::new(
+            vec!["127.0.0.1".to_string(), "Images.Example.com".to_string()],
+            max_bytes,
+            Duration::from_secs(5),
+        )
+    }
+
+    #[test]
+    fn test_is_image_url() {
+        assert!(is_image_url("https://images.example.com/photo.jpg"));
+        assert!(is_image_url("HTTP://images.example.com/photo.jpg"));
+        assert!(!is_image_url("s3://bucket/photo.jpg"));
+        assert!(!is_image_url("/tmp/photo.jpg"));
+    }
+
+    #[test]
+    fn test_check_url() {
+        let downloader = downloader(1024);
+
+        assert!(downloader
+            .check_url("https://IMAGES.example.com/photo.jpg")
+            .is_ok());
+        assert!(matches!(
+            downloader.check_url("https://evil.example.com/photo.jpg"),
+            Err(RemoteImageError::HostNotAllowed(host)) if host == "evil.example.com"
+        ));
+        assert!(matches!(
+            downloader.check_url("https://images.example.com.evil.net/photo.jpg"),
+            Err(RemoteImageError::HostNotAllowed(_))
+        ));
+        assert!(matches!(
+            downloader.check_url("ftp://images.example.com/photo.jpg"),
+            Err(RemoteImageError::InvalidUrl(_))
+        ));
+
+        let nothing_allowed = ImageDownloader::new(Vec::new(), 1024, Duration::from_secs(5));
+        assert!(matches!(
+            nothing_allowed.check_url("https://images.example.com/photo.jpg"),
+            Err(RemoteImageError::HostNotAllowed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_download() {
+        let server = spawn_image_server().await;
+        let downloader = downloader(512);
+
+        let bytes = downloader
+            .download(&format!("{server}/photo.jpg"))
+            .await
+            .unwrap();
+        assert_eq!(bytes, [0xFF, 0xD8, 0xFF, 0xD9]);
+
+        let error = downloader
+            .download(&format!("{server}/missing.jpg"))
+            .await
+            .unwrap_err();
+        assert!(error.is_not_found());
+        assert!(matches!(
+            downloader.download(&format!("{server}/large.jpg")).await,
+            Err(RemoteImageError::TooLarge { limit: 512 })
+        ));
+    }
+}
@@ -0,0 +1,256 @@
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageError, ImageReader};
+use std::io::Cursor;
+
+/// JPEG quality of re-encoded copies; high enough that the model sees no artifacts
+const JPEG_QUALITY: u8 = 90;
+
+/// Shrinks an image so its longer side is at most `max_dimension` pixels
+///
+/// The aspect ratio is preserved and the EXIF orientation applied, since the
+/// re-encoded JPEG carries no metadata. Returns `None` when the image already fits,
+/// so callers can send the original bytes unchanged.
+pub fn downscale_to_max_dimension(
+    bytes: &[u8],
+    max_dimension: u32,
+) -> Result<Option<Vec<u8>>, ImageError> {
+    prepare_for_model(bytes, Some(max_dimension), false)
+}
+
+/// Re-encodes an image the way the vision model should see it
+///
+/// With `auto_orient`, images whose EXIF orientation is not upright are rotated and
+/// flipped accordingly; with `max_dimension`, larger images are downscaled. Whenever
+/// the image is re-encoded the orientation is applied, as the JPEG written carries no
+/// metadata. Returns `None` when the original bytes can be sent unchanged.
+pub fn prepare_for_model(
+    bytes: &[u8],
+    max_dimension: Option<u32>,
+    auto_orient: bool,
+) -> Result<Option<Vec<u8>>, ImageError> {
+    let mut decoder = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()?
+        .into_decoder()?;
+
+    let (width, height) = decoder.dimensions();
+    let orientation = decoder.orientation()?;
+    let needs_resize = max_dimension.is_some_and(|max| width.max(height) > max);
+    let needs_rotation = auto_orient && orientation != Orientation::NoTransforms;
+    if !needs_resize && !needs_rotation {
+        return Ok(None);
+    }
+
+    let mut image = DynamicImage::from_decoder(decoder)?;
+    image.apply_orientation(orientation);
+    if let Some(max_dimension) = max_dimension.filter(|_| needs_resize) {
+        image = image.resize(max_dimension, max_dimension, FilterType::CatmullRom);
+    }
+
+    let mut jpeg = Vec::new();
+    JpegEncoder::new_with_quality(&mut jpeg, JPEG_QUALITY).encode_image(&image.to_rgb8())?;
+    Ok(Some(jpeg))
+}
+
+/// Checks an embedded EXIF thumbnail is large enough to send instead of the photo
+///
+/// Returns `None` when the thumbnail's longer side is below `min_dimension` pixels.
+/// Thumbnails carry no orientation of their own, so `orientation` is that of the photo;
+/// when it is not upright the thumbnail is turned and re-encoded.
+pub fn prepare_thumbnail(
+    bytes: &[u8],
+    min_dimension: u32,
+    orientation: Option<u16>,
+) -> Result<Option<Vec<u8>>, ImageError> {
+    let mut image = image::load_from_memory(bytes)?;
+    if image.width().max(image.height()) < min_dimension {
+        return Ok(None);
+    }
+
+    match orientation.filter(|&value| value != 1) {
+        None => Ok(Some(bytes.to_vec())),
+        Some(value) => {
+            apply_exif_orientation(&mut image, value);
+            let mut jpeg = Vec::new();
+            JpegEncoder::new_with_quality(&mut jpeg, JPEG_QUALITY)
+                .encode_image(&image.to_rgb8())?;
+            Ok(Some(jpeg))
+        }
+    }
+}
+
+/// Turns an image stored with EXIF `Orientation` `value` upright
+///
+/// Values outside 1-8 are treated as upright.
+pub fn apply_exif_orientation(image: &mut DynamicImage, value: u16) {
+    let orientation = u8::try_from(value)
+        .ok()
+        .and_then(Orientation::from_exif)
+        .unwrap_or(Orientation::NoTransforms);
+    image.apply_orientation(orientation);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, RgbImage};
+
+    fn encode(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+        let image = RgbImage::from_fn(width, height, |x, y| {
+            image::Rgb([(x % 256) as u8, (y % 256) as u8, 128])
+        });
+        let mut bytes = Cursor::new(Vec::new());
+        image.write_to(&mut bytes, format).unwrap();
+        bytes.into_inner()
+    }
+
+    #[test]
+    fn test_large_image_is_scaled_down() {
+        let original = encode(600, 300, ImageFormat::Jpeg);
+
+        let resized = downscale_to_max_dimension(&original, 256)
+            .unwrap()
+            .expect("image should have been resized");
+        let image = image::load_from_memory(&resized).unwrap();
+        assert_eq!((image.width(), image.height()), (256, 128));
+        assert!(resized.len() < original.len());
+
+        // Portrait PNGs are scaled on their height and re-encoded as JPEG
+        let original = encode(200, 800, ImageFormat::Png);
+        let resized = downscale_to_max_dimension(&original, 256).unwrap().unwrap();
+        assert!(resized.starts_with(&[0xFF, 0xD8, 0xFF]));
+        let image = image::load_from_memory(&resized).unwrap();
+        assert_eq!((image.width(), image.height()), (64, 256));
+    }
+
+    #[test]
+    fn test_small_image_is_untouched() {
+        let original = encode(200, 150, ImageFormat::Jpeg);
+        assert!(downscale_to_max_dimension(&original, 256)
+            .unwrap()
+            .is_none());
+
+        // Exactly at the limit is not resized either
+        let original = encode(256, 128, ImageFormat::Png);
+        assert!(downscale_to_max_dimension(&original, 256)
+            .unwrap()
+            .is_none());
+    }
+
+    /// A 3x2 image whose pixels are numbered 1-6 row by row in the red channel
+    fn numbered() -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(3, 2, |x, y| {
+            image::Rgb([(y * 3 + x + 1) as u8, 0, 0])
+        }))
+    }
+
+    /// Red channel of each row, top to bottom
+    fn rows(image: &DynamicImage) -> Vec<Vec<u8>> {
+        let image = image.to_rgb8();
+        (0..image.height())
+            .map(|y| {
+                (0..image.width())
+                    .map(|x| image.get_pixel(x, y)[0])
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_each_exif_orientation_is_turned_upright() {
+        // Stored pixels:   1 2 3
+        //                  4 5 6
+        let expected: [(u16, Vec<Vec<u8>>); 8] = [
+            (1, vec![vec![1, 2, 3], vec![4, 5, 6]]),
+            // Mirrored horizontally
+            (2, vec![vec![3, 2, 1], vec![6, 5, 4]]),
+            // Rotated 180°
+            (3, vec![vec![6, 5, 4], vec![3, 2, 1]]),
+            // Mirrored vertically
+            (4, vec![vec![4, 5, 6], vec![1, 2, 3]]),
+            // Transposed (mirrored horizontally, then rotated 270° clockwise)
+            (5, vec![vec![1, 4], vec![2, 5], vec![3, 6]]),
+            // Rotated 90° clockwise
+            (6, vec![vec![4, 1], vec![5, 2], vec![6, 3]]),
+            // Transversed (mirrored horizontally, then rotated 90° clockwise)
+            (7, vec![vec![6, 3], vec![5, 2], vec![4, 1]]),
+            // Rotated 270° clockwise
+            (8, vec![vec![3, 6], vec![2, 5], vec![1, 4]]),
+        ];
+
+        for (value, upright) in expected {
+            let mut image = numbered();
+            apply_exif_orientation(&mut image, value);
+            assert_eq!(rows(&image), upright, "orientation {value}");
+        }
+
+        let mut image = numbered();
+        apply_exif_orientation(&mut image, 9);
+        assert_eq!(rows(&image), rows(&numbered()));
+    }
+
+    /// Inserts an EXIF block holding only `Orientation` after the JPEG's SOI marker
+    fn with_orientation(jpeg: &[u8], value: u16) -> Vec<u8> {
+        use exif::experimental::Writer;
+        use exif::{Field, In, Tag, Value};
+
+        let field = Field {
+            tag: Tag::Orientation,
+            ifd_num: In::PRIMARY,
+            value: Value::Short(vec![value]),
+        };
+        let mut writer = Writer::new();
+        writer.push_field(&field);
+        let mut tiff = Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+        let tiff = tiff.into_inner();
+
+        let mut tagged = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        tagged.extend(((tiff.len() + 8) as u16).to_be_bytes());
+        tagged.extend(b"Exif\0\0");
+        tagged.extend(tiff);
+        tagged.extend(&jpeg[2..]);
+        tagged
+    }
+
+    #[test]
+    fn test_auto_orient_rotates_sideways_photos() {
+        let sideways = with_orientation(&encode(120, 60, ImageFormat::Jpeg), 6);
+
+        let upright = prepare_for_model(&sideways, None, true)
+            .unwrap()
+            .expect("image should have been rotated");
+        let image = image::load_from_memory(&upright).unwrap();
+        assert_eq!((image.width(), image.height()), (60, 120));
+
+        // Left alone when auto-orientation is off or the photo is already upright
+        assert!(prepare_for_model(&sideways, None, false).unwrap().is_none());
+        let upright = with_orientation(&encode(120, 60, ImageFormat::Jpeg), 1);
+        assert!(prepare_for_model(&upright, None, true).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_thumbnail_below_minimum_is_skipped() {
+        let thumbnail = encode(160, 120, ImageFormat::Jpeg);
+        assert_eq!(
+            prepare_thumbnail(&thumbnail, 160, None).unwrap(),
+            Some(thumbnail.clone())
+        );
+        assert!(prepare_thumbnail(&thumbnail, 320, None).unwrap().is_none());
+
+        // Turned like the photo it belongs to
+        let rotated = prepare_thumbnail(&thumbnail, 160, Some(6))
+            .unwrap()
+            .unwrap();
+        let image = image::load_from_memory(&rotated).unwrap();
+        assert_eq!((image.width(), image.height()), (120, 160));
+
+        assert!(prepare_thumbnail(b"not an image", 160, None).is_err());
+    }
+
+    #[test]
+    fn test_undecodable_image_is_an_error() {
+        assert!(downscale_to_max_dimension(b"not an image", 256).is_err());
+    }
+}
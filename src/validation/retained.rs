@@ -0,0 +1,137 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Images kept in memory; the oldest are dropped first when the store fills up
+const MAX_RETAINED_IMAGES: usize = 100;
+
+/// An image exactly as it was sent to the model
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessedImage {
+    pub bytes: Vec<u8>,
+    /// MIME type guessed from the bytes, `application/octet-stream` when unknown
+    pub content_type: String,
+}
+
+impl ProcessedImage {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        let content_type = image::guess_format(&bytes)
+            .map(|format| format.to_mime_type())
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        Self {
+            bytes,
+            content_type,
+        }
+    }
+}
+
+/// The images sent to the model for recent requests, for `GET /debug/{id}/image`
+///
+/// Only enabled with `DEBUG_RETAIN_PROCESSED_IMAGES`. Images are kept in memory for
+/// `retention`, at most [`MAX_RETAINED_IMAGES`] of them, and forgotten on restart. A
+/// request with several images keeps the last one checked.
+pub struct ProcessedImages {
+    retention: Duration,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Default)]
+struct Entries {
+    images: HashMap<String, (ProcessedImage, Instant)>,
+    /// Processing ids in the order their image was last stored
+    order: VecDeque<String>,
+}
+
+impl ProcessedImages {
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            retention,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// Keeps the bytes sent to the model for `processing_id`, replacing any earlier ones
+    pub fn insert(&self, processing_id: &str, bytes: Vec<u8>) {
+        let mut entries = self.entries.lock().expect("processed images lock poisoned");
+        entries.order.retain(|id| id != processing_id);
+        while entries.order.len() >= MAX_RETAINED_IMAGES {
+            if let Some(oldest) = entries.order.pop_front() {
+                entries.images.remove(&oldest);
+            }
+        }
+
+        entries.order.push_back(processing_id.to_string());
+        entries.images.insert(
+            processing_id.to_string(),
+            (ProcessedImage::new(bytes), Instant::now()),
+        );
+    }
+
+    /// The image sent to the model for `processing_id`, unless it expired
+    pub fn get(&self, processing_id: &str) -> Option<ProcessedImage> {
+        let entries = self.entries.lock().expect("processed images lock poisoned");
+        entries
+            .images
+            .get(processing_id)
+            .filter(|(_, stored_at)| stored_at.elapsed() <= self.retention)
+            .map(|(image, _)| image.clone())
+    }
+
+    /// Number of images currently held
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .expect("processed images lock poisoned")
+            .images
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, RgbImage};
+    use std::io::Cursor;
+
+    fn png() -> Vec<u8> {
+        let mut bytes = Cursor::new(Vec::new());
+        RgbImage::new(4, 4)
+            .write_to(&mut bytes, ImageFormat::Png)
+            .unwrap();
+        bytes.into_inner()
+    }
+
+    #[test]
+    fn test_processed_images_expire_and_evict() {
+        let images = ProcessedImages::new(Duration::from_secs(60));
+        images.insert("abc", png());
+        let image = images.get("abc").unwrap();
+        assert_eq!(image.content_type, "image/png");
+        assert_eq!(image.bytes, png());
+        assert_eq!(images.get("unknown"), None);
+
+        // Storing the same request again replaces its image
+        images.insert("abc", b"not an image".to_vec());
+        assert_eq!(images.len(), 1);
+        assert_eq!(
+            images.get("abc").unwrap().content_type,
+            "application/octet-stream"
+        );
+
+        for index in 0..MAX_RETAINED_IMAGES {
+            images.insert(&format!("request-{index}"), png());
+        }
+        assert_eq!(images.len(), MAX_RETAINED_IMAGES);
+        assert_eq!(images.get("abc"), None);
+
+        let expired = ProcessedImages::new(Duration::ZERO);
+        expired.insert("abc", png());
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(expired.get("abc"), None);
+    }
+}
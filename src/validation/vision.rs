@@ -0,0 +1,607 @@
+use crate::config::LlmBackend;
+use crate::validation::llm::LlmError;
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::error::Error as StdError;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, trace};
+
+/// Sampling settings sent with every request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenerationOptions {
+    /// Low values keep verdicts deterministic
+    pub temperature: f32,
+    /// Upper bound on generated tokens (`max_tokens` / Ollama's `num_predict`)
+    pub max_tokens: u32,
+}
+
+impl Default for GenerationOptions {
+    fn default() -> Self {
+        Self {
+            temperature: 0.1,
+            max_tokens: 500,
+        }
+    }
+}
+
+/// Connection reuse settings for the HTTP client talking to the model
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoolOptions {
+    /// Idle connections kept open to the model's host for reuse
+    pub max_idle_per_host: usize,
+    /// How long an unused connection stays in the pool before it is closed
+    pub idle_timeout: Duration,
+    /// Interval of TCP keep-alive probes; `None` leaves them off
+    pub tcp_keepalive: Option<Duration>,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        Self {
+            max_idle_per_host: 32,
+            idle_timeout: Duration::from_secs(90),
+            tcp_keepalive: Some(Duration::from_secs(60)),
+        }
+    }
+}
+
+/// A multimodal model API that can answer a prompt about an image
+///
+/// Implementations make a single attempt; retries and backoff are handled by
+/// [`LlmClient`](crate::validation::llm::LlmClient).
+#[async_trait]
+pub trait VisionBackend: Send + Sync {
+    /// Asks `model`, or the backend's configured model when `None`, about the image
+    async fn analyze(
+        &self,
+        prompt: &str,
+        image_bytes: &[u8],
+        model: Option<&str>,
+    ) -> Result<String, LlmError>;
+}
+
+/// Builds the backend selected by `LLM_BACKEND`
+pub fn create_backend(
+    backend: LlmBackend,
+    api_url: String,
+    model_name: String,
+    timeout: Duration,
+    options: GenerationOptions,
+    pool: PoolOptions,
+) -> Arc<dyn VisionBackend> {
+    let client = build_http_client(timeout, pool);
+    match backend {
+        LlmBackend::OpenAi => {
+            Arc::new(OpenAiBackend::with_client(api_url, model_name, client).with_options(options))
+        }
+        LlmBackend::Ollama => {
+            Arc::new(OllamaBackend::with_client(api_url, model_name, client).with_options(options))
+        }
+    }
+}
+
+fn build_http_client(timeout: Duration, pool: PoolOptions) -> Client {
+    Client::builder()
+        .timeout(timeout)
+        .pool_max_idle_per_host(pool.max_idle_per_host)
+        .pool_idle_timeout(pool.idle_timeout)
+        .tcp_keepalive(pool.tcp_keepalive)
+        .build()
+        .expect("Failed to create HTTP client")
+}
+
+/// OpenAI-compatible `/v1/chat/completions` APIs, with the image sent as an
+/// `image_url` content part holding a data URL
+#[derive(Debug, Clone)]
+pub struct OpenAiBackend {
+    client: Client,
+    api_url: String,
+    model_name: String,
+    options: GenerationOptions,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatCompletionRequest {
+    model: String,
+    messages: Vec<Message>,
+    stream: bool,
+    max_tokens: u32,
+    temperature: f32,
+}
+
+#[derive(Debug, Serialize)]
+struct Message {
+    role: String,
+    content: Vec<Content>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Content {
+    #[serde(rename = "type")]
+    content_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image_url: Option<ImageUrl>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ImageUrl {
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionResponse {
+    choices: Vec<Choice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Choice {
+    message: ResponseMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseMessage {
+    content: String,
+}
+
+impl OpenAiBackend {
+    pub fn new(api_url: String, model_name: String, timeout: Duration) -> Self {
+        Self::with_client(
+            api_url,
+            model_name,
+            build_http_client(timeout, PoolOptions::default()),
+        )
+    }
+
+    /// Creates a backend that sends requests through an already configured client
+    pub fn with_client(api_url: String, model_name: String, client: Client) -> Self {
+        Self {
+            client,
+            api_url,
+            model_name,
+            options: GenerationOptions::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_options(mut self, options: GenerationOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+#[async_trait]
+impl VisionBackend for OpenAiBackend {
+    async fn analyze(
+        &self,
+        prompt: &str,
+        image_bytes: &[u8],
+        model: Option<&str>,
+    ) -> Result<String, LlmError> {
+        let model = model.unwrap_or(&self.model_name);
+        let image_data = general_purpose::STANDARD.encode(image_bytes);
+        let request = ChatCompletionRequest {
+            model: model.to_string(),
+            messages: vec![Message {
+                role: "user".to_string(),
+                content: vec![
+                    Content {
+                        content_type: "text".to_string(),
+                        text: Some(prompt.to_string()),
+                        image_url: None,
+                    },
+                    Content {
+                        content_type: "image_url".to_string(),
+                        text: None,
+                        image_url: Some(ImageUrl {
+                            url: format!("data:image/jpeg;base64,{image_data}"),
+                        }),
+                    },
+                ],
+            }],
+            stream: false,
+            max_tokens: self.options.max_tokens,
+            temperature: self.options.temperature,
+        };
+
+        debug!(
+            "Sending request to LLM API: {} (model {}, {} image bytes)",
+            self.api_url,
+            model,
+            image_bytes.len()
+        );
+        trace!("LLM prompt: {}", prompt);
+
+        let response = self
+            .client
+            .post(&self.api_url)
+            .json(&request)
+            .send()
+            .await
+            .inspect_err(|e| match StdError::source(e) {
+                Some(source) => debug!("LLM HTTP request failed: {} ({})", e, source),
+                None => debug!("LLM HTTP request failed: {}", e),
+            })?;
+
+        debug!("LLM API responded with status: {}", response.status());
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            debug!("LLM API error response body: {}", error_text);
+            return Err(LlmError::Api(format!("HTTP {status}: {error_text}")));
+        }
+
+        let completion: ChatCompletionResponse = response.json().await?;
+
+        if completion.choices.is_empty() {
+            return Err(LlmError::Api(
+                "No choices returned from LLM API".to_string(),
+            ));
+        }
+
+        Ok(completion.choices[0].message.content.trim().to_string())
+    }
+}
+
+/// Ollama's native `/api/chat` API, with the image sent base64-encoded in the
+/// message's `images` array
+#[derive(Debug, Clone)]
+pub struct OllamaBackend {
+    client: Client,
+    api_url: String,
+    model_name: String,
+    options: GenerationOptions,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaChatRequest {
+    model: String,
+    messages: Vec<OllamaMessage>,
+    stream: bool,
+    options: OllamaOptions,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaMessage {
+    role: String,
+    content: String,
+    images: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaOptions {
+    temperature: f32,
+    num_predict: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaChatResponse {
+    message: ResponseMessage,
+}
+
+impl OllamaBackend {
+    pub fn new(api_url: String, model_name: String, timeout: Duration) -> Self {
+        Self::with_client(
+            api_url,
+            model_name,
+            build_http_client(timeout, PoolOptions::default()),
+        )
+    }
+
+    /// Creates a backend that sends requests through an already configured client
+    pub fn with_client(api_url: String, model_name: String, client: Client) -> Self {
+        Self {
+            client,
+            api_url,
+            model_name,
+            options: GenerationOptions::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_options(mut self, options: GenerationOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+#[async_trait]
+impl VisionBackend for OllamaBackend {
+    async fn analyze(
+        &self,
+        prompt: &str,
+        image_bytes: &[u8],
+        model: Option<&str>,
+    ) -> Result<String, LlmError> {
+        let model = model.unwrap_or(&self.model_name);
+        let request = OllamaChatRequest {
+            model: model.to_string(),
+            messages: vec![OllamaMessage {
+                role: "user".to_string(),
+                content: prompt.to_string(),
+                images: vec![general_purpose::STANDARD.encode(image_bytes)],
+            }],
+            stream: false,
+            options: OllamaOptions {
+                temperature: self.options.temperature,
+                num_predict: self.options.max_tokens,
+            },
+        };
+
+        debug!(
+            "Sending request to Ollama API: {} (model {}, {} image bytes)",
+            self.api_url,
+            model,
+            image_bytes.len()
+        );
+        trace!("LLM prompt: {}", prompt);
+
+        let response = self
+            .client
+            .post(&self.api_url)
+            .json(&request)
+            .send()
+            .await?;
+
+        debug!("Ollama API responded with status: {}", response.status());
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(LlmError::Api(format!("HTTP {status}: {error_text}")));
+        }
+
+        let chat: OllamaChatResponse = response.json().await?;
+
+        Ok(chat.message.content.trim().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, routing::post, Json, Router};
+    use serde_json::{json, Value};
+    use tokio::sync::Mutex;
+
+    type CapturedRequest = Arc<Mutex<Option<Value>>>;
+
+    /// Serves `reply` on any POST and records the JSON body it received
+    async fn spawn_mock_server(reply: Value) -> (String, CapturedRequest) {
+        let captured: CapturedRequest = Arc::new(Mutex::new(None));
+
+        let app = Router::new()
+            .fallback(post(
+                |State((captured, reply)): State<(CapturedRequest, Value)>,
+                 Json(body): Json<Value>| async move {
+                    *captured.lock().await = Some(body);
+                    Json(reply)
+                },
+            ))
+            .with_state((captured.clone(), reply));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (format!("http://{addr}/chat"), captured)
+    }
+
+    #[tokio::test]
+    async fn test_openai_backend_request_shape() {
+        let (url, captured) = spawn_mock_server(json!({
+            "choices": [{ "message": { "content": " ACCEPTED\nSCORE: 0.9 " } }]
+        }))
+        .await;
+
+        let backend = OpenAiBackend::new(url, "gpt-4o".into(), Duration::from_secs(5));
+        let reply = backend
+            .analyze("Describe", b"image-bytes", None)
+            .await
+            .unwrap();
+        assert_eq!(reply, "ACCEPTED\nSCORE: 0.9");
+
+        let body = captured.lock().await.take().unwrap();
+        assert_eq!(body["model"], "gpt-4o");
+        assert_eq!(body["stream"], false);
+        let content = &body["messages"][0]["content"];
+        assert_eq!(content[0]["type"], "text");
+        assert_eq!(content[0]["text"], "Describe");
+        assert_eq!(content[1]["type"], "image_url");
+        assert_eq!(
+            content[1]["image_url"]["url"],
+            format!(
+                "data:image/jpeg;base64,{}",
+                general_purpose::STANDARD.encode(b"image-bytes")
+            )
+        );
+        assert!(body["messages"][0].get("images").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ollama_backend_request_shape() {
+        let (url, captured) = spawn_mock_server(json!({
+            "model": "llava:7b",
+            "message": { "role": "assistant", "content": "REJECTED: no birds\nSCORE: 0.2" },
+            "done": true
+        }))
+        .await;
+
+        let backend = OllamaBackend::new(url, "llava:7b".into(), Duration::from_secs(5));
+        let reply = backend
+            .analyze("Describe", b"image-bytes", None)
+            .await
+            .unwrap();
+        assert_eq!(reply, "REJECTED: no birds\nSCORE: 0.2");
+
+        let body = captured.lock().await.take().unwrap();
+        assert_eq!(body["model"], "llava:7b");
+        assert_eq!(body["stream"], false);
+        let message = &body["messages"][0];
+        assert_eq!(message["role"], "user");
+        assert_eq!(message["content"], "Describe");
+        assert_eq!(
+            message["images"][0],
+            general_purpose::STANDARD.encode(b"image-bytes")
+        );
+        assert_eq!(body["options"]["num_predict"], 500);
+    }
+
+    #[tokio::test]
+    async fn test_generation_options_reach_request_payload() {
+        let options = GenerationOptions {
+            temperature: 0.7,
+            max_tokens: 1200,
+        };
+
+        let (url, captured) = spawn_mock_server(json!({
+            "choices": [{ "message": { "content": "ACCEPTED" } }]
+        }))
+        .await;
+        let backend = create_backend(
+            LlmBackend::OpenAi,
+            url,
+            "gpt-4o".into(),
+            Duration::from_secs(5),
+            options,
+            PoolOptions::default(),
+        );
+        backend
+            .analyze("Describe", b"image-bytes", None)
+            .await
+            .unwrap();
+        let body = captured.lock().await.take().unwrap();
+        assert_eq!(body["max_tokens"], 1200);
+        assert!((body["temperature"].as_f64().unwrap() - 0.7).abs() < 1e-6);
+
+        let (url, captured) = spawn_mock_server(json!({
+            "message": { "role": "assistant", "content": "ACCEPTED" }
+        }))
+        .await;
+        let backend = create_backend(
+            LlmBackend::Ollama,
+            url,
+            "llava:7b".into(),
+            Duration::from_secs(5),
+            options,
+            PoolOptions::default(),
+        );
+        backend
+            .analyze("Describe", b"image-bytes", None)
+            .await
+            .unwrap();
+        let body = captured.lock().await.take().unwrap();
+        assert_eq!(body["options"]["num_predict"], 1200);
+        assert!((body["options"]["temperature"].as_f64().unwrap() - 0.7).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_custom_pool_options() {
+        let pool = PoolOptions {
+            max_idle_per_host: 4,
+            idle_timeout: Duration::from_secs(15),
+            tcp_keepalive: None,
+        };
+        for backend in [LlmBackend::OpenAi, LlmBackend::Ollama] {
+            let (url, captured) = spawn_mock_server(json!({
+                "choices": [{ "message": { "content": "ACCEPTED" } }],
+                "message": { "role": "assistant", "content": "ACCEPTED" }
+            }))
+            .await;
+            let backend = create_backend(
+                backend,
+                url,
+                "llava:7b".into(),
+                Duration::from_secs(5),
+                GenerationOptions::default(),
+                pool,
+            );
+
+            // Both calls go through the same pooled client
+            for _ in 0..2 {
+                let reply = backend
+                    .analyze("Describe", b"image-bytes", None)
+                    .await
+                    .unwrap();
+                assert_eq!(reply, "ACCEPTED");
+                assert!(captured.lock().await.take().is_some());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_model_override_reaches_request_payload() {
+        for backend in [LlmBackend::OpenAi, LlmBackend::Ollama] {
+            let (url, captured) = spawn_mock_server(json!({
+                "choices": [{ "message": { "content": "ACCEPTED" } }],
+                "message": { "role": "assistant", "content": "ACCEPTED" }
+            }))
+            .await;
+            let backend = create_backend(
+                backend,
+                url,
+                "llava:7b".into(),
+                Duration::from_secs(5),
+                GenerationOptions::default(),
+                PoolOptions::default(),
+            );
+
+            backend
+                .analyze("Describe", b"image-bytes", Some("llava:34b"))
+                .await
+                .unwrap();
+            let body = captured.lock().await.take().unwrap();
+            assert_eq!(body["model"], "llava:34b");
+
+            backend
+                .analyze("Describe", b"image-bytes", None)
+                .await
+                .unwrap();
+            let body = captured.lock().await.take().unwrap();
+            assert_eq!(body["model"], "llava:7b");
+        }
+    }
+
+    #[test]
+    fn test_llm_request_path_does_not_write_to_stdout() {
+        // Logging must go through tracing so it respects the EnvFilter
+        let print_macro = concat!("print", "ln!");
+        for (file, source) in [
+            ("llm.rs", include_str!("llm.rs")),
+            ("vision.rs", include_str!("vision.rs")),
+        ] {
+            assert!(
+                !source.contains(print_macro),
+                "{file} must log through tracing instead of stdout"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backend_http_error() {
+        let app = Router::new()
+            .fallback(|| async { (axum::http::StatusCode::SERVICE_UNAVAILABLE, "model loading") });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let backend = create_backend(
+            LlmBackend::Ollama,
+            format!("http://{addr}/api/chat"),
+            "llava:7b".into(),
+            Duration::from_secs(5),
+            GenerationOptions::default(),
+            PoolOptions::default(),
+        );
+        match backend.analyze("Describe", b"image-bytes", None).await {
+            Err(LlmError::Api(message)) => assert!(message.contains("503")),
+            other => panic!("Expected API error, got {other:?}"),
+        }
+    }
+}
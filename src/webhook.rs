@@ -0,0 +1,177 @@
+use crate::models::{ProcessingStatus, ValidationResults};
+
+use reqwest::{Client, Url};
+use serde::Serialize;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::time::sleep;
+use tracing::{debug, error, warn};
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error("HTTP request error: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("Callback returned status {0}")]
+    Status(reqwest::StatusCode),
+    #[error("Max retries exceeded")]
+    MaxRetriesExceeded,
+}
+
+/// Body POSTed to `callback-url` when a request completes or fails
+///
+/// For completed requests this is the `ValidationResponse` JSON plus `status`.
+#[derive(Debug, Serialize, Clone)]
+pub struct CallbackPayload {
+    #[serde(rename = "processing-id")]
+    pub processing_id: String,
+
+    pub status: ProcessingStatus,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub results: Option<ValidationResults>,
+}
+
+/// Checks that a callback URL is an absolute http or https URL
+pub fn validate_callback_url(callback_url: &str) -> Result<(), String> {
+    let url = Url::parse(callback_url).map_err(|e| format!("{e}"))?;
+
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!(
+            "scheme must be http or https, got: {}",
+            url.scheme()
+        ));
+    }
+    if url.host_str().is_none() {
+        return Err("URL has no host".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct WebhookClient {
+    client: Client,
+    max_retries: u32,
+}
+
+impl WebhookClient {
+    pub fn new(timeout: Duration) -> Self {
+        let client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            max_retries: 3,
+        }
+    }
+
+    /// POSTs the payload, retrying with exponential backoff on errors and non-2xx responses
+    pub async fn deliver(
+        &self,
+        callback_url: &str,
+        payload: &CallbackPayload,
+    ) -> Result<(), WebhookError> {
+        let mut attempt = 0;
+        let mut delay = Duration::from_millis(1000); // Start with 1 second
+
+        while attempt < self.max_retries {
+            match self.post(callback_url, payload).await {
+                Ok(()) => {
+                    debug!(
+                        processing_id = %payload.processing_id,
+                        "Delivered callback to {}",
+                        callback_url
+                    );
+                    return Ok(());
+                }
+                Err(e) => {
+                    attempt += 1;
+
+                    if attempt >= self.max_retries {
+                        error!(
+                            "Max retries exceeded for callback to {}: {}",
+                            callback_url, e
+                        );
+                        return Err(WebhookError::MaxRetriesExceeded);
+                    }
+
+                    warn!(
+                        "Callback to {} failed (attempt {}): {}. Retrying in {:?}",
+                        callback_url, attempt, e, delay
+                    );
+                    sleep(delay).await;
+
+                    delay = std::cmp::min(delay * 2, Duration::from_secs(30));
+                }
+            }
+        }
+
+        Err(WebhookError::MaxRetriesExceeded)
+    }
+
+    async fn post(
+        &self,
+        callback_url: &str,
+        payload: &CallbackPayload,
+    ) -> Result<(), WebhookError> {
+        let response = self.client.post(callback_url).json(payload).send().await?;
+
+        if !response.status().is_success() {
+            return Err(WebhookError::Status(response.status()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_callback_url() {
+        assert!(validate_callback_url("http://localhost:8080/hooks/results").is_ok());
+        assert!(validate_callback_url("https://example.com/callback?token=abc").is_ok());
+
+        assert!(validate_callback_url("ftp://example.com/callback").is_err());
+        assert!(validate_callback_url("file:///tmp/callback").is_err());
+        assert!(validate_callback_url("example.com/callback").is_err());
+        assert!(validate_callback_url("").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_deliver_retries_until_success() {
+        use axum::{http::StatusCode, routing::post, Router};
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use std::sync::Arc;
+
+        // Fails the first attempt, then accepts
+        let attempts = Arc::new(AtomicU32::new(0));
+        let counter = attempts.clone();
+        let app = Router::new().route(
+            "/callback",
+            post(move || async move {
+                if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    StatusCode::SERVICE_UNAVAILABLE
+                } else {
+                    StatusCode::OK
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let payload = CallbackPayload {
+            processing_id: "001".to_string(),
+            status: ProcessingStatus::Failed,
+            results: None,
+        };
+        WebhookClient::new(Duration::from_secs(5))
+            .deliver(&format!("http://{addr}/callback"), &payload)
+            .await
+            .unwrap();
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+}
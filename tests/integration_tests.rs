@@ -1,14 +1,19 @@
+use image_checker::auth::ApiKeyLayer;
+use image_checker::client_limit::ClientRateLimitLayer;
 use image_checker::handlers::*;
 use image_checker::{Config, ProcessingQueue};
 
 use axum::{
     body::Body,
+    extract::DefaultBodyLimit,
     http::{Request, StatusCode},
-    routing::{get, post},
+    middleware::map_response_with_state,
+    routing::{delete, get, post},
     Router,
 };
 use serde_json::json;
 use tower::util::ServiceExt;
+use tower_http::limit::RequestBodyLimitLayer;
 
 fn create_test_config() -> Config {
     Config {
@@ -21,20 +26,56 @@ fn create_test_config() -> Config {
         processing_timeout_minutes: 5,
         queue_size: 100,
         throttle_requests_per_minute: 60,
+        ..Config::default()
     }
 }
 
 fn create_test_app() -> Router {
-    let config = create_test_config();
+    create_test_app_with_config(create_test_config())
+}
+
+/// Allows one request per minute, so only the first submission starts right away
+fn create_throttled_test_app() -> Router {
+    create_test_app_with_config(Config {
+        throttle_requests_per_minute: 1,
+        ..create_test_config()
+    })
+}
+
+fn create_test_app_with_config(config: Config) -> Router {
     let queue = ProcessingQueue::new(&config);
+    create_test_app_with_queue(config, queue)
+}
+
+fn create_test_app_with_queue(config: Config, queue: ProcessingQueue) -> Router {
+    image_checker::metrics::init();
 
     Router::new()
         .route("/validate", post(submit_validation))
+        .route("/validate/batch", post(submit_validation_batch))
+        .route("/validate/check", post(check_validation))
+        .route("/validate/:id", delete(cancel_validation))
+        .route("/validate/:id/retry", post(retry_validation))
+        .route("/validate-sync", post(validate_sync))
+        .route("/inspect", post(inspect_image))
+        .route("/status/batch", post(check_status_batch))
         .route("/status/:id", get(check_status))
         .route("/results/:id", get(get_results))
+        .route("/debug/:id/image", get(debug_image))
+        .route("/jobs", get(list_jobs))
         .route("/health", get(health_check))
+        .route("/health/ready", get(readiness_check))
+        .route("/version", get(version))
         .route("/stats", get(queue_stats))
-        .with_state(queue)
+        .route("/admin/queue", get(admin_queue))
+        .route("/admin/queue/flush", post(flush_queue))
+        .route("/metrics", get(metrics))
+        .route("/openapi.json", get(openapi))
+        .fallback(handle_404)
+        .with_state(queue.clone())
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(config.max_request_body_bytes))
+        .layer(map_response_with_state(queue, error_envelope))
 }
 
 #[tokio::test]
@@ -73,6 +114,30 @@ async fn test_submit_validation_endpoint() {
     assert_eq!(response_json["status"], "accepted");
 }
 
+#[tokio::test]
+async fn test_submit_validation_with_short_processing_ids() {
+    let app = create_test_app_with_config(Config {
+        processing_id_length: Some(10),
+        processing_id_alphabet: image_checker::IdAlphabet::Base62,
+        ..create_test_config()
+    });
+
+    let request_body = json!({
+        "image-path": "/tmp/test.jpg",
+        "analysis-request": { "content": "Three birds on a wire" }
+    });
+    let (status, body) = post_json(&app, "/validate", request_body).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+
+    let processing_id = body["processing-id"].as_str().unwrap();
+    assert_eq!(processing_id.len(), 10);
+    assert!(processing_id.chars().all(|c| c.is_ascii_alphanumeric()));
+
+    let (status, body) = send(&app, "GET", &format!("/status/{processing_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["processing-id"], processing_id);
+}
+
 #[tokio::test]
 async fn test_submit_validation_missing_fields() {
     let app = create_test_app();
@@ -101,6 +166,71 @@ async fn test_submit_validation_missing_fields() {
     assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 }
 
+#[tokio::test]
+async fn test_validate_sync_returns_results() {
+    let app = create_test_app();
+
+    // A missing image is rejected without calling the LLM, so this completes quickly
+    let request_body = json!({
+        "image-path": "/tmp/image-checker-sync-test-missing.jpg",
+        "analysis-request": {
+            "content": "Three birds on a wire"
+        }
+    });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/validate-sync")
+                .header("content-type", "application/json")
+                .body(Body::from(request_body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert!(response_json["processing-id"].is_string());
+    assert_eq!(response_json["results"]["resolution"], "rejected");
+    assert_eq!(
+        response_json["results"]["reasons"][0],
+        json!({ "code": "IMAGE_NOT_FOUND", "message": "cannot locate image" })
+    );
+}
+
+#[tokio::test]
+async fn test_validate_sync_missing_fields() {
+    let app = create_test_app();
+
+    let request_body = json!({
+        "image-path": "/tmp/test.jpg",
+        "analysis-request": {
+            "content": ""
+        }
+    });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/validate-sync")
+                .header("content-type", "application/json")
+                .body(Body::from(request_body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
 #[tokio::test]
 async fn test_status_endpoint() {
     let app = create_test_app();
@@ -140,7 +270,7 @@ async fn test_status_endpoint() {
         .oneshot(
             Request::builder()
                 .method("GET")
-                .uri(&format!("/status/{}", processing_id))
+                .uri(format!("/status/{}", processing_id))
                 .body(Body::empty())
                 .unwrap(),
         )
@@ -157,6 +287,7 @@ async fn test_status_endpoint() {
     assert_eq!(response_json["processing-id"], processing_id);
     assert!(["accepted", "in_progress", "completed", "failed"]
         .contains(&response_json["status"].as_str().unwrap()));
+    assert!(response_json["attempts"].is_u64());
 }
 
 #[tokio::test]
@@ -177,6 +308,136 @@ async fn test_status_not_found() {
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
 }
 
+#[tokio::test]
+async fn test_error_envelope() {
+    let app = create_test_app();
+
+    let (status, body) = send(&app, "GET", "/status/nonexistent").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(
+        body,
+        json!({
+            "error": {
+                "code": "NOT_FOUND",
+                "message": "processing ID not found",
+                "details": null
+            }
+        })
+    );
+
+    let (status, body) = send(&app, "GET", "/no/such/endpoint").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"]["code"], "NOT_FOUND");
+    assert_eq!(body["error"]["message"], "endpoint not found");
+    assert!(body["error"]["details"].is_string());
+
+    // Bodies that do not match the expected fields name the field at fault
+    let (status, body) = post_json(&app, "/validate", json!({ "image-path": 42 })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "BAD_REQUEST");
+    assert_eq!(body["error"]["field"], "image-path");
+    assert!(body["error"]["message"].is_string());
+}
+
+#[tokio::test]
+async fn test_malformed_bodies_name_the_field() {
+    let app = create_test_app();
+
+    let cases = [
+        (
+            json!({ "image-path": "/tmp/test.jpg", "analysis-request": {} }),
+            "analysis-request.content",
+            "missing field `content`",
+        ),
+        (
+            json!({
+                "image-path": "/tmp/test.jpg",
+                "analysis-request": {
+                    "content": "Pub sign",
+                    "location": { "lat": 51.49, "long": -0.26, "max_distance": "100m" }
+                }
+            }),
+            "analysis-request.location.max_distance",
+            "invalid type: string \"100m\", expected f64",
+        ),
+        (
+            json!({
+                "image-path": "/tmp/test.jpg",
+                "analysis-request": {
+                    "content": "Pub sign",
+                    "datetime": { "start": "2025-08-01T15:23:00Z", "length": 10 }
+                }
+            }),
+            "analysis-request.datetime",
+            "Exactly two out of three fields (start, end, duration) must be provided",
+        ),
+        (
+            json!({
+                "image-path": "/tmp/test.jpg",
+                "analysis-request": { "content": "Pub sign", "match_mode": "most" }
+            }),
+            "analysis-request.match_mode",
+            "unknown variant `most`",
+        ),
+    ];
+    for (request_body, field, message) in cases {
+        let (status, body) = post_json(&app, "/validate", request_body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+        assert_eq!(body["error"]["code"], "BAD_REQUEST");
+        assert_eq!(body["error"]["field"], field);
+        let actual = body["error"]["message"].as_str().unwrap();
+        assert!(actual.contains(message), "{actual}");
+    }
+
+    // Batch items are numbered
+    let (status, body) = post_json(
+        &app,
+        "/validate/batch",
+        json!([
+            { "image-path": "/tmp/a.jpg", "analysis-request": { "content": "A" } },
+            { "image-path": "/tmp/b.jpg", "analysis-request": { "content": 7 } }
+        ]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["field"], "[1].analysis-request.content");
+
+    // An empty description passes parsing but is still pinned to its field
+    let (status, body) = post_json(
+        &app,
+        "/validate",
+        json!({ "image-path": "/tmp/test.jpg", "analysis-request": { "content": "" } }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["field"], "analysis-request.content");
+
+    // Broken JSON has no field, but says where parsing stopped
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/validate")
+                .header("content-type", "application/json")
+                .body(Body::from("{\"image-path\": "))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("malformed JSON"));
+    assert!(body["error"].get("field").is_none());
+    assert_eq!(body["error"]["details"], "at line 1 column 15");
+}
+
 #[tokio::test]
 async fn test_results_not_found() {
     let app = create_test_app();
@@ -222,6 +483,20 @@ async fn test_health_endpoint() {
     assert!(response_json["queue_stats"].is_object());
 }
 
+#[tokio::test]
+async fn test_version_endpoint() {
+    let app = create_test_app();
+
+    let (status, body) = send(&app, "GET", "/version").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.is_object());
+    assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    assert!(!body["version"].as_str().unwrap().is_empty());
+    for field in ["git_sha", "build_timestamp", "rustc_version"] {
+        assert!(!body[field].as_str().unwrap().is_empty(), "{field}");
+    }
+}
+
 #[tokio::test]
 async fn test_stats_endpoint() {
     let app = create_test_app();
@@ -249,6 +524,47 @@ async fn test_stats_endpoint() {
     assert!(response_json["in_progress"].is_number());
     assert!(response_json["completed"].is_number());
     assert!(response_json["failed"].is_number());
+    assert_eq!(response_json["llm_circuit"], "closed");
+}
+
+#[tokio::test]
+async fn test_readiness_reports_reachable_llm() {
+    // Chat endpoints reject HEAD, but any answer shows the backend is up
+    let llm = Router::new().fallback(|| async { StatusCode::METHOD_NOT_ALLOWED });
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, llm).await.unwrap() });
+
+    let app = create_test_app_with_config(Config {
+        llm_api_url: format!("http://{addr}/v1/chat/completions"),
+        ..create_test_config()
+    });
+
+    let (status, body) = send(&app, "GET", "/health/ready").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "status": "ready" }));
+}
+
+#[tokio::test]
+async fn test_readiness_reports_unreachable_llm() {
+    // Nothing listens on a port that was just released
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let app = create_test_app_with_config(Config {
+        llm_api_url: format!("http://{addr}/v1/chat/completions"),
+        ..create_test_config()
+    });
+
+    let (status, body) = send(&app, "GET", "/health/ready").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "unavailable");
+    assert!(body["error"].is_string());
+
+    // Liveness is unaffected
+    let (status, _) = send(&app, "GET", "/health").await;
+    assert_eq!(status, StatusCode::OK);
 }
 
 #[tokio::test]
@@ -296,6 +612,98 @@ async fn test_validation_request_with_location_and_datetime() {
     assert_eq!(response_json["status"], "accepted");
 }
 
+#[tokio::test]
+async fn test_validation_request_with_datetime_phrase() {
+    let app = create_test_app();
+
+    for phrase in [
+        "image was taken not more than 10 minutes after 2025-08-01T15:23:00Z+1",
+        "image was taken not more than 10 minutes before 2025-08-01T15:23:00+01:00",
+    ] {
+        let request_body = json!({
+            "image-path": "/tmp/test.jpg",
+            "analysis-request": {
+                "content": "Pub sign The Ale and Hops",
+                "datetime": phrase
+            }
+        });
+        let (status, body) = post_json(&app, "/validate", request_body).await;
+        assert_eq!(status, StatusCode::ACCEPTED, "{phrase}: {body}");
+        assert_eq!(body["status"], "accepted");
+    }
+
+    let request_body = json!({
+        "image-path": "/tmp/test.jpg",
+        "analysis-request": {
+            "content": "Pub sign The Ale and Hops",
+            "datetime": "some time on the first of August"
+        }
+    });
+    let (status, body) = post_json(&app, "/validate", request_body).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["field"], "analysis-request.datetime");
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Unrecognized datetime phrase"));
+
+    // A recognized phrase with an unparseable time is a bad request, like a bad `start`
+    let request_body = json!({
+        "image-path": "/tmp/test.jpg",
+        "analysis-request": {
+            "content": "Pub sign The Ale and Hops",
+            "datetime": "not more than 10 minutes after lunch"
+        }
+    });
+    let (status, body) = post_json(&app, "/validate", request_body).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Invalid datetime format: lunch"));
+}
+
+#[tokio::test]
+async fn test_validation_request_with_polygon_and_radius_rejected() {
+    let app = create_test_app();
+
+    let request_body = json!({
+        "image-path": "/tmp/test.jpg",
+        "analysis-request": {
+            "content": "Picnic in the park",
+            "location": {
+                "long": -0.16,
+                "lat": 51.505,
+                "max_distance": 100.0,
+                "polygon": [[51.50, -0.17], [51.50, -0.15], [51.51, -0.15], [51.51, -0.17]]
+            }
+        }
+    });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/validate")
+                .header("content-type", "application/json")
+                .body(Body::from(request_body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(response_json["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("not both"));
+}
+
 #[tokio::test]
 async fn test_validation_request_with_null_image() {
     let app = create_test_app();
@@ -321,3 +729,1383 @@ async fn test_validation_request_with_null_image() {
 
     assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 }
+
+// 1x1 transparent PNG
+const PNG_DATA_URL: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==";
+
+#[tokio::test]
+async fn test_validation_request_with_inline_png_data_url() {
+    let app = create_test_app();
+
+    let request_body = json!({
+        "image": PNG_DATA_URL,
+        "analysis-request": {
+            "content": "A single transparent pixel"
+        }
+    });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/validate")
+                .header("content-type", "application/json")
+                .body(Body::from(request_body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn test_validation_request_with_oversized_inline_image() {
+    let app = create_test_app_with_config(Config {
+        max_inline_image_bytes: 16,
+        ..create_test_config()
+    });
+
+    let request_body = json!({
+        "image": PNG_DATA_URL,
+        "analysis-request": {
+            "content": "A single transparent pixel"
+        }
+    });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/validate")
+                .header("content-type", "application/json")
+                .body(Body::from(request_body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(response_json["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("maximum size of 16 bytes"));
+}
+
+const MULTIPART_BOUNDARY: &str = "image-checker-test-boundary";
+
+/// Encodes `(name, content type, bytes)` parts as a `multipart/form-data` body
+fn multipart_body(parts: &[(&str, Option<&str>, &[u8])]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (name, content_type, bytes) in parts {
+        body.extend_from_slice(format!("--{MULTIPART_BOUNDARY}\r\n").as_bytes());
+        match content_type {
+            Some(content_type) => body.extend_from_slice(
+                format!(
+                    "Content-Disposition: form-data; name=\"{name}\"; filename=\"upload\"\r\n\
+                     Content-Type: {content_type}\r\n\r\n"
+                )
+                .as_bytes(),
+            ),
+            None => body.extend_from_slice(
+                format!("Content-Disposition: form-data; name=\"{name}\"\r\n\r\n").as_bytes(),
+            ),
+        }
+        body.extend_from_slice(bytes);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{MULTIPART_BOUNDARY}--\r\n").as_bytes());
+    body
+}
+
+async fn post_multipart(app: &Router, body: Vec<u8>) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/validate")
+                .header(
+                    "content-type",
+                    format!("multipart/form-data; boundary={MULTIPART_BOUNDARY}"),
+                )
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_validation_request_with_multipart_upload() {
+    use base64::{engine::general_purpose, Engine as _};
+
+    let app = create_test_app_with_config(Config {
+        max_request_body_bytes: 4 * 1024,
+        ..create_test_config()
+    });
+    let png = general_purpose::STANDARD
+        .decode(PNG_DATA_URL.trim_start_matches("data:image/png;base64,"))
+        .unwrap();
+    let analysis_request = json!({ "content": "A single transparent pixel" }).to_string();
+
+    let (status, body) = post_multipart(
+        &app,
+        multipart_body(&[
+            ("image", Some("image/png"), &png),
+            ("analysis-request", None, analysis_request.as_bytes()),
+        ]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED, "{body}");
+    assert_eq!(body["status"], "accepted");
+    let processing_id = body["processing-id"].as_str().unwrap();
+    let (status, _) = send(&app, "GET", &format!("/status/{processing_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Field errors name the same paths as for a JSON body
+    let (status, body) = post_multipart(
+        &app,
+        multipart_body(&[
+            ("image", Some("image/png"), &png),
+            ("analysis-request", None, b"{}"),
+        ]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["field"], "analysis-request.content");
+
+    let (status, body) = post_multipart(
+        &app,
+        multipart_body(&[("analysis-request", None, analysis_request.as_bytes())]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["field"], "image");
+
+    // Uploads count towards the request body limit
+    let (status, body) = post_multipart(
+        &app,
+        multipart_body(&[
+            ("image", Some("image/png"), &[0u8; 8 * 1024]),
+            ("analysis-request", None, analysis_request.as_bytes()),
+        ]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(body["error"]["code"], "PAYLOAD_TOO_LARGE");
+}
+
+#[tokio::test]
+async fn test_request_body_over_limit() {
+    let app = create_test_app_with_config(Config {
+        max_inline_image_bytes: 1024,
+        max_request_body_bytes: 128 * 1024,
+        ..create_test_config()
+    });
+
+    let request_body = json!({
+        "image-path": "/tmp/test.jpg",
+        "analysis-request": {
+            "content": "x".repeat(256 * 1024)
+        }
+    })
+    .to_string();
+
+    // Refused up front from the Content-Length header
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/validate")
+                .header("content-type", "application/json")
+                .header("content-length", request_body.len())
+                .body(Body::from(request_body.clone()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(response_json["error"]["message"], "request body too large");
+    assert!(response_json["error"]["details"]
+        .as_str()
+        .unwrap()
+        .contains("131072 bytes"));
+
+    // Without a Content-Length the limit trips while the body is read
+    let request_body: serde_json::Value = serde_json::from_str(&request_body).unwrap();
+    let (status, response_json) = post_json(&app, "/validate-sync", request_body).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(response_json["error"]["message"], "request body too large");
+}
+
+#[tokio::test]
+async fn test_openapi_document() {
+    let app = create_test_app();
+
+    let (status, doc) = send(&app, "GET", "/openapi.json").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(doc["openapi"].as_str().unwrap().starts_with("3."));
+
+    let validate = &doc["paths"]["/validate"]["post"];
+    assert_eq!(
+        validate["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/ValidationRequest"
+    );
+    assert!(validate["responses"]["202"].is_object());
+    assert!(doc["paths"]["/results/{id}"]["get"].is_object());
+
+    let properties = &doc["components"]["schemas"]["ValidationRequest"]["properties"];
+    assert!(properties["analysis-request"].is_object());
+    assert!(properties["image-path"].is_object());
+}
+
+#[tokio::test]
+async fn test_submit_validation_batch() {
+    let app = create_test_app();
+
+    let item = |image_path: &str| {
+        json!({
+            "image-path": image_path,
+            "analysis-request": { "content": "Three birds on a wire" }
+        })
+    };
+    let request_body = json!([item("/tmp/a.jpg"), item("/tmp/b.jpg"), item("/tmp/c.jpg")]);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/validate/batch")
+                .header("content-type", "application/json")
+                .body(Body::from(request_body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let items: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(items.len(), 3);
+
+    let mut ids = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        assert_eq!(item["index"], index);
+        assert_eq!(item["status"], "accepted");
+        let id = item["processing-id"].as_str().unwrap().to_string();
+        let (status, _) = send(&app, "GET", &format!("/status/{id}")).await;
+        assert_eq!(status, StatusCode::OK);
+        ids.push(id);
+    }
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 3, "processing ids must be distinct");
+}
+
+#[tokio::test]
+async fn test_submit_validation_batch_partial() {
+    let app = create_test_app();
+
+    let request_body = json!([
+        {
+            "image-path": "/tmp/a.jpg",
+            "analysis-request": { "content": "Three birds on a wire" }
+        },
+        { "analysis-request": { "content": "No image given" } }
+    ]);
+    let (status, items) = post_json(&app, "/validate/batch", request_body).await;
+
+    assert_eq!(status, StatusCode::MULTI_STATUS);
+    assert_eq!(items[0]["status"], "accepted");
+    assert_eq!(items[1]["status"], "rejected");
+    assert_eq!(items[1]["error"], "image path is required");
+
+    let (status, _) = post_json(&app, "/validate/batch", json!([])).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_check_status_batch() {
+    let app = create_test_app_with_config(Config {
+        max_status_batch_ids: 4,
+        ..create_test_config()
+    });
+
+    let first = submit(&app, "/tmp/a.jpg").await;
+    let second = submit(&app, "/tmp/b.jpg").await;
+    let (status, statuses) =
+        post_json(&app, "/status/batch", json!([first, "unknown-id", second])).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let statuses = statuses.as_array().unwrap();
+    assert_eq!(statuses.len(), 3);
+    assert_eq!(statuses[0]["processing-id"], first.as_str());
+    assert_ne!(statuses[0]["status"], "not_found");
+    assert_eq!(statuses[1]["processing-id"], "unknown-id");
+    assert_eq!(statuses[1]["status"], "not_found");
+    assert_eq!(statuses[1]["attempts"], 0);
+    assert_eq!(statuses[2]["processing-id"], second.as_str());
+    assert_ne!(statuses[2]["status"], "not_found");
+
+    let (status, _) = post_json(&app, "/status/batch", json!([])).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, body) = post_json(&app, "/status/batch", json!(["a", "b", "c", "d", "e"])).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["error"]["message"],
+        "batch of 5 processing ids exceeds the limit of 4"
+    );
+}
+
+async fn submit(app: &Router, image_path: &str) -> String {
+    let request_body = json!({
+        "image-path": image_path,
+        "analysis-request": {
+            "content": "Three birds on a wire"
+        }
+    });
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/validate")
+                .header("content-type", "application/json")
+                .body(Body::from(request_body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let response_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    response_json["processing-id"].as_str().unwrap().to_string()
+}
+
+async fn send(app: &Router, method: &str, uri: &str) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_cancel_validation() {
+    let app = create_throttled_test_app();
+
+    // The second submission waits for a throttle token, so it is still queued
+    // when it is cancelled
+    let first_id = submit(&app, "/tmp/image-checker-cancel-test-1.jpg").await;
+    let second_id = submit(&app, "/tmp/image-checker-cancel-test-2.jpg").await;
+
+    let (status, body) = send(&app, "DELETE", &format!("/validate/{second_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "cancelled");
+
+    let (status, body) = send(&app, "GET", &format!("/status/{second_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "cancelled");
+
+    let (status, _) = send(&app, "GET", &format!("/results/{second_id}")).await;
+    assert_eq!(status, StatusCode::GONE);
+
+    // Cancelling again is a no-op
+    let (status, _) = send(&app, "DELETE", &format!("/validate/{second_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Once the first request completes it can no longer be cancelled
+    let mut completed = false;
+    for _ in 0..50 {
+        let (_, body) = send(&app, "GET", &format!("/status/{first_id}")).await;
+        if body["status"] == "completed" {
+            completed = true;
+            break;
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(20)).await;
+    }
+    assert!(completed, "first request did not complete");
+
+    let (status, _) = send(&app, "DELETE", &format!("/validate/{first_id}")).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_cancel_validation_not_found() {
+    let app = create_test_app();
+
+    let (status, _) = send(&app, "DELETE", "/validate/nonexistent").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_list_jobs() {
+    let app = create_throttled_test_app();
+
+    let mut ids = Vec::new();
+    for n in 1..=3 {
+        ids.push(submit(&app, &format!("/tmp/image-checker-jobs-test-{n}.jpg")).await);
+    }
+    // Still waiting for a throttle token, so cancellation succeeds
+    let (status, _) = send(&app, "DELETE", &format!("/validate/{}", ids[2])).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let job_ids = |body: &serde_json::Value| -> Vec<String> {
+        body["jobs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|job| job["processing-id"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    // Newest first
+    let (status, body) = send(&app, "GET", "/jobs").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], 3);
+    assert_eq!(body["limit"], 50);
+    assert_eq!(
+        job_ids(&body),
+        [ids[2].clone(), ids[1].clone(), ids[0].clone()]
+    );
+    assert!(body["jobs"][0]["submitted_at_ms"].as_i64().unwrap() > 0);
+
+    let (_, body) = send(&app, "GET", "/jobs?limit=1&offset=1").await;
+    assert_eq!(body["total"], 3);
+    assert_eq!(job_ids(&body), [ids[1].clone()]);
+
+    let (_, body) = send(&app, "GET", "/jobs?status=cancelled").await;
+    assert_eq!(body["total"], 1);
+    assert_eq!(job_ids(&body), [ids[2].clone()]);
+    assert_eq!(body["jobs"][0]["status"], "cancelled");
+
+    let (status, body) = send(&app, "GET", "/jobs?status=bogus").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("invalid status parameter"));
+}
+
+#[tokio::test]
+async fn test_results_survive_restart_with_persistence() {
+    let persistence_dir = tempfile::TempDir::new().unwrap();
+    let config = Config {
+        persistence_path: Some(persistence_dir.path().to_string_lossy().into_owned()),
+        ..create_test_config()
+    };
+
+    let app = create_test_app_with_config(config.clone());
+    let processing_id = submit(&app, "/tmp/image-checker-persistence-test.jpg").await;
+
+    let mut completed = false;
+    for _ in 0..50 {
+        let (_, body) = send(&app, "GET", &format!("/status/{processing_id}")).await;
+        if body["status"] == "completed" {
+            completed = true;
+            break;
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(20)).await;
+    }
+    assert!(completed, "request did not complete");
+    drop(app);
+
+    // A fresh queue over the same directory serves the earlier result
+    let app = create_test_app_with_config(config);
+
+    let (status, body) = send(&app, "GET", &format!("/status/{processing_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "completed");
+
+    let (status, body) = send(&app, "GET", &format!("/results/{processing_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["results"]["resolution"], "rejected");
+    assert_eq!(
+        body["results"]["reasons"][0]["message"],
+        "cannot locate image"
+    );
+}
+
+#[tokio::test]
+async fn test_results_long_poll_returns_on_completion() {
+    let app = create_test_app();
+
+    // A missing image is rejected without calling the LLM, so the wait ends early
+    let processing_id = submit(&app, "/tmp/image-checker-long-poll-test.jpg").await;
+
+    let started = std::time::Instant::now();
+    let (status, body) = send(&app, "GET", &format!("/results/{processing_id}?wait=10s")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["results"]["resolution"], "rejected");
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+}
+
+/// GETs `uri` with an `Accept` header, returning the status, content type and body text
+async fn get_accepting(app: &Router, uri: &str, accept: &str) -> (StatusCode, String, String) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(uri)
+                .header("accept", accept)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let content_type = response.headers()["content-type"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (
+        status,
+        content_type,
+        String::from_utf8(body.to_vec()).unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn test_results_as_csv() {
+    let app = create_test_app();
+
+    // A missing image is rejected without calling the LLM
+    let processing_id = submit(&app, "/tmp/image-checker-csv-test.jpg").await;
+    let (status, _) = send(&app, "GET", &format!("/results/{processing_id}?wait=10s")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let expected = format!(
+        "processing-id,resolution,reason_codes,reasons\r\n\
+         {processing_id},rejected,IMAGE_NOT_FOUND,cannot locate image\r\n"
+    );
+    let (status, content_type, body) =
+        get_accepting(&app, &format!("/results/{processing_id}"), "text/csv").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "text/csv; charset=utf-8");
+    assert_eq!(body, expected);
+
+    // The suffix works without the header
+    let (status, content_type, body) =
+        get_accepting(&app, &format!("/results/{processing_id}.csv"), "*/*").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "text/csv; charset=utf-8");
+    assert_eq!(body, expected);
+
+    // JSON stays the default, and errors keep the JSON envelope
+    let (_, content_type, _) =
+        get_accepting(&app, &format!("/results/{processing_id}"), "*/*").await;
+    assert_eq!(content_type, "application/json");
+    let (status, content_type, _) =
+        get_accepting(&app, "/results/nonexistent.csv", "text/csv").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(content_type, "application/json");
+}
+
+#[tokio::test]
+async fn test_retry_completed_request() {
+    let app = create_test_app();
+
+    let processing_id = submit(&app, "/tmp/image-checker-retry-test.jpg").await;
+    let (status, _) = send(&app, "GET", &format!("/results/{processing_id}?wait=10s")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) =
+        post_json(&app, &format!("/validate/{processing_id}/retry"), json!({})).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(body["processing-id"], processing_id);
+    assert_eq!(body["status"], "accepted");
+
+    // Re-run under the same id rather than as a new request
+    let (status, body) = send(&app, "GET", &format!("/results/{processing_id}?wait=10s")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["results"]["resolution"], "rejected");
+    let (_, stats) = send(&app, "GET", "/stats").await;
+    assert_eq!(stats["total"], 1);
+
+    let (status, _) = post_json(&app, "/validate/nonexistent/retry", json!({})).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_retry_unfinished_request_conflicts() {
+    let app = create_throttled_test_app();
+
+    // The second request waits for a throttle token, so it has not finished
+    submit(&app, "/tmp/image-checker-retry-test-1.jpg").await;
+    let processing_id = submit(&app, "/tmp/image-checker-retry-test-2.jpg").await;
+
+    let (status, body) =
+        post_json(&app, &format!("/validate/{processing_id}/retry"), json!({})).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["error"]["message"], "processing not finished");
+}
+
+#[tokio::test]
+async fn test_admin_queue_snapshot() {
+    let app = create_throttled_test_app();
+
+    let (status, snapshot) = send(&app, "GET", "/admin/queue").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(snapshot["queue_depth"], 0);
+    assert_eq!(snapshot["queue_capacity"], 100);
+    assert!(snapshot["oldest_accepted_age_ms"].is_null());
+    assert!(snapshot["oldest_in_progress_age_ms"].is_null());
+    assert_eq!(snapshot["worker"]["running"], true);
+    assert_eq!(snapshot["worker"]["concurrency"], 1);
+    assert_eq!(snapshot["available_tokens"], 1);
+
+    // The first request uses the only token; the others wait, one of them for the
+    // worker slot held by the second
+    let first = submit(&app, "/tmp/image-checker-admin-test-1.jpg").await;
+    let (status, _) = send(&app, "GET", &format!("/results/{first}?wait=10s")).await;
+    assert_eq!(status, StatusCode::OK);
+    submit(&app, "/tmp/image-checker-admin-test-2.jpg").await;
+    submit(&app, "/tmp/image-checker-admin-test-3.jpg").await;
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let (_, snapshot) = send(&app, "GET", "/admin/queue").await;
+    assert_eq!(snapshot["accepted"], 2);
+    assert_eq!(snapshot["in_progress"], 0);
+    assert!((1..=2).contains(&snapshot["queue_depth"].as_u64().unwrap()));
+    let age_ms = snapshot["oldest_accepted_age_ms"].as_u64().unwrap();
+    assert!((50..10_000).contains(&age_ms), "{age_ms}");
+    assert_eq!(snapshot["available_tokens"], 0);
+}
+
+#[tokio::test]
+async fn test_admin_queue_flush() {
+    let app = create_throttled_test_app();
+
+    let first = submit(&app, "/tmp/image-checker-flush-test-1.jpg").await;
+    let (status, _) = send(&app, "GET", &format!("/results/{first}?wait=10s")).await;
+    assert_eq!(status, StatusCode::OK);
+    let waiting = submit(&app, "/tmp/image-checker-flush-test-2.jpg").await;
+
+    let (status, body) = post_json(&app, "/admin/queue/flush", json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["flushed"], 1);
+
+    // Finished requests keep their results
+    let (_, body) = send(&app, "GET", &format!("/status/{first}")).await;
+    assert_eq!(body["status"], "completed");
+    let (status, _) = send(&app, "GET", &format!("/status/{waiting}")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, snapshot) = send(&app, "GET", "/admin/queue").await;
+    assert_eq!(snapshot["accepted"], 0);
+}
+
+#[tokio::test]
+async fn test_results_long_poll_times_out() {
+    let app = create_throttled_test_app();
+
+    // The second request waits for a throttle token, so it is still pending
+    // when the short wait runs out
+    submit(&app, "/tmp/image-checker-long-poll-test-1.jpg").await;
+    let processing_id = submit(&app, "/tmp/image-checker-long-poll-test-2.jpg").await;
+
+    let started = std::time::Instant::now();
+    let (status, body) = send(&app, "GET", &format!("/results/{processing_id}?wait=100ms")).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(body["error"]["message"], "processing not complete");
+    assert!(started.elapsed() >= std::time::Duration::from_millis(100));
+}
+
+#[tokio::test]
+async fn test_results_long_poll_not_found_and_invalid_wait() {
+    let app = create_test_app();
+
+    let started = std::time::Instant::now();
+    let (status, _) = send(&app, "GET", "/results/nonexistent?wait=30s").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+
+    let (status, body) = send(&app, "GET", "/results/nonexistent?wait=soon").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("invalid wait parameter"));
+}
+
+#[tokio::test]
+async fn test_metrics_endpoint() {
+    let app = create_test_app();
+    submit(&app, "/tmp/image-checker-metrics-test.jpg").await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/metrics")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()["content-type"]
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain"));
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(text
+        .lines()
+        .any(|line| line.starts_with("image_checker_submissions_total")));
+}
+
+async fn post_json_with_idempotency_key(
+    app: &Router,
+    body: &serde_json::Value,
+    key: &str,
+) -> (StatusCode, serde_json::Value) {
+    post_validation_with_header(app, body, "idempotency-key", key).await
+}
+
+async fn post_validation_with_header(
+    app: &Router,
+    body: &serde_json::Value,
+    name: &str,
+    value: &str,
+) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/validate")
+                .header("content-type", "application/json")
+                .header(name, value)
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_submit_validation_idempotency_key() {
+    let app = create_throttled_test_app();
+    let request_body = json!({
+        "image-path": "/tmp/test.jpg",
+        "analysis-request": { "content": "Three birds on a wire" }
+    });
+
+    let (status, first) = post_json_with_idempotency_key(&app, &request_body, "order-1234").await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    let processing_id = first["processing-id"].as_str().unwrap();
+
+    // The retry returns the original request instead of queueing another
+    let (status, retried) = post_json_with_idempotency_key(&app, &request_body, "order-1234").await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(retried["processing-id"], processing_id);
+    assert!(retried["status"].is_string());
+
+    let (_, other) = post_json_with_idempotency_key(&app, &request_body, "order-5678").await;
+    assert_ne!(other["processing-id"], processing_id);
+
+    let (_, stats) = send(&app, "GET", "/stats").await;
+    assert_eq!(stats["total"], 2);
+
+    let (status, _) = post_json_with_idempotency_key(&app, &request_body, &"k".repeat(256)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_submissions_with_same_idempotency_key() {
+    let app = create_throttled_test_app();
+    let request_body = json!({
+        "image-path": "/tmp/test.jpg",
+        "analysis-request": { "content": "Three birds on a wire" }
+    });
+
+    // A client retrying after a timeout while its first attempt is still being queued
+    let submissions = (0..20).map(|_| {
+        let app = app.clone();
+        let request_body = request_body.clone();
+        tokio::spawn(async move {
+            post_json_with_idempotency_key(&app, &request_body, "order-1234").await
+        })
+    });
+    let mut processing_ids = Vec::new();
+    for submission in submissions.collect::<Vec<_>>() {
+        let (status, body) = submission.await.unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+        processing_ids.push(body["processing-id"].as_str().unwrap().to_string());
+    }
+    processing_ids.dedup();
+    assert_eq!(processing_ids.len(), 1, "{processing_ids:?}");
+
+    let (_, stats) = send(&app, "GET", "/stats").await;
+    assert_eq!(stats["total"], 1);
+}
+
+#[tokio::test]
+async fn test_submit_validation_with_passed_deadline() {
+    let app = create_test_app();
+    let request_body = json!({
+        "image-path": "/tmp/test.jpg",
+        "analysis-request": { "content": "Three birds on a wire" }
+    });
+
+    let (status, body) =
+        post_validation_with_header(&app, &request_body, "x-request-deadline", "soon").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("X-Request-Deadline"));
+
+    // Accepted, then failed by the worker instead of being processed
+    let (status, body) =
+        post_validation_with_header(&app, &request_body, "x-request-deadline", "1700000000000")
+            .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    let processing_id = body["processing-id"].as_str().unwrap().to_string();
+
+    let mut status_body = json!(null);
+    for _ in 0..50 {
+        let (_, body) = send(&app, "GET", &format!("/status/{processing_id}")).await;
+        status_body = body;
+        if status_body["status"] == "failed" {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(status_body["status"], "failed");
+    assert_eq!(status_body["attempts"], 0);
+    assert_eq!(status_body["reason"], "deadline exceeded before processing");
+}
+
+#[tokio::test]
+async fn test_submit_validation_during_shutdown() {
+    let config = create_test_config();
+    let queue = ProcessingQueue::new(&config);
+    let app = create_test_app_with_queue(config, queue.clone());
+    let request_body = json!({
+        "image-path": "/tmp/test.jpg",
+        "analysis-request": { "content": "Three birds on a wire" }
+    });
+
+    let (status, body) = post_json(&app, "/validate", request_body.clone()).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    let processing_id = body["processing-id"].as_str().unwrap().to_string();
+
+    // Refused at once, while the worker may still be draining the queue
+    queue.shutdown().await;
+    let (status, body) = post_json(&app, "/validate", request_body).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["error"]["message"], "service is shutting down");
+
+    let (status, body) = send(&app, "GET", &format!("/status/{processing_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["processing-id"], processing_id);
+}
+
+#[tokio::test]
+async fn test_debug_image_returns_what_the_model_saw() {
+    use axum::Json;
+    use image::{ImageFormat, RgbImage};
+
+    // An LLM that accepts every image
+    let llm = Router::new().fallback(post(|| async {
+        Json(json!({ "choices": [{ "message": { "content": "ACCEPTED: birds\nSCORE: 0.9" } }] }))
+    }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, llm).await.unwrap() });
+
+    let image_dir = tempfile::tempdir().unwrap();
+    RgbImage::new(400, 200)
+        .save_with_format(image_dir.path().join("wide.jpg"), ImageFormat::Jpeg)
+        .unwrap();
+    let app = create_test_app_with_config(Config {
+        image_base_dir: image_dir.path().to_string_lossy().into_owned(),
+        llm_api_url: format!("http://{addr}/v1/chat/completions"),
+        max_image_dimension: Some(100),
+        debug_retain_processed_images: true,
+        ..create_test_config()
+    });
+
+    let (status, body) = post_json(
+        &app,
+        "/validate-sync",
+        json!({
+            "image-path": "wide.jpg",
+            "analysis-request": { "content": "Three birds on a wire" }
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["results"]["resolution"], "accepted");
+    let processing_id = body["processing-id"].as_str().unwrap();
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/debug/{processing_id}/image"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "image/jpeg");
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    // Downscaled to MAX_IMAGE_DIMENSION, as sent to the model
+    let sent = image::load_from_memory(&bytes).unwrap();
+    assert_eq!((sent.width(), sent.height()), (100, 50));
+
+    let (status, _) = send(&app, "GET", "/debug/unknown/image").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_per_client_rate_limit() {
+    let config = Config {
+        api_keys: Some("noisy-key,quiet-key".to_string()),
+        per_client_requests_per_minute: Some(2),
+        ..create_test_config()
+    };
+    let app = create_test_app_with_config(config.clone())
+        .layer(ClientRateLimitLayer::from_config(&config))
+        .layer(ApiKeyLayer::from_config(&config));
+    let body = json!({
+        "image-path": "/tmp/test.jpg",
+        "analysis-request": { "content": "Three birds on a wire" }
+    });
+    let submit = |key: &str| {
+        app.clone().oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/validate")
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {key}"))
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+    };
+
+    for _ in 0..2 {
+        let response = submit("noisy-key").await.unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+    let response = submit("noisy-key").await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = response.headers()["retry-after"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((1..=30).contains(&retry_after), "{retry_after}");
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["code"], "RATE_LIMITED");
+
+    // The other client is counted on its own and proceeds
+    let response = submit("quiet-key").await.unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn test_per_client_rate_limit_ignores_unverified_tokens() {
+    let config = Config {
+        api_keys: Some("real-key".to_string()),
+        auth_exclude_paths: Some("/validate".to_string()),
+        per_client_requests_per_minute: Some(2),
+        ..create_test_config()
+    };
+    let app = create_test_app_with_config(config.clone())
+        .layer(ClientRateLimitLayer::from_config(&config))
+        .layer(ApiKeyLayer::from_config(&config));
+    let body = json!({
+        "image-path": "/tmp/test.jpg",
+        "analysis-request": { "content": "Three birds on a wire" }
+    });
+    let submit = |token: String| {
+        app.clone().oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/validate")
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {token}"))
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+    };
+
+    // The path needs no key, so made-up tokens are let through but share one bucket
+    let mut statuses = Vec::new();
+    for attempt in 0..4 {
+        statuses.push(submit(format!("made-up-{attempt}")).await.unwrap().status());
+    }
+    assert_eq!(
+        statuses,
+        [
+            StatusCode::ACCEPTED,
+            StatusCode::ACCEPTED,
+            StatusCode::TOO_MANY_REQUESTS,
+            StatusCode::TOO_MANY_REQUESTS
+        ]
+    );
+
+    // A verified key is still counted on its own
+    let response = submit("real-key".to_string()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+}
+
+async fn post_json(
+    app: &Router,
+    uri: &str,
+    body: serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_callback_receives_results() {
+    use axum::Json;
+    use tokio::sync::mpsc;
+
+    // A local receiver that forwards every callback body to the test
+    let (sender, mut receiver) = mpsc::channel::<serde_json::Value>(1);
+    let receiver_app = Router::new().route(
+        "/callback",
+        post(move |Json(body): Json<serde_json::Value>| async move {
+            sender.send(body).await.unwrap();
+            StatusCode::OK
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, receiver_app).await.unwrap() });
+
+    let app = create_test_app();
+    let (status, body) = post_json(
+        &app,
+        "/validate",
+        json!({
+            "image-path": "/tmp/image-checker-callback-test.jpg",
+            "analysis-request": { "content": "Three birds on a wire" },
+            "callback-url": format!("http://{addr}/callback")
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    let processing_id = body["processing-id"].as_str().unwrap();
+
+    let callback = tokio::time::timeout(std::time::Duration::from_secs(5), receiver.recv())
+        .await
+        .expect("callback was not delivered")
+        .unwrap();
+    assert_eq!(callback["processing-id"], processing_id);
+    assert_eq!(callback["status"], "completed");
+    assert_eq!(callback["results"]["resolution"], "rejected");
+    assert_eq!(callback["results"]["reasons"][0]["code"], "IMAGE_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn test_callback_url_must_be_http() {
+    let app = create_test_app();
+
+    let (status, body) = post_json(
+        &app,
+        "/validate",
+        json!({
+            "image-path": "/tmp/test.jpg",
+            "analysis-request": { "content": "Three birds on a wire" },
+            "callback-url": "ftp://example.com/callback"
+        }),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("invalid callback-url"));
+}
+
+#[tokio::test]
+async fn test_check_validation_returns_normalized_context() {
+    let app = create_test_app();
+
+    let (status, body) = post_json(
+        &app,
+        "/validate/check",
+        json!({
+            "image-path": "/tmp/does-not-need-to-exist.jpg",
+            "analysis-request": {
+                "content": "Three birds on a wire",
+                "location": { "long": -0.266108, "lat": 51.492191, "max_distance": 100.0 },
+                "datetime": { "start": "2025-08-01T15:23:00+01:00", "duration": 10 }
+            }
+        }),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["content_checks"], json!(["Three birds on a wire"]));
+    assert_eq!(body["content_match_mode"], "all");
+    assert_eq!(body["location_constraint"]["radius"]["latitude"], 51.492191);
+    assert_eq!(
+        body["datetime_constraint"]["end_time"],
+        "2025-08-01T15:33:00+01:00"
+    );
+
+    // Nothing was queued
+    let (_, stats) = send(&app, "GET", "/stats").await;
+    assert_eq!(stats["total"], 0);
+}
+
+#[tokio::test]
+async fn test_submit_validation_model_allowlist() {
+    let app = create_test_app_with_config(Config {
+        llm_allowed_models: Some("llava:34b".to_string()),
+        ..create_test_config()
+    });
+    let request = |model: &str| {
+        json!({
+            "image-path": "/tmp/test.jpg",
+            "analysis-request": { "content": "Three birds on a wire", "model": model }
+        })
+    };
+
+    let (status, body) = post_json(&app, "/validate", request("gpt-4o")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["message"], "model gpt-4o is not allowed");
+    let (_, stats) = send(&app, "GET", "/stats").await;
+    assert_eq!(stats["total"], 0);
+
+    // The default model needs no listing
+    for model in ["llava:34b", "llava:7b"] {
+        let (status, _) = post_json(&app, "/validate", request(model)).await;
+        assert_eq!(status, StatusCode::ACCEPTED, "{model}");
+    }
+
+    let (status, body) = post_json(&app, "/validate/check", request("llava:34b")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["model"], "llava:34b");
+    let (status, _) = post_json(&app, "/validate/check", request("gpt-4o")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_submit_validation_timeout_bounds() {
+    let app = create_test_app_with_config(Config {
+        max_request_timeout_seconds: 60,
+        ..create_test_config()
+    });
+    let request = |timeout_seconds: u64| {
+        json!({
+            "image-path": "/tmp/test.jpg",
+            "analysis-request": { "content": "Three birds on a wire" },
+            "timeout-seconds": timeout_seconds
+        })
+    };
+
+    for timeout_seconds in [0, 61] {
+        let (status, body) = post_json(&app, "/validate", request(timeout_seconds)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{timeout_seconds}");
+        assert_eq!(
+            body["error"]["message"],
+            format!("timeout-seconds must be between 1 and 60, got: {timeout_seconds}")
+        );
+    }
+    let (_, stats) = send(&app, "GET", "/stats").await;
+    assert_eq!(stats["total"], 0);
+
+    for timeout_seconds in [1, 60] {
+        let (status, _) = post_json(&app, "/validate", request(timeout_seconds)).await;
+        assert_eq!(status, StatusCode::ACCEPTED, "{timeout_seconds}");
+    }
+}
+
+#[tokio::test]
+async fn test_check_validation_rejects_invalid_requests() {
+    let app = create_test_app();
+
+    let (status, body) = post_json(
+        &app,
+        "/validate/check",
+        json!({
+            "image-path": "/tmp/test.jpg",
+            "analysis-request": {
+                "content": "Three birds on a wire",
+                "datetime": {
+                    "start": "2025-08-01T15:23:00+01:00",
+                    "end": "2025-08-01T15:33:00+01:00",
+                    "duration": 10
+                }
+            }
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["field"], "analysis-request.datetime");
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Exactly two out of three fields"));
+
+    let (status, body) = post_json(
+        &app,
+        "/validate/check",
+        json!({
+            "image-path": "/tmp/test.jpg",
+            "analysis-request": {
+                "content": "Three birds on a wire",
+                "location": { "long": -0.26, "lat": 95.0, "max_distance": 100.0 }
+            }
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Invalid location coordinates: [95, -0.26]"));
+}
+
+#[tokio::test]
+async fn test_submit_validation_duplicate_window_bounds() {
+    let app = create_test_app();
+    let request = |minutes: u64| {
+        json!({
+            "image-path": "/tmp/test.jpg",
+            "analysis-request": {
+                "content": "Three birds on a wire",
+                "reject_duplicate_within": minutes
+            }
+        })
+    };
+
+    let (status, body) = post_json(&app, "/validate/check", request(60)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["reject_duplicate_within"], 60);
+
+    let (status, body) = post_json(&app, "/validate", request(0)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("reject_duplicate_within must be at least 1 minute"));
+
+    // DUPLICATE_RETENTION_MINUTES defaults to a day
+    let (status, body) = post_json(&app, "/validate", request(24 * 60 + 1)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["error"]["message"],
+        "reject_duplicate_within must be at most 1440 minutes, got: 1441"
+    );
+}
+
+#[tokio::test]
+async fn test_inspect_image() {
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+    let app = create_test_app_with_config(Config {
+        image_base_dir: fixtures.to_string(),
+        ..create_test_config()
+    });
+
+    // A JPEG with GPS coordinates and a capture time
+    let (status, body) = post_json(&app, "/inspect", json!({ "image-path": "gps.jpg" })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body,
+        json!({
+            "resolved_path": format!("{fixtures}/gps.jpg"),
+            "format": "jpeg",
+            "width": 64,
+            "height": 48,
+            "has_gps": true,
+            "has_timestamp": true,
+        })
+    );
+
+    // Missing metadata is reported, not an error; HEIC dimensions are not decoded
+    let (status, body) = post_json(&app, "/inspect", json!({ "image-path": "exif.heic" })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["format"], "heif");
+    assert!(body["width"].is_null());
+    assert_eq!(body["has_gps"], false);
+    assert_eq!(body["has_timestamp"], false);
+
+    let (status, body) = post_json(&app, "/inspect", json!({ "image-path": "missing.jpg" })).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"]["code"], "NOT_FOUND");
+
+    let (status, _) = post_json(&app, "/inspect", json!({})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -25,16 +25,29 @@ fn test_haversine_distance_calculation() {
 #[test]
 fn test_location_constraint_from_request() {
     let location_request = LocationRequest {
-        long: -0.266108,
-        lat: 51.492191,
-        max_distance: 100.0,
+        long: Some(-0.266108),
+        lat: Some(51.492191),
+        max_distance: Some(100.0),
+        polygon: None,
+        place_name: None,
+        distance_unit: None,
+        altitude: None,
+        tolerate_gps_precision: None,
     };
 
-    let constraint = LocationConstraint::from(location_request);
+    let LocationConstraint::Radius {
+        latitude,
+        longitude,
+        max_distance_meters,
+        ..
+    } = LocationConstraint::try_from(location_request).unwrap()
+    else {
+        panic!("expected a radius constraint");
+    };
 
-    assert_eq!(constraint.max_distance_meters, 100.0);
-    assert!((constraint.latitude - 51.492191).abs() < 0.000001);
-    assert!((constraint.longitude + 0.266108).abs() < 0.000001);
+    assert_eq!(max_distance_meters, 100.0);
+    assert!((latitude - 51.492191).abs() < 0.000001);
+    assert!((longitude + 0.266108).abs() < 0.000001);
 }
 
 #[test]
@@ -42,27 +55,50 @@ fn test_location_request_variations() {
     // Test different valid location requests
     let variations = vec![
         LocationRequest {
-            long: 0.0,
-            lat: 0.0,
-            max_distance: 50.0,
+            long: Some(0.0),
+            lat: Some(0.0),
+            max_distance: Some(50.0),
+            polygon: None,
+            place_name: None,
+            distance_unit: None,
+            altitude: None,
+            tolerate_gps_precision: None,
         },
         LocationRequest {
-            long: -180.0,
-            lat: -90.0,
-            max_distance: 1000.0,
+            long: Some(-180.0),
+            lat: Some(-90.0),
+            max_distance: Some(1000.0),
+            polygon: None,
+            place_name: None,
+            distance_unit: None,
+            altitude: None,
+            tolerate_gps_precision: None,
         },
         LocationRequest {
-            long: 180.0,
-            lat: 90.0,
-            max_distance: 25.0,
+            long: Some(180.0),
+            lat: Some(90.0),
+            max_distance: Some(25.0),
+            polygon: None,
+            place_name: None,
+            distance_unit: None,
+            altitude: None,
+            tolerate_gps_precision: None,
         },
     ];
 
     for location_request in variations {
-        let constraint = LocationConstraint::from(location_request.clone());
-        assert!(constraint.max_distance_meters > 0.0);
-        assert!(constraint.latitude >= -90.0 && constraint.latitude <= 90.0);
-        assert!(constraint.longitude >= -180.0 && constraint.longitude <= 180.0);
+        let LocationConstraint::Radius {
+            latitude,
+            longitude,
+            max_distance_meters,
+            ..
+        } = LocationConstraint::try_from(location_request.clone()).unwrap()
+        else {
+            panic!("expected a radius constraint");
+        };
+        assert!(max_distance_meters > 0.0);
+        assert!((-90.0..=90.0).contains(&latitude));
+        assert!((-180.0..=180.0).contains(&longitude));
     }
 }
 
@@ -71,7 +107,9 @@ fn test_datetime_request_start_and_duration() {
     let datetime_request = DateTimeRequest {
         start: Some("2025-08-01T15:23:00+01:00".to_string()),
         end: None,
-        duration: Some(10),
+        duration: Some(DateTimeDuration::Minutes(10)),
+        local_date: None,
+        timezone: None,
     };
 
     let constraint = DateTimeConstraint::try_from(datetime_request).unwrap();
@@ -93,16 +131,22 @@ fn test_datetime_request_variations() {
             start: Some("2025-08-01T15:23:00+01:00".to_string()),
             end: Some("2025-08-01T15:28:00+01:00".to_string()),
             duration: None,
+            local_date: None,
+            timezone: None,
         },
         DateTimeRequest {
             start: Some("2025-12-31T23:59:00+01:00".to_string()),
             end: None,
-            duration: Some(60),
+            duration: Some(DateTimeDuration::Minutes(60)),
+            local_date: None,
+            timezone: None,
         },
         DateTimeRequest {
             start: None,
             end: Some("2025-01-01T00:01:00+01:00".to_string()),
-            duration: Some(1),
+            duration: Some(DateTimeDuration::Minutes(1)),
+            local_date: None,
+            timezone: None,
         },
     ];
 
@@ -115,10 +159,12 @@ fn test_datetime_request_variations() {
 #[test]
 fn test_validate_location_within_range() {
     let actual = (51.491079, -0.269590);
-    let constraint = LocationConstraint {
+    let constraint = LocationConstraint::Radius {
         max_distance_meters: 500.0,
         latitude: 51.492191,
         longitude: -0.266108,
+        distance_unit: DistanceUnit::Meters,
+        tolerate_gps_precision: false,
     };
 
     let result = validate_location(actual, &constraint).unwrap();
@@ -128,10 +174,12 @@ fn test_validate_location_within_range() {
 #[test]
 fn test_validate_location_outside_range() {
     let actual = (51.491079, -0.269590);
-    let constraint = LocationConstraint {
+    let constraint = LocationConstraint::Radius {
         max_distance_meters: 50.0, // Very strict limit
         latitude: 51.492191,
         longitude: -0.266108,
+        distance_unit: DistanceUnit::Meters,
+        tolerate_gps_precision: false,
     };
 
     let result = validate_location(actual, &constraint).unwrap();
@@ -147,10 +195,12 @@ fn test_validate_location_invalid_coordinates() {
         (0.0, -181.0), // Invalid longitude < -180
     ];
 
-    let constraint = LocationConstraint {
+    let constraint = LocationConstraint::Radius {
         max_distance_meters: 100.0,
         latitude: 0.0,
         longitude: 0.0,
+        distance_unit: DistanceUnit::Meters,
+        tolerate_gps_precision: false,
     };
 
     for coords in invalid_coords {
@@ -298,30 +348,44 @@ fn test_estimate_gps_precision() {
 #[test]
 fn test_validation_context_creation() {
     let analysis_request = AnalysisRequest {
-        image_path: None,
-        content: "Three birds on a wire".to_string(),
         location: Some(LocationRequest {
-            long: -0.266108,
-            lat: 51.492191,
-            max_distance: 100.0,
+            long: Some(-0.266108),
+            lat: Some(51.492191),
+            max_distance: Some(100.0),
+            polygon: None,
+            place_name: None,
+            distance_unit: None,
+            altitude: None,
+            tolerate_gps_precision: None,
         }),
         datetime: Some(DateTimeRequest {
             start: Some("2025-08-01T15:23:00+01:00".to_string()),
             end: None,
-            duration: Some(10), // 10 minutes
+            duration: Some(DateTimeDuration::Minutes(10)), // 10 minutes
+            local_date: None,
+            timezone: None,
         }),
+        ..AnalysisRequest::new("Three birds on a wire")
     };
 
     let context = ValidationContext::try_from(analysis_request).unwrap();
 
-    assert_eq!(context.content_check, "Three birds on a wire");
+    assert_eq!(context.content_checks, vec!["Three birds on a wire"]);
     assert!(context.location_constraint.is_some());
     assert!(context.datetime_constraint.is_some());
 
-    let location = context.location_constraint.unwrap();
-    assert_eq!(location.max_distance_meters, 100.0);
-    assert!((location.latitude - 51.492191).abs() < 0.000001);
-    assert!((location.longitude + 0.266108).abs() < 0.000001);
+    let Some(LocationConstraint::Radius {
+        latitude,
+        longitude,
+        max_distance_meters,
+        ..
+    }) = context.location_constraint
+    else {
+        panic!("expected a radius constraint");
+    };
+    assert_eq!(max_distance_meters, 100.0);
+    assert!((latitude - 51.492191).abs() < 0.000001);
+    assert!((longitude + 0.266108).abs() < 0.000001);
 
     let datetime = context.datetime_constraint.unwrap();
     assert_eq!(
@@ -336,16 +400,11 @@ fn test_validation_context_creation() {
 
 #[test]
 fn test_validation_context_optional_fields() {
-    let analysis_request = AnalysisRequest {
-        image_path: None,
-        content: "Just content check".to_string(),
-        location: None,
-        datetime: None,
-    };
+    let analysis_request = AnalysisRequest::new("Just content check");
 
     let context = ValidationContext::try_from(analysis_request).unwrap();
 
-    assert_eq!(context.content_check, "Just content check");
+    assert_eq!(context.content_checks, vec!["Just content check"]);
     assert!(context.location_constraint.is_none());
     assert!(context.datetime_constraint.is_none());
 }
@@ -367,11 +426,16 @@ async fn test_llm_validation_integration() {
 
     // This test will show the debug output we added
     match validate_image_content(&client, image_path, content_description).await {
-        Ok(is_valid) => {
+        Ok(verdict) => {
             println!("✅ LLM validation completed successfully");
             println!(
-                "   Result: {}",
-                if is_valid { "ACCEPTED" } else { "REJECTED" }
+                "   Result: {} (confidence {:.2})",
+                if verdict.accepted {
+                    "ACCEPTED"
+                } else {
+                    "REJECTED"
+                },
+                verdict.confidence
             );
         }
         Err(e) => {